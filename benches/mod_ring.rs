@@ -0,0 +1,71 @@
+//! Benchmarks for the modular arithmetic and elliptic curve primitives.
+
+use {
+    criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion},
+    icao_9303::crypto::{
+        groups::{
+            named::{brainpool_p256r1, brainpool_p512r1, secp192r1, secp384r1},
+            EllipticCurve,
+        },
+        mod_ring::{RingRefExt, UintMont},
+    },
+    num_traits::Inv,
+    rand::thread_rng,
+};
+
+fn bench_field<U: UintMont>(c: &mut Criterion, curve: &EllipticCurve<U>) {
+    let mut rng = thread_rng();
+    let field = curve.base_field();
+    let bits = field.modulus().bit_len();
+    let mut group = c.benchmark_group(format!("mod_ring/{bits}"));
+
+    let a = field.random(&mut rng);
+    let b = field.random(&mut rng);
+    group.bench_function("mul", |bencher| {
+        bencher.iter(|| black_box(a) * black_box(b))
+    });
+
+    let exponent = field.random(&mut rng).to_uint();
+    group.bench_function("pow_ct", |bencher| {
+        bencher.iter(|| black_box(a).pow_ct(black_box(exponent)))
+    });
+
+    // There is no dedicated batch inversion, this is the baseline for one.
+    group.bench_function("inv_100", |bencher| {
+        bencher.iter_batched(
+            || (0..100).map(|_| field.random(&mut rng)).collect::<Vec<_>>(),
+            |elements| {
+                elements
+                    .into_iter()
+                    .map(|element| element.inv())
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_curve<U: UintMont>(c: &mut Criterion, name: &str, curve: &EllipticCurve<U>) {
+    let mut rng = thread_rng();
+    let point = curve.generator() * curve.scalar_field().random(&mut rng);
+    let scalar = curve.scalar_field().random(&mut rng);
+    c.bench_function(&format!("elliptic_curve/{name}/mul"), |bencher| {
+        bencher.iter(|| black_box(point) * black_box(scalar))
+    });
+}
+
+fn mod_ring(c: &mut Criterion) {
+    bench_field(c, &secp192r1());
+    bench_field(c, &brainpool_p256r1());
+    bench_field(c, &secp384r1());
+    bench_field(c, &brainpool_p512r1());
+}
+
+fn elliptic_curve(c: &mut Criterion) {
+    bench_curve(c, "brainpool_p256r1", &brainpool_p256r1());
+    bench_curve(c, "secp384r1", &secp384r1());
+}
+
+criterion_group!(benches, mod_ring, elliptic_curve);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+#![no_main]
+
+use {icao_9303::iso7816::parse_apdu, libfuzzer_sys::fuzz_target};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(apdu) = parse_apdu(data) {
+        assert_eq!(apdu.header.len(), 4);
+        assert!(apdu.lc.len() <= 3);
+        assert!(apdu.data.len() <= 65535);
+        assert!(apdu.le.len() <= 3);
+        assert_eq!(
+            apdu.header.len() + apdu.lc.len() + apdu.data.len() + apdu.le.len(),
+            data.len()
+        );
+        assert_eq!(apdu.encode(), data);
+    }
+});
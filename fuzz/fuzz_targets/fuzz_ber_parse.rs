@@ -0,0 +1,20 @@
+#![no_main]
+
+use {
+    der::Decode,
+    icao_9303::asn1::emrtd::{EfDg14, EfSod},
+    libfuzzer_sys::fuzz_target,
+};
+
+fuzz_target!(|data: &[u8]| {
+    // Parsing must fail gracefully, never panic.
+    if let Ok(ef_sod) = EfSod::from_der(data) {
+        let _ = ef_sod.lds_security_object();
+        let _ = ef_sod.canonical_hash();
+    }
+    if let Ok(ef_dg14) = EfDg14::from_der(data) {
+        for security_info in ef_dg14.0.iter() {
+            let _ = format!("{security_info:?}");
+        }
+    }
+});
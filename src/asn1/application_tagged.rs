@@ -4,7 +4,14 @@ use der::{
 };
 
 /// Wrapper that adds an application specific tag.
+///
+/// The tag is constructed, so `APPLICATION` 14 is encoded as `0x6E` and 23 as
+/// `0x77`. Only single byte tags (numbers up to 30) are supported by `der`,
+/// larger numbers fail to compile. Two byte tags such as those in EF.COM are
+/// parsed by hand instead.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct ApplicationTagged<const APPLICATION: u8, T>(pub T);
 
 impl<const APPLICATION: u8, T: for<'a> DecodeValue<'a>> FixedTag
@@ -38,10 +45,31 @@ impl<'a, const APPLICATION: u8, T: Decode<'a>> DecodeValue<'a>
 mod tests {
     use {
         super::*,
+        crate::asn1::emrtd::{EfDg14, EfDg15, EfSod},
         der::{asn1::ObjectIdentifier, Decode},
         hex_literal::hex,
     };
 
+    #[test]
+    fn test_tag() {
+        assert_eq!(EfDg14::TAG.octet(), 0x6e);
+        assert_eq!(EfDg15::TAG.octet(), 0x6f);
+        assert_eq!(EfSod::TAG.octet(), 0x77);
+        assert_eq!(ApplicationTagged::<0, ObjectIdentifier>::TAG.octet(), 0x60);
+        assert_eq!(ApplicationTagged::<30, ObjectIdentifier>::TAG.octet(), 0x7e);
+
+        // Other classes or primitive encodings are rejected.
+        let oid = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.48.1");
+        let der = hex!("57 0a 06082b06010505073001");
+        assert!(ApplicationTagged::<23, ObjectIdentifier>::from_der(&der).is_err());
+        let der = hex!("6e 0a 06082b06010505073001");
+        assert!(ApplicationTagged::<23, ObjectIdentifier>::from_der(&der).is_err());
+        assert_eq!(
+            ApplicationTagged::<14, ObjectIdentifier>::from_der(&der).unwrap(),
+            ApplicationTagged(oid)
+        );
+    }
+
     #[test]
     fn test_encode_decode() {
         let app_data =
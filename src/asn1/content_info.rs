@@ -5,7 +5,7 @@
 use {
     cms::signed_data::SignedData,
     der::{
-        asn1::{ContextSpecific, ContextSpecificRef, ObjectIdentifier as Oid},
+        asn1::{ContextSpecific, ContextSpecificRef, ObjectIdentifier as Oid, OctetString},
         Decode, DecodeValue, Encode, EncodeValue, Error, ErrorKind, Header, Length, Reader, Result,
         Sequence, Tag, TagMode, TagNumber, Tagged, Writer,
     },
@@ -37,6 +37,36 @@ impl ContentType for SignedData {
     const CONTENT_TYPE: Oid = Oid::new_unwrap("1.2.840.113549.1.7.2");
 }
 
+impl ContentInfo<SignedData> {
+    /// Decode the encapsulated content, checking that its content type matches
+    /// `T`.
+    pub fn decode_econtent<T: ContentType>(&self) -> Result<T> {
+        let econ = &self.0.encap_content_info;
+        if econ.econtent_type != T::CONTENT_TYPE {
+            return Err(Error::new(
+                ErrorKind::OidUnknown {
+                    oid: econ.econtent_type,
+                },
+                Length::ZERO,
+            ));
+        }
+        let octet_string = econ
+            .econtent
+            .as_ref()
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::TagUnexpected {
+                        expected: Some(Tag::OctetString),
+                        actual:   Tag::Null, // Actually None
+                    },
+                    Length::ZERO,
+                )
+            })?
+            .decode_as::<OctetString>()?;
+        T::from_der(octet_string.as_bytes())
+    }
+}
+
 impl<T: ContentType> Sequence<'_> for ContentInfo<T> {}
 
 impl<T: ContentType> EncodeValue for ContentInfo<T> {
@@ -93,6 +123,29 @@ impl<'a, T: ContentType> DecodeValue<'a> for ContentInfo<T> {
     }
 }
 
+/// Serialized as the hex string of the DER encoding, the content is usually a
+/// signed structure that has to round-trip byte exact.
+#[cfg(feature = "serde")]
+impl<T: ContentType> serde::Serialize for ContentInfo<T> {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use {crate::asn1::serde_adapters::DerHex, serde_with::SerializeAs};
+        DerHex::serialize_as(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: ContentType> serde::Deserialize<'de> for ContentInfo<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use {crate::asn1::serde_adapters::DerHex, serde_with::DeserializeAs};
+        DerHex::deserialize_as(deserializer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -124,4 +177,12 @@ mod tests {
         let decoded = ContentInfo::<TestObject>::from_der(&encoded).unwrap();
         assert_eq!(decoded, content_info);
     }
+
+    #[test]
+    fn test_content_type_mismatch() {
+        // Same content, but with content type 1.3.6.1.5.5.7.48.2.
+        let encoded = hex!("30 14 06082b06010505073002a0083006020101020102");
+        let err = ContentInfo::<TestObject>::from_der(&encoded).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::OidUnknown { .. }));
+    }
 }
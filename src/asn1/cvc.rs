@@ -0,0 +1,501 @@
+//! Card Verifiable Certificates, see BSI TR-03110-3 appendix C.
+//!
+//! CVCs use BER-TLV with two byte application tags, which the `der` crate does
+//! not support, so they are parsed by hand.
+
+use {
+    super::{
+        emrtd::tlv::read_tlv,
+        public_key_info::{
+            Curve, ECAlgoParameters, EcParameters, EcPublicKeyInfo, FieldId, RsaPublicKeyInfo,
+            SubjectPublicKeyInfo,
+        },
+    },
+    anyhow::{anyhow, bail, ensure, Result},
+    der::{
+        asn1::{Int, ObjectIdentifier as Oid, OctetString},
+        DateTime,
+    },
+};
+
+const TAG_CV_CERTIFICATE: u16 = 0x7f21;
+const TAG_CERTIFICATE_BODY: u16 = 0x7f4e;
+const TAG_SIGNATURE: u16 = 0x5f37;
+const TAG_PROFILE_IDENTIFIER: u16 = 0x5f29;
+const TAG_AUTHORITY_REFERENCE: u16 = 0x42;
+const TAG_PUBLIC_KEY: u16 = 0x7f49;
+const TAG_HOLDER_REFERENCE: u16 = 0x5f20;
+const TAG_HOLDER_AUTHORIZATION: u16 = 0x7f4c;
+const TAG_EFFECTIVE_DATE: u16 = 0x5f25;
+const TAG_EXPIRATION_DATE: u16 = 0x5f24;
+const TAG_OID: u16 = 0x06;
+const TAG_DISCRETIONARY_DATA: u16 = 0x53;
+
+/// Parent of the `id-TA-RSA-*` algorithms, see BSI TR-03110-3 section A.6.4.
+const ID_TA_RSA: Oid = Oid::new_unwrap("0.4.0.127.0.7.2.2.2.1");
+/// Parent of the `id-TA-ECDSA-*` algorithms.
+const ID_TA_ECDSA: Oid = Oid::new_unwrap("0.4.0.127.0.7.2.2.2.2");
+
+/// Card Verifiable Certificate.
+///
+/// See BSI TR-03110-3 section C.1.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CardVerifiableCertificate {
+    /// Encoded certificate body, including its tag and length.
+    body: Vec<u8>,
+
+    /// Signature over the encoded body.
+    signature: Vec<u8>,
+
+    /// Certificate Profile Identifier, 0 for version 1.
+    pub profile_identifier: u8,
+
+    /// Certification Authority Reference.
+    authority_reference: String,
+
+    /// Public key of the certificate holder.
+    pub public_key: CvcPublicKey,
+
+    /// Certificate Holder Reference.
+    holder_reference: String,
+
+    /// Role and access rights of the certificate holder.
+    pub holder_authorization: HolderAuthorization,
+
+    /// First day the certificate is valid.
+    pub effective_date: DateTime,
+
+    /// Last day the certificate is valid.
+    pub expiration_date: DateTime,
+}
+
+/// Public key data object, see BSI TR-03110-3 section D.3.
+///
+/// Integers are unsigned big-endian. The domain parameters of EC keys are
+/// only present in CVCA certificates.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CvcPublicKey {
+    Rsa {
+        oid:      Oid,
+        modulus:  Vec<u8>,
+        exponent: Vec<u8>,
+    },
+    Ec {
+        oid:        Oid,
+        domain:     Option<CvcEcDomain>,
+        public_key: Vec<u8>,
+    },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CvcEcDomain {
+    pub prime:    Vec<u8>,
+    pub a:        Vec<u8>,
+    pub b:        Vec<u8>,
+    pub base:     Vec<u8>,
+    pub order:    Vec<u8>,
+    pub cofactor: Option<Vec<u8>>,
+}
+
+/// Certificate Holder Authorization Template, see BSI TR-03110-3 section C.1.5.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HolderAuthorization {
+    /// Terminal type, for example `id-IS` for inspection systems.
+    pub role:          Oid,
+    /// Relative authorization bit map, including the role bits.
+    pub authorization: Vec<u8>,
+}
+
+impl CardVerifiableCertificate {
+    /// Parse a certificate from its `7F21` encoding.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self> {
+        let (tag, mut certificate, _) = read_tlv(&mut bytes)?;
+        ensure!(tag == TAG_CV_CERTIFICATE, "Expected CV certificate tag");
+        ensure!(bytes.is_empty(), "Trailing bytes after CV certificate");
+
+        let (tag, mut fields, body) = read_tlv(&mut certificate)?;
+        ensure!(tag == TAG_CERTIFICATE_BODY, "Expected certificate body");
+        let (tag, signature, _) = read_tlv(&mut certificate)?;
+        ensure!(tag == TAG_SIGNATURE, "Expected certificate signature");
+        ensure!(certificate.is_empty(), "Trailing bytes in CV certificate");
+
+        // The body data objects have a fixed order, see BSI TR-03110-3 table C.1.
+        let mut next = |expected: u16| -> Result<&[u8]> {
+            let (tag, value, _) = read_tlv(&mut fields)?;
+            ensure!(
+                tag == expected,
+                "Expected tag {expected:04X}, got {tag:04X}"
+            );
+            Ok(value)
+        };
+        let profile_identifier = match next(TAG_PROFILE_IDENTIFIER)? {
+            [profile] => *profile,
+            _ => bail!("Invalid Certificate Profile Identifier"),
+        };
+        let authority_reference = reference(next(TAG_AUTHORITY_REFERENCE)?)?;
+        let public_key = CvcPublicKey::from_value(next(TAG_PUBLIC_KEY)?)?;
+        let holder_reference = reference(next(TAG_HOLDER_REFERENCE)?)?;
+        let holder_authorization =
+            HolderAuthorization::from_value(next(TAG_HOLDER_AUTHORIZATION)?)?;
+        let effective_date = date(next(TAG_EFFECTIVE_DATE)?)?;
+        let expiration_date = date(next(TAG_EXPIRATION_DATE)?)?;
+        // Certificate extensions (tag 65) are not parsed.
+
+        Ok(Self {
+            body: body.to_vec(),
+            signature: signature.to_vec(),
+            profile_identifier,
+            authority_reference,
+            public_key,
+            holder_reference,
+            holder_authorization,
+            effective_date,
+            expiration_date,
+        })
+    }
+
+    /// Encoded certificate body, including its tag and length.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Certification Authority Reference, identifying the key that signed
+    /// this certificate.
+    pub fn authority_reference(&self) -> &str {
+        &self.authority_reference
+    }
+
+    /// Certificate Holder Reference, identifying the public key in this
+    /// certificate.
+    pub fn holder_reference(&self) -> &str {
+        &self.holder_reference
+    }
+
+    /// The public key as an X.509 [`SubjectPublicKeyInfo`].
+    ///
+    /// Fails for EC keys without domain parameters, these are inherited from
+    /// the CVCA certificate.
+    pub fn subject_public_key(&self) -> Result<SubjectPublicKeyInfo> {
+        Ok(match &self.public_key {
+            CvcPublicKey::Rsa {
+                modulus, exponent, ..
+            } => SubjectPublicKeyInfo::Rsa(RsaPublicKeyInfo {
+                modulus:         unsigned_int(modulus)?,
+                public_exponent: unsigned_int(exponent)?,
+            }),
+            CvcPublicKey::Ec {
+                domain, public_key, ..
+            } => {
+                let domain = domain
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("EC public key without domain parameters"))?;
+                let field_len = strip_zeros(&domain.prime).len();
+                SubjectPublicKeyInfo::Ec(EcPublicKeyInfo {
+                    parameters: ECAlgoParameters::EcParameters(EcParameters {
+                        version:  1,
+                        field_id: FieldId::PrimeField {
+                            modulus: unsigned_int(&domain.prime)?,
+                        },
+                        curve:    Curve {
+                            a:    field_element(&domain.a, field_len)?,
+                            b:    field_element(&domain.b, field_len)?,
+                            seed: None,
+                        },
+                        base:     OctetString::new(domain.base.as_slice())?,
+                        order:    unsigned_int(&domain.order)?,
+                        cofactor: domain.cofactor.as_deref().map(unsigned_int).transpose()?,
+                    }),
+                    point:      OctetString::new(public_key.as_slice())?,
+                })
+            }
+        })
+    }
+}
+
+impl CvcPublicKey {
+    fn from_value(mut value: &[u8]) -> Result<Self> {
+        let mut objects = [None; 8];
+        let mut oid = None;
+        while !value.is_empty() {
+            let (tag, object, _) = read_tlv(&mut value)?;
+            match tag {
+                TAG_OID => oid = Some(Oid::from_bytes(object).map_err(|e| anyhow!(e))?),
+                0x81..=0x87 => objects[usize::from(tag - 0x80)] = Some(object),
+                _ => bail!("Unexpected tag {tag:02X} in public key"),
+            }
+        }
+        let oid = oid.ok_or_else(|| anyhow!("Missing public key algorithm"))?;
+        let object = |index: usize| -> Result<Vec<u8>> {
+            objects[index]
+                .map(<[u8]>::to_vec)
+                .ok_or_else(|| anyhow!("Missing public key object {:02X}", 0x80 + index))
+        };
+
+        match oid.parent() {
+            Some(ID_TA_RSA) => {
+                ensure!(
+                    objects[3..].iter().all(Option::is_none),
+                    "Unexpected object in RSA public key"
+                );
+                Ok(Self::Rsa {
+                    oid,
+                    modulus: object(1)?,
+                    exponent: object(2)?,
+                })
+            }
+            Some(ID_TA_ECDSA) => {
+                // Domain parameters are all present or all absent, the cofactor
+                // is optional.
+                let domain = if objects[1..=5].iter().any(Option::is_some) {
+                    Some(CvcEcDomain {
+                        prime:    object(1)?,
+                        a:        object(2)?,
+                        b:        object(3)?,
+                        base:     object(4)?,
+                        order:    object(5)?,
+                        cofactor: objects[7].map(<[u8]>::to_vec),
+                    })
+                } else {
+                    ensure!(objects[7].is_none(), "Cofactor without domain parameters");
+                    None
+                };
+                Ok(Self::Ec {
+                    oid,
+                    domain,
+                    public_key: object(6)?,
+                })
+            }
+            _ => bail!("Unsupported public key algorithm {oid}"),
+        }
+    }
+}
+
+impl HolderAuthorization {
+    fn from_value(mut value: &[u8]) -> Result<Self> {
+        let (tag, role, _) = read_tlv(&mut value)?;
+        ensure!(tag == TAG_OID, "Expected terminal type");
+        let (tag, authorization, _) = read_tlv(&mut value)?;
+        ensure!(
+            tag == TAG_DISCRETIONARY_DATA,
+            "Expected relative authorization"
+        );
+        ensure!(value.is_empty(), "Trailing data in holder authorization");
+        Ok(Self {
+            role:          Oid::from_bytes(role).map_err(|e| anyhow!(e))?,
+            authorization: authorization.to_vec(),
+        })
+    }
+}
+
+fn reference(value: &[u8]) -> Result<String> {
+    Ok(std::str::from_utf8(value)?.to_owned())
+}
+
+/// Dates are six unpacked BCD digits `YYMMDD` in the 21st century.
+fn date(value: &[u8]) -> Result<DateTime> {
+    ensure!(
+        value.len() == 6 && value.iter().all(|&digit| digit <= 9),
+        "Invalid date"
+    );
+    let pair = |i: usize| value[i] * 10 + value[i + 1];
+    Ok(DateTime::new(
+        2000 + u16::from(pair(0)),
+        pair(2),
+        pair(4),
+        0,
+        0,
+        0,
+    )?)
+}
+
+fn strip_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Convert an unsigned big-endian integer into a minimally encoded INTEGER.
+fn unsigned_int(bytes: &[u8]) -> Result<Int> {
+    let bytes = strip_zeros(bytes);
+    let int = match bytes.first() {
+        None => Int::new(&[0])?,
+        Some(&first) if first >= 0x80 => Int::new(&[&[0], bytes].concat())?,
+        Some(_) => Int::new(bytes)?,
+    };
+    Ok(int)
+}
+
+/// Left pad a field element to the length of the field.
+fn field_element(bytes: &[u8], field_len: usize) -> Result<OctetString> {
+    let bytes = strip_zeros(bytes);
+    ensure!(bytes.len() <= field_len, "Field element too large");
+    let mut padded = vec![0; field_len - bytes.len()];
+    padded.extend_from_slice(bytes);
+    Ok(OctetString::new(padded)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::crypto::groups::{named::brainpool_p256r1, EllipticCurve},
+        hex_literal::hex,
+        ruint::aliases::U256,
+    };
+
+    const P: [u8; 32] = hex!("A9FB57DBA1EEA9BC3E660A909D838D726E3BF623D52620282013481D1F6E5377");
+    const A: [u8; 32] = hex!("7D5A0975FC2C3057EEF67530417AFFE7FB8055C126DC5C6CE94A4B44F330B5D9");
+    const B: [u8; 32] = hex!("26DC5C6CE94A4B44F330B5D9BBD77CBF958416295CF7E1CE6BCCDC18FF8C07B6");
+    const G: [u8; 65] = hex!(
+        "04 8BD2AEB9CB7E57CB2C4B482FFC81B7AFB9DE27E1E3BD23C23A4453BD9ACE3262
+            547EF835C3DAC4FD97F8461A14611DC9C27745132DED8E545C1D54C72F046997"
+    );
+    const R: [u8; 32] = hex!("A9FB57DBA1EEA9BC3E660A909D838D718C397AA3B561A6F7901E0E82974856A7");
+
+    fn tlv(tag: u16, value: &[u8]) -> Vec<u8> {
+        let mut encoded = match tag.to_be_bytes() {
+            [0, tag] => vec![tag],
+            tag => tag.to_vec(),
+        };
+        match value.len() {
+            len @ 0..=0x7f => encoded.push(len as u8),
+            len @ 0x80..=0xff => encoded.extend_from_slice(&[0x81, len as u8]),
+            len => {
+                encoded.push(0x82);
+                encoded.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+        }
+        encoded.extend_from_slice(value);
+        encoded
+    }
+
+    fn certificate(car: &str, public_key: &[u8], chr: &str) -> Vec<u8> {
+        let authorization = [
+            tlv(0x06, &hex!("04007F000703010201")),
+            tlv(0x53, &hex!("E3")),
+        ]
+        .concat();
+        let body = [
+            tlv(0x5f29, &[0]),
+            tlv(0x42, car.as_bytes()),
+            tlv(0x7f49, public_key),
+            tlv(0x5f20, chr.as_bytes()),
+            tlv(0x7f4c, &authorization),
+            tlv(0x5f25, &hex!("020400010105")),
+            tlv(0x5f24, &hex!("020700010105")),
+        ]
+        .concat();
+        let inner = [tlv(0x7f4e, &body), tlv(0x5f37, &hex!("0102030405"))].concat();
+        tlv(0x7f21, &inner)
+    }
+
+    #[test]
+    fn test_parse_cvca() {
+        let public_key = [
+            tlv(0x06, &hex!("04007F00070202020203")),
+            tlv(0x81, &P),
+            tlv(0x82, &A),
+            tlv(0x83, &B),
+            tlv(0x84, &G),
+            tlv(0x85, &R),
+            tlv(0x86, &G),
+            tlv(0x87, &[1]),
+        ]
+        .concat();
+        let encoded = certificate("DECVCA00001", &public_key, "DECVCA00001");
+        let cvc = CardVerifiableCertificate::from_bytes(&encoded).unwrap();
+        assert_eq!(cvc.profile_identifier, 0);
+        assert_eq!(cvc.authority_reference(), "DECVCA00001");
+        assert_eq!(cvc.holder_reference(), "DECVCA00001");
+        assert_eq!(
+            cvc.holder_authorization.role,
+            Oid::new_unwrap("0.4.0.127.0.7.3.1.2.1")
+        );
+        assert_eq!(cvc.holder_authorization.authorization, [0xe3]);
+        assert_eq!(
+            cvc.effective_date,
+            DateTime::new(2024, 1, 15, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            cvc.expiration_date,
+            DateTime::new(2027, 1, 15, 0, 0, 0).unwrap()
+        );
+        assert_eq!(cvc.signature(), hex!("0102030405"));
+        assert_eq!(cvc.body(), &encoded[5..encoded.len() - 8]);
+
+        let SubjectPublicKeyInfo::Ec(info) = cvc.subject_public_key().unwrap() else {
+            panic!("Expected EC key");
+        };
+        let ECAlgoParameters::EcParameters(params) = &info.parameters else {
+            panic!("Expected explicit parameters");
+        };
+        let curve = EllipticCurve::<U256>::from_parameters(params).unwrap();
+        assert_eq!(curve, brainpool_p256r1());
+        assert_eq!(info.point.as_bytes(), G);
+    }
+
+    #[test]
+    fn test_parse_without_domain() {
+        let public_key = [tlv(0x06, &hex!("04007F00070202020203")), tlv(0x86, &G)].concat();
+        let encoded = certificate("DECVCA00001", &public_key, "DEDV00001");
+        let cvc = CardVerifiableCertificate::from_bytes(&encoded).unwrap();
+        assert!(matches!(cvc.public_key, CvcPublicKey::Ec {
+            domain: None,
+            ..
+        }));
+        assert!(cvc.subject_public_key().is_err());
+
+        // Truncated certificate.
+        assert!(CardVerifiableCertificate::from_bytes(&encoded[..30]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rsa() {
+        let public_key = [
+            tlv(0x06, &hex!("04007F00070202020101")),
+            tlv(0x81, &hex!("C1C3")),
+            tlv(0x82, &hex!("010001")),
+        ]
+        .concat();
+        let encoded = certificate("DEDV00001", &public_key, "DEIS00001");
+        let cvc = CardVerifiableCertificate::from_bytes(&encoded).unwrap();
+        let SubjectPublicKeyInfo::Rsa(info) = cvc.subject_public_key().unwrap() else {
+            panic!("Expected RSA key");
+        };
+        assert_eq!(info.modulus.as_bytes(), hex!("00C1C3"));
+        assert_eq!(info.public_exponent.as_bytes(), hex!("010001"));
+    }
+
+    #[test]
+    fn test_parse_public_key_errors() {
+        let rsa = tlv(0x06, &hex!("04007F00070202020101"));
+        let ecdsa = tlv(0x06, &hex!("04007F00070202020203"));
+        let invalid = [
+            // RSA without exponent.
+            [rsa.clone(), tlv(0x81, &hex!("C1C3"))].concat(),
+            // RSA with a point.
+            [
+                rsa,
+                tlv(0x81, &hex!("C1C3")),
+                tlv(0x82, &hex!("010001")),
+                tlv(0x86, &G),
+            ]
+            .concat(),
+            // ECDSA without point, which used to be taken for an RSA key.
+            [ecdsa.clone(), tlv(0x81, &P), tlv(0x82, &A)].concat(),
+            // ECDSA with incomplete domain parameters.
+            [ecdsa, tlv(0x81, &P), tlv(0x86, &G)].concat(),
+            // Unknown algorithm.
+            [tlv(0x06, &hex!("04007F00070202030202")), tlv(0x86, &G)].concat(),
+            // Missing algorithm.
+            tlv(0x86, &G),
+        ];
+        for public_key in invalid {
+            let encoded = certificate("DECVCA00001", &public_key, "DEDV00001");
+            assert!(CardVerifiableCertificate::from_bytes(&encoded).is_err());
+        }
+    }
+}
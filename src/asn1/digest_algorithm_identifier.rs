@@ -23,6 +23,7 @@ pub const ID_SHA512_256: Oid = Oid::new_unwrap("2.16.840.1.101.3.4.2.6");
 // pub const ID_SHA3_256: Oid = Oid::new_unwrap("2.16.840.1.101.3.4.2.7");
 
 #[derive(Clone, PartialEq, Eq, Debug, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DigestAlgorithmIdentifier {
     Sha1(Parameters),
     Sha256(Parameters),
@@ -36,6 +37,7 @@ pub enum DigestAlgorithmIdentifier {
 
 /// See ICAO-9303-10 4.6 Note 2
 #[derive(Clone, Copy, PartialEq, Eq, Debug, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Parameters {
     Absent,
     Null,
@@ -204,3 +206,55 @@ impl<'a> DecodeValue<'a> for DigestAlgorithmIdentifier {
             .map_err(|err| Error::new(err.kind(), reader.position()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        der::{Decode, Encode},
+        hex_literal::hex,
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let cases: [(&[u8], DigestAlgorithmIdentifier); 8] = [
+            (
+                &hex!("3007 0605 2b0e03021a"),
+                DigestAlgorithmIdentifier::Sha1(Parameters::Absent),
+            ),
+            (
+                &hex!("3009 0605 2b0e03021a 0500"),
+                DigestAlgorithmIdentifier::Sha1(Parameters::Null),
+            ),
+            (
+                &hex!("300b 0609 608648016503040201"),
+                DigestAlgorithmIdentifier::Sha256(Parameters::Absent),
+            ),
+            (
+                &hex!("300d 0609 608648016503040201 0500"),
+                DigestAlgorithmIdentifier::Sha256(Parameters::Null),
+            ),
+            (
+                &hex!("300b 0609 608648016503040202"),
+                DigestAlgorithmIdentifier::Sha384(Parameters::Absent),
+            ),
+            (
+                &hex!("300d 0609 608648016503040202 0500"),
+                DigestAlgorithmIdentifier::Sha384(Parameters::Null),
+            ),
+            (
+                &hex!("300b 0609 608648016503040203"),
+                DigestAlgorithmIdentifier::Sha512(Parameters::Absent),
+            ),
+            (
+                &hex!("300d 0609 608648016503040203 0500"),
+                DigestAlgorithmIdentifier::Sha512(Parameters::Null),
+            ),
+        ];
+        for (der, expected) in cases {
+            let decoded = DigestAlgorithmIdentifier::from_der(der).unwrap();
+            assert_eq!(decoded, expected);
+            assert_eq!(decoded.to_der().unwrap(), der);
+        }
+    }
+}
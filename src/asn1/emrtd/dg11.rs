@@ -0,0 +1,208 @@
+use {
+    super::tlv::{push_list, push_tlv, read_list, read_tlv},
+    der::{
+        DecodeValue, EncodeValue, FixedTag, Header, Length, Reader, Result, Tag, TagNumber, Writer,
+    },
+};
+
+/// EF_DG11 holds additional personal details.
+///
+/// See ICAO-9303-10 4.7.11. All fields are optional, present fields are
+/// listed in the tag list. Like [`EfCom`](super::EfCom) the data objects have
+/// two byte tags and are parsed by hand.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EfDg11 {
+    /// Tags of the present data objects, for example `5F0E 5F2B`.
+    pub tag_list:               Vec<u8>,
+    /// Full name in the national characters.
+    pub full_name:              Option<String>,
+    /// Other names, the count is implied by the length.
+    pub other_names:            Vec<String>,
+    pub personal_number:        Option<String>,
+    /// Full date of birth as `YYYYMMDD`.
+    pub full_date_of_birth:     Option<String>,
+    pub place_of_birth:         Option<String>,
+    pub permanent_address:      Option<String>,
+    pub telephone:              Option<String>,
+    pub profession:             Option<String>,
+    pub title:                  Option<String>,
+    pub personal_summary:       Option<String>,
+    /// Image of the proof of citizenship, as JPEG or JPEG 2000.
+    pub proof_of_citizenship:   Option<Vec<u8>>,
+    pub other_valid_td_numbers: Option<String>,
+    pub custody_information:    Option<String>,
+}
+
+const TAG_TAG_LIST: u16 = 0x5c;
+const TAG_FULL_NAME: u16 = 0x5f0e;
+const TAG_OTHER_NAMES: u16 = 0xa0;
+const TAG_OTHER_NAME: u16 = 0x5f0f;
+const TAG_PERSONAL_NUMBER: u16 = 0x5f10;
+const TAG_FULL_DATE_OF_BIRTH: u16 = 0x5f2b;
+const TAG_PLACE_OF_BIRTH: u16 = 0x5f11;
+const TAG_PERMANENT_ADDRESS: u16 = 0x5f42;
+const TAG_TELEPHONE: u16 = 0x5f12;
+const TAG_PROFESSION: u16 = 0x5f13;
+const TAG_TITLE: u16 = 0x5f14;
+const TAG_PERSONAL_SUMMARY: u16 = 0x5f15;
+const TAG_PROOF_OF_CITIZENSHIP: u16 = 0x5f16;
+const TAG_OTHER_VALID_TD_NUMBERS: u16 = 0x5f17;
+const TAG_CUSTODY_INFORMATION: u16 = 0x5f18;
+
+impl EfDg11 {
+    /// The text fields in the order of ICAO-9303-10 4.7.11, excluding other
+    /// names.
+    const fn text_fields(&self) -> [(u16, &Option<String>); 11] {
+        [
+            (TAG_FULL_NAME, &self.full_name),
+            (TAG_PERSONAL_NUMBER, &self.personal_number),
+            (TAG_FULL_DATE_OF_BIRTH, &self.full_date_of_birth),
+            (TAG_PLACE_OF_BIRTH, &self.place_of_birth),
+            (TAG_PERMANENT_ADDRESS, &self.permanent_address),
+            (TAG_TELEPHONE, &self.telephone),
+            (TAG_PROFESSION, &self.profession),
+            (TAG_TITLE, &self.title),
+            (TAG_PERSONAL_SUMMARY, &self.personal_summary),
+            (TAG_OTHER_VALID_TD_NUMBERS, &self.other_valid_td_numbers),
+            (TAG_CUSTODY_INFORMATION, &self.custody_information),
+        ]
+    }
+
+    const fn text_field_mut(&mut self, tag: u16) -> Option<&mut Option<String>> {
+        Some(match tag {
+            TAG_FULL_NAME => &mut self.full_name,
+            TAG_PERSONAL_NUMBER => &mut self.personal_number,
+            TAG_FULL_DATE_OF_BIRTH => &mut self.full_date_of_birth,
+            TAG_PLACE_OF_BIRTH => &mut self.place_of_birth,
+            TAG_PERMANENT_ADDRESS => &mut self.permanent_address,
+            TAG_TELEPHONE => &mut self.telephone,
+            TAG_PROFESSION => &mut self.profession,
+            TAG_TITLE => &mut self.title,
+            TAG_PERSONAL_SUMMARY => &mut self.personal_summary,
+            TAG_OTHER_VALID_TD_NUMBERS => &mut self.other_valid_td_numbers,
+            TAG_CUSTODY_INFORMATION => &mut self.custody_information,
+            _ => return None,
+        })
+    }
+
+    /// The encoded data objects, without the outer tag and length.
+    fn encode_fields(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        push_tlv(&mut out, TAG_TAG_LIST, &self.tag_list)?;
+        let [full_name, rest @ ..] = self.text_fields();
+        if let Some(value) = full_name.1 {
+            push_tlv(&mut out, full_name.0, value.as_bytes())?;
+        }
+        if !self.other_names.is_empty() {
+            push_list(&mut out, TAG_OTHER_NAMES, TAG_OTHER_NAME, &self.other_names)?;
+        }
+        for (tag, value) in rest {
+            if let Some(value) = value {
+                push_tlv(&mut out, tag, value.as_bytes())?;
+            }
+            // The proof of citizenship comes between the summary and the
+            // other travel document numbers.
+            if tag == TAG_PERSONAL_SUMMARY {
+                if let Some(image) = &self.proof_of_citizenship {
+                    push_tlv(&mut out, TAG_PROOF_OF_CITIZENSHIP, image)?;
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl FixedTag for EfDg11 {
+    const TAG: Tag = Tag::Application {
+        constructed: true,
+        number:      TagNumber::N11,
+    };
+}
+
+impl<'a> DecodeValue<'a> for EfDg11 {
+    fn decode_value<R: Reader<'a>>(reader: &mut R, header: Header) -> Result<Self> {
+        let err = || Self::TAG.value_error();
+        let string = |bytes: &[u8]| String::from_utf8(bytes.to_vec()).map_err(|_| err());
+        let value = reader.read_vec(header.length)?;
+        let mut value = value.as_slice();
+
+        let (tag, tag_list, _) = read_tlv(&mut value).map_err(|_| err())?;
+        if tag != TAG_TAG_LIST {
+            return Err(err());
+        }
+        let mut result = Self {
+            tag_list: tag_list.to_vec(),
+            ..Self::default()
+        };
+        while !value.is_empty() {
+            let (tag, field, _) = read_tlv(&mut value).map_err(|_| err())?;
+            match tag {
+                TAG_OTHER_NAMES => {
+                    if !result.other_names.is_empty() {
+                        return Err(err());
+                    }
+                    result.other_names = read_list(field, TAG_OTHER_NAME).ok_or_else(err)?;
+                }
+                TAG_PROOF_OF_CITIZENSHIP => {
+                    if result.proof_of_citizenship.is_some() {
+                        return Err(err());
+                    }
+                    result.proof_of_citizenship = Some(field.to_vec());
+                }
+                tag => {
+                    let slot = result.text_field_mut(tag).ok_or_else(err)?;
+                    if slot.is_some() {
+                        return Err(err());
+                    }
+                    *slot = Some(string(field)?);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl EncodeValue for EfDg11 {
+    fn value_len(&self) -> Result<Length> {
+        Length::try_from(self.encode_fields()?.len())
+    }
+
+    fn encode_value(&self, writer: &mut impl Writer) -> Result<()> {
+        writer.write(&self.encode_fields()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        der::{Decode, Encode},
+        hex_literal::hex,
+    };
+
+    #[test]
+    fn test_decode_dg11() {
+        let der = hex!(
+            "6B 39
+             5C 06 5F0E 5F2B 5F12
+             5F0E 0D 536D6974683C3C4A6F686E3C4A
+             A0 0C 02 01 01 5F0F 06 4A6F6E6E7931
+             5F2B 08 31393734303931 31
+             5F12 05 2B31323334"
+        );
+        let dg11 = EfDg11::from_der(&der).unwrap();
+        assert_eq!(dg11.tag_list, hex!("5F0E 5F2B 5F12"));
+        assert_eq!(dg11.full_name.as_deref(), Some("Smith<<John<J"));
+        assert_eq!(dg11.other_names, ["Jonny1"]);
+        assert_eq!(dg11.full_date_of_birth.as_deref(), Some("19740911"));
+        assert_eq!(dg11.telephone.as_deref(), Some("+1234"));
+        assert_eq!(dg11.place_of_birth, None);
+        assert_eq!(dg11.proof_of_citizenship, None);
+        assert_eq!(dg11.to_der().unwrap(), der);
+
+        // The tag list is required and unknown tags are rejected.
+        assert!(EfDg11::from_der(&hex!("6B 08 5F12 05 2B31323334")).is_err());
+        assert!(EfDg11::from_der(&hex!("6B 07 5C 00 5F19 02 4142")).is_err());
+    }
+}
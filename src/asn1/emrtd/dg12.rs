@@ -0,0 +1,191 @@
+use {
+    super::tlv::{push_list, push_tlv, read_list, read_tlv},
+    der::{
+        DecodeValue, EncodeValue, FixedTag, Header, Length, Reader, Result, Tag, TagNumber, Writer,
+    },
+};
+
+/// EF_DG12 holds additional document details.
+///
+/// See ICAO-9303-10 4.7.12. All fields are optional, present fields are
+/// listed in the tag list.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EfDg12 {
+    /// Tags of the present data objects, for example `5F19 5F26`.
+    pub tag_list: Vec<u8>,
+    pub issuing_authority: Option<String>,
+    /// Date of issue as `YYYYMMDD`.
+    pub date_of_issue: Option<String>,
+    /// Names of other persons, the count is implied by the length.
+    pub other_persons: Vec<String>,
+    pub endorsements: Option<String>,
+    pub tax_exit_requirements: Option<String>,
+    /// Image of the front of the document, as JPEG or JPEG 2000.
+    pub image_front: Option<Vec<u8>>,
+    /// Image of the rear of the document, as JPEG or JPEG 2000.
+    pub image_rear: Option<Vec<u8>>,
+    /// Date and time of personalization as `YYYYMMDDhhmmss`.
+    pub personalization_time: Option<String>,
+    pub personalization_system_serial: Option<String>,
+}
+
+const TAG_TAG_LIST: u16 = 0x5c;
+const TAG_ISSUING_AUTHORITY: u16 = 0x5f19;
+const TAG_DATE_OF_ISSUE: u16 = 0x5f26;
+const TAG_OTHER_PERSONS: u16 = 0xa0;
+const TAG_OTHER_PERSON: u16 = 0x5f1a;
+const TAG_ENDORSEMENTS: u16 = 0x5f1b;
+const TAG_TAX_EXIT_REQUIREMENTS: u16 = 0x5f1c;
+const TAG_IMAGE_FRONT: u16 = 0x5f1d;
+const TAG_IMAGE_REAR: u16 = 0x5f1e;
+const TAG_PERSONALIZATION_TIME: u16 = 0x5f55;
+const TAG_PERSONALIZATION_SYSTEM_SERIAL: u16 = 0x5f56;
+
+impl EfDg12 {
+    const fn text_field_mut(&mut self, tag: u16) -> Option<&mut Option<String>> {
+        Some(match tag {
+            TAG_ISSUING_AUTHORITY => &mut self.issuing_authority,
+            TAG_DATE_OF_ISSUE => &mut self.date_of_issue,
+            TAG_ENDORSEMENTS => &mut self.endorsements,
+            TAG_TAX_EXIT_REQUIREMENTS => &mut self.tax_exit_requirements,
+            TAG_PERSONALIZATION_TIME => &mut self.personalization_time,
+            TAG_PERSONALIZATION_SYSTEM_SERIAL => &mut self.personalization_system_serial,
+            _ => return None,
+        })
+    }
+
+    const fn image_mut(&mut self, tag: u16) -> Option<&mut Option<Vec<u8>>> {
+        Some(match tag {
+            TAG_IMAGE_FRONT => &mut self.image_front,
+            TAG_IMAGE_REAR => &mut self.image_rear,
+            _ => return None,
+        })
+    }
+
+    /// The encoded data objects, without the outer tag and length.
+    fn encode_fields(&self) -> Result<Vec<u8>> {
+        let text = |value: &Option<String>| value.as_ref().map(|s| s.as_bytes().to_vec());
+        let mut out = Vec::new();
+        push_tlv(&mut out, TAG_TAG_LIST, &self.tag_list)?;
+        for (tag, value) in [
+            (TAG_ISSUING_AUTHORITY, text(&self.issuing_authority)),
+            (TAG_DATE_OF_ISSUE, text(&self.date_of_issue)),
+        ] {
+            if let Some(value) = value {
+                push_tlv(&mut out, tag, &value)?;
+            }
+        }
+        if !self.other_persons.is_empty() {
+            push_list(
+                &mut out,
+                TAG_OTHER_PERSONS,
+                TAG_OTHER_PERSON,
+                &self.other_persons,
+            )?;
+        }
+        for (tag, value) in [
+            (TAG_ENDORSEMENTS, text(&self.endorsements)),
+            (TAG_TAX_EXIT_REQUIREMENTS, text(&self.tax_exit_requirements)),
+            (TAG_IMAGE_FRONT, self.image_front.clone()),
+            (TAG_IMAGE_REAR, self.image_rear.clone()),
+            (TAG_PERSONALIZATION_TIME, text(&self.personalization_time)),
+            (
+                TAG_PERSONALIZATION_SYSTEM_SERIAL,
+                text(&self.personalization_system_serial),
+            ),
+        ] {
+            if let Some(value) = value {
+                push_tlv(&mut out, tag, &value)?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl FixedTag for EfDg12 {
+    const TAG: Tag = Tag::Application {
+        constructed: true,
+        number:      TagNumber::N12,
+    };
+}
+
+impl<'a> DecodeValue<'a> for EfDg12 {
+    fn decode_value<R: Reader<'a>>(reader: &mut R, header: Header) -> Result<Self> {
+        let err = || Self::TAG.value_error();
+        let value = reader.read_vec(header.length)?;
+        let mut value = value.as_slice();
+
+        let (tag, tag_list, _) = read_tlv(&mut value).map_err(|_| err())?;
+        if tag != TAG_TAG_LIST {
+            return Err(err());
+        }
+        let mut result = Self {
+            tag_list: tag_list.to_vec(),
+            ..Self::default()
+        };
+        while !value.is_empty() {
+            let (tag, field, _) = read_tlv(&mut value).map_err(|_| err())?;
+            if tag == TAG_OTHER_PERSONS {
+                if !result.other_persons.is_empty() {
+                    return Err(err());
+                }
+                result.other_persons = read_list(field, TAG_OTHER_PERSON).ok_or_else(err)?;
+            } else if let Some(slot) = result.image_mut(tag) {
+                if slot.is_some() {
+                    return Err(err());
+                }
+                *slot = Some(field.to_vec());
+            } else {
+                let slot = result.text_field_mut(tag).ok_or_else(err)?;
+                if slot.is_some() {
+                    return Err(err());
+                }
+                *slot = Some(String::from_utf8(field.to_vec()).map_err(|_| err())?);
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl EncodeValue for EfDg12 {
+    fn value_len(&self) -> Result<Length> {
+        Length::try_from(self.encode_fields()?.len())
+    }
+
+    fn encode_value(&self, writer: &mut impl Writer) -> Result<()> {
+        writer.write(&self.encode_fields()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        der::{Decode, Encode},
+        hex_literal::hex,
+    };
+
+    #[test]
+    fn test_decode_dg12() {
+        let der = hex!(
+            "6C 2D
+             5C 06 5F19 5F26 5F1D
+             5F19 04 55534131
+             5F26 08 3230323431323331
+             A0 0B 02 01 01 5F1A 05 4A616E6531
+             5F1D 03 FFD8FF"
+        );
+        let dg12 = EfDg12::from_der(&der).unwrap();
+        assert_eq!(dg12.tag_list, hex!("5F19 5F26 5F1D"));
+        assert_eq!(dg12.issuing_authority.as_deref(), Some("USA1"));
+        assert_eq!(dg12.date_of_issue.as_deref(), Some("20241231"));
+        assert_eq!(dg12.other_persons, ["Jane1"]);
+        assert_eq!(dg12.image_front.as_deref(), Some(&hex!("FFD8FF")[..]));
+        assert_eq!(dg12.image_rear, None);
+        assert_eq!(dg12.to_der().unwrap(), der);
+
+        // Duplicate fields are rejected.
+        assert!(EfDg12::from_der(&hex!("6C 0C 5C 00 5F1D 02 0102 5F1D 02 0304")).is_err());
+    }
+}
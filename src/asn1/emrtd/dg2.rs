@@ -0,0 +1,277 @@
+use {
+    super::tlv::{push_tlv, read_tlv},
+    der::{
+        DecodeValue, EncodeValue, FixedTag, Header, Length, Reader, Result, Tag, TagNumber, Writer,
+    },
+};
+
+/// EF_DG2 holds the encoded faces of the holder.
+///
+/// See ICAO-9303-10 4.7.2. The data group is a biometric information group
+/// template with one biometric information template per face, for example a
+/// frontal and a profile image.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EfDg2 {
+    faces: Vec<BiometricInformationTemplate>,
+}
+
+/// A biometric information template with its CBEFF header.
+///
+/// See ICAO-9303-10 4.7.2.1 and ISO/IEC 7816-11.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BiometricInformationTemplate {
+    /// Patron header version, `0101` for ICAO-9303.
+    pub header_version:     Option<Vec<u8>>,
+    /// Biometric type, `02` for the facial features.
+    pub biometric_type:     Option<Vec<u8>>,
+    pub biometric_sub_type: Option<Vec<u8>>,
+    /// Creation date and time as `YYYYMMDDhhmmss` in BCD.
+    pub creation_datetime:  Option<Vec<u8>>,
+    /// Validity period as two dates `YYYYMMDD` in BCD.
+    pub validity_period:    Option<Vec<u8>>,
+    /// Creator of the biometric reference data.
+    pub creator:            Option<Vec<u8>>,
+    pub format_owner:       Vec<u8>,
+    pub format_type:        Vec<u8>,
+    /// The biometric data block, an ISO/IEC 19794-5 facial record.
+    ///
+    /// Enciphered data with tag `7F2E` is accepted but encoded with the
+    /// plain tag `5F2E`.
+    pub biometric_data:     Vec<u8>,
+}
+
+const TAG_GROUP: u16 = 0x7f61;
+const TAG_COUNT: u16 = 0x02;
+const TAG_TEMPLATE: u16 = 0x7f60;
+const TAG_HEADER: u16 = 0xa1;
+const TAG_HEADER_VERSION: u16 = 0x80;
+const TAG_BIOMETRIC_TYPE: u16 = 0x81;
+const TAG_BIOMETRIC_SUB_TYPE: u16 = 0x82;
+const TAG_CREATION_DATETIME: u16 = 0x83;
+const TAG_VALIDITY_PERIOD: u16 = 0x85;
+const TAG_CREATOR: u16 = 0x86;
+const TAG_FORMAT_OWNER: u16 = 0x87;
+const TAG_FORMAT_TYPE: u16 = 0x88;
+const TAG_BIOMETRIC_DATA: u16 = 0x5f2e;
+const TAG_BIOMETRIC_DATA_ENCIPHERED: u16 = 0x7f2e;
+
+impl EfDg2 {
+    pub const fn new(faces: Vec<BiometricInformationTemplate>) -> Self {
+        Self { faces }
+    }
+
+    /// All encoded faces, in the order of the data group.
+    pub fn faces(&self) -> &[BiometricInformationTemplate] {
+        &self.faces
+    }
+
+    /// The encoded group template, without the outer tag and length.
+    fn encode_fields(&self) -> Result<Vec<u8>> {
+        let count = u8::try_from(self.faces.len()).map_err(|_| Self::TAG.length_error())?;
+        let mut group = Vec::new();
+        push_tlv(&mut group, TAG_COUNT, &[count])?;
+        for face in &self.faces {
+            push_tlv(&mut group, TAG_TEMPLATE, &face.encode_fields()?)?;
+        }
+        let mut out = Vec::new();
+        push_tlv(&mut out, TAG_GROUP, &group)?;
+        Ok(out)
+    }
+}
+
+impl BiometricInformationTemplate {
+    const fn header_field_mut(&mut self, tag: u16) -> Option<&mut Option<Vec<u8>>> {
+        Some(match tag {
+            TAG_HEADER_VERSION => &mut self.header_version,
+            TAG_BIOMETRIC_TYPE => &mut self.biometric_type,
+            TAG_BIOMETRIC_SUB_TYPE => &mut self.biometric_sub_type,
+            TAG_CREATION_DATETIME => &mut self.creation_datetime,
+            TAG_VALIDITY_PERIOD => &mut self.validity_period,
+            TAG_CREATOR => &mut self.creator,
+            _ => return None,
+        })
+    }
+
+    /// Parse the contents of a `7F60` template.
+    ///
+    /// Returns `None` if the template is malformed.
+    fn decode_fields(mut value: &[u8]) -> Option<Self> {
+        let (TAG_HEADER, mut header, _) = read_tlv(&mut value).ok()? else {
+            return None;
+        };
+        let mut result = Self::default();
+        let (mut format_owner, mut format_type) = (None, None);
+        while !header.is_empty() {
+            let (tag, field, _) = read_tlv(&mut header).ok()?;
+            let slot = match tag {
+                TAG_FORMAT_OWNER => &mut format_owner,
+                TAG_FORMAT_TYPE => &mut format_type,
+                tag => result.header_field_mut(tag)?,
+            };
+            if slot.replace(field.to_vec()).is_some() {
+                return None;
+            }
+        }
+        result.format_owner = format_owner?;
+        result.format_type = format_type?;
+
+        let (TAG_BIOMETRIC_DATA | TAG_BIOMETRIC_DATA_ENCIPHERED, data, _) =
+            read_tlv(&mut value).ok()?
+        else {
+            return None;
+        };
+        if !value.is_empty() {
+            return None;
+        }
+        result.biometric_data = data.to_vec();
+        Some(result)
+    }
+
+    /// The encoded template, without the outer tag and length.
+    fn encode_fields(&self) -> Result<Vec<u8>> {
+        let mut header = Vec::new();
+        for (tag, value) in [
+            (TAG_HEADER_VERSION, &self.header_version),
+            (TAG_BIOMETRIC_TYPE, &self.biometric_type),
+            (TAG_BIOMETRIC_SUB_TYPE, &self.biometric_sub_type),
+            (TAG_CREATION_DATETIME, &self.creation_datetime),
+            (TAG_VALIDITY_PERIOD, &self.validity_period),
+            (TAG_CREATOR, &self.creator),
+        ] {
+            if let Some(value) = value {
+                push_tlv(&mut header, tag, value)?;
+            }
+        }
+        push_tlv(&mut header, TAG_FORMAT_OWNER, &self.format_owner)?;
+        push_tlv(&mut header, TAG_FORMAT_TYPE, &self.format_type)?;
+        let mut out = Vec::new();
+        push_tlv(&mut out, TAG_HEADER, &header)?;
+        push_tlv(&mut out, TAG_BIOMETRIC_DATA, &self.biometric_data)?;
+        Ok(out)
+    }
+}
+
+impl FixedTag for EfDg2 {
+    const TAG: Tag = Tag::Application {
+        constructed: true,
+        number:      TagNumber::N21,
+    };
+}
+
+impl<'a> DecodeValue<'a> for EfDg2 {
+    fn decode_value<R: Reader<'a>>(reader: &mut R, header: Header) -> Result<Self> {
+        let err = || Self::TAG.value_error();
+        let value = reader.read_vec(header.length)?;
+        let mut value = value.as_slice();
+
+        let (TAG_GROUP, mut group, _) = read_tlv(&mut value).map_err(|_| err())? else {
+            return Err(err());
+        };
+        if !value.is_empty() {
+            return Err(err());
+        }
+        let (TAG_COUNT, &[count], _) = read_tlv(&mut group).map_err(|_| err())? else {
+            return Err(err());
+        };
+        let mut faces = Vec::with_capacity(count.into());
+        while !group.is_empty() {
+            let (TAG_TEMPLATE, template, _) = read_tlv(&mut group).map_err(|_| err())? else {
+                return Err(err());
+            };
+            faces.push(BiometricInformationTemplate::decode_fields(template).ok_or_else(err)?);
+        }
+        if faces.len() != usize::from(count) {
+            return Err(err());
+        }
+        Ok(Self { faces })
+    }
+}
+
+impl EncodeValue for EfDg2 {
+    fn value_len(&self) -> Result<Length> {
+        Length::try_from(self.encode_fields()?.len())
+    }
+
+    fn encode_value(&self, writer: &mut impl Writer) -> Result<()> {
+        writer.write(&self.encode_fields()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        der::{Decode, Encode},
+        hex_literal::hex,
+    };
+
+    #[test]
+    fn test_decode_dg2() {
+        let der = hex!(
+            "75 4C
+             7F61 49
+                02 01 02
+                7F60 20
+                   A1 18
+                      80 02 0101
+                      81 01 02
+                      83 07 20240131120000
+                      87 02 0101
+                      88 02 0008
+                   5F2E 03 464143
+                7F60 20
+                   A1 18
+                      81 01 02
+                      82 01 01
+                      85 08 2024013120340131
+                      87 02 0101
+                      88 02 0008
+                   5F2E 03 505246"
+        );
+        let dg2 = EfDg2::from_der(&der).unwrap();
+        let [front, profile] = dg2.faces() else {
+            panic!("expected two faces");
+        };
+        assert_eq!(front.header_version.as_deref(), Some(&hex!("0101")[..]));
+        assert_eq!(front.biometric_type.as_deref(), Some(&[0x02][..]));
+        assert_eq!(front.biometric_sub_type, None);
+        assert_eq!(
+            front.creation_datetime.as_deref(),
+            Some(&hex!("20240131120000")[..])
+        );
+        assert_eq!(front.format_type, hex!("0008"));
+        assert_eq!(front.biometric_data, b"FAC");
+        assert_eq!(profile.biometric_sub_type.as_deref(), Some(&[0x01][..]));
+        assert_eq!(
+            profile.validity_period.as_deref(),
+            Some(&hex!("2024013120340131")[..])
+        );
+        assert_eq!(profile.biometric_data, b"PRF");
+        assert_eq!(dg2.to_der().unwrap(), der);
+        assert_eq!(EfDg2::new(dg2.faces().to_vec()), dg2);
+
+        // The count must match the number of templates.
+        let mut bad = der;
+        bad[7] = 0x03;
+        assert!(EfDg2::from_der(&bad).is_err());
+    }
+
+    #[test]
+    fn test_decode_dg2_dataset() {
+        let der = include_bytes!("../../../tests/dataset/Datagroup2.bin");
+        let dg2 = EfDg2::from_der(der).unwrap();
+        let [face] = dg2.faces() else {
+            panic!("expected one face");
+        };
+        assert_eq!(face.header_version.as_deref(), None);
+        assert_eq!(face.biometric_type.as_deref(), Some(&[0x02][..]));
+        assert_eq!(face.biometric_sub_type.as_deref(), Some(&[0x00][..]));
+        assert_eq!(face.format_owner, hex!("0101"));
+        assert_eq!(face.format_type, hex!("0008"));
+        assert_eq!(face.biometric_data.len(), 0x3ac5);
+        assert!(face.biometric_data.starts_with(b"FAC\x00010\x00"));
+        assert_eq!(dg2.to_der().unwrap(), der);
+    }
+}
@@ -1,37 +1,90 @@
+mod dg11;
+mod dg12;
+mod dg2;
 pub mod security_info;
+pub(crate) mod tlv;
 
+pub use self::{
+    dg11::EfDg11,
+    dg12::EfDg12,
+    dg2::{BiometricInformationTemplate, EfDg2},
+};
 use {
-    self::security_info::{
-        ChipAuthenticationInfo, ChipAuthenticationPublicKeyInfo, SecurityInfo, SecurityInfos,
+    self::{
+        security_info::{
+            ActiveAuthenticationInfo, ChipAuthenticationInfo, ChipAuthenticationPublicKeyInfo,
+            SecurityInfo, SecurityInfos,
+        },
+        tlv::read_tlv,
+    },
+    super::{
+        public_key_info::SubjectPublicKeyInfo, ApplicationTagged, ContentInfo, ContentType,
+        DigestAlgorithmIdentifier,
     },
-    super::{ApplicationTagged, ContentInfo, ContentType, DigestAlgorithmIdentifier},
-    crate::ensure_err,
     cms::signed_data::{EncapsulatedContentInfo, SignedData, SignerInfo},
     der::{
         asn1::{ObjectIdentifier as Oid, OctetString, PrintableString},
-        Decode, Error, ErrorKind, Length, Result, Sequence, Tag,
+        Decode, DecodeValue, Encode, EncodeValue, FixedTag, Header, Length, Reader, Result,
+        Sequence, Tag, TagNumber, Writer,
     },
-    security_info::{ChipAuthenticationProtocol, KeyAgreement, SymmetricCipher},
 };
+#[cfg(feature = "serde")]
+use {
+    crate::asn1::serde_adapters::{AsStr, Hex},
+    serde_with::As,
+};
+
+/// EF_COM holds the LDS version and the tags of the present data groups.
+///
+/// See ICAO-9303-10 4.6.1. The data objects have two byte application tags,
+/// which the `der` crate does not support, so they are parsed by hand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EfCom {
+    /// LDS version as `aabb`, for example `0107` for version 1.7.
+    pub lds_version:     String,
+    /// Unicode version as `aabbcc`, for example `040000` for version 4.0.0.
+    pub unicode_version: String,
+    /// Tags of the present data groups, for example `0x61` for EF_DG1.
+    pub tag_list:        Vec<u8>,
+}
 
 /// EF_CardAccess is a [`SecurityInfos`] with no further wrapping.
 ///
 /// See ICAO-9303-10 3.11.3
 pub type EfCardAccess = SecurityInfos;
 
+/// EF_CardSecurity is a [`SignedData`] structure with [`SecurityInfos`] as
+/// encapsulated content.
+///
+/// Like EF_CardAccess it lives in the master file, but it can only be read
+/// after PACE has been performed. It contains at least all the
+/// [`SecurityInfos`] from EF_CardAccess, signed by the document signer.
+///
+/// Unlike EF_SOD it has no application tag.
+///
+/// See ICAO-9303-10 3.11.5 and ICAO-9303-11 9.2.
+pub type EfCardSecurity = ContentInfo<SignedData>;
+
 /// EF_DG14 is a [`SecurityInfos`] with no further wrapping.
 ///
 /// See ICAO-9303-10 3.11.4
 pub type EfDg14 = ApplicationTagged<14, SecurityInfos>;
 
+/// EF_DG15 holds the Active Authentication public key.
+///
+/// See ICAO-9303-10 4.7.15
+pub type EfDg15 = ApplicationTagged<15, SubjectPublicKeyInfo>;
+
 /// EF_SOD is a wrapped [`SignedData`] structure.
 ///
-/// See ICAO-9303-10 4.7.14. The 0x6E tag is an ASN1 Application
-/// constructed application tag with the value 14.
+/// See ICAO-9303-10 4.7.14. The 0x77 tag is an ASN1 Application
+/// constructed application tag with the value 23.
 pub type EfSod = ApplicationTagged<23, ContentInfo<SignedData>>;
 
 /// ICAO-9303-10 4.6.2.3
 #[derive(Clone, Debug, PartialEq, Eq, Sequence)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LdsSecurityObject {
     pub version:                u64,
     pub hash_algorithm:         DigestAlgorithmIdentifier,
@@ -41,50 +94,128 @@ pub struct LdsSecurityObject {
 
 /// ICAO-9303-10 4.6.2.3
 #[derive(Clone, Debug, PartialEq, Eq, Sequence)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LdsVersionInfo {
+    #[cfg_attr(feature = "serde", serde(with = "As::<AsStr>"))]
     pub lds_version:     PrintableString,
+    #[cfg_attr(feature = "serde", serde(with = "As::<AsStr>"))]
     pub unicode_version: PrintableString,
 }
 
 /// ICAO-9303-10 4.6.2.3
 #[derive(Clone, Debug, PartialEq, Eq, Sequence)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataGroupHash {
     pub data_group_number: u64,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Hex>"))]
     pub hash_value:        OctetString,
 }
 
+const TAG_LDS_VERSION: u16 = 0x5f01;
+const TAG_UNICODE_VERSION: u16 = 0x5f36;
+const TAG_TAG_LIST: u16 = 0x5c;
+
+impl EfCom {
+    fn fields(&self) -> [(u16, &[u8]); 3] {
+        [
+            (TAG_LDS_VERSION, self.lds_version.as_bytes()),
+            (TAG_UNICODE_VERSION, self.unicode_version.as_bytes()),
+            (TAG_TAG_LIST, &self.tag_list),
+        ]
+    }
+}
+
+impl FixedTag for EfCom {
+    const TAG: Tag = Tag::Application {
+        constructed: true,
+        number:      TagNumber::N0,
+    };
+}
+
+impl<'a> DecodeValue<'a> for EfCom {
+    fn decode_value<R: Reader<'a>>(reader: &mut R, header: Header) -> Result<Self> {
+        let value = reader.read_vec(header.length)?;
+        let mut value = value.as_slice();
+        let mut next = |expected: u16| match read_tlv(&mut value) {
+            Ok((tag, value, _)) if tag == expected => Ok(value.to_vec()),
+            _ => Err(Self::TAG.value_error()),
+        };
+        let string = |bytes: Vec<u8>| String::from_utf8(bytes).map_err(|_| Self::TAG.value_error());
+        let result = Self {
+            lds_version:     string(next(TAG_LDS_VERSION)?)?,
+            unicode_version: string(next(TAG_UNICODE_VERSION)?)?,
+            tag_list:        next(TAG_TAG_LIST)?,
+        };
+        if !value.is_empty() {
+            return Err(Self::TAG.value_error());
+        }
+        Ok(result)
+    }
+}
+
+impl EncodeValue for EfCom {
+    fn value_len(&self) -> Result<Length> {
+        self.fields()
+            .into_iter()
+            .try_fold(Length::ZERO, |sum, (tag, value)| {
+                let tag_len = if tag > 0xff { 2_u8 } else { 1 };
+                let value_len = Length::try_from(value.len())?;
+                sum + Length::from(tag_len) + value_len.encoded_len()? + value_len
+            })
+    }
+
+    fn encode_value(&self, writer: &mut impl Writer) -> Result<()> {
+        for (tag, value) in self.fields() {
+            match tag.to_be_bytes() {
+                [0, tag] => writer.write_byte(tag)?,
+                tag => writer.write(&tag)?,
+            }
+            Length::try_from(value.len())?.encode(writer)?;
+            writer.write(value)?;
+        }
+        Ok(())
+    }
+}
+
+impl ContentType for SecurityInfos {
+    /// ICAO-9303-11 9.2, `id-SecurityObject` from BSI TR-03110-3.
+    const CONTENT_TYPE: Oid = Oid::new_unwrap("0.4.0.127.0.7.3.2.1");
+}
+
 impl ContentType for LdsSecurityObject {
     /// ICAO-9303-10 4.6.2.3
     const CONTENT_TYPE: Oid = Oid::new_unwrap("2.23.136.1.1.1");
 }
 
 impl EfDg14 {
+    /// The Chip Authentication protocol and the matching public key.
+    ///
+    /// Documents that only have a `ChipAuthenticationPublicKeyInfo` use
+    /// version 1 with the protocol inferred from the public key, see
+    /// [`ChipAuthenticationPublicKeyInfo::inferred_protocol`].
     pub fn chip_authentication(
         &self,
-    ) -> Option<(&ChipAuthenticationInfo, &ChipAuthenticationPublicKeyInfo)> {
+    ) -> Option<(ChipAuthenticationInfo, &ChipAuthenticationPublicKeyInfo)> {
         // For now, we take the first ChipAuthentication and
         // ChipAuthenticationPublicKey.
-        let ca = self
-            .0
-            .iter()
-            .find_map(|si| match si {
-                SecurityInfo::ChipAuthentication(ca) => Some(ca),
+        let ca = self.0.iter().find_map(|si| match si {
+            SecurityInfo::ChipAuthentication(ca) => Some(*ca),
+            _ => None,
+        });
+        let Some(ca) = ca else {
+            let capk = self.0.iter().find_map(|si| match si {
+                SecurityInfo::ChipAuthenticationPublicKey(capk) => Some(capk),
                 _ => None,
-            })
-            .unwrap_or(
-                // Some passports only have ChipAuthenticationPublicKey. In this case we assume
-                // that the Cipher is the 3DES-CBC-CBC.
-                &ChipAuthenticationInfo {
-                    protocol: ChipAuthenticationProtocol {
-                        key_agreement: KeyAgreement::Ecdh, // TODO: From pubkey
-                        cipher:        Some(SymmetricCipher::Tdes),
-                    },
-                    version:  1,
-                    key_id:   None,
-                },
-            );
+            })?;
+            let ca = ChipAuthenticationInfo {
+                protocol: capk.inferred_protocol(),
+                version:  1,
+                key_id:   capk.key_id,
+            };
+            return Some((ca, capk));
+        };
         // Do some verification checks
-        if ca.protocol.cipher.is_none() || ca.version != 1 {
+        if ca.protocol.cipher.is_none() || !matches!(ca.version, 1 | 2) {
             // TODO: Error message
             return None;
         }
@@ -98,6 +229,22 @@ impl EfDg14 {
         })?;
         Some((ca, capk))
     }
+
+    /// Returns the Active Authentication info if present.
+    ///
+    /// This is only present for ECDSA based Active Authentication.
+    pub fn active_authentication(&self) -> Option<&ActiveAuthenticationInfo> {
+        self.0.iter().find_map(|si| match si {
+            SecurityInfo::ActiveAuthentication(aa) => Some(aa),
+            _ => None,
+        })
+    }
+}
+
+impl EfCardSecurity {
+    pub fn security_infos(&self) -> Result<SecurityInfos> {
+        self.decode_econtent()
+    }
 }
 
 impl EfSod {
@@ -105,23 +252,38 @@ impl EfSod {
         &self.0 .0
     }
 
-    pub fn signer_info(&self) -> &SignerInfo {
-        // TODO: Handle errors
-        self.signed_data()
-            .signer_infos
-            .0
-            .as_slice()
+    /// All signer infos, a `SignedData` may have several signers.
+    pub fn signer_infos(&self) -> &[SignerInfo] {
+        self.signed_data().signer_infos.0.as_slice()
+    }
+
+    /// The first signer info, errors if the `SignedData` has none.
+    pub fn signer_info(&self) -> Result<&SignerInfo> {
+        self.signer_infos()
             .first()
-            .expect("missing signer info")
+            .ok_or_else(|| Tag::Set.value_error())
     }
 
-    pub fn signature(&self) -> &[u8] {
-        self.signer_info().signature.as_bytes()
+    /// Signature of the first signer.
+    #[deprecated(note = "only covers the first signer, use `signer_infos`")]
+    pub fn signature(&self) -> Result<&[u8]> {
+        Ok(self.signer_info()?.signature.as_bytes())
     }
 
     /// Returns the Blake3 hash of the document signature
-    pub fn document_hash(&self) -> [u8; 32] {
-        *blake3::hash(self.signature()).as_bytes()
+    #[deprecated(note = "not reproducible with standard tools, use `canonical_hash`")]
+    pub fn document_hash(&self) -> Result<[u8; 32]> {
+        Ok(*blake3::hash(self.signer_info()?.signature.as_bytes()).as_bytes())
+    }
+
+    /// Hash of the DER encoded EF.SOD using the signer's digest algorithm.
+    pub fn canonical_hash(&self) -> Result<Vec<u8>> {
+        let digest_alg = &self.signer_info()?.digest_alg;
+        let algorithm = DigestAlgorithmIdentifier::from_der(&digest_alg.to_der()?)?;
+        if let DigestAlgorithmIdentifier::Unknown(_) = algorithm {
+            return Err(Tag::ObjectIdentifier.value_error());
+        }
+        Ok(algorithm.hash_der(self))
     }
 
     pub fn encapsulated_content(&self) -> &EncapsulatedContentInfo {
@@ -129,28 +291,19 @@ impl EfSod {
     }
 
     pub fn lds_security_object(&self) -> Result<LdsSecurityObject> {
-        let econ = self.encapsulated_content();
-        ensure_err!(
-            econ.econtent_type == LdsSecurityObject::CONTENT_TYPE,
-            Error::new(
-                ErrorKind::OidUnknown {
-                    oid: econ.econtent_type,
-                },
-                Length::ZERO,
-            )
-        );
-        let octet_string = econ
-            .econtent
-            .as_ref()
-            .ok_or(Error::new(
-                ErrorKind::TagUnexpected {
-                    expected: Some(Tag::OctetString),
-                    actual:   Tag::Null, // Actually None
-                },
-                Length::ZERO,
-            ))?
-            .decode_as::<OctetString>()?;
-        LdsSecurityObject::from_der(octet_string.as_bytes())
+        self.0.decode_econtent()
+    }
+}
+
+impl DataGroupHash {
+    /// Whether `data` hashes to [`Self::hash_value`] under `algorithm`.
+    ///
+    /// Always false for unknown algorithms.
+    pub fn verify(&self, data: &[u8], algorithm: &DigestAlgorithmIdentifier) -> bool {
+        if matches!(algorithm, DigestAlgorithmIdentifier::Unknown(_)) {
+            return false;
+        }
+        algorithm.hash_bytes(data) == self.hash_value.as_bytes()
     }
 }
 
@@ -163,4 +316,173 @@ impl LdsSecurityObject {
         }
         None
     }
+
+    /// The LDS version info, or LDS 1.7 with Unicode 4.0.0 if absent.
+    ///
+    /// The field is only present from LDS 1.8 onwards (version 1 of the
+    /// security object), see ICAO-9303-10 4.6.2.3.
+    pub fn lds_version_info_or_default(&self) -> LdsVersionInfo {
+        self.lds_version_info
+            .clone()
+            .unwrap_or_else(|| LdsVersionInfo {
+                lds_version:     PrintableString::new("0107").unwrap(),
+                unicode_version: PrintableString::new("040000").unwrap(),
+            })
+    }
+}
+
+impl LdsVersionInfo {
+    pub fn lds_version_str(&self) -> &str {
+        self.lds_version.as_str()
+    }
+
+    pub fn unicode_version_str(&self) -> &str {
+        self.unicode_version.as_str()
+    }
+
+    /// Major and minor LDS version.
+    ///
+    /// Accepts the `aabb` format from ICAO-9303-10 as well as `a.b`.
+    pub fn lds_version_tuple(&self) -> Option<(u8, u8)> {
+        let version = self.lds_version_str();
+        let (major, minor) = match version.split_once('.') {
+            Some(parts) => parts,
+            None if version.len() == 4 && version.is_ascii() => version.split_at(2),
+            None => return None,
+        };
+        Some((major.parse().ok()?, minor.parse().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            security_info::{ChipAuthenticationProtocol, KeyAgreement},
+            *,
+        },
+        crate::asn1::{
+            ordered_set::OrderedSet,
+            public_key_info::{DhAlgoParameters, DhPublicKeyInfo},
+            DigestAlgorithmParameters,
+        },
+        cms::{
+            content_info::CmsVersion,
+            signed_data::{SignedData, SignerInfos},
+        },
+        der::{
+            asn1::{Int, SetOfVec},
+            Any, Decode, Encode,
+        },
+        hex_literal::hex,
+    };
+
+    #[test]
+    fn test_decode_com() {
+        let der = hex!("6017 5f0104 30313037 5f3606 303430303030 5c05 61756376 6e");
+        let com = EfCom::from_der(&der).unwrap();
+        assert_eq!(com.lds_version, "0107");
+        assert_eq!(com.unicode_version, "040000");
+        assert_eq!(com.tag_list, hex!("61756376 6e"));
+        assert_eq!(com.to_der().unwrap(), der);
+
+        // Fields are required and in order.
+        let der = hex!("600d 5f3606 303430303030 5c03 617563");
+        assert!(EfCom::from_der(&der).is_err());
+    }
+
+    #[test]
+    fn test_lds_version_info() {
+        let mut lds = LdsSecurityObject {
+            version:                0,
+            hash_algorithm:         DigestAlgorithmIdentifier::Sha256(
+                DigestAlgorithmParameters::Absent,
+            ),
+            data_group_hash_values: vec![],
+            lds_version_info:       None,
+        };
+        let info = lds.lds_version_info_or_default();
+        assert_eq!(info.lds_version_str(), "0107");
+        assert_eq!(info.unicode_version_str(), "040000");
+        assert_eq!(info.lds_version_tuple(), Some((1, 7)));
+
+        lds.version = 1;
+        lds.lds_version_info = Some(LdsVersionInfo {
+            lds_version:     PrintableString::new("0108").unwrap(),
+            unicode_version: PrintableString::new("080000").unwrap(),
+        });
+        let info = lds.lds_version_info_or_default();
+        assert_eq!(info.lds_version_str(), "0108");
+        assert_eq!(info.unicode_version_str(), "080000");
+        assert_eq!(info.lds_version_tuple(), Some((1, 8)));
+
+        let version = |s| LdsVersionInfo {
+            lds_version:     PrintableString::new(s).unwrap(),
+            unicode_version: PrintableString::new("040000").unwrap(),
+        };
+        assert_eq!(version("1.7").lds_version_tuple(), Some((1, 7)));
+        assert_eq!(version("17").lds_version_tuple(), None);
+        assert_eq!(version("1.x").lds_version_tuple(), None);
+    }
+
+    #[test]
+    fn test_chip_authentication_fallback() {
+        let capk = ChipAuthenticationPublicKeyInfo {
+            // Deliberately inconsistent, the public key algorithm wins.
+            protocol:   KeyAgreement::Ecdh,
+            public_key: SubjectPublicKeyInfo::Dh(DhPublicKeyInfo {
+                parameters: DhAlgoParameters {
+                    prime:                Int::new(&[23]).unwrap(),
+                    base:                 Int::new(&[5]).unwrap(),
+                    private_value_length: None,
+                },
+                public_key: Int::new(&[8]).unwrap(),
+            }),
+            key_id:     Some(1),
+        };
+        let dg14: EfDg14 =
+            ApplicationTagged(OrderedSet(vec![SecurityInfo::ChipAuthenticationPublicKey(
+                capk.clone(),
+            )]));
+        let (ca, pk) = dg14.chip_authentication().unwrap();
+        assert_eq!(ca, ChipAuthenticationInfo {
+            protocol: ChipAuthenticationProtocol {
+                key_agreement: KeyAgreement::Dh,
+                cipher:        None,
+            },
+            version:  1,
+            key_id:   Some(1),
+        });
+        assert_eq!(pk, &capk);
+
+        let dg14: EfDg14 = ApplicationTagged(OrderedSet(vec![]));
+        assert!(dg14.chip_authentication().is_none());
+    }
+
+    #[test]
+    fn test_decode_card_security() {
+        // EF.CardAccess with PACE-ECDH-GM-AES-CBC-CMAC-128 using brainpoolP256r1.
+        let security_infos = hex!("3114 3012 060a04007f00070202040202 020102 02010d");
+        let card_security = ContentInfo(SignedData {
+            version:            CmsVersion::V3,
+            digest_algorithms:  SetOfVec::new(),
+            encap_content_info: EncapsulatedContentInfo {
+                econtent_type: SecurityInfos::CONTENT_TYPE,
+                econtent:      Some(
+                    Any::encode_from(&OctetString::new(security_infos.as_slice()).unwrap())
+                        .unwrap(),
+                ),
+            },
+            certificates:       None,
+            crls:               None,
+            signer_infos:       SignerInfos(SetOfVec::new()),
+        });
+        let der = card_security.to_der().unwrap();
+        assert_eq!(der[0], 0x30);
+
+        let card_security = EfCardSecurity::from_der(&der).unwrap();
+        let infos = card_security.security_infos().unwrap();
+        assert_eq!(infos.to_der().unwrap(), security_infos);
+        assert!(matches!(infos.0.as_slice(), [SecurityInfo::Pace(_)]));
+    }
 }
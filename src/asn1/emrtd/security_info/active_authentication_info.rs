@@ -0,0 +1,91 @@
+#[cfg(feature = "serde")]
+use {crate::asn1::serde_adapters::Dotted, serde_with::As};
+use {
+    crate::asn1::{DigestAlgorithmIdentifier, DigestAlgorithmParameters},
+    der::{asn1::ObjectIdentifier as Oid, Sequence},
+};
+
+pub const ID_ACTIVE_AUTHENTICATION: Oid = Oid::new_unwrap("2.23.136.1.1.5");
+
+/// Plain format ECDSA signatures, see BSI TR-03111 5.2.1.
+pub const ECDSA_PLAIN_SIGNATURES: Oid = Oid::new_unwrap("0.4.0.127.0.7.1.1.4.1");
+
+/// See ICAO 9303-11 9.2.8.
+///
+/// ```asn1
+/// ActiveAuthenticationInfo ::= SEQUENCE {
+///     protocol id-AA,
+///     version INTEGER -- MUST be 1
+///     signatureAlgorithm OBJECT IDENTIFIER
+/// }
+/// ```
+///
+/// The info is only present for ECDSA based Active Authentication, RSA keys
+/// use ISO/IEC 9796-2 with the hash algorithm given in the signature trailer.
+#[derive(Clone, Debug, PartialEq, Eq, Sequence)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActiveAuthenticationInfo {
+    #[cfg_attr(feature = "serde", serde(with = "As::<Dotted>"))]
+    pub protocol:            Oid,
+    pub version:             u64,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Dotted>"))]
+    pub signature_algorithm: Oid,
+}
+
+impl ActiveAuthenticationInfo {
+    /// Returns true if the OID is `id-AA` or in the `id-AA` arc.
+    pub fn is_protocol(oid: Oid) -> bool {
+        oid == ID_ACTIVE_AUTHENTICATION || oid.parent() == Some(ID_ACTIVE_AUTHENTICATION)
+    }
+
+    /// The hash algorithm for a plain ECDSA signature algorithm.
+    ///
+    /// Returns `None` if the signature algorithm is not recognized.
+    pub fn digest_algorithm(&self) -> Option<DigestAlgorithmIdentifier> {
+        if self.signature_algorithm.parent() != Some(ECDSA_PLAIN_SIGNATURES) {
+            return None;
+        }
+        let params = DigestAlgorithmParameters::Absent;
+        match self.signature_algorithm.arc(10)? {
+            1 => Some(DigestAlgorithmIdentifier::Sha1(params)),
+            2 => Some(DigestAlgorithmIdentifier::Sha224(params)),
+            3 => Some(DigestAlgorithmIdentifier::Sha256(params)),
+            4 => Some(DigestAlgorithmIdentifier::Sha384(params)),
+            5 => Some(DigestAlgorithmIdentifier::Sha512(params)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::asn1::emrtd::security_info::SecurityInfo, der::Decode, hex_literal::hex,
+    };
+
+    #[test]
+    fn test_parse() {
+        // id-AA, version 1, ecdsa-plain-SHA256.
+        let der = hex!("3017 0606 678108010105 020101 060A 04007F00070101040103");
+        let info = ActiveAuthenticationInfo::from_der(&der).unwrap();
+        assert_eq!(info.protocol, ID_ACTIVE_AUTHENTICATION);
+        assert_eq!(info.version, 1);
+        assert_eq!(
+            info.digest_algorithm(),
+            Some(DigestAlgorithmIdentifier::Sha256(
+                DigestAlgorithmParameters::Absent
+            ))
+        );
+        assert!(matches!(
+            SecurityInfo::from_der(&der).unwrap(),
+            SecurityInfo::ActiveAuthentication(aa) if aa == info
+        ));
+
+        // Not a plain ECDSA signature algorithm.
+        let info = ActiveAuthenticationInfo {
+            signature_algorithm: Oid::new_unwrap("1.2.840.10045.4.3.2"),
+            ..info
+        };
+        assert_eq!(info.digest_algorithm(), None);
+    }
+}
@@ -12,6 +12,7 @@ pub const CHIP_AUTHENTICATION_OID: Oid = Oid::new_unwrap("0.4.0.127.0.7.2.2.3");
 
 /// See ICAO 9303-11 9.2.5.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Sequence)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChipAuthenticationInfo {
     pub protocol: ChipAuthenticationProtocol,
     pub version:  u64,
@@ -20,6 +21,7 @@ pub struct ChipAuthenticationInfo {
 
 /// See ICAO 9303-11 9.2.6.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Sequence)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChipAuthenticationPublicKeyInfo {
     pub protocol:   KeyAgreement,
     pub public_key: SubjectPublicKeyInfo,
@@ -28,6 +30,7 @@ pub struct ChipAuthenticationPublicKeyInfo {
 
 /// See ICAO 9303-11 9.2.7.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChipAuthenticationProtocol {
     pub key_agreement: KeyAgreement,
     pub cipher:        Option<SymmetricCipher>,
@@ -36,7 +39,46 @@ pub struct ChipAuthenticationProtocol {
 impl ChipAuthenticationInfo {
     pub fn ensure_valid(self) {
         assert!(self.protocol.cipher.is_some());
-        assert_eq!(self.version, 1);
+        assert!(matches!(self.version, 1 | 2));
+    }
+}
+
+impl ChipAuthenticationPublicKeyInfo {
+    /// Protocol to use when there is no `ChipAuthenticationInfo`.
+    ///
+    /// The key agreement follows from the public key algorithm, falling back
+    /// to the one in the protocol OID for other keys. The cipher is left to
+    /// the caller, ICAO 9303-11 6.2 assumes 3DES.
+    pub const fn inferred_protocol(&self) -> ChipAuthenticationProtocol {
+        let key_agreement = match self.public_key {
+            SubjectPublicKeyInfo::Ec(_) => KeyAgreement::Ecdh,
+            SubjectPublicKeyInfo::Dh(_) => KeyAgreement::Dh,
+            _ => self.protocol,
+        };
+        ChipAuthenticationProtocol {
+            key_agreement,
+            cipher: None,
+        }
+    }
+}
+
+impl ChipAuthenticationProtocol {
+    /// The ASN.1 name of the protocol object identifier.
+    ///
+    /// See BSI TR-03110-3 A.1.1.2.
+    pub const fn name(&self) -> &'static str {
+        match (self.key_agreement, self.cipher) {
+            (KeyAgreement::Dh, None) => "id-CA-DH",
+            (KeyAgreement::Dh, Some(SymmetricCipher::Tdes)) => "id-CA-DH-3DES-CBC-CBC",
+            (KeyAgreement::Dh, Some(SymmetricCipher::Aes128)) => "id-CA-DH-AES-CBC-CMAC-128",
+            (KeyAgreement::Dh, Some(SymmetricCipher::Aes192)) => "id-CA-DH-AES-CBC-CMAC-192",
+            (KeyAgreement::Dh, Some(SymmetricCipher::Aes256)) => "id-CA-DH-AES-CBC-CMAC-256",
+            (KeyAgreement::Ecdh, None) => "id-CA-ECDH",
+            (KeyAgreement::Ecdh, Some(SymmetricCipher::Tdes)) => "id-CA-ECDH-3DES-CBC-CBC",
+            (KeyAgreement::Ecdh, Some(SymmetricCipher::Aes128)) => "id-CA-ECDH-AES-CBC-CMAC-128",
+            (KeyAgreement::Ecdh, Some(SymmetricCipher::Aes192)) => "id-CA-ECDH-AES-CBC-CMAC-192",
+            (KeyAgreement::Ecdh, Some(SymmetricCipher::Aes256)) => "id-CA-ECDH-AES-CBC-CMAC-256",
+        }
     }
 }
 
@@ -1,29 +1,38 @@
+mod active_authentication_info;
 mod chip_authentication_info;
 mod pace_info;
+mod restricted_identification_info;
 
 pub use {
-    self::pace_info::{PaceInfo, PaceProtocol},
+    self::pace_info::{PaceDomainParameterInfo, PaceInfo, PaceProtocol},
+    active_authentication_info::{ActiveAuthenticationInfo, ID_ACTIVE_AUTHENTICATION},
     chip_authentication_info::{
         ChipAuthenticationInfo, ChipAuthenticationProtocol, ChipAuthenticationPublicKeyInfo,
     },
+    restricted_identification_info::{
+        ProtocolParams, RestrictedIdentificationInfo, ID_RESTRICTED_IDENTIFICATION,
+    },
+};
+#[cfg(feature = "serde")]
+use {
+    crate::asn1::serde_adapters::{DerHex, Dotted, Hex},
+    serde_with::As,
 };
 use {
-    crate::{asn1::ordered_set::OrderedSet, ensure_err},
+    crate::{asn1::ordered_set::OrderedSet, crypto::Leniency, ensure_err},
     der::{
         asn1::{ObjectIdentifier as Oid, OctetString},
         Any, Decode, DecodeValue, Encode, EncodeValue, Error, ErrorKind, FixedTag, Header, Length,
         Reader, Result, Sequence, Tag, ValueOrd, Writer,
     },
-    pace_info::PaceDomainParameterInfo,
-    serde::{Deserialize, Serialize},
     std::{
         cmp::Ordering,
         fmt::{self, Display, Formatter},
     },
+    tracing::warn,
 };
 
 pub const KEY_AGREEMENT_OID: Oid = Oid::new_unwrap("0.4.0.127.0.7.2.2.1");
-pub const ID_ACTIVE_AUTHENTICATION: Oid = Oid::new_unwrap("2.23.136.1.1.5");
 pub const ID_TERMINAL_AUTHENTICATION: Oid = Oid::new_unwrap("0.4.0.127.0.7.2.2.2");
 pub const ID_EF_DIR: Oid = Oid::new_unwrap("2.23.136.1.1.13");
 
@@ -34,17 +43,40 @@ pub const ID_EF_DIR: Oid = Oid::new_unwrap("2.23.136.1.1.13");
 /// ```
 pub type SecurityInfos = OrderedSet<SecurityInfo>;
 
+/// Decode [`SecurityInfos`], handling protocols not known to this crate
+/// according to `unknown`.
+///
+/// With [`Leniency::Strict`] they are rejected, otherwise they are kept as
+/// [`SecurityInfo::Unknown`]. Plain [`Decode`] always keeps them.
+pub fn decode_security_infos(der: &[u8], unknown: Leniency) -> Result<SecurityInfos> {
+    let infos = SecurityInfos::from_der(der)?;
+    for info in infos.iter() {
+        if let SecurityInfo::Unknown(info) = info {
+            match unknown {
+                Leniency::Strict => {
+                    return Err(ErrorKind::OidUnknown { oid: info.protocol }.into());
+                }
+                Leniency::Warn => warn!(protocol = %info.protocol, "Unknown SecurityInfo"),
+                Leniency::Allow => {}
+            }
+        }
+    }
+    Ok(infos)
+}
+
 /// Various subtypes of `SecurityInfo` from ICAO-9303-11 9.2.
 #[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SecurityInfo {
     Pace(PaceInfo),
     PaceDomainParameter(PaceDomainParameterInfo),
     ChipAuthentication(ChipAuthenticationInfo),
     ChipAuthenticationPublicKey(ChipAuthenticationPublicKeyInfo),
-    ActiveAutentication(ActiveAuthenticationInfo),
+    ActiveAuthentication(ActiveAuthenticationInfo),
     TerminalAuthentication(TerminalAuthenticationInfo),
+    RestrictedIdentification(RestrictedIdentificationInfo),
     EfDir(EfDirInfo),
-    Unknow(AnySecurityInfo),
+    Unknown(AnySecurityInfo),
 }
 
 /// A [`SecurityInfo`] object from ICAO-9303-11 9.2.
@@ -57,19 +89,27 @@ pub enum SecurityInfo {
 /// }
 /// ```
 #[derive(Clone, PartialEq, Eq, Debug, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnySecurityInfo {
+    #[cfg_attr(feature = "serde", serde(with = "As::<Dotted>"))]
     pub protocol:      Oid,
+    #[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))]
     pub required_data: Any,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Option<DerHex>>"))]
     pub optional_data: Option<Any>,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EfDirInfo {
+    #[cfg_attr(feature = "serde", serde(with = "As::<Dotted>"))]
     pub protocol: Oid,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Hex>"))]
     pub ef_dir:   OctetString,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum KeyAgreement {
     /// Diffie-Hellman over a prime field.
     Dh,
@@ -78,8 +118,12 @@ pub enum KeyAgreement {
     Ecdh,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "UPPERCASE")
+)]
 pub enum SymmetricCipher {
     Tdes,
     Aes128,
@@ -87,7 +131,6 @@ pub enum SymmetricCipher {
     Aes256,
 }
 
-pub type ActiveAuthenticationInfo = AnySecurityInfo; // TODO
 pub type TerminalAuthenticationInfo = AnySecurityInfo; // TODO
 
 impl SecurityInfo {
@@ -97,10 +140,11 @@ impl SecurityInfo {
             Self::PaceDomainParameter(info) => info.protocol.into(),
             Self::ChipAuthentication(info) => info.protocol.into(),
             Self::ChipAuthenticationPublicKey(info) => info.protocol.into(),
-            Self::ActiveAutentication(info) => info.protocol,
+            Self::ActiveAuthentication(info) => info.protocol,
             Self::TerminalAuthentication(info) => info.protocol,
+            Self::RestrictedIdentification(info) => info.protocol,
             Self::EfDir(info) => info.protocol,
-            Self::Unknow(info) => info.protocol,
+            Self::Unknown(info) => info.protocol,
         }
     }
 
@@ -110,10 +154,11 @@ impl SecurityInfo {
             Self::PaceDomainParameter(info) => info.protocol.to_string(),
             Self::ChipAuthentication(info) => info.protocol.to_string(),
             Self::ChipAuthenticationPublicKey(info) => info.to_string(),
-            Self::ActiveAutentication(_info) => "AA".to_string(),
+            Self::ActiveAuthentication(_info) => "AA".to_string(),
             Self::TerminalAuthentication(_info) => "TA".to_string(),
+            Self::RestrictedIdentification(_info) => "RI".to_string(),
             Self::EfDir(_info) => "EF_DIR".to_string(),
-            Self::Unknow(info) => info.protocol.to_string(),
+            Self::Unknown(info) => info.protocol.to_string(),
         }
     }
 }
@@ -137,10 +182,11 @@ impl EncodeValue for SecurityInfo {
             Self::PaceDomainParameter(info) => info.value_len(),
             Self::ChipAuthentication(info) => info.value_len(),
             Self::ChipAuthenticationPublicKey(info) => info.value_len(),
-            Self::ActiveAutentication(info) => info.value_len(),
+            Self::ActiveAuthentication(info) => info.value_len(),
             Self::TerminalAuthentication(info) => info.value_len(),
+            Self::RestrictedIdentification(info) => info.value_len(),
             Self::EfDir(info) => info.value_len(),
-            Self::Unknow(info) => info.value_len(),
+            Self::Unknown(info) => info.value_len(),
         }
     }
 
@@ -150,10 +196,11 @@ impl EncodeValue for SecurityInfo {
             Self::PaceDomainParameter(info) => info.encode_value(writer),
             Self::ChipAuthentication(info) => info.encode_value(writer),
             Self::ChipAuthenticationPublicKey(info) => info.encode_value(writer),
-            Self::ActiveAutentication(info) => info.encode_value(writer),
+            Self::ActiveAuthentication(info) => info.encode_value(writer),
             Self::TerminalAuthentication(info) => info.encode_value(writer),
+            Self::RestrictedIdentification(info) => info.encode_value(writer),
             Self::EfDir(info) => info.encode_value(writer),
-            Self::Unknow(info) => info.encode_value(writer),
+            Self::Unknown(info) => info.encode_value(writer),
         }
     }
 }
@@ -186,21 +233,25 @@ impl<'a> DecodeValue<'a> for SecurityInfo {
             ChipAuthenticationPublicKeyInfo::from_der(&der)
                 .map_err(offset_err)
                 .map(Self::ChipAuthenticationPublicKey)
-        } else if any.protocol == ID_ACTIVE_AUTHENTICATION {
+        } else if ActiveAuthenticationInfo::is_protocol(any.protocol) {
             ActiveAuthenticationInfo::from_der(&der)
                 .map_err(offset_err)
-                .map(Self::ActiveAutentication)
+                .map(Self::ActiveAuthentication)
         } else if any.protocol == ID_TERMINAL_AUTHENTICATION {
             // TODO: This ID can be a prefix.
             TerminalAuthenticationInfo::from_der(&der)
                 .map_err(offset_err)
                 .map(Self::TerminalAuthentication)
+        } else if RestrictedIdentificationInfo::is_protocol(any.protocol) {
+            RestrictedIdentificationInfo::from_der(&der)
+                .map_err(offset_err)
+                .map(Self::RestrictedIdentification)
         } else if any.protocol == ID_EF_DIR {
             EfDirInfo::from_der(&der)
                 .map_err(offset_err)
                 .map(Self::EfDir)
         } else {
-            Ok(Self::Unknow(any))
+            Ok(Self::Unknown(any))
         }
     }
 }
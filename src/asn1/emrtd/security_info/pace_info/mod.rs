@@ -5,6 +5,7 @@ use {crate::asn1::AnyAlgorithmIdentifier, der::Sequence};
 
 /// See ICAO-9303-11 9.2.1
 #[derive(Clone, PartialEq, Eq, Debug, Sequence)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PaceInfo {
     pub protocol: PaceProtocol,
 
@@ -16,6 +17,7 @@ pub struct PaceInfo {
 
 /// See ICAO-9303-11 9.2.2
 #[derive(Clone, PartialEq, Eq, Debug, Sequence)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PaceDomainParameterInfo {
     pub protocol: PaceProtocol,
 
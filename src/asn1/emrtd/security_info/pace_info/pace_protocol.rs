@@ -14,6 +14,7 @@ pub const PACE_OID: Oid = Oid::new_unwrap("0.4.0.127.0.7.2.2.4");
 ///
 /// See ICAO 9303-11 9.2.3.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PaceProtocol {
     pub key_agreement: KeyAgreement,
     pub key_mapping:   KeyMapping,
@@ -21,6 +22,7 @@ pub struct PaceProtocol {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum KeyMapping {
     /// Generic Mapping
     Gm,
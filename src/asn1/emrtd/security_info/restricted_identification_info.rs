@@ -0,0 +1,85 @@
+use der::{asn1::ObjectIdentifier as Oid, Sequence};
+#[cfg(feature = "serde")]
+use {crate::asn1::serde_adapters::Dotted, serde_with::As};
+
+pub const ID_RESTRICTED_IDENTIFICATION: Oid = Oid::new_unwrap("0.4.0.127.0.7.2.2.5");
+
+/// See BSI TR-03110-3 A.1.1.6.
+///
+/// ```asn1
+/// RestrictedIdentificationInfo ::= SEQUENCE {
+///     protocol  OBJECT IDENTIFIER(
+///               id-RI-DH-SHA-1 | id-RI-DH-SHA-224 | id-RI-DH-SHA-256 |
+///               id-RI-DH-SHA-384 | id-RI-DH-SHA-512 |
+///               id-RI-ECDH-SHA-1 | id-RI-ECDH-SHA-224 | id-RI-ECDH-SHA-256 |
+///               id-RI-ECDH-SHA-384 | id-RI-ECDH-SHA-512),
+///     params    ProtocolParams,
+///     maxKeyLen INTEGER OPTIONAL
+/// }
+/// ```
+///
+/// Only parsed, the Restricted Identification protocol itself is not
+/// implemented.
+#[derive(Clone, Debug, PartialEq, Eq, Sequence)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RestrictedIdentificationInfo {
+    #[cfg_attr(feature = "serde", serde(with = "As::<Dotted>"))]
+    pub protocol:    Oid,
+    pub params:      ProtocolParams,
+    pub max_key_len: Option<u64>,
+}
+
+/// See BSI TR-03110-3 A.1.1.6.
+///
+/// ```asn1
+/// ProtocolParams ::= SEQUENCE {
+///     version        INTEGER, -- MUST be 1
+///     keyId          INTEGER,
+///     authorizedOnly BOOLEAN
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Sequence)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProtocolParams {
+    pub version:         u64,
+    pub key_id:          u64,
+    pub authorized_only: bool,
+}
+
+impl RestrictedIdentificationInfo {
+    /// Returns true if the OID is one of the `id-RI-DH-*` or `id-RI-ECDH-*`
+    /// protocols.
+    ///
+    /// The shorter `id-RI-DH` and `id-RI-ECDH` identify domain parameters and
+    /// are not matched.
+    pub fn is_protocol(oid: Oid) -> bool {
+        oid.parent().and_then(|oid| oid.parent()) == Some(ID_RESTRICTED_IDENTIFICATION)
+            && matches!(oid.arc(9), Some(1 | 2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{super::SecurityInfo, *},
+        der::Decode,
+        hex_literal::hex,
+    };
+
+    #[test]
+    fn test_decode_restricted_identification_info() {
+        // id-RI-ECDH-SHA-256 with key id 2 and max key length 256.
+        let der = hex!("301b060a04007f000702020502033009020101020102010100 02020100");
+        let info = SecurityInfo::from_der(&der).unwrap();
+        let SecurityInfo::RestrictedIdentification(info) = info else {
+            panic!("expected RestrictedIdentificationInfo, got {info:?}");
+        };
+        assert_eq!(info.protocol, Oid::new_unwrap("0.4.0.127.0.7.2.2.5.2.3"));
+        assert_eq!(info.params, ProtocolParams {
+            version:         1,
+            key_id:          2,
+            authorized_only: false,
+        });
+        assert_eq!(info.max_key_len, Some(256));
+    }
+}
@@ -0,0 +1,93 @@
+//! BER-TLV helpers for the data groups and card verifiable certificates with
+//! two byte tags.
+
+use {
+    anyhow::{anyhow, bail, ensure},
+    der::{Encode, Error, Length, Result},
+};
+
+const TAG_COUNT: u16 = 0x02;
+
+/// Read a BER-TLV with a one or two byte tag.
+///
+/// Returns the tag, the value and the full encoding.
+pub fn read_tlv<'a>(input: &mut &'a [u8]) -> anyhow::Result<(u16, &'a [u8], &'a [u8])> {
+    let start = *input;
+    let mut next = || -> anyhow::Result<u8> {
+        let (&byte, rest) = input
+            .split_first()
+            .ok_or_else(|| anyhow!("Truncated TLV"))?;
+        *input = rest;
+        Ok(byte)
+    };
+
+    let mut tag = u16::from(next()?);
+    if tag & 0x1f == 0x1f {
+        let byte = next()?;
+        ensure!(
+            byte & 0x80 == 0,
+            "Tags longer than two bytes are not supported"
+        );
+        tag = tag << 8 | u16::from(byte);
+    }
+
+    let length = match next()? {
+        length @ 0..=0x7f => usize::from(length),
+        0x81 => usize::from(next()?),
+        0x82 => usize::from(u16::from_be_bytes([next()?, next()?])),
+        _ => bail!("Unsupported TLV length encoding"),
+    };
+    ensure!(input.len() >= length, "Truncated TLV");
+    let (value, rest) = input.split_at(length);
+    *input = rest;
+    Ok((tag, value, &start[..start.len() - rest.len()]))
+}
+
+/// Append a TLV with a one or two byte tag.
+pub(super) fn push_tlv(out: &mut Vec<u8>, tag: u16, value: &[u8]) -> Result<()> {
+    match tag.to_be_bytes() {
+        [0, tag] => out.push(tag),
+        tag => out.extend_from_slice(&tag),
+    }
+    out.extend_from_slice(&Length::try_from(value.len())?.to_der()?);
+    out.extend_from_slice(value);
+    Ok(())
+}
+
+/// Append a template with the number of items followed by the items.
+///
+/// Used for the other names in DG11 and the other persons in DG12.
+pub(super) fn push_list(
+    out: &mut Vec<u8>,
+    tag: u16,
+    item_tag: u16,
+    items: &[String],
+) -> Result<()> {
+    let count = u8::try_from(items.len()).map_err(|_| Error::from(der::ErrorKind::Overlength))?;
+    let mut list = Vec::new();
+    push_tlv(&mut list, TAG_COUNT, &[count])?;
+    for item in items {
+        push_tlv(&mut list, item_tag, item.as_bytes())?;
+    }
+    push_tlv(out, tag, &list)
+}
+
+/// Read the items of a template written by [`push_list`].
+///
+/// Returns `None` if the template is malformed.
+pub(super) fn read_list(mut list: &[u8], item_tag: u16) -> Option<Vec<String>> {
+    match read_tlv(&mut list) {
+        Ok((TAG_COUNT, ..)) => {}
+        _ => return None,
+    }
+    let mut items = Vec::new();
+    while !list.is_empty() {
+        match read_tlv(&mut list) {
+            Ok((tag, item, _)) if tag == item_tag => {
+                items.push(String::from_utf8(item.to_vec()).ok()?);
+            }
+            _ => return None,
+        }
+    }
+    Some(items)
+}
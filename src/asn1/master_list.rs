@@ -0,0 +1,80 @@
+//! CSCA Master List, see ICAO 9303-12 section 9.
+
+use {
+    super::{ContentInfo, ContentType},
+    cms::{cert::x509::Certificate, signed_data::SignedData},
+    const_oid::db::rfc4519::COUNTRY_NAME,
+    der::{
+        asn1::{ObjectIdentifier as Oid, PrintableString, SetOfVec},
+        Result, Sequence,
+    },
+    std::collections::BTreeSet,
+};
+
+/// A CSCA Master List is a [`SignedData`] structure with a [`CscaMasterList`]
+/// as encapsulated content, signed by a Master List Signer.
+///
+/// See ICAO 9303-12 9.
+pub type MasterList = ContentInfo<SignedData>;
+
+/// ICAO 9303-12 9.
+///
+/// ```asn1
+/// CscaMasterList ::= SEQUENCE {
+///     version CscaMasterListVersion,
+///     certList SET OF Certificate
+/// }
+///
+/// CscaMasterListVersion ::= INTEGER {v0(0)}
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Sequence)]
+pub struct CscaMasterList {
+    pub version:   u64,
+    pub cert_list: SetOfVec<Certificate>,
+}
+
+impl CscaMasterList {
+    /// The certificates with subject country `iso3166`, ignoring case.
+    pub fn certs_for_country<'a>(
+        &'a self,
+        iso3166: &'a str,
+    ) -> impl Iterator<Item = &'a Certificate> + 'a {
+        self.cert_list
+            .iter()
+            .filter(move |cert| country(cert).is_some_and(|c| c.eq_ignore_ascii_case(iso3166)))
+    }
+
+    /// Sorted unique subject country codes, in upper case.
+    pub fn countries(&self) -> Vec<String> {
+        let countries: BTreeSet<String> = self
+            .cert_list
+            .iter()
+            .filter_map(country)
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        countries.into_iter().collect()
+    }
+}
+
+/// The `C=` attribute of the certificate subject.
+fn country(cert: &Certificate) -> Option<String> {
+    cert.tbs_certificate
+        .subject
+        .0
+        .iter()
+        .flat_map(|rdn| rdn.0.iter())
+        .find(|atv| atv.oid == COUNTRY_NAME)
+        .and_then(|atv| atv.value.decode_as::<PrintableString>().ok())
+        .map(|c| c.to_string())
+}
+
+impl ContentType for CscaMasterList {
+    /// ICAO 9303-12 9, `id-icao-cscaMasterList`.
+    const CONTENT_TYPE: Oid = Oid::new_unwrap("2.23.136.1.1.2");
+}
+
+impl MasterList {
+    pub fn csca_master_list(&self) -> Result<CscaMasterList> {
+        self.decode_econtent()
+    }
+}
@@ -14,11 +14,16 @@
 
 mod application_tagged;
 mod content_info;
+pub mod cvc;
 mod digest_algorithm_identifier;
 pub mod emrtd;
+pub mod master_list;
 mod ordered_set;
 pub mod public_key_info;
+#[cfg(feature = "serde")]
+pub mod serde_adapters;
 pub mod signature_algorithm_identifier;
+pub mod tr03110;
 
 pub use self::{
     application_tagged::ApplicationTagged,
@@ -29,9 +34,17 @@ pub use self::{
     signature_algorithm_identifier::SignatureAlgorithmIdentifier,
 };
 use der::{asn1::ObjectIdentifier as Oid, Any, Sequence, ValueOrd};
+#[cfg(feature = "serde")]
+use {
+    crate::asn1::serde_adapters::{DerHex, Dotted},
+    serde_with::As,
+};
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnyAlgorithmIdentifier {
+    #[cfg_attr(feature = "serde", serde(with = "As::<Dotted>"))]
     pub algorithm:  Oid,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Option<DerHex>>"))]
     pub parameters: Option<Any>,
 }
@@ -12,6 +12,8 @@ use {
 /// Some passports do not order the elements of SET correctly, and we need to
 /// preserve this to be able to encode the data back to the exact same bytes.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct OrderedSet<T>(pub Vec<T>);
 
 impl<T> OrderedSet<T> {
@@ -20,6 +22,32 @@ impl<T> OrderedSet<T> {
     }
 }
 
+impl<T: Encode> OrderedSet<T> {
+    /// Whether the elements are in the canonical DER order.
+    ///
+    /// See X.690 11.6, elements are sorted by their encodings.
+    pub fn is_canonical(&self) -> Result<bool> {
+        let encodings = self.encodings()?;
+        Ok(encodings.windows(2).all(|pair| pair[0] <= pair[1]))
+    }
+
+    /// Sort the elements into the canonical DER order.
+    ///
+    /// This changes the encoding of non-canonical input, so it must not be
+    /// used on signed or hashed data that needs to be reproduced exactly.
+    pub fn sort_canonical(&mut self) -> Result<()> {
+        let encodings = self.encodings()?;
+        let mut elements = self.0.drain(..).zip(encodings).collect::<Vec<_>>();
+        elements.sort_by(|(_, a), (_, b)| a.cmp(b));
+        self.0 = elements.into_iter().map(|(elem, _)| elem).collect();
+        Ok(())
+    }
+
+    fn encodings(&self) -> Result<Vec<Vec<u8>>> {
+        self.0.iter().map(Encode::to_der).collect()
+    }
+}
+
 impl<T> AsRef<[T]> for OrderedSet<T> {
     fn as_ref(&self) -> &[T] {
         self.0.as_slice()
@@ -56,3 +84,22 @@ impl<'a, T: Decode<'a>> DecodeValue<'a> for OrderedSet<T> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    #[test]
+    fn test_order_preserved() {
+        let der = hex!("31 0A 020102 020101 02020100");
+        let mut set = OrderedSet::<u16>::from_der(&der).unwrap();
+        assert_eq!(set.0, [2, 1, 256]);
+        assert!(!set.is_canonical().unwrap());
+        assert_eq!(set.to_der().unwrap(), der);
+
+        set.sort_canonical().unwrap();
+        assert_eq!(set.0, [1, 2, 256]);
+        assert!(set.is_canonical().unwrap());
+        assert_eq!(set.to_der().unwrap(), hex!("31 0A 020101 020102 02020100"));
+    }
+}
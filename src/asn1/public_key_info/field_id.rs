@@ -1,3 +1,8 @@
+#[cfg(feature = "serde")]
+use {
+    crate::asn1::serde_adapters::{DerHex, Dotted},
+    serde_with::As,
+};
 use {
     der::{
         asn1::{Int, ObjectIdentifier as Oid},
@@ -10,14 +15,21 @@ use {
 pub const ID_PRIME_FIELD: Oid = Oid::new_unwrap("1.2.840.10045.1.1");
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FieldId {
-    PrimeField { modulus: Int },
+    PrimeField {
+        #[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))]
+        modulus: Int,
+    },
     Unknown(AnyFieldId),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnyFieldId {
+    #[cfg_attr(feature = "serde", serde(with = "As::<Dotted>"))]
     pub field_type: Oid,
+    #[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))]
     pub parameters: Any,
 }
 
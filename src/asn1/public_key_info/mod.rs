@@ -1,45 +1,78 @@
 mod field_id;
 mod pubkey_algorithm_identifier;
 
-pub use self::{field_id::FieldId, pubkey_algorithm_identifier::PubkeyAlgorithmIdentifier};
+pub use self::{
+    field_id::FieldId,
+    pubkey_algorithm_identifier::{PubkeyAlgorithmIdentifier, ID_DH, ID_EC, ID_EC_DH, ID_EC_MQV},
+};
+#[cfg(feature = "serde")]
+use {
+    crate::asn1::serde_adapters::{DerHex, Dotted, Hex},
+    serde_with::As,
+};
 use {
-    crate::asn1::AnyAlgorithmIdentifier,
+    crate::{asn1::AnyAlgorithmIdentifier, crypto::named_curves::*},
     der::{
         asn1::{BitString, Int, Null, ObjectIdentifier as Oid, OctetString},
-        Choice, Decode, DecodeValue, Encode, EncodeValue, Length, Reader, Result, Sequence,
+        Choice, Decode, DecodeValue, Encode, EncodeValue, Length, Reader, Result, Sequence, Tag,
         ValueOrd, Writer,
     },
     std::cmp::Ordering,
 };
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SubjectPublicKeyInfo {
     Rsa(RsaPublicKeyInfo),
     Ec(EcPublicKeyInfo),
+    Dh(DhPublicKeyInfo),
     Unknown(AnySubjectPublicKeyInfo),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnySubjectPublicKeyInfo {
     pub algorithm:          AnyAlgorithmIdentifier,
+    #[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))]
     pub subject_public_key: BitString,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RsaPublicKeyInfo {
+    #[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))]
     pub modulus:         Int,
+    #[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))]
     pub public_exponent: Int,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EcPublicKeyInfo {
-    pub point: ECPoint,
+    pub parameters: ECAlgoParameters,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Hex>"))]
+    pub point:      ECPoint,
+}
+
+/// Diffie-Hellman public key, the BIT STRING holds the DER encoded public
+/// value `y`.
+///
+/// See PKCS #3 and BSI TR-03110-3 D.3.2.
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DhPublicKeyInfo {
+    pub parameters: DhAlgoParameters,
+    #[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))]
+    pub public_key: Int,
 }
 
 /// Diffie-Hellman Mod-P Group Parameters.
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DhAlgoParameters {
+    #[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))]
     pub prime:                Int,
+    #[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))]
     pub base:                 Int,
     pub private_value_length: Option<u64>,
 }
@@ -53,26 +86,35 @@ pub struct DhAlgoParameters {
 /// [TR-03111] `Parameters`
 /// Details on parameters in [TR-03111]
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Choice, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ECAlgoParameters {
     EcParameters(EcParameters),
-    NamedCurve(Oid),
-    ImplicitlyCA(Null),
+    NamedCurve(#[cfg_attr(feature = "serde", serde(with = "As::<Dotted>"))] Oid),
+    ImplicitlyCA(#[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))] Null),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EcParameters {
     pub version:  u64,
     pub field_id: FieldId,
     pub curve:    Curve,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Hex>"))]
     pub base:     ECPoint,
+    #[cfg_attr(feature = "serde", serde(with = "As::<DerHex>"))]
     pub order:    Int,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Option<DerHex>>"))]
     pub cofactor: Option<Int>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Sequence, ValueOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Curve {
+    #[cfg_attr(feature = "serde", serde(with = "As::<Hex>"))]
     pub a:    FieldElement,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Hex>"))]
     pub b:    FieldElement,
+    #[cfg_attr(feature = "serde", serde(with = "As::<Option<DerHex>>"))]
     pub seed: Option<BitString>,
 }
 
@@ -81,15 +123,74 @@ pub type FieldElement = OctetString;
 pub type ECPoint = OctetString;
 
 impl SubjectPublicKeyInfo {
+    /// Key size in bits: the RSA modulus, the EC base field or the DH prime.
+    ///
+    /// Returns 0 if the size can not be determined from the parameters.
     pub fn bit_len(&self) -> usize {
         match self {
-            Self::Rsa(_info) => todo!(),
-            Self::Ec(_info) => todo!(),
+            Self::Rsa(info) => int_bit_len(&info.modulus),
+            Self::Ec(info) => info.parameters.field_bit_len().unwrap_or(0),
+            Self::Dh(info) => int_bit_len(&info.parameters.prime),
             Self::Unknown(info) => info.subject_public_key.bit_len(),
         }
     }
 }
 
+impl RsaPublicKeyInfo {
+    /// Size of the modulus in whole bytes, as bits.
+    ///
+    /// This is the size of the `Uint` needed to hold the modulus, so a 2047
+    /// bit modulus counts as 2048.
+    pub fn modulus_bits(&self) -> usize {
+        let bytes = self.modulus.as_bytes();
+        (bytes.len() - bytes.iter().take_while(|&&b| b == 0).count()) * 8
+    }
+}
+
+impl EcPublicKeyInfo {
+    /// Bit length of the base field of the curve.
+    ///
+    /// Errors for implicit or unknown curves.
+    pub fn curve_bits(&self) -> Result<usize> {
+        self.parameters
+            .field_bit_len()
+            .ok_or_else(|| Tag::ObjectIdentifier.value_error())
+    }
+}
+
+impl ECAlgoParameters {
+    /// Bit length of the base field, `None` for implicit or unknown curves.
+    pub fn field_bit_len(&self) -> Option<usize> {
+        match self {
+            Self::EcParameters(params) => match &params.field_id {
+                FieldId::PrimeField { modulus } => Some(int_bit_len(modulus)),
+                FieldId::Unknown(_) => None,
+            },
+            Self::NamedCurve(oid) => Some(match *oid {
+                ID_BRAINPOOL_P160R1 => 160,
+                ID_SEC_P192R1 | ID_BRAINPOOL_P192R1 => 192,
+                ID_SEC_P224R1 | ID_BRAINPOOL_P224R1 => 224,
+                ID_SEC_P256R1 | ID_BRAINPOOL_P256R1 => 256,
+                ID_BRAINPOOL_P320R1 => 320,
+                ID_SEC_P384R1 | ID_BRAINPOOL_P384R1 => 384,
+                ID_BRAINPOOL_P512R1 => 512,
+                ID_SEC_P521R1 => 521,
+                _ => return None,
+            }),
+            Self::ImplicitlyCA(_) => None,
+        }
+    }
+}
+
+/// Bit length of a non-negative integer.
+fn int_bit_len(int: &Int) -> usize {
+    let bytes = int.as_bytes();
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    bytes.get(zeros).map_or(0, |first| {
+        (bytes.len() - zeros) * 8 - first.leading_zeros() as usize
+    })
+}
+
 impl Sequence<'_> for SubjectPublicKeyInfo {}
 
 impl ValueOrd for SubjectPublicKeyInfo {
@@ -101,21 +202,44 @@ impl ValueOrd for SubjectPublicKeyInfo {
     }
 }
 
+impl SubjectPublicKeyInfo {
+    /// Algorithm identifier and subject public key BIT STRING of known key
+    /// types.
+    fn encode_parts(&self) -> Result<(PubkeyAlgorithmIdentifier, BitString)> {
+        Ok(match self {
+            Self::Rsa(info) => (
+                PubkeyAlgorithmIdentifier::Rsa,
+                BitString::from_bytes(&info.to_der()?)?,
+            ),
+            Self::Ec(info) => (
+                PubkeyAlgorithmIdentifier::Ec(info.parameters.clone()),
+                BitString::from_bytes(info.point.as_bytes())?,
+            ),
+            Self::Dh(info) => (
+                PubkeyAlgorithmIdentifier::Dh(info.parameters.clone()),
+                BitString::from_bytes(&info.public_key.to_der()?)?,
+            ),
+            Self::Unknown(_) => unreachable!(),
+        })
+    }
+}
+
 impl EncodeValue for SubjectPublicKeyInfo {
     fn value_len(&self) -> Result<Length> {
-        match self {
-            Self::Rsa(_info) => todo!(),
-            Self::Ec(_info) => todo!(),
-            Self::Unknown(info) => info.value_len(),
+        if let Self::Unknown(info) = self {
+            return info.value_len();
         }
+        let (algorithm, subject_public_key) = self.encode_parts()?;
+        algorithm.encoded_len()? + subject_public_key.encoded_len()?
     }
 
     fn encode_value(&self, writer: &mut impl Writer) -> Result<()> {
-        match self {
-            Self::Rsa(_info) => todo!(),
-            Self::Ec(_info) => todo!(),
-            Self::Unknown(any) => any.encode(writer),
+        if let Self::Unknown(info) = self {
+            return info.encode_value(writer);
         }
+        let (algorithm, subject_public_key) = self.encode_parts()?;
+        algorithm.encode(writer)?;
+        subject_public_key.encode(writer)
     }
 }
 
@@ -130,16 +254,129 @@ impl<'a> DecodeValue<'a> for SubjectPublicKeyInfo {
                 let rsa_seq = RsaPublicKeyInfo::decode(&mut inner_reader)?;
                 Self::Rsa(rsa_seq)
             }
-            PubkeyAlgorithmIdentifier::Ec(_) => {
+            // Keys restricted to ECDH or ECMQV are treated as regular EC keys and
+            // re-encode with `id-ecPublicKey`.
+            PubkeyAlgorithmIdentifier::Ec(parameters)
+            | PubkeyAlgorithmIdentifier::EcDh(parameters)
+            | PubkeyAlgorithmIdentifier::EcMqv(parameters) => {
                 // EC key BIT STRING is mapped as an OCTET STRING
                 let point = OctetString::new(subject_public_key.as_bytes().unwrap_or(&[]))?;
-                Self::Ec(EcPublicKeyInfo { point })
+                Self::Ec(EcPublicKeyInfo { parameters, point })
+            }
+            PubkeyAlgorithmIdentifier::Dh(parameters) => {
+                // DH public value is encoded as BIT STRING { INTEGER }
+                let public_key = Int::from_der(subject_public_key.raw_bytes())?;
+                Self::Dh(DhPublicKeyInfo {
+                    parameters,
+                    public_key,
+                })
             }
             PubkeyAlgorithmIdentifier::Unknown(id) => Self::Unknown(AnySubjectPublicKeyInfo {
                 algorithm: id,
                 subject_public_key,
             }),
-            _ => todo!(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    #[test]
+    fn test_bit_len() {
+        let modulus = [&[0x00, 0xc0][..], &[0x55; 255]].concat();
+        let rsa = SubjectPublicKeyInfo::Rsa(RsaPublicKeyInfo {
+            modulus:         Int::new(&modulus).unwrap(),
+            public_exponent: Int::new(&hex!("010001")).unwrap(),
+        });
+        assert_eq!(rsa.bit_len(), 2048);
+        let SubjectPublicKeyInfo::Rsa(info) = &rsa else {
+            unreachable!()
+        };
+        assert_eq!(info.modulus_bits(), 2048);
+        let short = RsaPublicKeyInfo {
+            modulus:         Int::new(&[&[0x7f][..], &[0x55; 255]].concat()).unwrap(),
+            public_exponent: Int::new(&hex!("010001")).unwrap(),
+        };
+        assert_eq!(short.modulus_bits(), 2048);
+
+        let ec = SubjectPublicKeyInfo::Ec(EcPublicKeyInfo {
+            parameters: ECAlgoParameters::NamedCurve(ID_SEC_P256R1),
+            point:      OctetString::new([0x04; 65]).unwrap(),
+        });
+        assert_eq!(ec.bit_len(), 256);
+        let SubjectPublicKeyInfo::Ec(info) = &ec else {
+            unreachable!()
+        };
+        assert_eq!(info.curve_bits().unwrap(), 256);
+
+        let implicit = ECAlgoParameters::ImplicitlyCA(Null);
+        assert_eq!(implicit.field_bit_len(), None);
+        let implicit = EcPublicKeyInfo {
+            parameters: implicit,
+            point:      OctetString::new([0x04; 65]).unwrap(),
+        };
+        assert!(implicit.curve_bits().is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let rsa = SubjectPublicKeyInfo::Rsa(RsaPublicKeyInfo {
+            modulus:         Int::new(&hex!("00c2a7e5d3b1")).unwrap(),
+            public_exponent: Int::new(&hex!("010001")).unwrap(),
+        });
+        let ec = SubjectPublicKeyInfo::Ec(EcPublicKeyInfo {
+            parameters: ECAlgoParameters::NamedCurve(ID_BRAINPOOL_P256R1),
+            point:      OctetString::new(hex!("04 0102 0304")).unwrap(),
+        });
+        let unknown = SubjectPublicKeyInfo::Unknown(AnySubjectPublicKeyInfo {
+            algorithm:          AnyAlgorithmIdentifier {
+                algorithm:  Oid::new_unwrap("1.2.3.4"),
+                parameters: None,
+            },
+            subject_public_key: BitString::from_bytes(&hex!("0102")).unwrap(),
+        });
+        let algorithm = PubkeyAlgorithmIdentifier::Unknown(AnyAlgorithmIdentifier {
+            algorithm:  Oid::new_unwrap("1.2.3.4"),
+            parameters: None,
+        });
+        let der = algorithm.to_der().unwrap();
+        assert_eq!(
+            PubkeyAlgorithmIdentifier::from_der(&der).unwrap(),
+            algorithm
+        );
+
+        for spki in [rsa, ec, unknown] {
+            let der = spki.to_der().unwrap();
+            assert_eq!(SubjectPublicKeyInfo::from_der(&der).unwrap(), spki);
+        }
+    }
+
+    #[test]
+    fn test_ec_dh_mqv() {
+        let parameters = ECAlgoParameters::NamedCurve(ID_BRAINPOOL_P256R1);
+        let point = hex!("04 0102 0304");
+        for algorithm in [
+            PubkeyAlgorithmIdentifier::EcDh(parameters.clone()),
+            PubkeyAlgorithmIdentifier::EcMqv(parameters.clone()),
+        ] {
+            let der = algorithm.to_der().unwrap();
+            assert_eq!(
+                PubkeyAlgorithmIdentifier::from_der(&der).unwrap(),
+                algorithm
+            );
+
+            let key = BitString::from_bytes(&point).unwrap();
+            let body = [der, key.to_der().unwrap()].concat();
+            let spki = [&[0x30, body.len() as u8][..], &body].concat();
+            assert_eq!(
+                SubjectPublicKeyInfo::from_der(&spki).unwrap(),
+                SubjectPublicKeyInfo::Ec(EcPublicKeyInfo {
+                    parameters: parameters.clone(),
+                    point:      OctetString::new(point).unwrap(),
+                })
+            );
+        }
+    }
+}
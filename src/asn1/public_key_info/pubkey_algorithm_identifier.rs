@@ -18,15 +18,23 @@ pub const ID_RSA: Oid = Oid::new_unwrap("1.2.840.113549.1.1.1");
 // elliptic curve 1
 pub const ID_EC: Oid = Oid::new_unwrap("1.2.840.10045.2.1");
 
+// SEC 1 C.1, keys restricted to ECDH or ECMQV. Some older chips use these for
+// their chip authentication public keys.
+pub const ID_EC_DH: Oid = Oid::new_unwrap("1.3.132.1.12");
+pub const ID_EC_MQV: Oid = Oid::new_unwrap("1.3.132.1.13");
+
 /// PKCS 3
 ///
 /// https://www.teletrust.de/fileadmin/files/oid/oid_pkcs-3v1-4.pdf
 pub const ID_DH: Oid = Oid::new_unwrap("1.2.840.113549.1.3.1");
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PubkeyAlgorithmIdentifier {
     Rsa,
     Ec(ECAlgoParameters),
+    EcDh(ECAlgoParameters),
+    EcMqv(ECAlgoParameters),
     Dh(DhAlgoParameters),
     Unknown(AnyAlgorithmIdentifier),
 }
@@ -47,6 +55,8 @@ impl EncodeValue for PubkeyAlgorithmIdentifier {
         match self {
             Self::Rsa => ID_RSA.encoded_len()? + Null.encoded_len()?,
             Self::Ec(params) => ID_EC.encoded_len()? + params.encoded_len()?,
+            Self::EcDh(params) => ID_EC_DH.encoded_len()? + params.encoded_len()?,
+            Self::EcMqv(params) => ID_EC_MQV.encoded_len()? + params.encoded_len()?,
             Self::Dh(params) => ID_DH.encoded_len()? + params.encoded_len()?,
             Self::Unknown(any) => any.value_len(),
         }
@@ -62,29 +72,42 @@ impl EncodeValue for PubkeyAlgorithmIdentifier {
                 ID_EC.encode(writer)?;
                 params.encode(writer)
             }
+            Self::EcDh(params) => {
+                ID_EC_DH.encode(writer)?;
+                params.encode(writer)
+            }
+            Self::EcMqv(params) => {
+                ID_EC_MQV.encode(writer)?;
+                params.encode(writer)
+            }
             Self::Dh(params) => {
                 ID_DH.encode(writer)?;
                 params.encode(writer)
             }
-            Self::Unknown(any) => any.encode(writer),
+            Self::Unknown(any) => any.encode_value(writer),
         }
     }
 }
 
 impl<'a> DecodeValue<'a> for PubkeyAlgorithmIdentifier {
-    fn decode_value<R: Reader<'a>>(reader: &mut R, _header: der::Header) -> Result<Self> {
-        let oid = Oid::decode(reader)?;
-        Ok(match oid {
-            ID_RSA => {
-                Null::decode(reader)?;
-                Self::Rsa
-            }
-            ID_EC => Self::Ec(ECAlgoParameters::decode(reader)?),
-            ID_DH => Self::Dh(DhAlgoParameters::decode(reader)?),
-            _ => Self::Unknown(AnyAlgorithmIdentifier {
-                algorithm:  oid,
-                parameters: Option::<Any>::decode(reader)?,
-            }),
+    fn decode_value<R: Reader<'a>>(reader: &mut R, header: der::Header) -> Result<Self> {
+        // Parameters are optional, so limit the reader to this sequence.
+        reader.read_nested(header.length, |reader| {
+            let oid = Oid::decode(reader)?;
+            Ok(match oid {
+                ID_RSA => {
+                    Null::decode(reader)?;
+                    Self::Rsa
+                }
+                ID_EC => Self::Ec(ECAlgoParameters::decode(reader)?),
+                ID_EC_DH => Self::EcDh(ECAlgoParameters::decode(reader)?),
+                ID_EC_MQV => Self::EcMqv(ECAlgoParameters::decode(reader)?),
+                ID_DH => Self::Dh(DhAlgoParameters::decode(reader)?),
+                _ => Self::Unknown(AnyAlgorithmIdentifier {
+                    algorithm:  oid,
+                    parameters: Option::<Any>::decode(reader)?,
+                }),
+            })
         })
     }
 }
@@ -0,0 +1,107 @@
+//! [`serde_with`] adapters for the `der` types used in the ASN1 structures.
+//!
+//! * [`Dotted`] for `OBJECT IDENTIFIER` as a dotted-decimal string.
+//! * [`Hex`] for `OCTET STRING` contents as a hex string.
+//! * [`AsStr`] for `PrintableString` as a string.
+//! * [`DerHex`] for anything else as the hex string of its DER encoding.
+//!
+//! The DER encoding is used for `ANY` and other types without an obvious
+//! human readable form, so that round trips are exact. Plain byte fields can
+//! use [`serde_with::hex::Hex`] directly, the adapters here only convert the
+//! `der` types to and from bytes for it.
+
+use {
+    der::{
+        asn1::{ObjectIdentifier as Oid, OctetString, PrintableString},
+        Decode, Encode,
+    },
+    serde::{
+        de::{self, Visitor},
+        Deserializer, Serializer,
+    },
+    serde_with::{hex, DeserializeAs, SerializeAs},
+    std::fmt,
+};
+
+/// Serialize an [`Oid`] as a dotted-decimal string.
+pub struct Dotted;
+
+/// Serialize an [`OctetString`] as a hex string of its contents.
+///
+/// [`hex::Hex`] needs `TryFrom<Vec<u8>>`, which [`OctetString`] lacks.
+pub struct Hex;
+
+/// Serialize a [`PrintableString`] as a string.
+pub struct AsStr;
+
+/// Serialize a DER type as a hex string of its encoding.
+pub struct DerHex;
+
+impl SerializeAs<Oid> for Dotted {
+    fn serialize_as<S: Serializer>(source: &Oid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(source)
+    }
+}
+
+impl<'de> DeserializeAs<'de, Oid> for Dotted {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Oid, D::Error> {
+        struct OidVisitor;
+
+        impl Visitor<'_> for OidVisitor {
+            type Value = Oid;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a dotted-decimal object identifier")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Oid, E> {
+                Oid::new(value).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(OidVisitor)
+    }
+}
+
+impl SerializeAs<OctetString> for Hex {
+    fn serialize_as<S: Serializer>(source: &OctetString, serializer: S) -> Result<S::Ok, S::Error> {
+        <hex::Hex>::serialize_as(&source.as_bytes(), serializer)
+    }
+}
+
+impl<'de> DeserializeAs<'de, OctetString> for Hex {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<OctetString, D::Error> {
+        let bytes: Vec<u8> = <hex::Hex>::deserialize_as(deserializer)?;
+        OctetString::new(bytes).map_err(de::Error::custom)
+    }
+}
+
+impl SerializeAs<PrintableString> for AsStr {
+    fn serialize_as<S: Serializer>(
+        source: &PrintableString,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(source.as_str())
+    }
+}
+
+impl<'de> DeserializeAs<'de, PrintableString> for AsStr {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<PrintableString, D::Error> {
+        let string = <String as serde::Deserialize>::deserialize(deserializer)?;
+        PrintableString::new(&string).map_err(de::Error::custom)
+    }
+}
+
+impl<T: Encode> SerializeAs<T> for DerHex {
+    fn serialize_as<S: Serializer>(source: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        let der = source.to_der().map_err(serde::ser::Error::custom)?;
+        <hex::Hex>::serialize_as(&der, serializer)
+    }
+}
+
+impl<'de, T: for<'a> Decode<'a>> DeserializeAs<'de, T> for DerHex {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        let bytes: Vec<u8> = <hex::Hex>::deserialize_as(deserializer)?;
+        T::from_der(&bytes).map_err(de::Error::custom)
+    }
+}
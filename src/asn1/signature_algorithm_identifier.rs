@@ -1,9 +1,9 @@
 use {
     super::{AnyAlgorithmIdentifier, DigestAlgorithmIdentifier, DigestAlgorithmParameters},
     der::{
-        asn1::{Int, ObjectIdentifier as Oid},
-        Any, Decode, DecodeValue, Encode, EncodeValue, Length, Reader, Result, Sequence, ValueOrd,
-        Writer,
+        asn1::{Int, ObjectIdentifier as Oid, Uint},
+        Any, Decode, DecodeValue, Encode, EncodeValue, ErrorKind, FixedTag, Length, Reader, Result,
+        Sequence, ValueOrd, Writer,
     },
     std::cmp::Ordering,
 };
@@ -11,12 +11,73 @@ use {
 pub const ID_SIG_RSASSA_PSS: Oid = Oid::new_unwrap("1.2.840.113549.1.1.10");
 pub const ID_MGFA_MGF1: Oid = Oid::new_unwrap("1.2.840.113549.1.1.8");
 
+// RFC 8017 A.2.4
+pub const ID_SHA1_WITH_RSA_ENCRYPTION: Oid = Oid::new_unwrap("1.2.840.113549.1.1.5");
+pub const ID_SHA256_WITH_RSA_ENCRYPTION: Oid = Oid::new_unwrap("1.2.840.113549.1.1.11");
+pub const ID_SHA384_WITH_RSA_ENCRYPTION: Oid = Oid::new_unwrap("1.2.840.113549.1.1.12");
+pub const ID_SHA512_WITH_RSA_ENCRYPTION: Oid = Oid::new_unwrap("1.2.840.113549.1.1.13");
+pub const ID_SHA224_WITH_RSA_ENCRYPTION: Oid = Oid::new_unwrap("1.2.840.113549.1.1.14");
+
+// RFC 5758 3.2 and RFC 3279 2.2.3
+pub const ID_ECDSA_WITH_SHA1: Oid = Oid::new_unwrap("1.2.840.10045.4.1");
+pub const ID_ECDSA_WITH_SHA224: Oid = Oid::new_unwrap("1.2.840.10045.4.3.1");
+pub const ID_ECDSA_WITH_SHA256: Oid = Oid::new_unwrap("1.2.840.10045.4.3.2");
+pub const ID_ECDSA_WITH_SHA384: Oid = Oid::new_unwrap("1.2.840.10045.4.3.3");
+pub const ID_ECDSA_WITH_SHA512: Oid = Oid::new_unwrap("1.2.840.10045.4.3.4");
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum SignatureAlgorithmIdentifier {
     RsaPss(RsaPssParameters),
+
+    /// RSASSA-PKCS1-v1_5. The digest parameters record whether the
+    /// algorithm parameters were NULL or absent.
+    RsaPkcs1v15(DigestAlgorithmIdentifier),
+
+    /// ECDSA with the signature DER encoded as `SEQUENCE { r, s }`. The
+    /// parameters are absent.
+    Ecdsa(DigestAlgorithmIdentifier),
+
     Unknown(AnyAlgorithmIdentifier),
 }
 
+/// DER encoded ECDSA signature, see RFC 3279 2.2.3.
+///
+/// ```asn1
+/// Ecdsa-Sig-Value ::= SEQUENCE {
+///     r INTEGER,
+///     s INTEGER
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Sequence, ValueOrd)]
+pub struct EcdsaSigValue {
+    pub r: Uint,
+    pub s: Uint,
+}
+
+impl SignatureAlgorithmIdentifier {
+    fn ecdsa_oid(digest: &DigestAlgorithmIdentifier) -> Result<Oid> {
+        Ok(match digest {
+            DigestAlgorithmIdentifier::Sha1(_) => ID_ECDSA_WITH_SHA1,
+            DigestAlgorithmIdentifier::Sha224(_) => ID_ECDSA_WITH_SHA224,
+            DigestAlgorithmIdentifier::Sha256(_) => ID_ECDSA_WITH_SHA256,
+            DigestAlgorithmIdentifier::Sha384(_) => ID_ECDSA_WITH_SHA384,
+            DigestAlgorithmIdentifier::Sha512(_) => ID_ECDSA_WITH_SHA512,
+            _ => return Err(ErrorKind::Value { tag: Oid::TAG }.into()),
+        })
+    }
+
+    fn rsa_pkcs1v15_oid(digest: &DigestAlgorithmIdentifier) -> Result<Oid> {
+        Ok(match digest {
+            DigestAlgorithmIdentifier::Sha1(_) => ID_SHA1_WITH_RSA_ENCRYPTION,
+            DigestAlgorithmIdentifier::Sha224(_) => ID_SHA224_WITH_RSA_ENCRYPTION,
+            DigestAlgorithmIdentifier::Sha256(_) => ID_SHA256_WITH_RSA_ENCRYPTION,
+            DigestAlgorithmIdentifier::Sha384(_) => ID_SHA384_WITH_RSA_ENCRYPTION,
+            DigestAlgorithmIdentifier::Sha512(_) => ID_SHA512_WITH_RSA_ENCRYPTION,
+            _ => return Err(ErrorKind::Value { tag: Oid::TAG }.into()),
+        })
+    }
+}
+
 impl Sequence<'_> for SignatureAlgorithmIdentifier {}
 
 impl ValueOrd for SignatureAlgorithmIdentifier {
@@ -31,27 +92,84 @@ impl ValueOrd for SignatureAlgorithmIdentifier {
 impl EncodeValue for SignatureAlgorithmIdentifier {
     fn value_len(&self) -> Result<Length> {
         match self {
-            Self::RsaPss(_) => todo!(),
+            Self::RsaPss(params) => ID_SIG_RSASSA_PSS.encoded_len()? + params.encoded_len()?,
+            Self::RsaPkcs1v15(digest) => {
+                Self::rsa_pkcs1v15_oid(digest)?.encoded_len()?
+                    + digest.parameters().encoded_len()?
+            }
+            Self::Ecdsa(digest) => Self::ecdsa_oid(digest)?.encoded_len(),
             Self::Unknown(any) => any.value_len(),
         }
     }
 
     fn encode_value(&self, writer: &mut impl Writer) -> Result<()> {
         match self {
-            Self::RsaPss(_) => todo!(),
-            Self::Unknown(any) => any.encode(writer),
+            Self::RsaPss(params) => {
+                ID_SIG_RSASSA_PSS.encode(writer)?;
+                params.encode(writer)
+            }
+            Self::RsaPkcs1v15(digest) => {
+                Self::rsa_pkcs1v15_oid(digest)?.encode(writer)?;
+                digest.parameters().encode(writer)
+            }
+            Self::Ecdsa(digest) => Self::ecdsa_oid(digest)?.encode(writer),
+            Self::Unknown(any) => any.encode_value(writer),
         }
     }
 }
 
 impl<'a> DecodeValue<'a> for SignatureAlgorithmIdentifier {
-    fn decode_value<R: Reader<'a>>(reader: &mut R, _header: der::Header) -> Result<Self> {
+    fn decode_value<R: Reader<'a>>(reader: &mut R, header: der::Header) -> Result<Self> {
+        // Parameters are optional, so limit the reader to this sequence.
+        reader.read_nested(header.length, Self::decode_fields)
+    }
+}
+
+impl SignatureAlgorithmIdentifier {
+    fn decode_fields<'a, R: Reader<'a>>(reader: &mut R) -> Result<Self> {
         let oid = Oid::decode(reader)?;
-        Ok(match oid {
-            ID_SIG_RSASSA_PSS => Self::RsaPss(RsaPssParameters::decode(reader)?),
-            _ => Self::Unknown(AnyAlgorithmIdentifier {
-                algorithm:  oid,
-                parameters: Option::<Any>::decode(reader)?,
+        if oid == ID_SIG_RSASSA_PSS {
+            return Ok(Self::RsaPss(RsaPssParameters::decode(reader)?));
+        }
+        let parameters = Option::<Any>::decode(reader)?;
+        let absent = DigestAlgorithmParameters::Absent;
+        let rsa_params = match &parameters {
+            None => Some(absent),
+            Some(any) if any.is_null() => Some(DigestAlgorithmParameters::Null),
+            Some(_) => None,
+        };
+        Ok(match (oid, parameters, rsa_params) {
+            (ID_SHA1_WITH_RSA_ENCRYPTION, _, Some(p)) => {
+                Self::RsaPkcs1v15(DigestAlgorithmIdentifier::Sha1(p))
+            }
+            (ID_SHA224_WITH_RSA_ENCRYPTION, _, Some(p)) => {
+                Self::RsaPkcs1v15(DigestAlgorithmIdentifier::Sha224(p))
+            }
+            (ID_SHA256_WITH_RSA_ENCRYPTION, _, Some(p)) => {
+                Self::RsaPkcs1v15(DigestAlgorithmIdentifier::Sha256(p))
+            }
+            (ID_SHA384_WITH_RSA_ENCRYPTION, _, Some(p)) => {
+                Self::RsaPkcs1v15(DigestAlgorithmIdentifier::Sha384(p))
+            }
+            (ID_SHA512_WITH_RSA_ENCRYPTION, _, Some(p)) => {
+                Self::RsaPkcs1v15(DigestAlgorithmIdentifier::Sha512(p))
+            }
+            (ID_ECDSA_WITH_SHA1, None, _) => Self::Ecdsa(DigestAlgorithmIdentifier::Sha1(absent)),
+            (ID_ECDSA_WITH_SHA224, None, _) => {
+                Self::Ecdsa(DigestAlgorithmIdentifier::Sha224(absent))
+            }
+            (ID_ECDSA_WITH_SHA256, None, _) => {
+                Self::Ecdsa(DigestAlgorithmIdentifier::Sha256(absent))
+            }
+            (ID_ECDSA_WITH_SHA384, None, _) => {
+                Self::Ecdsa(DigestAlgorithmIdentifier::Sha384(absent))
+            }
+            (ID_ECDSA_WITH_SHA512, None, _) => {
+                Self::Ecdsa(DigestAlgorithmIdentifier::Sha512(absent))
+            }
+            (algorithm, parameters, _) => Self::Unknown(AnyAlgorithmIdentifier {
+                algorithm,
+                parameters,
             }),
         })
     }
@@ -115,28 +233,33 @@ impl ValueOrd for MaskGenAlgorithm {
 impl EncodeValue for MaskGenAlgorithm {
     fn value_len(&self) -> Result<Length> {
         match self {
-            Self::Mgf1(_) => todo!(),
+            Self::Mgf1(digest) => ID_MGFA_MGF1.encoded_len()? + digest.encoded_len()?,
             Self::Unknown(any) => any.value_len(),
         }
     }
 
     fn encode_value(&self, writer: &mut impl Writer) -> Result<()> {
         match self {
-            Self::Mgf1(_) => todo!(),
-            Self::Unknown(any) => any.encode(writer),
+            Self::Mgf1(digest) => {
+                ID_MGFA_MGF1.encode(writer)?;
+                digest.encode(writer)
+            }
+            Self::Unknown(any) => any.encode_value(writer),
         }
     }
 }
 
 impl<'a> DecodeValue<'a> for MaskGenAlgorithm {
-    fn decode_value<R: Reader<'a>>(reader: &mut R, _header: der::Header) -> Result<Self> {
-        let oid = Oid::decode(reader)?;
-        Ok(match oid {
-            ID_MGFA_MGF1 => Self::Mgf1(DigestAlgorithmIdentifier::decode(reader)?),
-            _ => Self::Unknown(AnyAlgorithmIdentifier {
-                algorithm:  oid,
-                parameters: Option::<Any>::decode(reader)?,
-            }),
+    fn decode_value<R: Reader<'a>>(reader: &mut R, header: der::Header) -> Result<Self> {
+        reader.read_nested(header.length, |reader| {
+            let oid = Oid::decode(reader)?;
+            Ok(match oid {
+                ID_MGFA_MGF1 => Self::Mgf1(DigestAlgorithmIdentifier::decode(reader)?),
+                _ => Self::Unknown(AnyAlgorithmIdentifier {
+                    algorithm:  oid,
+                    parameters: Option::<Any>::decode(reader)?,
+                }),
+            })
         })
     }
 }
@@ -156,4 +279,56 @@ mod tests {
         SignatureAlgorithmIdentifier::from_der(&der_params_w_mgf_sha384).unwrap();
         SignatureAlgorithmIdentifier::from_der(&der_params_w_mgf_sha512).unwrap();
     }
+
+    #[test]
+    fn test_rsa_ssa_pss_round_trip() {
+        let samples: [&[u8]; 4] = [
+            &hex!("300d06092a864886f70d01010a3000"),
+            &hex!("303d06092a864886f70d01010a3030a00d300b0609608648016503040201a11a301806092a864886f70d010108300b0609608648016503040201a203020120"),
+            &hex!("303d06092a864886f70d01010a3030a00d300b0609608648016503040202a11a301806092a864886f70d010108300b0609608648016503040202a203020130"),
+            &hex!("303d06092a864886f70d01010a3030a00d300b0609608648016503040203a11a301806092a864886f70d010108300b0609608648016503040203a203020140"),
+        ];
+        for der in samples {
+            let algo = SignatureAlgorithmIdentifier::from_der(der).unwrap();
+            assert!(matches!(algo, SignatureAlgorithmIdentifier::RsaPss(_)));
+            assert_eq!(algo.to_der().unwrap(), der);
+        }
+    }
+
+    #[test]
+    fn test_ecdsa_round_trip() {
+        let der = hex!("300a06082a8648ce3d040302");
+        let algo = SignatureAlgorithmIdentifier::from_der(&der).unwrap();
+        assert_eq!(
+            algo,
+            SignatureAlgorithmIdentifier::Ecdsa(DigestAlgorithmIdentifier::Sha256(
+                DigestAlgorithmParameters::Absent
+            ))
+        );
+        assert_eq!(algo.to_der().unwrap(), der);
+    }
+
+    #[test]
+    fn test_rsa_pkcs1v15_round_trip() {
+        // sha256WithRSAEncryption with NULL and absent parameters.
+        let der = hex!("300d06092a864886f70d01010b0500");
+        let algo = SignatureAlgorithmIdentifier::from_der(&der).unwrap();
+        assert_eq!(
+            algo,
+            SignatureAlgorithmIdentifier::RsaPkcs1v15(DigestAlgorithmIdentifier::Sha256(
+                DigestAlgorithmParameters::Null
+            ))
+        );
+        assert_eq!(algo.to_der().unwrap(), der);
+
+        let der = hex!("300b06092a864886f70d01010d");
+        let algo = SignatureAlgorithmIdentifier::from_der(&der).unwrap();
+        assert_eq!(
+            algo,
+            SignatureAlgorithmIdentifier::RsaPkcs1v15(DigestAlgorithmIdentifier::Sha512(
+                DigestAlgorithmParameters::Absent
+            ))
+        );
+        assert_eq!(algo.to_der().unwrap(), der);
+    }
 }
@@ -0,0 +1,235 @@
+//! Names of the BSI TR-03110 and ICAO 9303 object identifiers.
+
+use der::asn1::ObjectIdentifier as Oid;
+
+/// Object identifiers used in this crate with their ASN.1 names.
+const NAMES: &[(Oid, &str)] = &[
+    // Public keys, BSI TR-03110-3 A.1.1.1.
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.1.1"), "id-PK-DH"),
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.1.2"), "id-PK-ECDH"),
+    // Terminal Authentication, BSI TR-03110-3 A.1.1.3.
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.2"), "id-TA"),
+    // Chip Authentication, ICAO 9303-11 9.2.7.
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.3.1"), "id-CA-DH"),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.3.1.1"),
+        "id-CA-DH-3DES-CBC-CBC",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.3.1.2"),
+        "id-CA-DH-AES-CBC-CMAC-128",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.3.1.3"),
+        "id-CA-DH-AES-CBC-CMAC-192",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.3.1.4"),
+        "id-CA-DH-AES-CBC-CMAC-256",
+    ),
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.3.2"), "id-CA-ECDH"),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.3.2.1"),
+        "id-CA-ECDH-3DES-CBC-CBC",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.3.2.2"),
+        "id-CA-ECDH-AES-CBC-CMAC-128",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.3.2.3"),
+        "id-CA-ECDH-AES-CBC-CMAC-192",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.3.2.4"),
+        "id-CA-ECDH-AES-CBC-CMAC-256",
+    ),
+    // PACE, ICAO 9303-11 9.2.3.
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.4.1"), "id-PACE-DH-GM"),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.1.1"),
+        "id-PACE-DH-GM-3DES-CBC-CBC",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.1.2"),
+        "id-PACE-DH-GM-AES-CBC-CMAC-128",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.1.3"),
+        "id-PACE-DH-GM-AES-CBC-CMAC-192",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.1.4"),
+        "id-PACE-DH-GM-AES-CBC-CMAC-256",
+    ),
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.4.2"), "id-PACE-ECDH-GM"),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.2.1"),
+        "id-PACE-ECDH-GM-3DES-CBC-CBC",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.2.2"),
+        "id-PACE-ECDH-GM-AES-CBC-CMAC-128",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.2.3"),
+        "id-PACE-ECDH-GM-AES-CBC-CMAC-192",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.2.4"),
+        "id-PACE-ECDH-GM-AES-CBC-CMAC-256",
+    ),
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.4.3"), "id-PACE-DH-IM"),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.3.1"),
+        "id-PACE-DH-IM-3DES-CBC-CBC",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.3.2"),
+        "id-PACE-DH-IM-AES-CBC-CMAC-128",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.3.3"),
+        "id-PACE-DH-IM-AES-CBC-CMAC-192",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.3.4"),
+        "id-PACE-DH-IM-AES-CBC-CMAC-256",
+    ),
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.4.4"), "id-PACE-ECDH-IM"),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.4.1"),
+        "id-PACE-ECDH-IM-3DES-CBC-CBC",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.4.2"),
+        "id-PACE-ECDH-IM-AES-CBC-CMAC-128",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.4.3"),
+        "id-PACE-ECDH-IM-AES-CBC-CMAC-192",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.4.4"),
+        "id-PACE-ECDH-IM-AES-CBC-CMAC-256",
+    ),
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.4.6"), "id-PACE-ECDH-CAM"),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.6.2"),
+        "id-PACE-ECDH-CAM-AES-CBC-CMAC-128",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.6.3"),
+        "id-PACE-ECDH-CAM-AES-CBC-CMAC-192",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.4.6.4"),
+        "id-PACE-ECDH-CAM-AES-CBC-CMAC-256",
+    ),
+    // Restricted Identification, BSI TR-03110-3 A.1.1.6.
+    (Oid::new_unwrap("0.4.0.127.0.7.2.2.5.1.1"), "id-RI-DH-SHA-1"),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.5.1.2"),
+        "id-RI-DH-SHA-224",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.5.1.3"),
+        "id-RI-DH-SHA-256",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.5.1.4"),
+        "id-RI-DH-SHA-384",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.5.1.5"),
+        "id-RI-DH-SHA-512",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.5.2.1"),
+        "id-RI-ECDH-SHA-1",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.5.2.2"),
+        "id-RI-ECDH-SHA-224",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.5.2.3"),
+        "id-RI-ECDH-SHA-256",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.5.2.4"),
+        "id-RI-ECDH-SHA-384",
+    ),
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.2.2.5.2.5"),
+        "id-RI-ECDH-SHA-512",
+    ),
+    // Other BSI TR-03110 and TR-03111 identifiers.
+    (
+        Oid::new_unwrap("0.4.0.127.0.7.1.1.4.1"),
+        "ecdsa-plain-signatures",
+    ),
+    (Oid::new_unwrap("0.4.0.127.0.7.3.1.2.1"), "id-IS"),
+    (Oid::new_unwrap("0.4.0.127.0.7.3.2.1"), "id-SecurityObject"),
+    // ICAO 9303 identifiers.
+    (
+        Oid::new_unwrap("2.23.136.1.1.1"),
+        "id-icao-mrtd-security-ldsSecurityObject",
+    ),
+    (Oid::new_unwrap("2.23.136.1.1.2"), "id-icao-cscaMasterList"),
+    (
+        Oid::new_unwrap("2.23.136.1.1.5"),
+        "id-icao-mrtd-security-aaProtocolObject",
+    ),
+    (Oid::new_unwrap("2.23.136.1.1.13"), "id-EFDIR"),
+];
+
+/// The ASN.1 name of a BSI TR-03110 or ICAO 9303 object identifier, for
+/// example `id-CA-ECDH-AES-CBC-CMAC-128`.
+///
+/// Returns `None` for other object identifiers.
+pub fn oid_name(oid: Oid) -> Option<&'static str> {
+    NAMES
+        .iter()
+        .find_map(|&(known, name)| (known == oid).then_some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::asn1::emrtd::security_info::{
+            ChipAuthenticationProtocol, KeyAgreement, SymmetricCipher,
+        },
+    };
+
+    #[test]
+    fn test_oid_name() {
+        assert_eq!(
+            oid_name(Oid::new_unwrap("0.4.0.127.0.7.2.2.4.2.2")),
+            Some("id-PACE-ECDH-GM-AES-CBC-CMAC-128")
+        );
+        assert_eq!(
+            oid_name(Oid::new_unwrap("2.23.136.1.1.5")),
+            Some("id-icao-mrtd-security-aaProtocolObject")
+        );
+        assert_eq!(oid_name(Oid::new_unwrap("1.2.3.4")), None);
+
+        // The registry agrees with the protocol names.
+        for key_agreement in [KeyAgreement::Dh, KeyAgreement::Ecdh] {
+            for cipher in [
+                None,
+                Some(SymmetricCipher::Tdes),
+                Some(SymmetricCipher::Aes128),
+                Some(SymmetricCipher::Aes192),
+                Some(SymmetricCipher::Aes256),
+            ] {
+                let protocol = ChipAuthenticationProtocol {
+                    key_agreement,
+                    cipher,
+                };
+                assert_eq!(oid_name(protocol.into()), Some(protocol.name()));
+            }
+        }
+    }
+}
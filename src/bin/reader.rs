@@ -4,7 +4,7 @@ use {
     anyhow::{anyhow, Context, Result},
     icao_9303::{
         asn1::emrtd::EfSod,
-        emrtd::{Emrtd, Error, FileId},
+        emrtd::{Emrtd, Error},
         ensure_err,
         iso7816::StatusWord,
         nfc::connect_reader,
@@ -34,20 +34,18 @@ fn main() -> Result<()> {
     eprintln!("Basic Access Control successful.");
 
     // let ef_sod = card.read_cached::<EfSod>()?;
-    // println!("DOCUMENT HASH = 0x{}", hex::encode(ef_sod.document_hash()));
+    // println!("DOCUMENT HASH = 0x{}", hex::encode(ef_sod.canonical_hash()?));
 
     // Should be secured now!
     // Let's read some files.
-    for file_id in FileId::iter() {
-        match card.read_file_cached(file_id) {
-            Ok(Some(data)) => println!("{}: {}", file_id, hex::encode(data)),
-            Ok(None) => println!("{}: Not Found", file_id),
-            Err(Error::ErrorResponse(StatusWord::ACCESS_DENIED)) => {
-                println!("{}: Access Denied", file_id)
-            }
-            Err(e) => eprintln!("{}: {}", file_id, e),
+    let files = card.read_all_files(&mut rng, |file_id, result| match result {
+        Ok(Some(data)) => println!("{}: {}", file_id, hex::encode(data)),
+        Ok(None) => println!("{}: Not Found", file_id),
+        Err(Error::ErrorResponse(StatusWord::ACCESS_DENIED)) => {
+            println!("{}: Access Denied", file_id)
         }
-    }
+        Err(e) => eprintln!("{}: {}", file_id, e),
+    });
 
     // TODO: Verify SOD.
     // https://github.com/worldcoin/nfc-uniqueness-service/blob/d907d9ef33826034665592685c1e24d25bdb1259/src/routes/v1/mod.rs#L102
@@ -61,7 +59,16 @@ fn main() -> Result<()> {
 
     // Dump SOD
     let sod: EfSod = card.read_cached()?;
-    println!("DOCUMENT HASH = 0x{}", hex::encode(sod.document_hash()));
+    println!("DOCUMENT HASH = 0x{}", hex::encode(sod.canonical_hash()?));
+
+    // Check the data groups we could read against the SOD.
+    let data_groups: Vec<(usize, &[u8])> = files
+        .iter()
+        .filter_map(|(file_id, data)| Some((file_id.data_group_number()?, data.as_slice())))
+        .collect();
+    sod.verify_data_groups(&data_groups)
+        .context("Error verifying data groups.")?;
+    eprintln!("Data groups match EF.SOD.");
 
     // Do Chip Authentication
     card.chip_authenticate(&mut rng)
@@ -79,7 +79,7 @@ fn main() -> Result<()> {
         let document: Document = serde_json::from_reader(reader)?;
         println!(
             "Document with hash 0x{}",
-            hex::encode(document.sod.document_hash())
+            hex::encode(document.sod.canonical_hash()?)
         );
         let signed_data = document.sod.signed_data();
         let certs = signed_data.certificates.as_ref().unwrap();
@@ -116,11 +116,11 @@ fn main() -> Result<()> {
         //
         println!(
             " - SOD Signature Algoritm: {:?}",
-            hex::encode(document.sod.signer_info().signature_algorithm.to_der()?)
+            hex::encode(document.sod.signer_info()?.signature_algorithm.to_der()?)
         );
         println!(
             " - SOD Signature: {:?}",
-            hex::encode(document.sod.signer_info().signature.as_bytes())
+            hex::encode(document.sod.signer_info()?.signature.as_bytes())
         );
 
         // Get LDS Security Object and it's hash algorithm.
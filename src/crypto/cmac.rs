@@ -0,0 +1,147 @@
+//! AES-CMAC as used for AES Secure Messaging.
+//!
+//! See RFC 4493 and NIST SP 800-38B.
+
+use {
+    aes::{Aes128, Aes192, Aes256},
+    cipher::{consts::U16, BlockEncrypt, BlockSizeUser, KeyInit},
+};
+
+const BLOCK_SIZE: usize = 16;
+
+/// AES-CMAC with a 128-bit key.
+pub fn aes_cmac_128(key: &[u8; 16], data: &[u8]) -> [u8; 16] {
+    cmac(&Aes128::new(key.into()), data)
+}
+
+/// AES-CMAC with a 192-bit key.
+pub fn aes_cmac_192(key: &[u8; 24], data: &[u8]) -> [u8; 16] {
+    cmac(&Aes192::new(key.into()), data)
+}
+
+/// AES-CMAC with a 256-bit key.
+pub fn aes_cmac_256(key: &[u8; 32], data: &[u8]) -> [u8; 16] {
+    cmac(&Aes256::new(key.into()), data)
+}
+
+/// CMAC for any cipher with a 128-bit block size, RFC 4493 section 2.4.
+fn cmac<C: BlockEncrypt + BlockSizeUser<BlockSize = U16>>(cipher: &C, data: &[u8]) -> [u8; 16] {
+    // Subkeys, see RFC 4493 section 2.3.
+    let mut l = [0; BLOCK_SIZE];
+    cipher.encrypt_block((&mut l).into());
+    let k1 = double(l);
+    let k2 = double(k1);
+
+    // The last block is masked with K1 if it is complete, otherwise it is
+    // padded with 0x80 0x00.. and masked with K2.
+    let blocks = data.len().div_ceil(BLOCK_SIZE).max(1);
+    let (head, tail) = data.split_at((blocks - 1) * BLOCK_SIZE);
+    let mut last = [0; BLOCK_SIZE];
+    last[..tail.len()].copy_from_slice(tail);
+    let subkey = if tail.len() == BLOCK_SIZE {
+        k1
+    } else {
+        last[tail.len()] = 0x80;
+        k2
+    };
+    xor(&mut last, &subkey);
+
+    let mut x = [0; BLOCK_SIZE];
+    for block in head.chunks_exact(BLOCK_SIZE).chain([&last[..]]) {
+        xor(&mut x, block);
+        cipher.encrypt_block((&mut x).into());
+    }
+    x
+}
+
+/// Multiplication by `x` in GF(2^128), the subkey generation step.
+///
+/// The subkeys are secret, so the reduction is applied with a mask derived
+/// from the top bit instead of a branch.
+const fn double(block: [u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+    let value = u128::from_be_bytes(block);
+    let reduction = 0x87 & 0_u128.wrapping_sub(value >> 127);
+    ((value << 1) ^ reduction).to_be_bytes()
+}
+
+fn xor(block: &mut [u8; BLOCK_SIZE], other: &[u8]) {
+    for (a, b) in block.iter_mut().zip(other) {
+        *a ^= b;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    const MESSAGE: [u8; 64] = hex!(
+        "6bc1bee2 2e409f96 e93d7e11 7393172a
+        ae2d8a57 1e03ac9c 9eb76fac 45af8e51
+        30c81c46 a35ce411 e5fbc119 1a0a52ef
+        f69f2445 df4f9b17 ad2b417b e66c3710"
+    );
+
+    // RFC 4493 section 4, examples 1 to 4
+    #[test]
+    fn test_aes_cmac_128() {
+        let key = hex!("2b7e1516 28aed2a6 abf71588 09cf4f3c");
+
+        // Subkeys from RFC 4493 section 4
+        let mut l = [0; BLOCK_SIZE];
+        Aes128::new(&key.into()).encrypt_block((&mut l).into());
+        assert_eq!(l, hex!("7df76b0c 1ab899b3 3e42f047 b91b546f"));
+        assert_eq!(double(l), hex!("fbeed618 35713366 7c85e08f 7236a8de"));
+        assert_eq!(
+            double(double(l)),
+            hex!("f7ddac30 6ae266cc f90bc11e e46d513b")
+        );
+        // Without the top bit there is no reduction.
+        assert_eq!(double([0x01; BLOCK_SIZE]), [0x02; BLOCK_SIZE]);
+
+        let cases: [(usize, [u8; 16]); 4] = [
+            (0, hex!("bb1d6929 e9593728 7fa37d12 9b756746")),
+            (16, hex!("070a16b4 6b4d4144 f79bdd9d d04a287c")),
+            (40, hex!("dfa66747 de9ae630 30ca3261 1497c827")),
+            (64, hex!("51f0bebf 7e3b9d92 fc497417 79363cfe")),
+        ];
+        for (len, mac) in cases {
+            assert_eq!(aes_cmac_128(&key, &MESSAGE[..len]), mac);
+        }
+    }
+
+    // NIST SP 800-38B section D.2
+    #[test]
+    fn test_aes_cmac_192() {
+        let key = hex!(
+            "8e73b0f7 da0e6452 c810f32b 809079e5
+            62f8ead2 522c6b7b"
+        );
+        let cases: [(usize, [u8; 16]); 4] = [
+            (0, hex!("d17ddf46 adaacde5 31cac483 de7a9367")),
+            (16, hex!("9e99a7bf 31e71090 0662f65e 617c5184")),
+            (40, hex!("8a1de5be 2eb31aad 089a82e6 ee908b0e")),
+            (64, hex!("a1d5df0e ed790f79 4d775896 59f39a11")),
+        ];
+        for (len, mac) in cases {
+            assert_eq!(aes_cmac_192(&key, &MESSAGE[..len]), mac);
+        }
+    }
+
+    // NIST SP 800-38B section D.3
+    #[test]
+    fn test_aes_cmac_256() {
+        let key = hex!(
+            "603deb10 15ca71be 2b73aef0 857d7781
+            1f352c07 3b6108d7 2d9810a3 0914dff4"
+        );
+        let cases: [(usize, [u8; 16]); 4] = [
+            (0, hex!("028962f6 1b7bf89e fc6b551f 4667d983")),
+            (16, hex!("28a7023f 452e8f82 bd4bf28d 8c37c35c")),
+            (40, hex!("aaf3d8f1 de5640c2 32f5b169 b9c911e6")),
+            (64, hex!("e1992190 549f6ed5 696a2c05 6c315410")),
+        ];
+        for (len, mac) in cases {
+            assert_eq!(aes_cmac_256(&key, &MESSAGE[..len]), mac);
+        }
+    }
+}
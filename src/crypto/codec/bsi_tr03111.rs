@@ -63,11 +63,7 @@ where
     type Parent = R;
 
     fn encode<B: BufMut>(&self, buffer: &mut B, value: ModRingElement<R>) {
-        let codec = Self {
-            uint_bytes: Some(value.ring().modulus().byte_len()),
-            ..Default::default()
-        };
-        codec.encode(buffer, value.to_uint());
+        buffer.put_slice(&value.to_be_bytes_padded());
     }
 
     fn decode<B: Buf>(&self, buffer: &mut B, parent: Self::Parent) -> Result<ModRingElement<R>> {
@@ -92,8 +88,9 @@ impl<'a, const BITS: usize, const LIMBS: usize> Codec<EllipticCurvePoint<'a, Uin
             None => buffer.put_u8(0),
             Some((x, y)) => {
                 if self.compressed_points {
-                    let even = y.to_uint().bit(0);
-                    buffer.put_u8(if even { 2 } else { 3 });
+                    // The prefix encodes the least significant bit of y.
+                    let odd = y.to_uint().bit(0);
+                    buffer.put_u8(if odd { 3 } else { 2 });
                     self.encode(buffer, x);
                 } else {
                     buffer.put_u8(4);
@@ -109,22 +106,27 @@ impl<'a, const BITS: usize, const LIMBS: usize> Codec<EllipticCurvePoint<'a, Uin
         buffer: &mut B,
         parent: Self::Parent,
     ) -> Result<EllipticCurvePoint<'a, Uint<BITS, LIMBS>>> {
+        ensure!(
+            buffer.has_remaining(),
+            "EOF when reading elliptic curve point"
+        );
         let byte = buffer.get_u8();
         match byte {
             0 => Ok(parent.infinity()),
             2 | 3 => {
-                let want_even = byte == 2;
+                // `from_x` returns either root, so fix the parity of y.
+                let want_odd = byte == 3;
                 let x = self.decode(buffer, parent.base_field())?;
                 let p = parent
                     .from_x(x)
                     .ok_or_else(|| anyhow!("Invalid x coordinate"))?;
-                let is_even = p.y().unwrap().to_uint().bit(0);
-                Ok(if want_even == is_even { p } else { -p })
+                let is_odd = p.y().unwrap().to_uint().bit(0);
+                Ok(if want_odd == is_odd { p } else { -p })
             }
             4 => {
                 let x = self.decode(buffer, parent.base_field())?;
                 let y = self.decode(buffer, parent.base_field())?;
-                parent.from_affine(x, y)
+                Ok(parent.from_affine(x, y)?)
             }
             _ => Err(anyhow!("Invalid byte for elliptic curve point")),
         }
@@ -134,7 +136,10 @@ impl<'a, const BITS: usize, const LIMBS: usize> Codec<EllipticCurvePoint<'a, Uin
 #[cfg(test)]
 mod tests {
     use {
-        super::{super::BufCodecParent, *},
+        super::{
+            super::{BufCodecParent, BufMutCodec},
+            *,
+        },
         crate::crypto::groups::named::brainpool_p256r1,
         hex_literal::hex,
     };
@@ -161,4 +166,42 @@ mod tests {
             pk_pcd.as_ref().get_codec_parent(&codec, &curve).unwrap();
         assert_eq!(curve.generator() * sk_pcd, pk_pcd);
     }
+
+    #[test]
+    fn test_compressed_point() {
+        let codec = BsiTr031111Codec::default();
+        let curve = brainpool_p256r1();
+        let generator = curve.generator();
+
+        // The y coordinate of the generator is odd.
+        let mut encoded = Vec::new();
+        encoded.put_codec(&codec, generator);
+        assert_eq!(
+            encoded,
+            hex!("03 8BD2AEB9CB7E57CB2C4B482FFC81B7AFB9DE27E1E3BD23C23A4453BD9ACE3262")
+        );
+        let decoded: EllipticCurvePoint<_> =
+            encoded.as_slice().get_codec_parent(&codec, &curve).unwrap();
+        assert_eq!(decoded, generator);
+
+        // Even y for the negated generator.
+        let mut encoded = Vec::new();
+        encoded.put_codec(&codec, -generator);
+        assert_eq!(encoded[0], 2);
+        let decoded: EllipticCurvePoint<_> =
+            encoded.as_slice().get_codec_parent(&codec, &curve).unwrap();
+        assert_eq!(decoded, -generator);
+    }
+
+    #[test]
+    fn test_point_at_infinity() {
+        let codec = BsiTr031111Codec::default();
+        let curve = brainpool_p256r1();
+        let mut encoded = Vec::new();
+        encoded.put_codec(&codec, curve.infinity());
+        assert_eq!(encoded, [0]);
+        let decoded: EllipticCurvePoint<_> =
+            encoded.as_slice().get_codec_parent(&codec, &curve).unwrap();
+        assert_eq!(decoded, curve.infinity());
+    }
 }
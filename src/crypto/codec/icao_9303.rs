@@ -3,7 +3,7 @@ use {
     super::{BsiTr031111Codec, Codec},
     crate::crypto::groups::{EllipticCurve, EllipticCurvePoint},
     anyhow::{anyhow, ensure, Result},
-    bytes::{Buf, BufMut, BytesMut},
+    bytes::{Buf, BufMut, Bytes, BytesMut},
     const_oid::ObjectIdentifier,
     der::Encode,
     ruint::Uint,
@@ -41,8 +41,16 @@ pub struct Icao9303Codec {
     /// Allow missing cofactors in elliptic curves.
     /// When missing, the cofactor is assumed to be 1.
     missing_cofactor: Leniency,
+
+    /// Codec for elliptic curve points, always uncompressed.
+    point_codec: BsiTr031111Codec,
 }
 
+const POINT_CODEC: BsiTr031111Codec = BsiTr031111Codec {
+    uint_bytes:        None,
+    compressed_points: false,
+};
+
 /// Default behaviour is to warn.
 impl Default for Icao9303Codec {
     fn default() -> Self {
@@ -52,10 +60,74 @@ impl Default for Icao9303Codec {
             read_order:         Leniency::Warn,
             unknown_tag:        Leniency::Strict,
             missing_cofactor:   Leniency::Warn,
+            point_codec:        POINT_CODEC,
+        }
+    }
+}
+
+impl Icao9303Codec {
+    /// Reject all correctable errors.
+    pub const fn strict() -> Self {
+        Self::all(Leniency::Strict)
+    }
+
+    /// Silently correct all correctable errors.
+    pub const fn lenient() -> Self {
+        Self::all(Leniency::Allow)
+    }
+
+    /// Start from the default behaviour and override individual settings.
+    pub fn builder() -> Icao9303CodecBuilder {
+        Icao9303CodecBuilder(Self::default())
+    }
+
+    const fn all(leniency: Leniency) -> Self {
+        Self {
+            non_minimal_length: leniency,
+            leading_zeros:      leniency,
+            read_order:         leniency,
+            unknown_tag:        leniency,
+            missing_cofactor:   leniency,
+            point_codec:        POINT_CODEC,
         }
     }
 }
 
+/// Builder for [`Icao9303Codec`].
+#[derive(Clone, Copy, Debug)]
+pub struct Icao9303CodecBuilder(Icao9303Codec);
+
+impl Icao9303CodecBuilder {
+    pub const fn with_non_minimal_length(mut self, leniency: Leniency) -> Self {
+        self.0.non_minimal_length = leniency;
+        self
+    }
+
+    pub const fn with_leading_zeros(mut self, leniency: Leniency) -> Self {
+        self.0.leading_zeros = leniency;
+        self
+    }
+
+    pub const fn with_read_order(mut self, leniency: Leniency) -> Self {
+        self.0.read_order = leniency;
+        self
+    }
+
+    pub const fn with_unknown_tag(mut self, leniency: Leniency) -> Self {
+        self.0.unknown_tag = leniency;
+        self
+    }
+
+    pub const fn with_missing_cofactor(mut self, leniency: Leniency) -> Self {
+        self.0.missing_cofactor = leniency;
+        self
+    }
+
+    pub const fn build(self) -> Icao9303Codec {
+        self.0
+    }
+}
+
 pub struct BerSize(usize);
 
 pub struct PublicKeyRSA<U> {
@@ -72,6 +144,183 @@ pub struct PublicKeyDH<U, V> {
     public_key: U,
 }
 
+/// ICAO 9303-11 section 9.4.4 Elliptic Curve Public Keys
+///
+/// The domain parameters are conditional and absent when the curve is
+/// identified by the algorithm. Points are kept in their encoded form as
+/// decoding them requires the curve.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PublicKeyEC<U> {
+    oid:        ObjectIdentifier,
+    prime:      Option<U>,
+    a:          Option<U>,
+    b:          Option<U>,
+    base:       Option<Bytes>,
+    order:      Option<U>,
+    public_key: Bytes,
+    cofactor:   Option<U>,
+}
+
+impl<U: Copy> PublicKeyEC<U> {
+    /// Public key on the curve identified by the algorithm, without domain
+    /// parameters.
+    pub const fn new(oid: ObjectIdentifier, public_key: Bytes) -> Self {
+        Self {
+            oid,
+            prime: None,
+            a: None,
+            b: None,
+            base: None,
+            order: None,
+            public_key,
+            cofactor: None,
+        }
+    }
+
+    /// Public key with explicit domain parameters.
+    pub fn with_domain_parameters(params: EcDomainParametersTlv<U>, public_key: Bytes) -> Self {
+        Self {
+            oid: params.oid,
+            prime: Some(params.prime),
+            a: Some(params.a),
+            b: Some(params.b),
+            base: Some(params.base),
+            order: Some(params.order),
+            public_key,
+            cofactor: params.cofactor,
+        }
+    }
+
+    pub const fn oid(&self) -> ObjectIdentifier {
+        self.oid
+    }
+
+    /// The encoded public point.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// The domain parameters, `None` unless all required parameters are
+    /// present.
+    pub fn domain_parameters(&self) -> Option<EcDomainParametersTlv<U>> {
+        Some(EcDomainParametersTlv {
+            oid:      self.oid,
+            prime:    self.prime?,
+            a:        self.a?,
+            b:        self.b?,
+            base:     self.base.clone()?,
+            order:    self.order?,
+            cofactor: self.cofactor,
+        })
+    }
+}
+
+/// ICAO 9303-11 section 9.4.5 Elliptic Curve Domain Parameters
+///
+/// The explicit domain parameters of an elliptic curve public key, without
+/// the public point. The base point is kept in its encoded form as decoding
+/// it requires the curve.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EcDomainParametersTlv<U> {
+    oid:      ObjectIdentifier,
+    prime:    U,
+    a:        U,
+    b:        U,
+    base:     Bytes,
+    order:    U,
+    cofactor: Option<U>,
+}
+
+impl<U: Copy> EcDomainParametersTlv<U> {
+    pub const fn new(
+        oid: ObjectIdentifier,
+        prime: U,
+        a: U,
+        b: U,
+        base: Bytes,
+        order: U,
+        cofactor: Option<U>,
+    ) -> Self {
+        Self {
+            oid,
+            prime,
+            a,
+            b,
+            base,
+            order,
+            cofactor,
+        }
+    }
+
+    pub const fn oid(&self) -> ObjectIdentifier {
+        self.oid
+    }
+
+    pub const fn prime(&self) -> U {
+        self.prime
+    }
+
+    pub const fn a(&self) -> U {
+        self.a
+    }
+
+    pub const fn b(&self) -> U {
+        self.b
+    }
+
+    /// The encoded base point.
+    pub fn base(&self) -> &[u8] {
+        &self.base
+    }
+
+    pub const fn order(&self) -> U {
+        self.order
+    }
+
+    pub const fn cofactor(&self) -> Option<U> {
+        self.cofactor
+    }
+}
+
+impl<const BITS: usize, const LIMBS: usize> EcDomainParametersTlv<Uint<BITS, LIMBS>> {
+    /// The domain parameters of `curve` with an uncompressed base point.
+    pub fn from_curve(oid: ObjectIdentifier, curve: &EllipticCurve<Uint<BITS, LIMBS>>) -> Self {
+        Self {
+            oid,
+            prime: curve.base_field().modulus(),
+            a: curve.a().to_uint(),
+            b: curve.b().to_uint(),
+            base: curve.encode_point(curve.generator()).into(),
+            order: curve.scalar_field().modulus(),
+            cofactor: Some(curve.cofactor()),
+        }
+    }
+
+    /// Construct the curve, a missing cofactor is taken to be 1.
+    ///
+    /// Only uncompressed base points are supported.
+    pub fn to_curve(&self) -> Result<EllipticCurve<Uint<BITS, LIMBS>>> {
+        let base = self.base.as_ref();
+        ensure!(
+            base.first() == Some(&4) && base.len() % 2 == 1,
+            "Unsupported base point encoding"
+        );
+        let (x, y) = base[1..].split_at(base.len() / 2);
+        let coordinate = |bytes| {
+            Uint::try_from_be_slice(bytes).ok_or_else(|| anyhow!("Base point coordinate too large"))
+        };
+        Ok(EllipticCurve::new(
+            self.prime,
+            self.a,
+            self.b,
+            coordinate(x)?,
+            coordinate(y)?,
+            self.order,
+            self.cofactor.unwrap_or_else(|| Uint::from(1)),
+        )?)
+    }
+}
+
 fn lenient(leniency: Leniency, msg: &'static str) -> Result<()> {
     match leniency {
         Leniency::Strict => Err(anyhow!(msg)),
@@ -87,9 +336,9 @@ impl Codec<BerSize> for Icao9303Codec {
     type Parent = ();
 
     fn encoded_size(&self, value: BerSize) -> usize {
-        match value.0.ilog2() {
-            ..8 => 1,
-            n => 1 + (n as usize + 7) / 8,
+        match value.0 {
+            ..128 => 1,
+            n => 2 + n.ilog2() as usize / 8,
         }
     }
 
@@ -146,13 +395,35 @@ impl Codec<ObjectIdentifier> for Icao9303Codec {
     }
 }
 
+/// Octet strings are copied verbatim.
+impl Codec<Bytes> for Icao9303Codec {
+    type Parent = ();
+
+    fn encoded_size(&self, value: Bytes) -> usize {
+        value.len()
+    }
+
+    fn encode<B: BufMut>(&self, buffer: &mut B, value: Bytes) {
+        buffer.put_slice(&value);
+    }
+
+    fn decode<B: Buf>(&self, buffer: &mut B, _parent: Self::Parent) -> Result<Bytes> {
+        Ok(buffer.copy_to_bytes(buffer.remaining()))
+    }
+}
+
 /// ICAO 9303-11 section 9.4.1 Data Object Encoding
 ///
 /// An unsigned integer SHALL be converted to an octet string using the binary
 /// representation of the integer in big-endian format. The minimum number of
 /// octets SHALL be used, i.e. leading octets of value 0x00 MUST NOT be used.
+///
+/// The parent is an optional expected byte length for fixed size fields. If
+/// given the input must have exactly this length instead of being minimal.
+/// Minimal encodings are shorter than the field size for small values, so
+/// only pass a length where the field size is mandated.
 impl<const BITS: usize, const LIMBS: usize> Codec<Uint<BITS, LIMBS>> for Icao9303Codec {
-    type Parent = ();
+    type Parent = Option<usize>;
 
     fn encoded_size(&self, value: Uint<BITS, LIMBS>) -> usize {
         value.byte_len()
@@ -162,11 +433,20 @@ impl<const BITS: usize, const LIMBS: usize> Codec<Uint<BITS, LIMBS>> for Icao930
         buffer.put_slice(&value.to_be_bytes_trimmed_vec());
     }
 
-    fn decode<B: Buf>(&self, buffer: &mut B, _parent: Self::Parent) -> Result<Uint<BITS, LIMBS>> {
+    fn decode<B: Buf>(&self, buffer: &mut B, parent: Self::Parent) -> Result<Uint<BITS, LIMBS>> {
         let bytes = buffer.copy_to_bytes(buffer.remaining());
         let trim = bytes.iter().position(|&b| b != 0).unwrap_or(0);
-        if trim > 0 {
-            lenient(self.leading_zeros, "Leading zeros in integer.")?;
+        match parent {
+            Some(len) if bytes.len() != len => {
+                lenient(
+                    self.leading_zeros,
+                    "Integer length does not match field size.",
+                )?;
+            }
+            None if trim > 0 => {
+                lenient(self.leading_zeros, "Leading zeros in integer.")?;
+            }
+            _ => {}
         }
         let bytes = &bytes[trim..];
         Uint::try_from_be_slice(bytes).ok_or_else(|| anyhow!("Value to large for target Uint"))
@@ -177,25 +457,19 @@ impl<const BITS: usize, const LIMBS: usize> Codec<Uint<BITS, LIMBS>> for Icao930
 ///
 /// To encode elliptic curve points, uncompressed encoding according to
 /// [TR-03111] SHALL be used.
+///
+/// Coordinates padded beyond the field size are subject to `leading_zeros`.
 impl<'a, const BITS: usize, const LIMBS: usize> Codec<EllipticCurvePoint<'a, Uint<BITS, LIMBS>>>
     for Icao9303Codec
 {
     type Parent = &'a EllipticCurve<Uint<BITS, LIMBS>>;
 
     fn encoded_size(&self, value: EllipticCurvePoint<'a, Uint<BITS, LIMBS>>) -> usize {
-        let codec = BsiTr031111Codec {
-            compressed_points: false,
-            ..Default::default()
-        };
-        codec.encoded_size(value)
+        self.point_codec.encoded_size(value)
     }
 
     fn encode<B: BufMut>(&self, buffer: &mut B, value: EllipticCurvePoint<'a, Uint<BITS, LIMBS>>) {
-        let codec = BsiTr031111Codec {
-            compressed_points: false,
-            ..Default::default()
-        };
-        codec.encode(buffer, value)
+        self.point_codec.encode(buffer, value);
     }
 
     fn decode<B: Buf>(
@@ -203,44 +477,88 @@ impl<'a, const BITS: usize, const LIMBS: usize> Codec<EllipticCurvePoint<'a, Uin
         buffer: &mut B,
         parent: Self::Parent,
     ) -> Result<EllipticCurvePoint<'a, Uint<BITS, LIMBS>>> {
-        let codec = BsiTr031111Codec {
-            compressed_points: false,
-            ..Default::default()
-        };
-        codec.decode(buffer, parent)
+        let mut bytes = buffer.copy_to_bytes(buffer.remaining());
+        let field_len = parent.base_field().modulus().byte_len();
+        if bytes.first() == Some(&4) && bytes.len() > 1 + 2 * field_len {
+            let coordinate_len = (bytes.len() - 1) / 2;
+            let padding = coordinate_len - field_len;
+            let (x, y) = bytes[1..].split_at(coordinate_len);
+            ensure!(
+                bytes.len() % 2 == 1
+                    && x[..padding].iter().all(|&b| b == 0)
+                    && y[..padding].iter().all(|&b| b == 0),
+                "Invalid elliptic curve point length"
+            );
+            lenient(self.leading_zeros, "Leading zeros in point coordinates.")?;
+            bytes = [&[4], &x[padding..], &y[padding..]].concat().into();
+        }
+        let point = self.point_codec.decode(&mut bytes, parent)?;
+        ensure!(
+            !bytes.has_remaining(),
+            "Trailing bytes after elliptic curve point"
+        );
+        Ok(point)
     }
 }
 
 macro_rules! ber_size {
-    ($codec:expr; $($tag:literal $value:expr)+) => {{
+    ($codec:expr; $($($optional:ident)? $tag:literal $value:expr)+) => {{
         let mut size = 0;
         $(
-            let value_size = $codec.encoded_size($value);
-            size += 1 + $codec.encoded_size(BerSize(value_size)) + value_size;
+            ber_value!($($optional)? $value, |value| {
+                let value_size = $codec.encoded_size(value);
+                size += 1 + $codec.encoded_size(BerSize(value_size)) + value_size;
+            });
         )+
         size
     }};
 }
 
 macro_rules! ber_encoder {
-    ($buffer:expr, $codec:expr; $($tag:literal $value:expr)+) => {
+    ($buffer:expr, $codec:expr; $($($optional:ident)? $tag:literal $value:expr)+) => {
         // Data must be written in specifc tag order.
         $(
-            $buffer.put_u8($tag);
-            $codec.encode($buffer, BerSize($codec.encoded_size($value)));
-            $codec.encode($buffer, $value);
+            ber_value!($($optional)? $value, |value| {
+                $buffer.put_u8($tag);
+                $codec.encode($buffer, BerSize($codec.encoded_size(value.clone())));
+                $codec.encode($buffer, value);
+            });
         )+
     };
 }
 
+/// Binds a value to be encoded, skipping it if it is `optional` and `None`.
+macro_rules! ber_value {
+    (optional $value:expr, |$name:ident| $body:block) => {
+        if let Some($name) = $value $body
+    };
+    ($value:expr, |$name:ident| $body:block) => {{
+        let $name = $value;
+        $body
+    }};
+}
+
+/// Unwraps a decoded field, unless it is marked `optional`.
+macro_rules! ber_field {
+    ($name:ident) => {
+        $name.ok_or_else(|| anyhow!(concat!(stringify!($name), " missing")))?
+    };
+    ($name:ident optional) => {
+        $name
+    };
+}
+
 /// Helper macro to produce a BER decoder for a sequence of fields.
+///
+/// Fields prefixed with `optional` are returned as `Option<T>`, all other
+/// fields are required.
 macro_rules! ber_decoder {
-    ($buffer:expr, $codec:expr; $($n:literal $tag:literal $name:ident $type:ty)+) => {
+    ($buffer:expr, $codec:expr; $($($optional:ident)? $n:literal $tag:literal $name:ident $type:ty)+) => {
         // Data can be read in any order.
         $(
             let mut $name: Option<$type> = None;
         )+
-        let mut count = 0;
+        let mut next = 0;
         while $buffer.has_remaining() {
             let tag = $buffer.get_u8();
             let len: BerSize = $codec.decode($buffer, ())?;
@@ -248,23 +566,23 @@ macro_rules! ber_decoder {
             match tag {
                 $(
                     $tag => {
-                        if count != $n {
+                        if $n < next {
                             lenient($codec.read_order, concat!(stringify!($name), " out of order"))?;
                         }
+                        next = $n + 1;
                         ensure!($name.is_none(), concat!(stringify!($name), " already read"));
                         let mut bytes = $buffer.copy_to_bytes(len.0);
-                        $name = Some($codec.decode(&mut bytes, ())?);
+                        $name = Some($codec.decode(&mut bytes, Default::default())?);
                     }
                 )+
                 _ => {
                     lenient($codec.unknown_tag, "Unknown tag")?;
+                    $buffer.advance(len.0);
                 }
             }
-            count += 1;
         }
         $(
-            // TODO: Optional fields
-            let $name = $name.ok_or_else(|| anyhow!(concat!(stringify!($name), " missing")))?;
+            let $name = ber_field!($name $($optional)?);
         )+
     };
 }
@@ -356,3 +674,337 @@ impl<const B0: usize, const L0: usize, const B1: usize, const L1: usize>
         })
     }
 }
+
+/// ICAO 9303-11 section 9.4.4 Elliptic Curve Public Keys
+impl<const BITS: usize, const LIMBS: usize> Codec<PublicKeyEC<Uint<BITS, LIMBS>>>
+    for Icao9303Codec
+{
+    type Parent = ();
+
+    fn encoded_size(&self, value: PublicKeyEC<Uint<BITS, LIMBS>>) -> usize {
+        ber_size!(self;
+            0x06 value.oid
+            optional 0x81 value.prime
+            optional 0x82 value.a
+            optional 0x83 value.b
+            optional 0x84 value.base
+            optional 0x85 value.order
+            0x86 value.public_key
+            optional 0x87 value.cofactor
+        )
+    }
+
+    fn encode<B: BufMut>(&self, buffer: &mut B, value: PublicKeyEC<Uint<BITS, LIMBS>>) {
+        ber_encoder!(buffer, self;
+            0x06 value.oid
+            optional 0x81 value.prime
+            optional 0x82 value.a
+            optional 0x83 value.b
+            optional 0x84 value.base
+            optional 0x85 value.order
+            0x86 value.public_key
+            optional 0x87 value.cofactor
+        );
+    }
+
+    fn decode<B: Buf>(
+        &self,
+        buffer: &mut B,
+        _parent: Self::Parent,
+    ) -> Result<PublicKeyEC<Uint<BITS, LIMBS>>> {
+        ber_decoder!(buffer, self;
+            0 0x06 oid ObjectIdentifier
+            optional 1 0x81 prime Uint<BITS, LIMBS>
+            optional 2 0x82 a Uint<BITS, LIMBS>
+            optional 3 0x83 b Uint<BITS, LIMBS>
+            optional 4 0x84 base Bytes
+            optional 5 0x85 order Uint<BITS, LIMBS>
+            6 0x86 public_key Bytes
+            optional 7 0x87 cofactor Uint<BITS, LIMBS>
+        );
+        Ok(PublicKeyEC {
+            oid,
+            prime,
+            a,
+            b,
+            base,
+            order,
+            public_key,
+            cofactor,
+        })
+    }
+}
+
+/// ICAO 9303-11 section 9.4.5 Elliptic Curve Domain Parameters
+impl<const BITS: usize, const LIMBS: usize> Codec<EcDomainParametersTlv<Uint<BITS, LIMBS>>>
+    for Icao9303Codec
+{
+    type Parent = ();
+
+    fn encoded_size(&self, value: EcDomainParametersTlv<Uint<BITS, LIMBS>>) -> usize {
+        ber_size!(self;
+            0x06 value.oid
+            0x81 value.prime
+            0x82 value.a
+            0x83 value.b
+            0x84 value.base
+            0x85 value.order
+            optional 0x87 value.cofactor
+        )
+    }
+
+    fn encode<B: BufMut>(&self, buffer: &mut B, value: EcDomainParametersTlv<Uint<BITS, LIMBS>>) {
+        ber_encoder!(buffer, self;
+            0x06 value.oid
+            0x81 value.prime
+            0x82 value.a
+            0x83 value.b
+            0x84 value.base
+            0x85 value.order
+            optional 0x87 value.cofactor
+        );
+    }
+
+    fn decode<B: Buf>(
+        &self,
+        buffer: &mut B,
+        _parent: Self::Parent,
+    ) -> Result<EcDomainParametersTlv<Uint<BITS, LIMBS>>> {
+        ber_decoder!(buffer, self;
+            0 0x06 oid ObjectIdentifier
+            1 0x81 prime Uint<BITS, LIMBS>
+            2 0x82 a Uint<BITS, LIMBS>
+            3 0x83 b Uint<BITS, LIMBS>
+            4 0x84 base Bytes
+            5 0x85 order Uint<BITS, LIMBS>
+            optional 6 0x87 cofactor Uint<BITS, LIMBS>
+        );
+        if cofactor.is_none() {
+            lenient(self.missing_cofactor, "Missing cofactor.")?;
+        }
+        Ok(EcDomainParametersTlv {
+            oid,
+            prime,
+            a,
+            b,
+            base,
+            order,
+            cofactor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::crypto::groups::named::brainpool_p256r1, hex_literal::hex,
+        ruint::aliases::U64,
+    };
+
+    fn decode_optional(mut buffer: &[u8]) -> Result<(U64, Option<U64>)> {
+        let codec = Icao9303Codec::default();
+        let buffer = &mut buffer;
+        ber_decoder!(buffer, codec;
+            0 0x81 required U64
+            optional 1 0x87 cofactor U64
+        );
+        Ok((required, cofactor))
+    }
+
+    #[test]
+    fn test_optional_field() {
+        let (required, cofactor) = decode_optional(&hex!("8101 05 8701 01")).unwrap();
+        assert_eq!(required, U64::from(5));
+        assert_eq!(cofactor, Some(U64::from(1)));
+
+        let (required, cofactor) = decode_optional(&hex!("8101 05")).unwrap();
+        assert_eq!(required, U64::from(5));
+        assert_eq!(cofactor, None);
+
+        assert!(decode_optional(&hex!("8701 01")).is_err());
+    }
+
+    #[test]
+    fn test_leniency() {
+        let padded = hex!("0002 05");
+        let decode = |codec: Icao9303Codec| -> Result<U64> { codec.decode(&mut &padded[..], None) };
+        assert_eq!(decode(Icao9303Codec::lenient()).unwrap(), U64::from(517));
+        assert!(decode(Icao9303Codec::strict()).is_err());
+
+        let codec = Icao9303Codec::builder()
+            .with_leading_zeros(Leniency::Strict)
+            .build();
+        assert!(decode(codec).is_err());
+        let codec = Icao9303Codec::builder()
+            .with_leading_zeros(Leniency::Allow)
+            .build();
+        assert_eq!(decode(codec).unwrap(), U64::from(517));
+    }
+
+    #[test]
+    fn test_ec_public_key_named() {
+        let codec = Icao9303Codec::default();
+        // id-CA-ECDH-AES-CBC-CMAC-128 with a named curve: only the point.
+        let encoded = hex!("060a 04007f00070202030202 8605 0401020304");
+        let key: PublicKeyEC<U64> = codec.decode(&mut &encoded[..], ()).unwrap();
+        assert_eq!(key.prime, None);
+        assert_eq!(key.cofactor, None);
+        assert_eq!(key.public_key.as_ref(), hex!("0401020304"));
+
+        let mut buffer = Vec::new();
+        codec.encode(&mut buffer, key);
+        assert_eq!(buffer, encoded);
+    }
+
+    #[test]
+    fn test_ec_public_key_explicit() {
+        let codec = Icao9303Codec::default();
+        let encoded = hex!(
+            "060a 04007f00070202030202 8101 17 8101 01 8301 01 8403 040302 8101 1c 8603 040d0a
+             8701 01"
+        );
+        // Duplicate tags are rejected.
+        assert!(Codec::<PublicKeyEC<U64>>::decode(&codec, &mut &encoded[..], ()).is_err());
+
+        let encoded = hex!(
+            "060a 04007f00070202030202 8101 17 8201 01 8301 01 8403 040302 8501 1c 8603 040d0a
+             8701 01"
+        );
+        let key: PublicKeyEC<U64> = codec.decode(&mut &encoded[..], ()).unwrap();
+        assert_eq!(key.prime, Some(U64::from(23)));
+        assert_eq!(key.order, Some(U64::from(28)));
+        assert_eq!(key.cofactor, Some(U64::from(1)));
+        assert_eq!(codec.encoded_size(key.clone()), encoded.len());
+
+        let params = key.domain_parameters().unwrap();
+        assert_eq!(params.base(), hex!("040302"));
+        let rebuilt = PublicKeyEC::with_domain_parameters(params, key.public_key.clone());
+        assert_eq!(rebuilt, key);
+        let named = PublicKeyEC::<U64>::new(key.oid(), key.public_key);
+        assert_eq!(named.domain_parameters(), None);
+    }
+
+    #[test]
+    fn test_ber_size() {
+        let codec = Icao9303Codec::strict();
+        for size in [0, 1, 127, 128, 255, 256, 65535, 65536] {
+            let mut buffer = Vec::new();
+            codec.encode(&mut buffer, BerSize(size));
+            assert_eq!(codec.encoded_size(BerSize(size)), buffer.len(), "{size}");
+            let decoded: BerSize = codec.decode(&mut &buffer[..], ()).unwrap();
+            assert_eq!(decoded.0, size);
+        }
+    }
+
+    #[test]
+    fn test_ec_parameters() {
+        let codec = Icao9303Codec::default();
+        let encoded =
+            hex!("060a 04007f00070202030202 8101 17 8201 01 8301 01 8403 040302 8501 1c 8701 01");
+        let params: EcDomainParametersTlv<U64> = codec.decode(&mut &encoded[..], ()).unwrap();
+        assert_eq!(params.prime, U64::from(23));
+        assert_eq!(params.a, U64::from(1));
+        assert_eq!(params.base.as_ref(), hex!("040302"));
+        assert_eq!(params.order, U64::from(28));
+        assert_eq!(params.cofactor, Some(U64::from(1)));
+        assert_eq!(codec.encoded_size(params), encoded.len());
+
+        let params: EcDomainParametersTlv<U64> = codec.decode(&mut &encoded[..], ()).unwrap();
+        let mut buffer = Vec::new();
+        codec.encode(&mut buffer, params);
+        assert_eq!(buffer, encoded);
+
+        // The cofactor is optional unless strict, the other parameters are not.
+        let without_cofactor = &encoded[..encoded.len() - 3];
+        let params: EcDomainParametersTlv<U64> =
+            codec.decode(&mut &without_cofactor[..], ()).unwrap();
+        assert_eq!(params.cofactor, None);
+        let strict = Icao9303Codec::strict();
+        assert!(Codec::<EcDomainParametersTlv<U64>>::decode(
+            &strict,
+            &mut &without_cofactor[..],
+            ()
+        )
+        .is_err());
+        let without_order = hex!("060a 04007f00070202030202 8101 17 8201 01 8301 01 8403 040302");
+        assert!(
+            Codec::<EcDomainParametersTlv<U64>>::decode(&codec, &mut &without_order[..], ())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_ec_domain_parameters_curve() {
+        type U256 = Uint<256, 4>;
+        let curve = brainpool_p256r1();
+        let oid = ObjectIdentifier::new_unwrap("0.4.0.127.0.7.2.2.4.2");
+        let params = EcDomainParametersTlv::from_curve(oid, &curve);
+        assert_eq!(params.oid(), oid);
+        assert_eq!(params.prime(), curve.base_field().modulus());
+        assert_eq!(params.cofactor(), Some(U256::from(1)));
+
+        let mut encoded = Vec::new();
+        Icao9303Codec::strict().encode(&mut encoded, params.clone());
+        let decoded: EcDomainParametersTlv<U256> = Icao9303Codec::strict()
+            .decode(&mut &encoded[..], ())
+            .unwrap();
+        assert_eq!(decoded, params);
+        assert_eq!(decoded.to_curve().unwrap(), curve);
+
+        let compressed = EcDomainParametersTlv::new(
+            oid,
+            params.prime(),
+            params.a(),
+            params.b(),
+            Bytes::from_static(&[2, 1]),
+            params.order(),
+            None,
+        );
+        assert!(compressed.to_curve().is_err());
+    }
+
+    #[test]
+    fn test_expected_length() {
+        type U256 = Uint<256, 4>;
+        let short = [0x55; 31];
+        let full = [&[0][..], &short].concat();
+        let decode = |codec: Icao9303Codec, bytes: &[u8], len| -> Result<U256> {
+            codec.decode(&mut &bytes[..], len)
+        };
+        let value = U256::from_be_slice(&short);
+        let strict = Icao9303Codec::strict();
+
+        // Minimal encoding unless a field size is given.
+        assert_eq!(decode(strict, &short, None).unwrap(), value);
+        assert!(decode(strict, &full, None).is_err());
+        assert_eq!(decode(strict, &full, Some(32)).unwrap(), value);
+        assert!(decode(strict, &short, Some(32)).is_err());
+        assert_eq!(
+            decode(Icao9303Codec::lenient(), &short, Some(32)).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_point_leading_zeros() {
+        let curve = brainpool_p256r1();
+        let generator = curve.generator();
+        let mut encoded = Vec::new();
+        Icao9303Codec::strict().encode(&mut encoded, generator);
+        let padded = [&[4, 0][..], &encoded[1..33], &[0], &encoded[33..]].concat();
+
+        let decode = |codec: Icao9303Codec, bytes: &[u8]| {
+            Codec::<EllipticCurvePoint<_>>::decode(&codec, &mut &bytes[..], &curve)
+        };
+        assert_eq!(
+            decode(Icao9303Codec::strict(), &encoded).unwrap(),
+            generator
+        );
+        assert!(decode(Icao9303Codec::strict(), &padded).is_err());
+        assert_eq!(
+            decode(Icao9303Codec::lenient(), &padded).unwrap(),
+            generator
+        );
+        assert!(decode(Icao9303Codec::lenient(), &padded[..padded.len() - 1]).is_err());
+    }
+}
@@ -5,6 +5,9 @@ mod icao_9303;
 pub use self::{
     bsi_tr03111::BsiTr031111Codec,
     buf::{BufCodec, BufCodecParent, BufMutCodec},
+    icao_9303::{
+        EcDomainParametersTlv, Icao9303Codec, Icao9303CodecBuilder, Leniency, PublicKeyEC,
+    },
 };
 use {
     anyhow::Result,
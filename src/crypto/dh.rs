@@ -0,0 +1,151 @@
+//! Diffie-Hellman key agreement over Mod-P groups, see BSI TR-03110-3 A.2.
+//!
+//! Only the standardized RFC 5114 groups are supported, as PKCS #3
+//! parameters do not contain the order of the subgroup.
+
+use {
+    super::{
+        groups::{
+            named::{modp_160, modp_224, modp_256},
+            CryptoGroup, ModPGroup,
+        },
+        mod_ring::RingRefExt,
+        CryptoCoreRng, DhPrivateKey, KeyAgreementAlgorithm, PrivateKey, PublicKey,
+    },
+    crate::asn1::public_key_info::{DhAlgoParameters, SubjectPublicKeyInfo},
+    anyhow::{anyhow, bail, ensure, Result},
+    der::asn1::Int,
+    ruint::Uint,
+};
+
+/// Find the standardized group matching the DH domain parameters.
+pub fn named_group(parameters: &DhAlgoParameters) -> Result<Box<dyn KeyAgreementAlgorithm>> {
+    let group = modp_160();
+    if group.matches(parameters) {
+        return Ok(Box::new(group));
+    }
+    let group = modp_224();
+    if group.matches(parameters) {
+        return Ok(Box::new(group));
+    }
+    let group = modp_256();
+    if group.matches(parameters) {
+        return Ok(Box::new(group));
+    }
+    bail!("Unknown Diffie-Hellman group")
+}
+
+/// Big-endian bytes of an unsigned integer without leading zeros.
+fn strip_int(int: &Int) -> &[u8] {
+    let bytes = int.as_bytes();
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    &bytes[zeros..]
+}
+
+impl<const B: usize, const L: usize, const C: usize, const D: usize>
+    ModPGroup<Uint<B, L>, Uint<C, D>>
+{
+    fn matches(&self, parameters: &DhAlgoParameters) -> bool {
+        let prime = Uint::<B, L>::try_from_be_slice(strip_int(&parameters.prime));
+        let base = Uint::<B, L>::try_from_be_slice(strip_int(&parameters.base));
+        prime == Some(self.base_field().modulus()) && base == Some(self.generator().to_uint())
+    }
+
+    /// PKCS #3 parameters of the group.
+    fn algo_parameters(&self) -> DhAlgoParameters {
+        DhAlgoParameters {
+            prime:                Int::from(&self.base_field().modulus()),
+            base:                 Int::from(&self.generator().to_uint()),
+            private_value_length: None,
+        }
+    }
+
+    /// Decode and validate a public value `1 < y < p - 1` with `y^q = 1`.
+    fn decode_element(&self, bytes: &[u8]) -> Result<Uint<B, L>> {
+        let value = Uint::<B, L>::try_from_be_slice(bytes)
+            .ok_or_else(|| anyhow!("Public value too large"))?;
+        let modulus = self.base_field().modulus();
+        ensure!(
+            value > Uint::from(1) && value < modulus - Uint::from(1),
+            "Public value out of range"
+        );
+        ensure!(
+            self.base_field()
+                .from(value)
+                .pow_ct(self.scalar_field().modulus())
+                == self.base_field().one(),
+            "Public value not in subgroup"
+        );
+        Ok(value)
+    }
+}
+
+impl<const B: usize, const L: usize, const C: usize, const D: usize> KeyAgreementAlgorithm
+    for ModPGroup<Uint<B, L>, Uint<C, D>>
+{
+    fn subject_public_key(&self, pubkey: &SubjectPublicKeyInfo) -> Result<PublicKey> {
+        let SubjectPublicKeyInfo::Dh(info) = pubkey else {
+            bail!("Not a Diffie-Hellman public key");
+        };
+        ensure!(
+            self.matches(&info.parameters),
+            "Public key is for a different group"
+        );
+        let value = self.decode_element(strip_int(&info.public_key))?;
+        Ok(PublicKey(
+            self.base_field().from(value).to_be_bytes_padded(),
+        ))
+    }
+
+    fn generate_key_pair(&self, rng: &mut dyn CryptoCoreRng) -> (PrivateKey, PublicKey) {
+        let private = CryptoGroup::random_scalar(self, rng).to_uint();
+        let public = self.generator().pow_ct(private);
+        (
+            PrivateKey::Dh(DhPrivateKey {
+                parameters: self.algo_parameters(),
+                exponent:   private.to_be_bytes_trimmed_vec(),
+            }),
+            PublicKey(public.to_be_bytes_padded()),
+        )
+    }
+
+    /// Returns `y^x mod p` as an octet string of the length of `p`.
+    fn key_agreement(&self, private: &PrivateKey, public: &PublicKey) -> Result<Vec<u8>> {
+        let PrivateKey::Dh(private) = private else {
+            bail!("Not a Diffie-Hellman private key");
+        };
+        ensure!(
+            self.matches(&private.parameters),
+            "Private key is not for this group"
+        );
+        let private = Uint::<C, D>::try_from_be_slice(private.as_ref())
+            .filter(|private| *private < self.scalar_field().modulus())
+            .ok_or_else(|| anyhow!("Private key is not for this group"))?;
+        let public = self.decode_element(public.as_ref())?;
+        let shared = self.base_field().from(public).pow_ct(private);
+        Ok(shared.to_be_bytes_padded())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_agreement() {
+        let mut rng = rand::thread_rng();
+        let group = modp_224();
+        let (alice_private, alice_public) = group.generate_key_pair(&mut rng);
+        let (bob_private, bob_public) = group.generate_key_pair(&mut rng);
+        assert_eq!(alice_public.as_ref().len(), 256);
+
+        let alice_shared = group.key_agreement(&alice_private, &bob_public).unwrap();
+        let bob_shared = group.key_agreement(&bob_private, &alice_public).unwrap();
+        assert_eq!(alice_shared, bob_shared);
+        assert_eq!(alice_shared.len(), 256);
+
+        assert!(group
+            .key_agreement(&alice_private, &PublicKey(vec![1]))
+            .is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! Elliptic curve key agreement, see BSI TR-03111 section 4.3.1.
+
+use {
+    super::{
+        codec::{BsiTr031111Codec, BufCodecParent, BufMutCodec},
+        groups::{EllipticCurve, EllipticCurvePoint},
+        mod_ring::RingRefExt,
+        named_curves::{curve_oid, with_curve},
+        CryptoCoreRng, EcPrivateKey, KeyAgreementAlgorithm, PrivateKey, PublicKey,
+    },
+    crate::asn1::{
+        emrtd::security_info::ChipAuthenticationPublicKeyInfo,
+        public_key_info::{ECAlgoParameters, EcParameters, SubjectPublicKeyInfo},
+    },
+    anyhow::{anyhow, bail, ensure, Result},
+    ruint::Uint,
+    std::fmt::{self, Display, Formatter},
+};
+
+/// Construct the key agreement algorithm for explicit or named curve
+/// parameters.
+pub fn from_algo_parameters(params: &ECAlgoParameters) -> Result<Box<dyn KeyAgreementAlgorithm>> {
+    with_curve!(params, |curve| Ok(Box::new(curve)))
+}
+
+impl<const B: usize, const L: usize> EllipticCurve<Uint<B, L>> {
+    /// Decode a BSI TR-03111 encoded point on the curve.
+    pub fn decode_point(&self, mut bytes: &[u8]) -> Result<EllipticCurvePoint<'_, Uint<B, L>>> {
+        let codec = BsiTr031111Codec::default();
+        let point: EllipticCurvePoint<_> = bytes.get_codec_parent(&codec, self)?;
+        ensure!(bytes.is_empty(), "Trailing bytes after curve point");
+        Ok(point)
+    }
+
+    /// Named curve parameters for known curves, explicit parameters otherwise.
+    pub fn algo_parameters(&self) -> ECAlgoParameters {
+        let params = self.to_parameters();
+        self.name()
+            .and_then(curve_oid)
+            .filter(|&oid| EcParameters::from_named_curve_oid(oid).as_ref() == Some(&params))
+            .map_or(
+                ECAlgoParameters::EcParameters(params),
+                ECAlgoParameters::NamedCurve,
+            )
+    }
+
+    /// Whether the parameters describe this curve.
+    fn matches(&self, params: &ECAlgoParameters) -> bool {
+        let params = match params {
+            ECAlgoParameters::NamedCurve(oid) => EcParameters::from_named_curve_oid(*oid),
+            ECAlgoParameters::EcParameters(params) => Some(params.clone()),
+            ECAlgoParameters::ImplicitlyCA(_) => None,
+        };
+        params
+            .and_then(|params| Self::from_parameters(&params).ok())
+            .is_some_and(|curve| curve == *self)
+    }
+
+    /// Encode a point in uncompressed form.
+    pub fn encode_point(&self, point: EllipticCurvePoint<'_, Uint<B, L>>) -> Vec<u8> {
+        let codec = BsiTr031111Codec {
+            compressed_points: false,
+            ..Default::default()
+        };
+        let mut bytes = Vec::new();
+        bytes.put_codec(&codec, point);
+        bytes
+    }
+}
+
+impl ChipAuthenticationPublicKeyInfo {
+    /// Decode the chip's public key as a point on `curve`.
+    ///
+    /// Both compressed and uncompressed points are accepted. The domain
+    /// parameters of the key must describe `curve`, unless they are implicitly
+    /// inherited.
+    pub fn ec_public_point<'a, const B: usize, const L: usize>(
+        &self,
+        curve: &'a EllipticCurve<Uint<B, L>>,
+    ) -> Result<EllipticCurvePoint<'a, Uint<B, L>>> {
+        let SubjectPublicKeyInfo::Ec(info) = &self.public_key else {
+            bail!("Chip Authentication public key is not an elliptic curve key");
+        };
+        ensure!(
+            matches!(info.parameters, ECAlgoParameters::ImplicitlyCA(_))
+                || curve.matches(&info.parameters),
+            "Chip Authentication public key is not on {curve}"
+        );
+        let point = curve.decode_point(info.point.as_bytes())?;
+        ensure!(
+            point != curve.infinity(),
+            "Public key is the point at infinity"
+        );
+        Ok(point)
+    }
+}
+
+impl<const B: usize, const L: usize> Display for EllipticCurve<Uint<B, L>> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "ECDH-{}", self.base_field().modulus().bit_len())
+    }
+}
+
+impl<const B: usize, const L: usize> KeyAgreementAlgorithm for EllipticCurve<Uint<B, L>> {
+    fn subject_public_key(&self, pubkey: &SubjectPublicKeyInfo) -> Result<PublicKey> {
+        let SubjectPublicKeyInfo::Ec(info) = pubkey else {
+            bail!("Not an elliptic curve public key");
+        };
+        let point = self.decode_point(info.point.as_bytes())?;
+        ensure!(
+            point != self.infinity(),
+            "Public key is the point at infinity"
+        );
+        Ok(PublicKey(info.point.as_bytes().to_vec()))
+    }
+
+    fn generate_key_pair(&self, rng: &mut dyn CryptoCoreRng) -> (PrivateKey, PublicKey) {
+        let private = self.scalar_field().random_nonzero(rng);
+        let public = self.encode_point(self.generator() * private);
+        (
+            PrivateKey::Ec(EcPrivateKey {
+                parameters: self.algo_parameters(),
+                scalar:     private.to_uint().to_be_bytes_vec(),
+            }),
+            PublicKey(public),
+        )
+    }
+
+    /// ECKA-DH, returns the x-coordinate of the shared point.
+    fn key_agreement(&self, private: &PrivateKey, public: &PublicKey) -> Result<Vec<u8>> {
+        let PrivateKey::Ec(private) = private else {
+            bail!("Not an elliptic curve private key");
+        };
+        ensure!(
+            self.matches(&private.parameters),
+            "Private key is not for this curve"
+        );
+        let scalar_field = self.scalar_field();
+        let private = Uint::<B, L>::try_from_be_slice(private.as_ref())
+            .filter(|private| *private < scalar_field.modulus())
+            .ok_or_else(|| anyhow!("Private key is not for this curve"))?;
+        let scalar = scalar_field.from(private) * scalar_field.from(self.cofactor());
+        let shared = self.decode_point(public.as_ref())? * scalar;
+        let x = shared
+            .x()
+            .ok_or_else(|| anyhow!("Shared secret is the point at infinity"))?;
+        let codec = BsiTr031111Codec::default();
+        let mut bytes = Vec::new();
+        bytes.put_codec(&codec, x);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            asn1::{
+                emrtd::security_info::KeyAgreement,
+                public_key_info::{DhAlgoParameters, DhPublicKeyInfo, EcPublicKeyInfo},
+            },
+            crypto::{
+                groups::named::brainpool_p256r1,
+                named_curves::{ID_BRAINPOOL_P256R1, ID_SEC_P256R1},
+            },
+        },
+        der::asn1::{Int, Null, ObjectIdentifier as Oid, OctetString},
+    };
+
+    #[test]
+    fn test_key_agreement() {
+        let mut rng = rand::thread_rng();
+        let curve = brainpool_p256r1();
+        let (alice_private, alice_public) = curve.generate_key_pair(&mut rng);
+        let (bob_private, bob_public) = curve.generate_key_pair(&mut rng);
+        assert_eq!(alice_public.as_ref().len(), 65);
+
+        let alice_shared = curve.key_agreement(&alice_private, &bob_public).unwrap();
+        let bob_shared = curve.key_agreement(&bob_private, &alice_public).unwrap();
+        assert_eq!(alice_shared, bob_shared);
+        assert_eq!(alice_shared.len(), 32);
+    }
+
+    #[test]
+    fn test_from_algo_parameters() {
+        let params = ECAlgoParameters::NamedCurve(ID_BRAINPOOL_P256R1);
+        let algo = from_algo_parameters(&params).unwrap();
+        assert_eq!(algo.to_string(), "ECDH-256");
+
+        let mut rng = rand::thread_rng();
+        let (private, public) = algo.generate_key_pair(&mut rng);
+        let curve = brainpool_p256r1();
+        let (other_private, other_public) = curve.generate_key_pair(&mut rng);
+        assert_eq!(
+            algo.key_agreement(&private, &other_public).unwrap(),
+            curve.key_agreement(&other_private, &public).unwrap()
+        );
+
+        let unknown = ECAlgoParameters::NamedCurve(Oid::new_unwrap("1.2.3.4"));
+        assert!(from_algo_parameters(&unknown).is_err());
+        assert!(from_algo_parameters(&ECAlgoParameters::ImplicitlyCA(Null)).is_err());
+    }
+
+    #[test]
+    fn test_ec_public_point() {
+        let curve = brainpool_p256r1();
+        let generator = curve.generator();
+        // The y coordinate of the generator is odd.
+        let mut compressed = vec![0x03];
+        compressed.put_codec(&BsiTr031111Codec::default(), generator.x().unwrap());
+        for point in [curve.encode_point(generator), compressed] {
+            let info = ChipAuthenticationPublicKeyInfo {
+                protocol:   KeyAgreement::Ecdh,
+                public_key: SubjectPublicKeyInfo::Ec(EcPublicKeyInfo {
+                    parameters: ECAlgoParameters::NamedCurve(ID_BRAINPOOL_P256R1),
+                    point:      OctetString::new(point).unwrap(),
+                }),
+                key_id:     None,
+            };
+            assert_eq!(info.ec_public_point(&curve).unwrap(), generator);
+        }
+
+        // The parameters must describe the curve.
+        let with_parameters = |parameters| ChipAuthenticationPublicKeyInfo {
+            protocol:   KeyAgreement::Ecdh,
+            public_key: SubjectPublicKeyInfo::Ec(EcPublicKeyInfo {
+                parameters,
+                point: OctetString::new(curve.encode_point(generator)).unwrap(),
+            }),
+            key_id:     None,
+        };
+        let explicit = ECAlgoParameters::EcParameters(curve.to_parameters());
+        assert_eq!(
+            with_parameters(explicit).ec_public_point(&curve).unwrap(),
+            generator
+        );
+        let implicit = ECAlgoParameters::ImplicitlyCA(Null);
+        assert_eq!(
+            with_parameters(implicit).ec_public_point(&curve).unwrap(),
+            generator
+        );
+        let other = ECAlgoParameters::NamedCurve(ID_SEC_P256R1);
+        assert!(with_parameters(other).ec_public_point(&curve).is_err());
+
+        let info = ChipAuthenticationPublicKeyInfo {
+            protocol:   KeyAgreement::Dh,
+            public_key: SubjectPublicKeyInfo::Dh(DhPublicKeyInfo {
+                parameters: DhAlgoParameters {
+                    prime:                Int::new(&[23]).unwrap(),
+                    base:                 Int::new(&[5]).unwrap(),
+                    private_value_length: None,
+                },
+                public_key: Int::new(&[8]).unwrap(),
+            }),
+            key_id:     None,
+        };
+        assert!(info.ec_public_point(&curve).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+//! ECDSA signature verification, see BSI TR-03111 section 4.2.1.
+//!
+//! Like RSA this is verifying only.
+
+use {
+    super::{
+        codec::{BsiTr031111Codec, BufCodecParent},
+        groups::{CryptoGroup, EllipticCurve, EllipticCurvePoint},
+        mod_ring::{RingRefExt, UintMont},
+        named_curves::*,
+    },
+    crate::asn1::{
+        public_key_info::{Curve, ECAlgoParameters, EcParameters, FieldId},
+        signature_algorithm_identifier::EcdsaSigValue,
+    },
+    anyhow::{anyhow, bail, ensure, Result},
+    der::{
+        asn1::{Int, ObjectIdentifier as Oid, OctetString},
+        Decode,
+    },
+    num_traits::Inv,
+    ruint::Uint,
+};
+
+#[derive(Clone, Debug)]
+pub struct ECPublicKey<U: UintMont> {
+    pub curve: EllipticCurve<U>,
+    point:     (U, U),
+}
+
+impl<const B: usize, const L: usize> ECPublicKey<Uint<B, L>> {
+    /// Construct a public key from a BSI TR-03111 encoded point.
+    pub fn from_bytes(curve: EllipticCurve<Uint<B, L>>, mut point: &[u8]) -> Result<Self> {
+        let codec = BsiTr031111Codec::default();
+        let decoded: EllipticCurvePoint<_> = point.get_codec_parent(&codec, &curve)?;
+        ensure!(point.is_empty(), "Trailing bytes after public key");
+        let point = decoded
+            .as_monty()
+            .ok_or_else(|| anyhow!("Public key is the point at infinity"))?;
+        Ok(Self { curve, point })
+    }
+
+    /// Verify a DER encoded signature over a message hash.
+    pub fn verify(&self, hash: &[u8], signature: &[u8]) -> Result<()> {
+        let signature = EcdsaSigValue::from_der(signature)?;
+        let r = Uint::try_from_be_slice(signature.r.as_bytes())
+            .ok_or_else(|| anyhow!("Signature r out of range"))?;
+        let s = Uint::try_from_be_slice(signature.s.as_bytes())
+            .ok_or_else(|| anyhow!("Signature s out of range"))?;
+        self.verify_rs(hash, r, s)
+    }
+
+    /// Verify a signature `(r, s)` over a message hash.
+    pub fn verify_rs(&self, hash: &[u8], r: Uint<B, L>, s: Uint<B, L>) -> Result<()> {
+        let scalar_field = self.curve.scalar_field();
+        let order = scalar_field.modulus();
+        ensure!(
+            r != Uint::ZERO && r < order && s != Uint::ZERO && s < order,
+            "Signature out of range"
+        );
+
+        // Use the leftmost bits of the hash, as many as the order has.
+        let order_bits = order.bit_len();
+        let hash = &hash[..hash.len().min(order_bits.div_ceil(8))];
+        let mut e = Uint::<B, L>::try_from_be_slice(hash)
+            .ok_or_else(|| anyhow!("Hash too large for curve"))?;
+        if hash.len() * 8 > order_bits {
+            e >>= hash.len() * 8 - order_bits;
+        }
+
+        let w = scalar_field
+            .from(s)
+            .inv()
+            .ok_or_else(|| anyhow!("Signature s not invertible"))?;
+        let u1 = scalar_field.from(e % order) * w;
+        let u2 = scalar_field.from(r) * w;
+        let public_key = self.curve.from_montgomery(Some(self.point))?;
+        let point = self.curve.generator() * u1 + public_key * u2;
+        let x = self
+            .curve
+            .x_coordinate_as_scalar(&point)
+            .ok_or_else(|| anyhow!("ECDSA verification failed"))?;
+        ensure!(x.to_uint() == r, "ECDSA verification failed");
+        Ok(())
+    }
+}
+
+impl<const B: usize, const L: usize> EllipticCurve<Uint<B, L>> {
+    /// Construct a curve from explicit parameters.
+    ///
+    /// Only prime fields are supported.
+    pub fn from_parameters(params: &EcParameters) -> Result<Self> {
+        let FieldId::PrimeField { modulus } = &params.field_id else {
+            bail!("Only prime field curves are supported");
+        };
+        let modulus = Uint::try_from(modulus.clone())?;
+        let field_bytes = modulus.byte_len();
+        let a = uint_from_be_bytes(params.curve.a.as_bytes())?;
+        let b = uint_from_be_bytes(params.curve.b.as_bytes())?;
+
+        // Only uncompressed base points are supported.
+        let base = params.base.as_bytes();
+        ensure!(
+            base.len() == 1 + 2 * field_bytes && base[0] == 4,
+            "Unsupported base point encoding"
+        );
+        let x = uint_from_be_bytes(&base[1..=field_bytes])?;
+        let y = uint_from_be_bytes(&base[1 + field_bytes..])?;
+
+        let order = Uint::try_from(params.order.clone())?;
+        let cofactor = match &params.cofactor {
+            Some(cofactor) => Uint::try_from(cofactor.clone())?,
+            None => Uint::from(1),
+        };
+        Ok(Self::new(modulus, a, b, x, y, order, cofactor)?)
+    }
+
+    /// Explicit parameters of the curve, with an uncompressed base point.
+    pub fn to_parameters(&self) -> EcParameters {
+        let octet_string = |bytes| OctetString::new(bytes).expect("Field elements are short");
+        EcParameters {
+            version:  1,
+            field_id: FieldId::PrimeField {
+                modulus: Int::from(&self.base_field().modulus()),
+            },
+            curve:    Curve {
+                a:    octet_string(self.a().to_be_bytes_padded()),
+                b:    octet_string(self.b().to_be_bytes_padded()),
+                seed: None,
+            },
+            base:     octet_string(self.encode_point(self.generator())),
+            order:    Int::from(&self.scalar_field().modulus()),
+            cofactor: Some(Int::from(&self.cofactor())),
+        }
+    }
+}
+
+impl EcParameters {
+    /// Explicit parameters of a named curve, `None` for unknown curves.
+    pub fn from_named_curve_oid(oid: Oid) -> Option<Self> {
+        named_parameters(&ECAlgoParameters::NamedCurve(oid)).ok()
+    }
+}
+
+fn named_parameters(params: &ECAlgoParameters) -> Result<EcParameters> {
+    with_curve!(params, |curve| Ok(curve.to_parameters()))
+}
+
+/// Verify a DER encoded ECDSA signature over a message hash using a public key
+/// point on the curve given by `params`.
+pub fn verify_ecdsa(
+    params: &ECAlgoParameters,
+    point: &[u8],
+    hash: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    with_curve!(params, |curve| ECPublicKey::from_bytes(curve, point)?
+        .verify(hash, signature))
+}
+
+fn uint_from_be_bytes<const B: usize, const L: usize>(bytes: &[u8]) -> Result<Uint<B, L>> {
+    Uint::try_from_be_slice(bytes).ok_or_else(|| anyhow!("Field element too large"))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::asn1::{DigestAlgorithmIdentifier, DigestAlgorithmParameters},
+        hex_literal::hex,
+    };
+
+    // RFC 6979 A.2.5, ECDSA on P-256 with SHA-256 and message "sample".
+    const PUBLIC_KEY: [u8; 65] = hex!(
+        "04 60FED4BA255A9D31C961EB74C6356D68C049B8923B61FA6CE669622E60F29FB6
+            7903FE1008B8BC99A41AE9E95628BC64F2F1B20C2D7E9F5177A3C294D4462299"
+    );
+    const SIGNATURE: [u8; 72] = hex!(
+        "3046
+         0221 00EFD48B2AACB6A8FD1140DD9CD45E81D69D2C877B56AAF991C34D0EA84EAF3716
+         0221 00F7CB1C942D657C41D436C7A1B6E29F65F3E900DBB9AFF4064DC4AB2F843ACDA8"
+    );
+
+    fn sample_hash() -> Vec<u8> {
+        DigestAlgorithmIdentifier::Sha256(DigestAlgorithmParameters::Absent).hash_bytes(b"sample")
+    }
+
+    #[test]
+    fn test_verify_named() {
+        let params = ECAlgoParameters::NamedCurve(ID_SEC_P256R1);
+        let hash = sample_hash();
+        verify_ecdsa(&params, &PUBLIC_KEY, &hash, &SIGNATURE).unwrap();
+
+        let mut hash = hash;
+        hash[0] ^= 1;
+        assert!(verify_ecdsa(&params, &PUBLIC_KEY, &hash, &SIGNATURE).is_err());
+    }
+
+    #[test]
+    fn test_verify_explicit() {
+        let p256 = hex!("FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFF");
+        let params = ECAlgoParameters::EcParameters(EcParameters {
+            version:  1,
+            field_id: FieldId::PrimeField {
+                modulus: Int::new(&[&[0][..], &p256].concat()).unwrap(),
+            },
+            curve:    Curve {
+                a:    OctetString::new(hex!(
+                    "FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFC"
+                ))
+                .unwrap(),
+                b:    OctetString::new(hex!(
+                    "5AC635D8AA3A93E7B3EBBD55769886BC651D06B0CC53B0F63BCE3C3E27D2604B"
+                ))
+                .unwrap(),
+                seed: None,
+            },
+            base:     OctetString::new(hex!(
+                "04 6B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296
+                    4FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5"
+            ))
+            .unwrap(),
+            order:    Int::new(&hex!(
+                "00FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551"
+            ))
+            .unwrap(),
+            cofactor: None,
+        });
+        verify_ecdsa(&params, &PUBLIC_KEY, &sample_hash(), &SIGNATURE).unwrap();
+
+        let ECAlgoParameters::EcParameters(explicit) = params else {
+            unreachable!()
+        };
+        let named = EcParameters::from_named_curve_oid(ID_SEC_P256R1).unwrap();
+        assert_eq!(named.field_id, explicit.field_id);
+        assert_eq!(named.curve, explicit.curve);
+        assert_eq!(named.base, explicit.base);
+        assert_eq!(named.order, explicit.order);
+        assert_eq!(named.cofactor, Some(Int::new(&[1]).unwrap()));
+    }
+
+    #[test]
+    fn test_from_named_curve_oid() {
+        for (oid, bits) in [
+            (ID_SEC_P192R1, 192),
+            (ID_SEC_P224R1, 224),
+            (ID_SEC_P256R1, 256),
+            (ID_SEC_P384R1, 384),
+            (ID_SEC_P521R1, 521),
+            (ID_BRAINPOOL_P160R1, 160),
+            (ID_BRAINPOOL_P192R1, 192),
+            (ID_BRAINPOOL_P224R1, 224),
+            (ID_BRAINPOOL_P256R1, 256),
+            (ID_BRAINPOOL_P320R1, 320),
+            (ID_BRAINPOOL_P384R1, 384),
+            (ID_BRAINPOOL_P512R1, 512),
+        ] {
+            let params = EcParameters::from_named_curve_oid(oid).unwrap();
+            let explicit = ECAlgoParameters::EcParameters(params.clone());
+            assert_eq!(explicit.field_bit_len(), Some(bits), "{oid}");
+            // Parsing the explicit parameters gives back the same curve.
+            assert_eq!(named_parameters(&explicit).unwrap(), params, "{oid}");
+        }
+        assert_eq!(
+            EcParameters::from_named_curve_oid(Oid::new_unwrap("1.2.3.4")),
+            None
+        );
+    }
+}
@@ -0,0 +1,33 @@
+//! Errors of the group and ring primitives.
+//!
+//! These do not use `anyhow` so the primitives are usable without `std`.
+
+use core::fmt::{self, Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CryptoError {
+    /// Group or curve parameters failed validation.
+    InvalidParameters(&'static str),
+
+    /// The embedding degree is below the minimum of BSI TR-03111.
+    LowEmbeddingDegree(u64),
+
+    /// A point or element is not valid for the group.
+    InvalidElement(&'static str),
+}
+
+pub type Result<T> = core::result::Result<T, CryptoError>;
+
+impl Display for CryptoError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidParameters(reason) | Self::InvalidElement(reason) => {
+                write!(f, "{reason}")
+            }
+            Self::LowEmbeddingDegree(k) => write!(f, "Low embedding degree {k}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CryptoError {}
@@ -1,18 +1,21 @@
 use {
     super::{
         super::mod_ring::{ModRing, ModRingElementRef, RingRefExt, UintExp, UintMont},
-        CryptoGroup,
+        named, CryptoGroup,
     },
-    anyhow::{ensure, Result},
-    num_traits::Inv,
-    std::{
+    crate::{
+        crypto::error::{CryptoError, Result},
+        ensure_err,
+    },
+    core::{
         fmt::{self, Debug, Formatter},
         ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
     },
+    num_traits::Inv,
     subtle::{Choice, ConditionallySelectable, ConstantTimeEq},
 };
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct EllipticCurve<U: UintMont> {
     base_field:      ModRing<U>,
     scalar_field:    ModRing<U>,
@@ -36,10 +39,22 @@ enum Coordinates<'a, U: UintMont> {
 
 impl<U: UintMont> EllipticCurve<U> {
     pub fn new(modulus: U, a: U, b: U, x: U, y: U, order: U, cofactor: U) -> Result<Self> {
-        ensure!(a < modulus, "a not in field");
-        ensure!(b < modulus, "b not in field");
-        ensure!(x < modulus, "x not in field");
-        ensure!(y < modulus, "y not in field");
+        ensure_err!(
+            a < modulus,
+            CryptoError::InvalidParameters("a not in field")
+        );
+        ensure_err!(
+            b < modulus,
+            CryptoError::InvalidParameters("b not in field")
+        );
+        ensure_err!(
+            x < modulus,
+            CryptoError::InvalidParameters("x not in field")
+        );
+        ensure_err!(
+            y < modulus,
+            CryptoError::InvalidParameters("y not in field")
+        );
         let base_field = ModRing::from_modulus(modulus);
         let scalar_field = ModRing::from_modulus(order);
         let a = base_field.from(a);
@@ -52,25 +67,22 @@ impl<U: UintMont> EllipticCurve<U> {
         // Ensure non-singular
         let c4 = base_field.from_u64(4);
         let c27 = base_field.from_u64(27);
-        ensure!(
+        ensure_err!(
             c4 * a.pow(3) + c27 * b.pow(2) != base_field.zero(),
-            "Singular curve"
+            CryptoError::InvalidParameters("Singular curve")
         );
 
         // Ensure not anomalous
-        ensure!(modulus != order, "Anomalous curve");
-
-        // Ensure high embedding degree.
-        // BSI TR-03111:2018 requires embedding degree at least 10^4.
-        // let p = scalar_field.from(modulus);
-        // let mut pe = scalar_field.one();
-        // for i in 1..=10_000 {
-        //     pe *= p;
-        //     ensure!(pe != scalar_field.one(), "Low embedding degree {}", i);
-        // }
+        ensure_err!(
+            modulus != order,
+            CryptoError::InvalidParameters("Anomalous curve")
+        );
 
         // Ensure generator is on curve
-        ensure!(y.pow(2) == x.pow(3) + a * x + b, "Generator not on curve");
+        ensure_err!(
+            y.pow(2) == x.pow(3) + a * x + b,
+            CryptoError::InvalidParameters("Generator not on curve")
+        );
 
         let curve = Self {
             base_field,
@@ -83,14 +95,63 @@ impl<U: UintMont> EllipticCurve<U> {
 
         // Ensure generator has order `order`
         let generator = curve.generator();
-        ensure!(
-            generator.mul_uint(order) == curve.infinity(),
-            "Generator order mismatch"
+        ensure_err!(
+            generator.mul_public(order) == curve.infinity(),
+            CryptoError::InvalidParameters("Generator order mismatch")
         );
 
+        // Ensure high embedding degree.
+        #[cfg(test)]
+        curve.check_embedding_degree()?;
+
         Ok(curve)
     }
 
+    /// Ensure the embedding degree is at least 10^4 as required by
+    /// BSI TR-03111:2018.
+    ///
+    /// The embedding degree is the multiplicative order of p modulo the order
+    /// r. For prime r it divides r - 1, so only those divisors below the bound
+    /// need to be tried.
+    pub fn check_embedding_degree(&self) -> Result<()> {
+        const MIN_EMBEDDING_DEGREE: u64 = 10_000;
+        let order = self.scalar_field.modulus().to_be_bytes();
+        let p = reduce_be_bytes(&self.scalar_field, &self.base_field.modulus().to_be_bytes());
+        let one = self.scalar_field.one();
+        for k in 1..MIN_EMBEDDING_DEGREE {
+            let order_mod_k = order
+                .iter()
+                .fold(0, |acc, &byte| (acc * 256 + u64::from(byte)) % k);
+            if order_mod_k == 1 % k && p.pow(k as usize) == one {
+                return Err(CryptoError::LowEmbeddingDegree(k));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the same curve with a different generator.
+    ///
+    /// The generator must be a point on this curve other than infinity, this is
+    /// used for the nonce mappings in PACE, see ICAO 9303-11 4.4.3.3.
+    pub fn with_generator(&self, generator: EllipticCurvePoint<'_, U>) -> Result<Self> {
+        ensure_err!(
+            generator.curve() == self,
+            CryptoError::InvalidElement("Generator not on curve")
+        );
+        let (x, y) = generator
+            .as_monty()
+            .ok_or(CryptoError::InvalidElement("Generator is infinity"))?;
+        Ok(Self {
+            generator_monty: (x, y),
+            ..*self
+        })
+    }
+
+    /// Name of the curve if it is one of the [`named`] curves.
+    pub fn name(&self) -> Option<&'static str> {
+        named::curve_name(&self.base_field.modulus().to_be_bytes())
+    }
+
     pub const fn base_field(&self) -> &ModRing<U> {
         &self.base_field
     }
@@ -171,13 +232,15 @@ impl<U: UintMont> EllipticCurve<U> {
         x: ModRingElementRef<'a, U>,
         y: ModRingElementRef<'a, U>,
     ) -> Result<()> {
-        ensure!(x.ring() == &self.base_field);
-        ensure!(y.ring() == &self.base_field);
+        ensure_err!(
+            x.ring() == &self.base_field && y.ring() == &self.base_field,
+            CryptoError::InvalidElement("Point not in base field.")
+        );
 
         // Check curve equation y^2 = x^3 + ax + b
-        ensure!(
+        ensure_err!(
             y.pow(2) == x.pow(3) + self.a() * x + self.b(),
-            "Point not on curve."
+            CryptoError::InvalidElement("Point not on curve.")
         );
 
         if self.cofactor() != U::from_u64(1) {
@@ -185,9 +248,9 @@ impl<U: UintMont> EllipticCurve<U> {
                 curve:       self,
                 coordinates: Coordinates::Affine(x, y),
             };
-            ensure!(
-                point.mul_uint(self.scalar_field().modulus()) == self.infinity(),
-                "Point not in subgroup."
+            ensure_err!(
+                point.mul_public(self.scalar_field().modulus()) == self.infinity(),
+                CryptoError::InvalidElement("Point not in subgroup.")
             );
         }
         Ok(())
@@ -237,6 +300,35 @@ impl<'a, U: UintMont> EllipticCurvePoint<'a, U> {
         }
         result
     }
+
+    /// Variable time multiplication by a public scalar such as the order or
+    /// cofactor, only iterating over its significant bits.
+    fn mul_public(mut self, scalar: U) -> Self {
+        let mut result = self.curve.infinity();
+        for i in 0..scalar.actual_bit_len() {
+            if bool::from(scalar.bit_ct(i)) {
+                result += self;
+            }
+            self += self;
+        }
+        result
+    }
+}
+
+impl<U: UintMont> Debug for EllipticCurve<U> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("EllipticCurve");
+        if let Some(name) = self.name() {
+            debug.field("name", &name);
+        } else {
+            let modulus = self.base_field.modulus();
+            debug.field("bits", &modulus.actual_bit_len()).field(
+                "modulus",
+                &format_args!("0x{}", hex::encode(modulus.to_be_bytes())),
+            );
+        }
+        debug.finish()
+    }
 }
 
 macro_rules! forward_fmt {
@@ -302,7 +394,12 @@ impl<U: UintMont> Add for EllipticCurvePoint<'_, U> {
                     let lambda = lambda.unwrap();
                     let x3 = lambda.pow(2) - x1 - x2;
                     let y3 = lambda * (x1 - x3) - y1;
-                    self.curve.from_affine(x3, y3).unwrap()
+                    // The sum of valid points is valid, and validating here would
+                    // recurse through the subgroup check for cofactor curves.
+                    EllipticCurvePoint {
+                        curve:       self.curve,
+                        coordinates: Coordinates::Affine(x3, y3),
+                    }
                 }
             }
         }
@@ -435,18 +532,47 @@ impl<'a, U: 'a + UintMont> CryptoGroup<'a> for EllipticCurve<U> {
     }
 
     fn random_scalar(&'a self, rng: &mut dyn super::CryptoCoreRng) -> Self::ScalarElement {
-        self.scalar_field().random(rng)
+        self.scalar_field().random_nonzero(rng)
+    }
+
+    fn x_coordinate_as_scalar(&'a self, point: &Self::BaseElement) -> Option<Self::ScalarElement> {
+        let x = point.x()?.to_uint().to_be_bytes();
+        Some(reduce_be_bytes(self.scalar_field(), &x))
+    }
+}
+
+/// Reduce a big-endian integer of any size into the ring.
+fn reduce_be_bytes<'a, U: UintMont>(
+    ring: &'a ModRing<U>,
+    bytes: &[u8],
+) -> ModRingElementRef<'a, U> {
+    let one = ring.one();
+    let mut result = ring.zero();
+    for byte in bytes {
+        for i in (0..8).rev() {
+            result += result;
+            if byte >> i & 1 == 1 {
+                result += one;
+            }
+        }
     }
+    result
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::{
-        named::{
-            brainpool_p160r1, brainpool_p512r1, secp192r1, secp224r1, secp256r1, secp384r1,
-            secp521r1,
+    use {
+        super::{
+            super::{
+                named::{
+                    brainpool_p160r1, brainpool_p512r1, secp192r1, secp224r1, secp256r1, secp384r1,
+                    secp521r1,
+                },
+                test_dh, test_schnorr, CryptoGroup,
+            },
+            *,
         },
-        test_dh, test_schnorr,
+        ruint::aliases::U64,
     };
 
     #[test]
@@ -461,6 +587,18 @@ mod tests {
         let group = secp224r1();
         test_dh(&group);
         test_schnorr(&group);
+
+        // The secp224r1 prime is 1 mod 8, so this needs Tonelli-Shanks.
+        let generator = group.generator();
+        let point = group.from_x(generator.x().unwrap()).unwrap();
+        assert!(point == generator || point == -generator);
+        let y2 = generator.y().unwrap().pow(2);
+        assert_eq!(y2.sqrt().map(|y| y.pow(2)), Some(y2));
+        let field = group.base_field();
+        let non_residue = (2..)
+            .map(|n| field.from_u64(n))
+            .find(|n| n.legendre() == -1);
+        assert_eq!(non_residue.unwrap().sqrt(), None);
     }
 
     #[test]
@@ -497,4 +635,64 @@ mod tests {
         test_dh(&group);
         test_schnorr(&group);
     }
+
+    #[test]
+    fn test_low_embedding_degree() {
+        // Supersingular curve y^2 = x^3 + x over p = 3 mod 4 with embedding
+        // degree 2.
+        let result = EllipticCurve::new(
+            U64::from(1019),
+            U64::from(1),
+            U64::from(0),
+            U64::from(568),
+            U64::from(136),
+            U64::from(17),
+            U64::from(60),
+        );
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "Low embedding degree 2");
+
+        secp256r1().check_embedding_degree().unwrap();
+    }
+
+    #[test]
+    fn test_x_coordinate_as_scalar() {
+        // The P-192 modulus exceeds the order, so x may need reducing.
+        let curve = secp192r1();
+        let rng = &mut rand::thread_rng();
+        for _ in 0..10 {
+            let point = curve.generator() * curve.random_scalar(rng);
+            let x = point.x().unwrap().to_uint();
+            let scalar = curve.x_coordinate_as_scalar(&point).unwrap();
+            assert_eq!(scalar.to_uint(), x % curve.scalar_field().modulus());
+        }
+        assert!(curve.x_coordinate_as_scalar(&curve.infinity()).is_none());
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(secp256r1().name(), Some("secp256r1"));
+        assert_eq!(secp521r1().name(), Some("secp521r1"));
+        assert_eq!(
+            format!("{:?}", brainpool_p160r1()),
+            r#"EllipticCurve { name: "brainpoolP160r1" }"#
+        );
+
+        // y^2 = x^3 + x + 5 over p = 100003 with prime order.
+        let curve = EllipticCurve::new(
+            U64::from(100_003),
+            U64::from(1),
+            U64::from(5),
+            U64::from(2),
+            U64::from(13_675),
+            U64::from(99_707),
+            U64::from(1),
+        )
+        .unwrap();
+        assert_eq!(curve.name(), None);
+        assert_eq!(
+            format!("{curve:?}"),
+            "EllipticCurve { bits: 17, modulus: 0x00000000000186a3 }"
+        );
+    }
 }
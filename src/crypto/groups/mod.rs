@@ -5,14 +5,17 @@ mod modp_group;
 mod mul_group;
 pub mod named;
 
-pub use self::elliptic_curve::{EllipticCurve, EllipticCurvePoint};
+pub use self::{
+    elliptic_curve::{EllipticCurve, EllipticCurvePoint},
+    modp_group::ModPGroup,
+};
 use {
     super::CryptoCoreRng,
-    num_traits::Inv,
-    std::{
+    core::{
         fmt::Debug,
         ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
     },
+    num_traits::Inv,
 };
 
 /// An algebraic group, written additively.
@@ -57,6 +60,15 @@ pub trait CryptoGroup<'s> {
     /// This is used for key generation and should meet the security
     /// requirements of the group.
     fn random_scalar(&'s self, rng: &mut dyn CryptoCoreRng) -> Self::ScalarElement;
+
+    /// Returns the x-coordinate of a point reduced to a scalar, as used in
+    /// ECDSA.
+    ///
+    /// Returns `None` for the point at infinity and for groups without
+    /// coordinates.
+    fn x_coordinate_as_scalar(&'s self, _point: &Self::BaseElement) -> Option<Self::ScalarElement> {
+        None
+    }
 }
 
 impl<T> GroupElement for T where
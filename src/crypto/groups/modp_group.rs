@@ -6,7 +6,11 @@ use {
         mul_group::MulGroup,
         CryptoCoreRng, CryptoGroup,
     },
-    anyhow::{ensure, Result},
+    crate::{
+        crypto::error::{CryptoError, Result},
+        ensure_err,
+    },
+    core::fmt::{self, Debug, Display, Formatter},
 };
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -18,13 +22,16 @@ pub struct ModPGroup<U: UintMont, V: UintMont> {
 
 impl<U: UintMont, V: UintMont> ModPGroup<U, V> {
     pub fn new(modulus: U, generator: U, order: V) -> Result<Self> {
-        ensure!(generator < modulus);
+        ensure_err!(
+            generator < modulus,
+            CryptoError::InvalidParameters("Generator not in field")
+        );
         let base_field = ModRing::from_modulus(modulus);
         let scalar_field = ModRing::from_modulus(order);
         let generator = base_field.from(generator);
-        ensure!(
+        ensure_err!(
             generator.pow_ct(scalar_field.modulus()) == base_field.one(),
-            "Generator has incorrect order"
+            CryptoError::InvalidParameters("Generator has incorrect order")
         );
         Ok(Self {
             base_field,
@@ -52,6 +59,21 @@ impl<U: UintMont, V: UintMont> ModPGroup<U, V> {
     }
 }
 
+impl<U: UintMont, V: UintMont> Debug for ModPGroup<U, V> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("ModPGroup")
+            .field("bits", &self.base_field.modulus().actual_bit_len())
+            .field("order_bits", &self.scalar_field.modulus().actual_bit_len())
+            .finish()
+    }
+}
+
+impl<U: UintMont, V: UintMont> Display for ModPGroup<U, V> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "DH-{}", self.base_field.modulus().actual_bit_len())
+    }
+}
+
 impl<'s, U: 's + UintMont, V: 's + UintMont> CryptoGroup<'s> for ModPGroup<U, V> {
     type BaseElement = MulGroup<ModRingElementRef<'s, U>>;
     type ScalarElement = ModRingElementRef<'s, V>;
@@ -61,9 +83,10 @@ impl<'s, U: 's + UintMont, V: 's + UintMont> CryptoGroup<'s> for ModPGroup<U, V>
     }
 
     fn random_scalar(&'s self, rng: &mut dyn CryptoCoreRng) -> Self::ScalarElement {
-        // TODO: Use the range [2, order - 2] as per
-        // X9.42 (repro in RFC 2631) require [2, (q - 2)]
-        self.scalar_field().random(rng)
+        // X9.42 (repro in RFC 2631) requires the range [2, q - 2].
+        let field = self.scalar_field();
+        let two = V::from_u64(2);
+        field.random_in_range(two, field.modulus().sub_mod(two, field.modulus()), rng)
     }
 }
 
@@ -74,6 +97,17 @@ mod tests {
         test_dh, test_schnorr,
     };
 
+    #[test]
+    fn test_fmt() {
+        let group = modp_160();
+        assert_eq!(
+            format!("{group:?}"),
+            "ModPGroup { bits: 1024, order_bits: 160 }"
+        );
+        assert_eq!(group.to_string(), "DH-1024");
+        assert_eq!(modp_256().to_string(), "DH-2048");
+    }
+
     #[test]
     fn test_modp_160() {
         let group = modp_160();
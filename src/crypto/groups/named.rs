@@ -3,6 +3,7 @@
 
 use {
     super::{modp_group::ModPGroup, EllipticCurve},
+    hex_literal::hex,
     ruint::{
         aliases::{U1024, U160, U192, U2048, U256, U320, U384, U512},
         uint, Uint,
@@ -12,6 +13,34 @@ use {
 type U224 = Uint<224, 4>;
 type U521 = Uint<521, 9>;
 
+/// Base field moduli of the curves in this module, see [`curve_name`].
+const CURVE_MODULI: [(&str, &[u8]); 12] = [
+    ("secp192r1", &hex!("fffffffffffffffffffffffffffffffeffffffffffffffff")),
+    ("secp224r1", &hex!("ffffffffffffffffffffffffffffffff000000000000000000000001")),
+    ("secp256r1", &hex!("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff")),
+    ("secp384r1", &hex!("fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000ffffffff")),
+    ("secp521r1", &hex!("01ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")),
+    ("brainpoolP160r1", &hex!("e95e4a5f737059dc60dfc7ad95b3d8139515620f")),
+    ("brainpoolP192r1", &hex!("c302f41d932a36cda7a3463093d18db78fce476de1a86297")),
+    ("brainpoolP224r1", &hex!("d7c134aa264366862a18302575d1d787b09f075797da89f57ec8c0ff")),
+    ("brainpoolP256r1", &hex!("a9fb57dba1eea9bc3e660a909d838d726e3bf623d52620282013481d1f6e5377")),
+    ("brainpoolP320r1", &hex!("d35e472036bc4fb7e13c785ed201e065f98fcfa6f6f40def4f92b9ec7893ec28fcd412b1f1b32e27")),
+    ("brainpoolP384r1", &hex!("8cb91e82a3386d280f5d6f7e50e641df152f7109ed5456b412b1da197fb71123acd3a729901d1a71874700133107ec53")),
+    ("brainpoolP512r1", &hex!("aadd9db8dbe9c48b3fd4e6ae33c9fc07cb308db3b3c9d20ed6639cca703308717d4d9b009bc66842aecda12ae6a380e62881ff2f2d82c68528aa6056583a48f3")),
+];
+
+/// Name of the named curve with the given big-endian base field modulus.
+pub fn curve_name(modulus: &[u8]) -> Option<&'static str> {
+    let start = modulus
+        .iter()
+        .position(|&b| b != 0)
+        .unwrap_or(modulus.len());
+    let modulus = &modulus[start..];
+    CURVE_MODULI
+        .iter()
+        .find_map(|&(name, known)| (known == modulus).then_some(name))
+}
+
 /// RFC 5114 1024-bit MODP Group with 160-bit Prime Order Subgroup
 pub fn modp_160() -> ModPGroup<U1024, U160> {
     uint!(ModPGroup::new(
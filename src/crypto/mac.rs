@@ -0,0 +1,58 @@
+//! Retail MAC as used for BAC and 3DES Secure Messaging.
+
+use {
+    crate::iso7816::pad_iso7816_2,
+    cipher::{BlockDecrypt, BlockEncrypt, KeyInit},
+    des::Des,
+};
+
+const BLOCK_SIZE: usize = 8;
+
+/// Retail MAC (ISO 9797-1 MAC algorithm 3) with padding method 2.
+///
+/// See ICAO 9303-11 section 9.8.6.1.
+pub fn retail_mac(key: &[u8; 16], data: &[u8]) -> [u8; 8] {
+    retail_mac_padded(key, &pad_iso7816_2(data, BLOCK_SIZE))
+}
+
+/// Retail MAC over `data` that is already padded to the block size.
+///
+/// CBC-MAC with single DES using the first half of the key, followed by a
+/// final 3DES operation using both halves.
+pub fn retail_mac_padded(key: &[u8; 16], data: &[u8]) -> [u8; 8] {
+    assert_eq!(data.len() % BLOCK_SIZE, 0);
+    let des1 = Des::new_from_slice(&key[..8]).unwrap();
+    let des2 = Des::new_from_slice(&key[8..]).unwrap();
+    let mut state = [0_u8; BLOCK_SIZE];
+    for block in data.chunks_exact(BLOCK_SIZE) {
+        for (state, byte) in state.iter_mut().zip(block) {
+            *state ^= byte;
+        }
+        des1.encrypt_block((&mut state).into());
+    }
+    des2.decrypt_block((&mut state).into());
+    des1.encrypt_block((&mut state).into());
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    // ICAO 9303-11 appendix D.3 and D.4
+    #[test]
+    fn test_retail_mac() {
+        let key = hex!("7962D9ECE03D1ACD4C76089DCE131543");
+        let msg = hex!("72C29C2371CC9BDB65B779B8E8D37B29ECC154AA56A8799FAE2F498F76ED92F2");
+        assert_eq!(retail_mac(&key, &msg), hex!("5F1448EEA8AD90A7"));
+
+        let msg = hex!("46B9342A41396CD7386BF5803104D7CEDC122B9132139BAF2EEDC94EE178534F");
+        assert_eq!(retail_mac(&key, &msg), hex!("2F2D235D074D7449"));
+
+        let key = hex!("F1CB1F1FB5ADF208806B89DC579DC1F8");
+        let msg = hex!("887022120C06C2270CA4020C800000008709016375432908C044F6");
+        assert_eq!(retail_mac(&key, &msg), hex!("BF8B92D635FF24F8"));
+        let padded = hex!("887022120C06C2270CA4020C800000008709016375432908C044F68000000000");
+        assert_eq!(retail_mac_padded(&key, &padded), hex!("BF8B92D635FF24F8"));
+    }
+}
@@ -2,22 +2,60 @@
 //!
 //! Primarily based on TR-03111.
 
+//! Without the `std` feature only [`groups`] and [`mod_ring`] are available.
+
+#[cfg(feature = "std")]
+pub mod cmac;
+#[cfg(feature = "std")]
 mod codec;
+#[cfg(feature = "std")]
+mod dh;
+#[cfg(feature = "std")]
+mod ecdh;
+#[cfg(feature = "std")]
+mod ecdsa;
+mod error;
 pub mod groups;
+#[cfg(feature = "std")]
+pub mod mac;
 pub mod mod_ring;
+#[cfg(feature = "std")]
+pub(crate) mod named_curves;
+#[cfg(feature = "std")]
+mod pki;
+#[cfg(feature = "std")]
+mod private_key;
+#[cfg(feature = "std")]
 mod rsa;
+#[cfg(feature = "std")]
 mod signature;
 
-pub use codec::Codec;
+pub use error::CryptoError;
+use rand::{CryptoRng, RngCore};
+#[cfg(feature = "std")]
 use {
-    crate::asn1::public_key_info::SubjectPublicKeyInfo,
-    anyhow::{ensure, Result},
-    der::asn1::OctetString,
-    rand::{CryptoRng, RngCore},
+    crate::asn1::{
+        emrtd::security_info::{KeyAgreement, PaceDomainParameterInfo, PaceInfo, SymmetricCipher},
+        public_key_info::{ECAlgoParameters, EcParameters, SubjectPublicKeyInfo},
+    },
+    anyhow::{anyhow, bail, ensure, Result},
+    der::{asn1::OctetString, Decode, Encode},
+    groups::EllipticCurve,
     ruint::Uint,
-    std::{
-        any::Any,
-        fmt::{Debug, Display},
+    std::fmt::{Debug, Display},
+};
+#[cfg(feature = "std")]
+pub use {
+    codec::{
+        Codec, EcDomainParametersTlv, Icao9303Codec, Icao9303CodecBuilder, Leniency, PublicKeyEC,
+    },
+    ecdsa::{verify_ecdsa, ECPublicKey},
+    pki::CertificateExt,
+    private_key::{DhPrivateKey, EcPrivateKey, PrivateKey},
+    rsa::RSAPublicKey,
+    signature::{
+        check_signed_attributes, verify_signed_content, verify_signed_data, verify_with_spki,
+        DataGroupHashMismatch,
     },
 };
 
@@ -25,20 +63,20 @@ pub trait CryptoCoreRng: CryptoRng + RngCore {}
 
 impl<T> CryptoCoreRng for T where T: CryptoRng + RngCore {}
 
+#[cfg(feature = "std")]
 /// Opaque wrapper for public keys.
 ///
 /// Derefs as a byte slice.
 pub struct PublicKey(Vec<u8>);
 
-/// Opaque wrapper for private keys.
-pub struct PrivateKey(Box<dyn Any>);
-
+#[cfg(feature = "std")]
 pub trait DiffieHellman {
     fn generate_private_key(&self, rng: &mut dyn CryptoCoreRng) -> Vec<u8>;
     fn private_to_public(&self, private: &[u8]) -> Result<Vec<u8>>;
     fn shared_secret(&self, private: &[u8], public: &[u8]) -> Result<Vec<u8>>;
 }
 
+#[cfg(feature = "std")]
 /// Object safe trait for key agreement algorithms
 pub trait KeyAgreementAlgorithm: Display + Debug {
     fn subject_public_key(&self, pubkey: &SubjectPublicKeyInfo) -> Result<PublicKey>;
@@ -46,48 +84,204 @@ pub trait KeyAgreementAlgorithm: Display + Debug {
     fn key_agreement(&self, private: &PrivateKey, public: &PublicKey) -> Result<Vec<u8>>;
 }
 
+#[cfg(feature = "std")]
 impl AsRef<[u8]> for PublicKey {
     fn as_ref(&self) -> &[u8] {
         self.0.as_ref()
     }
 }
 
+#[cfg(feature = "std")]
 impl SubjectPublicKeyInfo {
     /// Returns the KeyAgreementAlgorithm and public key.
     pub fn to_algorithm_public_key(&self) -> Result<(Box<dyn KeyAgreementAlgorithm>, PublicKey)> {
-        todo!()
-
-        // let algo: Box<dyn KeyAgreementAlgorithm> = match &self.algorithm {
-        //     PubkeyAlgorithmIdentifier::Dh(params) => todo!(), /*
-        // Box::new(ModPGroup::from_parameters(params)?), */
-        //     PubkeyAlgorithmIdentifier::Ec(ec) => match ec {
-        //         ECAlgoParameters::EcParameters(params) => {
-        //             Box::new(EllipticCurve::from_parameters(params)?)
-        //         }
-        //         ECAlgoParameters::NamedCurve(_) => bail!("Unknown named
-        // curve"),         ECAlgoParameters::ImplicitlyCA(_) =>
-        // bail!("Implicit CA not implemented"),     },
-        //     _ => bail!("Unknown key agreement algorithm."),
-        // };
-        // let public = algo.subject_public_key(self)?;
-        // Ok((algo, public))
+        let algo: Box<dyn KeyAgreementAlgorithm> = match self {
+            Self::Ec(info) => ecdh::from_algo_parameters(&info.parameters)?,
+            Self::Dh(info) => dh::named_group(&info.parameters)?,
+            _ => bail!("Unknown key agreement algorithm."),
+        };
+        let public = algo.subject_public_key(self)?;
+        Ok((algo, public))
     }
 }
 
+/// The standardized domain parameters for PACE and Chip Authentication.
+///
+/// See ICAO 9303-11 9.5.1. Returns `None` for reserved and proprietary
+/// identifiers.
+#[cfg(feature = "std")]
+pub fn standardized_domain_parameters(id: u64) -> Option<Box<dyn KeyAgreementAlgorithm>> {
+    use {groups::named::*, named_curves::*};
+    let curve = match id {
+        0 => return Some(Box::new(modp_160())),
+        1 => return Some(Box::new(modp_224())),
+        2 => return Some(Box::new(modp_256())),
+        8 => ID_SEC_P192R1,
+        9 => ID_BRAINPOOL_P192R1,
+        10 => ID_SEC_P224R1,
+        11 => ID_BRAINPOOL_P224R1,
+        12 => ID_SEC_P256R1,
+        13 => ID_BRAINPOOL_P256R1,
+        14 => ID_BRAINPOOL_P320R1,
+        15 => ID_SEC_P384R1,
+        16 => ID_BRAINPOOL_P384R1,
+        17 => ID_BRAINPOOL_P512R1,
+        18 => ID_SEC_P521R1,
+        _ => return None,
+    };
+    ecdh::from_algo_parameters(&ECAlgoParameters::NamedCurve(curve)).ok()
+}
+
+#[cfg(feature = "std")]
+impl PaceInfo {
+    /// The key agreement algorithm and cipher for standardized domain
+    /// parameters.
+    ///
+    /// Returns `None` if the parameters are not standardized or do not match
+    /// the key agreement of the protocol.
+    pub fn key_agreement_algorithm(
+        &self,
+    ) -> Option<(Box<dyn KeyAgreementAlgorithm>, SymmetricCipher)> {
+        let id = self.parameter_id?;
+        let key_agreement = match id {
+            0..=2 => KeyAgreement::Dh,
+            _ => KeyAgreement::Ecdh,
+        };
+        if key_agreement != self.protocol.key_agreement {
+            return None;
+        }
+        Some((standardized_domain_parameters(id)?, self.protocol.cipher?))
+    }
+}
+
+#[cfg(feature = "std")]
+impl PaceDomainParameterInfo {
+    /// The explicit elliptic curve domain parameters from EF.CardAccess.
+    ///
+    /// Named curves are expanded to their explicit parameters. The result is
+    /// in the ICAO 9303-11 9.4.5 form used in public key data objects.
+    pub fn ec_domain_parameters<const B: usize, const L: usize>(
+        &self,
+    ) -> Result<EcDomainParametersTlv<Uint<B, L>>> {
+        ensure!(
+            self.protocol.key_agreement == KeyAgreement::Ecdh,
+            "Domain parameters are not for ECDH"
+        );
+        let params = self
+            .domain_parameter
+            .parameters
+            .as_ref()
+            .ok_or_else(|| anyhow!("Missing domain parameters"))?;
+        let params = match ECAlgoParameters::from_der(&params.to_der()?)? {
+            ECAlgoParameters::EcParameters(params) => params,
+            ECAlgoParameters::NamedCurve(oid) => EcParameters::from_named_curve_oid(oid)
+                .ok_or_else(|| anyhow!("Unknown named curve {oid}"))?,
+            ECAlgoParameters::ImplicitlyCA(_) => bail!("Implicit domain parameters"),
+        };
+        let curve = EllipticCurve::<Uint<B, L>>::from_parameters(&params)?;
+        Ok(EcDomainParametersTlv::from_curve(
+            self.protocol.into(),
+            &curve,
+        ))
+    }
+}
+
+#[cfg(feature = "std")]
 pub fn parse_uint_os<const B: usize, const L: usize>(os: &OctetString) -> Result<Uint<B, L>> {
     // Get twos-complement big-endian bytes
     let big_endian = os.as_bytes();
 
     // TODO: Length should be exactly length of modulus in bytes.
 
-    // Ensure the number is not too large
-    ensure!(big_endian.len() <= 40, "Modulus is too large");
-
-    // Zero extend to 320 bits
-    let mut zero_extended = [0; 40];
-    zero_extended[40 - big_endian.len()..].copy_from_slice(big_endian);
+    // Strip leading zeros, such as the sign byte of a positive integer
+    let start = big_endian
+        .iter()
+        .position(|&b| b != 0)
+        .unwrap_or(big_endian.len());
 
-    // Parse as Uint
-    let uint = Uint::from_be_slice(&zero_extended);
+    // Parse as Uint, this fails if the value does not fit in B bits
+    let uint = Uint::try_from_be_slice(&big_endian[start..])
+        .ok_or_else(|| anyhow!("Modulus is too large for {B} bits"))?;
     Ok(uint)
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, der::Decode, hex_literal::hex, ruint::aliases::U384};
+
+    #[test]
+    fn test_standardized_domain_parameters() {
+        let name = |id| standardized_domain_parameters(id).map(|algo| algo.to_string());
+        assert_eq!(name(0).as_deref(), Some("DH-1024"));
+        assert_eq!(name(2).as_deref(), Some("DH-2048"));
+        assert_eq!(name(3), None);
+        assert_eq!(name(8).as_deref(), Some("ECDH-192"));
+        assert_eq!(name(18).as_deref(), Some("ECDH-521"));
+        assert_eq!(name(19), None);
+    }
+
+    #[test]
+    fn test_pace_info_key_agreement() {
+        // id-PACE-ECDH-GM-AES-CBC-CMAC-128 with brainpoolP256r1.
+        let mut info =
+            PaceInfo::from_der(&hex!("3012 060A 04007F00070202040202 020102 02010D")).unwrap();
+        let (algo, cipher) = info.key_agreement_algorithm().unwrap();
+        assert_eq!(algo.to_string(), "ECDH-256");
+        assert_eq!(cipher, SymmetricCipher::Aes128);
+
+        // A DH group does not match an ECDH protocol.
+        info.parameter_id = Some(2);
+        assert!(info.key_agreement_algorithm().is_none());
+    }
+
+    #[test]
+    fn test_pace_domain_parameters() {
+        use {
+            crate::{
+                asn1::{
+                    emrtd::security_info::PaceProtocol, public_key_info::ID_EC,
+                    AnyAlgorithmIdentifier,
+                },
+                crypto::{groups::named::brainpool_p256r1, named_curves::ID_BRAINPOOL_P256R1},
+            },
+            der::{asn1::ObjectIdentifier as Oid, Any},
+        };
+
+        // id-PACE-ECDH-GM with explicit brainpoolP256r1 parameters.
+        let params = EcParameters::from_named_curve_oid(ID_BRAINPOOL_P256R1).unwrap();
+        let info = PaceDomainParameterInfo {
+            protocol:         PaceProtocol::try_from(Oid::new_unwrap("0.4.0.127.0.7.2.2.4.2"))
+                .unwrap(),
+            domain_parameter: AnyAlgorithmIdentifier {
+                algorithm:  ID_EC,
+                parameters: Some(Any::encode_from(&params).unwrap()),
+            },
+            parameter_id:     None,
+        };
+        let info = PaceDomainParameterInfo::from_der(&info.to_der().unwrap()).unwrap();
+        let tlv = info.ec_domain_parameters::<256, 4>().unwrap();
+        assert_eq!(tlv.to_curve().unwrap(), brainpool_p256r1());
+    }
+
+    #[test]
+    fn test_parse_uint_os_384() {
+        let bytes = [0xff; 48];
+        let os = OctetString::new(bytes).unwrap();
+        assert_eq!(parse_uint_os::<384, 6>(&os).unwrap(), U384::MAX);
+        let os = OctetString::new([&[0; 8][..], &bytes].concat()).unwrap();
+        assert_eq!(parse_uint_os::<384, 6>(&os).unwrap(), U384::MAX);
+        let os = OctetString::new([&[1][..], &bytes].concat()).unwrap();
+        assert!(parse_uint_os::<384, 6>(&os).is_err());
+    }
+
+    #[test]
+    fn test_parse_uint_os_521() {
+        let mut bytes = [0xff; 66];
+        bytes[0] = 0x01;
+        let os = OctetString::new(bytes).unwrap();
+        assert_eq!(parse_uint_os::<521, 9>(&os).unwrap(), Uint::<521, 9>::MAX);
+        bytes[0] = 0x02;
+        let os = OctetString::new(bytes).unwrap();
+        assert!(parse_uint_os::<521, 9>(&os).is_err());
+    }
+}
@@ -1,15 +1,23 @@
 use {
-    super::{uint_exp::UintExp, ModRing, RingRef, RingRefExt, UintMont},
-    num_traits::{Inv, One, Pow, Zero},
-    rand::{
-        distributions::{Distribution, Standard},
-        Rng,
+    super::{
+        uint_exp::{PredShr, UintExp},
+        ModRing, RingRef, RingRefExt, UintMont,
+    },
+    crate::{
+        crypto::error::{CryptoError, Result},
+        ensure_err,
     },
-    std::{
+    alloc::vec::Vec,
+    core::{
         fmt::{self, Formatter},
         iter::{Product, Sum},
         ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
     },
+    num_traits::{Inv, One, Pow, Zero},
+    rand::{
+        distributions::{Distribution, Standard},
+        Rng,
+    },
     subtle::{Choice, ConditionallySelectable, ConstantTimeEq},
 };
 
@@ -51,6 +59,31 @@ impl<Ring: RingRef> ModRingElement<Ring> {
         self.ring.mont_mul(self.value, Ring::Uint::from_u64(1))
     }
 
+    /// Big-endian bytes, zero padded to the byte length of the modulus.
+    #[must_use]
+    pub fn to_be_bytes_padded(self) -> Vec<u8> {
+        let len = self.ring.modulus().actual_bit_len().div_ceil(8);
+        let bytes = self.to_uint().to_be_bytes();
+        bytes[bytes.len() - len..].to_vec()
+    }
+
+    /// Parse big-endian bytes of exactly the byte length of the modulus.
+    ///
+    /// Errors on other lengths or if the value is not less than the modulus.
+    pub fn from_be_bytes(ring: Ring, bytes: &[u8]) -> Result<Self> {
+        ensure_err!(
+            bytes.len() == ring.modulus().actual_bit_len().div_ceil(8),
+            CryptoError::InvalidElement("Invalid element length")
+        );
+        let value = Ring::Uint::try_from_be_bytes(bytes)
+            .ok_or(CryptoError::InvalidElement("Element not less than modulus"))?;
+        ensure_err!(
+            value < ring.modulus(),
+            CryptoError::InvalidElement("Element not less than modulus")
+        );
+        Ok(ring.from(value))
+    }
+
     #[inline]
     #[must_use]
     pub fn square(mut self) -> Self {
@@ -91,12 +124,91 @@ impl<Ring: RingRef> ModRingElement<Ring> {
 
     /// Square root of the element.
     ///
-    /// Requires the modulus to be a prime number with p mod 8 in {3, 5, 7}.
-    // TODO: Tonelli-Shanks algorithm.
+    /// Returns `None` if the element is not a quadratic residue. Either root
+    /// may be returned, use negation to pick the other.
+    ///
+    /// Requires the modulus to be a prime number. Primes that are 1 mod 8 use
+    /// the Tonelli-Shanks algorithm, which is not constant time.
     pub fn sqrt(self) -> Option<Self> {
-        self.ring
-            .mont_sqrt(self.value)
-            .map(|value| self.ring.from_montgomery(value))
+        let modulus = self.ring.modulus();
+        if bool::from(modulus.bit_ct(1) | modulus.bit_ct(2)) {
+            self.ring
+                .mont_sqrt(self.value)
+                .map(|value| self.ring.from_montgomery(value))
+        } else {
+            self.tonelli_shanks()
+        }
+    }
+
+    /// Tonelli-Shanks square root for primes that are 1 mod 8.
+    fn tonelli_shanks(self) -> Option<Self> {
+        let ring = self.ring;
+        let modulus = ring.modulus();
+        let one = ring.one();
+        if self == ring.zero() {
+            return Some(self);
+        }
+
+        // Write p - 1 = q * 2^s with q odd.
+        let s = (1..).find(|&i| bool::from(modulus.bit_ct(i))).unwrap();
+        let q = PredShr {
+            value: modulus,
+            shift: s,
+        };
+        let half_q = PredShr {
+            value: modulus,
+            shift: s + 1,
+        };
+
+        // Any non-residue z gives a generator c = z^q of the 2^s-torsion.
+        let z = (2..)
+            .map_while(|z| (Ring::Uint::from_u64(z) < modulus).then(|| ring.from_u64(z)))
+            .find(|z| z.legendre() == -1)?;
+        let mut c = z.pow_ct(q);
+
+        // Invariant: root^2 = self * t, with t of order 2^m.
+        let power = self.pow_ct(half_q);
+        let mut root = self * power;
+        let mut t = root * power;
+        let mut m = s;
+        while t != one {
+            let mut order = 0;
+            let mut t2 = t;
+            while t2 != one {
+                t2 = t2.square();
+                order += 1;
+                if order == m {
+                    return None;
+                }
+            }
+            let b = (0..m - order - 1).fold(c, |b, _| b.square());
+            m = order;
+            c = b.square();
+            t *= c;
+            root *= b;
+        }
+        Some(root)
+    }
+}
+
+impl<Ring: RingRef> ModRingElement<Ring> {
+    /// Legendre symbol of the element using Euler's criterion.
+    ///
+    /// Returns `0` for zero, `1` for non-zero quadratic residues and `-1` for
+    /// non-residues. Requires the modulus to be an odd prime.
+    pub fn legendre(self) -> i8 {
+        let exponent = PredShr {
+            value: self.ring.modulus(),
+            shift: 1,
+        };
+        let symbol = self.pow_ct(exponent);
+        if symbol == self.ring.zero() {
+            0
+        } else if symbol == self.ring.one() {
+            1
+        } else {
+            -1
+        }
     }
 }
 
@@ -322,3 +434,77 @@ where
         self.pow_ct(rhs.to_uint())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        ruint::{aliases::U64, Uint},
+    };
+
+    #[test]
+    fn test_be_bytes_padded() {
+        let ring = ModRing::from_modulus(U64::from(0x01_0001_u64));
+        let element = ring.from(U64::from(0x12_u64));
+        assert_eq!(element.to_be_bytes_padded(), [0x00, 0x00, 0x12]);
+        assert_eq!(
+            ModRingElement::from_be_bytes(&ring, &[0x00, 0x00, 0x12]),
+            Ok(element)
+        );
+        assert!(ModRingElement::from_be_bytes(&ring, &[0x00, 0x12]).is_err());
+        assert!(ModRingElement::from_be_bytes(&ring, &[0x01, 0x00, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_from_be_bytes_p521() {
+        // The P-521 modulus 2^521 - 1 does not fill its top byte.
+        let ring = ModRing::from_modulus(Uint::<521, 9>::MAX);
+        let mut bytes = [0xff; 66];
+        bytes[0] = 0x01;
+        assert!(ModRingElement::from_be_bytes(&ring, &bytes).is_err());
+        bytes[65] = 0xfe;
+        assert!(ModRingElement::from_be_bytes(&ring, &bytes).is_ok());
+        bytes[0] = 0x02;
+        assert_eq!(
+            ModRingElement::from_be_bytes(&ring, &bytes),
+            Err(CryptoError::InvalidElement("Element not less than modulus"))
+        );
+    }
+
+    #[test]
+    fn test_sqrt() {
+        // Primes that are 3, 5, 7 and 1 mod 8.
+        for modulus in [11_u64, 13, 23, 17, 41, 97, 257] {
+            let ring = ModRing::from_modulus(U64::from(modulus));
+            let squares: Vec<_> = (0..modulus)
+                .map(|x| ring.from(U64::from(x)).pow(2).to_uint())
+                .collect();
+            for a in 0..modulus {
+                let a = ring.from(U64::from(a));
+                match a.sqrt() {
+                    Some(root) => assert_eq!(root.pow(2), a),
+                    None => assert!(!squares.contains(&a.to_uint())),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_legendre() {
+        let ring = ModRing::from_modulus(U64::from(23));
+        let squares: Vec<_> = (1..23_u64)
+            .map(|x| ring.from(U64::from(x)).pow(2).to_uint())
+            .collect();
+        assert_eq!(ring.zero().legendre(), 0);
+        for a in 1..23_u64 {
+            let a = ring.from(U64::from(a));
+            let expected = if squares.contains(&a.to_uint()) {
+                1
+            } else {
+                -1
+            };
+            assert_eq!(a.legendre(), expected);
+            assert_eq!(a.sqrt().is_some(), expected == 1);
+        }
+    }
+}
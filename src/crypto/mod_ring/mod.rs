@@ -3,6 +3,8 @@
 mod element;
 mod ring;
 mod ring_ref;
+#[cfg(feature = "crypto-bigint")]
+mod uint_bigint;
 mod uint_exp;
 mod uint_mont;
 
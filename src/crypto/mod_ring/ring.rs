@@ -1,4 +1,9 @@
 use super::UintMont;
+#[cfg(feature = "std")]
+use super::{uint_exp::PredShr, RingRefExt};
+
+/// Number of Miller-Rabin rounds, for an error probability below 2^-80.
+const MILLER_RABIN_ROUNDS: usize = 40;
 
 /// Ring of integers modulo an odd positive integer.
 /// TODO: Support even positive integers.
@@ -62,6 +67,47 @@ impl<Uint: UintMont> ModRing<Uint> {
         self.mod_inv
     }
 
+    /// Probabilistic Miller-Rabin test whether the modulus is prime.
+    ///
+    /// Uses random bases so a crafted composite modulus can not pass.
+    #[cfg(feature = "std")]
+    pub fn is_prime_field(&self) -> bool {
+        let one = Uint::from_u64(1);
+        if self.modulus <= Uint::from_u64(3) {
+            return self.modulus > one;
+        }
+
+        // Write p - 1 = d * 2^s with d odd.
+        let s = (1..).find(|&i| bool::from(self.modulus.bit_ct(i))).unwrap();
+        let d = PredShr {
+            value: self.modulus,
+            shift: s,
+        };
+
+        let mut rng = rand::thread_rng();
+        let one = self.one();
+        let minus_one = -one;
+        (0..MILLER_RABIN_ROUNDS).all(|_| {
+            let base = loop {
+                let base = self.random(&mut rng);
+                if base != self.zero() && base != one && base != minus_one {
+                    break base;
+                }
+            };
+            let mut x = base.pow_ct(d);
+            if x == one || x == minus_one {
+                return true;
+            }
+            for _ in 1..s {
+                x = x.square();
+                if x == minus_one {
+                    return true;
+                }
+            }
+            false
+        })
+    }
+
     /// Montogomery multiplication for the ring.
     #[inline]
     #[must_use]
@@ -83,3 +129,41 @@ impl<Uint: UintMont> ModRing<Uint> {
         a.sqrt_mont(self.modulus, self.montgomery_r, self.mod_inv)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        ruint::aliases::{U128, U64},
+    };
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_is_prime_field() {
+        for p in [3_u64, 5, 7, 23, 2147483647, 18446744069414584321] {
+            assert!(ModRing::from_modulus(U64::from(p)).is_prime_field(), "{p}");
+        }
+        // Including the Carmichael numbers 561 and 41041.
+        for n in [9_u64, 15, 561, 41041, 2147483647 * 3] {
+            assert!(!ModRing::from_modulus(U64::from(n)).is_prime_field(), "{n}");
+        }
+        let semiprime = U128::from(4294967291_u64) * U128::from(4294967279_u64);
+        assert!(!ModRing::from_modulus(semiprime).is_prime_field());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_random_in_range() {
+        let ring = ModRing::from_modulus(U64::from(7_u64));
+        let mut rng = rand::thread_rng();
+        let mut seen = [false; 7];
+        for _ in 0..200 {
+            assert_ne!(ring.random_nonzero(&mut rng), ring.zero());
+            let value = ring
+                .random_in_range(U64::from(2_u64), U64::from(5_u64), &mut rng)
+                .to_uint();
+            seen[value.to::<usize>()] = true;
+        }
+        assert_eq!(seen, [false, false, true, true, true, true, false]);
+    }
+}
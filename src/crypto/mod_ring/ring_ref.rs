@@ -1,7 +1,7 @@
 use {
     super::{ModRing, ModRingElement, UintMont},
+    core::ops::Deref,
     rand::Rng,
-    std::ops::Deref,
 };
 
 /// Trait for ModRing parameter references.
@@ -22,6 +22,19 @@ pub trait RingRefExt: RingRef {
     fn from_u64(self, value: u64) -> ModRingElement<Self>;
     fn from<T: Into<Self::Uint>>(self, value: T) -> ModRingElement<Self>;
     fn random<R: Rng + ?Sized>(self, rng: &mut R) -> ModRingElement<Self>;
+
+    /// Uniform random element other than zero.
+    fn random_nonzero<R: Rng + ?Sized>(self, rng: &mut R) -> ModRingElement<Self>;
+
+    /// Uniform random element in the inclusive range `[min, max]`.
+    ///
+    /// Panics if `min > max` or `max` is not less than the modulus.
+    fn random_in_range<R: Rng + ?Sized>(
+        self,
+        min: Self::Uint,
+        max: Self::Uint,
+        rng: &mut R,
+    ) -> ModRingElement<Self>;
 }
 
 impl<Uint: UintMont> RingRef for &ModRing<Uint> {
@@ -58,6 +71,30 @@ impl<Ring: RingRef> RingRefExt for Ring {
     }
 
     fn random<R: Rng + ?Sized>(self, rng: &mut R) -> ModRingElement<Self> {
-        self.from_montgomery(Ring::Uint::random(rng, self.modulus()))
+        // `UintMont::random` is inclusive of the maximum.
+        let one = Ring::Uint::from_u64(1);
+        let max = self.modulus().sub_mod(one, self.modulus());
+        self.from_montgomery(Ring::Uint::random(rng, max))
+    }
+
+    fn random_nonzero<R: Rng + ?Sized>(self, rng: &mut R) -> ModRingElement<Self> {
+        loop {
+            let value = self.random(rng);
+            if value != self.zero() {
+                return value;
+            }
+        }
+    }
+
+    fn random_in_range<R: Rng + ?Sized>(
+        self,
+        min: Ring::Uint,
+        max: Ring::Uint,
+        rng: &mut R,
+    ) -> ModRingElement<Self> {
+        assert!(min <= max && max < self.modulus());
+        let modulus = self.modulus();
+        let offset = Ring::Uint::random(rng, max.sub_mod(min, modulus));
+        self.from(offset.add_mod(min, modulus))
     }
 }
@@ -0,0 +1,267 @@
+//! [`UintMont`] backend for [`crypto_bigint::Uint`].
+
+use {
+    super::{ModRing, UintExp, UintMont},
+    alloc::{vec, vec::Vec},
+    core::array,
+    crypto_bigint::{modular::montgomery_reduction, Limb, Uint, Word},
+    rand::Rng,
+    subtle::Choice,
+};
+
+impl<const LIMBS: usize> UintExp for Uint<LIMBS> {
+    fn bit_len(&self) -> usize {
+        Self::BITS
+    }
+
+    fn bit_ct(&self, index: usize) -> Choice {
+        self.bit(index).into()
+    }
+}
+
+impl<const LIMBS: usize> UintMont for Uint<LIMBS> {
+    fn parameters_from_modulus(modulus: Self) -> ModRing<Self> {
+        assert!(
+            bool::from(modulus.bit_ct(0)) && modulus > Self::ONE,
+            "Modulus not an odd positive integer."
+        );
+
+        // The limbs may be 32 bits, so take the low 64 bits from the bytes.
+        let bytes = UintMont::to_be_bytes(&modulus);
+        let mut low = [0; 8];
+        let len = bytes.len().min(8);
+        low[8 - len..].copy_from_slice(&bytes[bytes.len() - len..]);
+        let low = u64::from_be_bytes(low);
+        // Newton iteration doubles the correct low bits, an odd number is its own
+        // inverse modulo 8.
+        let inv = (0..5).fold(low, |inv, _| {
+            inv.wrapping_mul(2_u64.wrapping_sub(low.wrapping_mul(inv)))
+        });
+        let mod_inv = inv.wrapping_neg();
+
+        // montgomery_r2 = 2^(2 * BITS) mod modulus.
+        let montgomery_r = Self::MAX.const_rem(&modulus).0.wrapping_add(&Self::ONE);
+        let montgomery_r2 = Self::const_rem_wide(montgomery_r.square_wide(), &modulus).0;
+        ModRing::from_parameters(modulus, montgomery_r2, mod_inv)
+    }
+
+    #[inline]
+    fn from_u64(value: u64) -> Self {
+        Self::from_u64(value)
+    }
+
+    fn random<R: Rng + ?Sized>(rng: &mut R, max: Self) -> Self {
+        let leading_zeros = max.leading_zeros_vartime();
+        loop {
+            let value = Self::from_words(array::from_fn(|_| rng.gen()));
+            let value = value.shr_vartime(leading_zeros);
+            if value <= max {
+                return value;
+            }
+        }
+    }
+
+    #[inline]
+    fn add_mod(self, other: Self, modulus: Self) -> Self {
+        Self::add_mod(&self, &other, &modulus)
+    }
+
+    #[inline]
+    fn sub_mod(self, other: Self, modulus: Self) -> Self {
+        Self::sub_mod(&self, &other, &modulus)
+    }
+
+    #[inline]
+    fn mul_redc(self, other: Self, modulus: Self, mod_inv: u64) -> Self {
+        montgomery_reduction(&self.mul_wide(&other), &modulus, limb(mod_inv))
+    }
+
+    #[inline]
+    fn square_redc(self, modulus: Self, mod_inv: u64) -> Self {
+        montgomery_reduction(&self.square_wide(), &modulus, limb(mod_inv))
+    }
+
+    #[inline]
+    fn inv_mod(self, modulus: Self) -> Option<Self> {
+        let (inverse, exists) = self.inv_odd_mod(&modulus);
+        bool::from(exists).then_some(inverse)
+    }
+
+    #[inline]
+    fn from_be_bytes(bytes: &[u8]) -> Self {
+        Self::try_from_be_bytes(bytes).expect("Value too large")
+    }
+
+    fn try_from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        let start = bytes
+            .iter()
+            .position(|&byte| byte != 0)
+            .unwrap_or(bytes.len());
+        let bytes = &bytes[start..];
+        if bytes.len() > Self::BYTES {
+            return None;
+        }
+        let mut padded = vec![0; Self::BYTES];
+        padded[Self::BYTES - bytes.len()..].copy_from_slice(bytes);
+        Some(Self::from_be_slice(&padded))
+    }
+
+    fn to_be_bytes(&self) -> Vec<u8> {
+        self.as_words()
+            .iter()
+            .rev()
+            .flat_map(|word| word.to_be_bytes())
+            .collect()
+    }
+
+    #[inline]
+    fn actual_bit_len(&self) -> usize {
+        self.bits_vartime()
+    }
+}
+
+/// The Montgomery reduction only needs `mod_inv` modulo the limb size.
+#[allow(clippy::cast_possible_truncation)]
+const fn limb(mod_inv: u64) -> Limb {
+    Limb(mod_inv as Word)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::crypto::mod_ring::RingRefExt,
+        crypto_bigint::{U192, U256, U64},
+        ruint::uint,
+    };
+
+    /// Converts the test constants, which are written as Ruint literals.
+    fn from_ruint<const BITS: usize, const RUINT_LIMBS: usize, const LIMBS: usize>(
+        value: ruint::Uint<BITS, RUINT_LIMBS>,
+    ) -> Uint<LIMBS> {
+        UintMont::from_be_bytes(&value.to_be_bytes_vec())
+    }
+
+    #[test]
+    fn test_m31_param() {
+        // Like Ruint, R is 2^64 for a single limb.
+        let modulus = U64::from_u64(2147483647);
+        let ring = U64::parameters_from_modulus(modulus);
+        assert_eq!(ring.modulus(), modulus);
+        assert_eq!(ring.mod_inv(), 4611686020574871553_u64);
+        assert_eq!(ring.montgomery_r(), U64::from_u64(4));
+        assert_eq!(ring.montgomery_r2(), U64::from_u64(16));
+        assert_eq!(ring.montgomery_r3(), U64::from_u64(64));
+    }
+
+    #[test]
+    fn test_goldilocks_param() {
+        let modulus = U64::from_u64(18446744069414584321);
+        let ring = U64::parameters_from_modulus(modulus);
+        assert_eq!(ring.modulus(), modulus);
+        assert_eq!(ring.mod_inv(), 18446744069414584319_u64);
+        assert_eq!(ring.montgomery_r(), U64::from_u64(4294967295));
+        assert_eq!(ring.montgomery_r2(), U64::from_u64(18446744065119617025));
+        assert_eq!(ring.montgomery_r3(), U64::from_u64(1));
+    }
+
+    #[test]
+    fn test_group1_param() {
+        // Three limbs like Ruint's U160, so R is 2^192 for both.
+        let modulus: U192 = from_ruint(uint!(
+            1399252811935680595399801714158014275474696840019_U160
+        ));
+        let ring = U192::parameters_from_modulus(modulus);
+        assert_eq!(ring.modulus(), modulus);
+        assert_eq!(ring.mod_inv(), 17279742035199256357_u64);
+        assert_eq!(
+            ring.montgomery_r(),
+            from_ruint(uint!(276211425656182617693326127057814954281194144797_U160))
+        );
+        assert_eq!(
+            ring.montgomery_r2(),
+            from_ruint(uint!(
+                1328697288359250963969439540253036463178824026347_U160
+            ))
+        );
+        assert_eq!(
+            ring.montgomery_r3(),
+            from_ruint(uint!(604439593675794661367692917915221321770756884129_U160))
+        );
+    }
+
+    #[test]
+    fn test_bn254_param() {
+        let modulus: U256 = from_ruint(uint!(
+            21888242871839275222246405745257275088548364400416034343698204186575808495617_U256
+        ));
+        let ring = U256::parameters_from_modulus(modulus);
+        assert_eq!(ring.modulus(), modulus);
+        assert_eq!(
+            ring.montgomery_r(),
+            from_ruint(uint!(
+                6350874878119819312338956282401532410528162663560392320966563075034087161851_U256
+            ))
+        );
+        assert_eq!(
+            ring.montgomery_r2(),
+            from_ruint(uint!(
+                944936681149208446651664254269745548490766851729442924617792859073125903783_U256
+            ))
+        );
+        assert_eq!(
+            ring.montgomery_r3(),
+            from_ruint(uint!(
+                5866548545943845227489894872040244720403868105578784105281690076696998248512_U256
+            ))
+        );
+        assert_eq!(ring.mod_inv(), 14042775128853446655_u64);
+    }
+
+    #[test]
+    fn test_actual_bit_len() {
+        assert_eq!(UintExp::bit_len(&U256::ZERO), 256);
+        assert_eq!(U256::ZERO.actual_bit_len(), 0);
+        assert_eq!(U256::ONE.actual_bit_len(), 1);
+        assert_eq!(U256::from_u64(60).actual_bit_len(), 6);
+        assert_eq!(U256::MAX.actual_bit_len(), 256);
+    }
+
+    #[test]
+    fn test_be_bytes() {
+        let value = U192::from_u64(0x0102);
+        assert_eq!(value.to_be_bytes().len(), 24);
+        assert_eq!(U192::from_be_bytes(&value.to_be_bytes()), value);
+        assert_eq!(U192::try_from_be_bytes(&[0x01, 0x02]), Some(value));
+        assert_eq!(
+            U64::try_from_be_bytes(&[0, 1, 0, 0, 0, 0, 0, 0, 0, 0]),
+            None
+        );
+        assert_eq!(
+            U64::try_from_be_bytes(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 1]),
+            Some(U64::ONE)
+        );
+    }
+
+    #[test]
+    fn test_ring() {
+        // Primes that are 3, 5, 7 and 1 mod 8.
+        for modulus in [11_u64, 13, 23, 17, 41, 97, 257] {
+            let ring = ModRing::from_modulus(U64::from_u64(modulus));
+            let squares: Vec<_> = (0..modulus).map(|x| ring.from_u64(x).pow(2)).collect();
+            for a in 0..modulus {
+                let a = ring.from_u64(a);
+                assert_eq!((a + ring.one()) - ring.one(), a);
+                match a.sqrt() {
+                    Some(root) => assert_eq!(root.pow(2), a),
+                    None => assert!(!squares.contains(&a)),
+                }
+                if a != ring.zero() {
+                    assert_eq!(a * num_traits::Inv::inv(a).unwrap(), ring.one());
+                    assert_eq!(a.pow_ct(U64::from_u64(modulus - 1)), ring.one());
+                    assert_eq!(a.legendre() == 1, squares.contains(&a));
+                }
+            }
+        }
+    }
+}
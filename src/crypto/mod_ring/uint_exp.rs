@@ -1,5 +1,6 @@
 use {
-    num_traits::{PrimInt, Unsigned},
+    num_traits::PrimInt,
+    ruint::Uint,
     subtle::{Choice, ConstantTimeEq},
 };
 
@@ -13,17 +14,56 @@ pub trait UintExp {
     fn bit_ct(&self, index: usize) -> Choice;
 }
 
-// Implementation that should work for most unsigned integers.
-impl<T> UintExp for T
-where
-    T: PrimInt + Unsigned + ConstantTimeEq,
-{
+// Implementation that should work for most unsigned integers. This is not a
+// blanket implementation so other backends can implement the trait too.
+macro_rules! impl_uint_exp {
+    ($([$($generics:tt)*] $type:ty),* $(,)?) => {$(
+        impl<$($generics)*> UintExp for $type {
+            fn bit_len(&self) -> usize {
+                prim_int_bit_len::<Self>()
+            }
+
+            fn bit_ct(&self, index: usize) -> Choice {
+                prim_int_bit_ct(*self, index)
+            }
+        }
+    )*};
+}
+
+impl_uint_exp!(
+    [] u8,
+    [] u16,
+    [] u32,
+    [] u64,
+    [] usize,
+    [const BITS: usize, const LIMBS: usize] Uint<BITS, LIMBS>,
+);
+
+fn prim_int_bit_len<T: PrimInt>() -> usize {
+    T::zero().count_zeros() as usize
+}
+
+fn prim_int_bit_ct<T: PrimInt + ConstantTimeEq>(value: T, index: usize) -> Choice {
+    let bit = T::one() << index;
+    (value & bit).ct_eq(&bit)
+}
+
+/// The exponent `(value - 1) >> shift` for odd `value` and `shift >= 1`.
+///
+/// Subtracting one from an odd number only clears the lowest bit, so this
+/// only requires bit access to `value`.
+#[derive(Clone, Copy)]
+pub(super) struct PredShr<U> {
+    pub value: U,
+    pub shift: usize,
+}
+
+impl<U: UintExp> UintExp for PredShr<U> {
     fn bit_len(&self) -> usize {
-        T::zero().count_zeros() as usize
+        self.value.bit_len().saturating_sub(self.shift)
     }
 
     fn bit_ct(&self, index: usize) -> Choice {
-        let bit = T::one() << index;
-        (*self & bit).ct_eq(&bit)
+        self.value.bit_ct(index + self.shift)
     }
 }
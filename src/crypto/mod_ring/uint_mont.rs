@@ -1,26 +1,19 @@
 use {
-    super::{ModRing, UintExp},
+    super::{uint_exp::PredShr, ModRing, UintExp},
+    alloc::vec::Vec,
+    core::fmt::Debug,
     rand::Rng,
     ruint::{aliases::U64, Uint},
-    std::fmt::Debug,
     subtle::{ConditionallySelectable, ConstantTimeEq},
 };
 
 /// Trait for Uint backends supporting Montgomery multiplication.
 ///
-/// The only implemented backed is Ruint, but the code is cleaner
-/// if we abstract this, otherwise we would have to pass along the
-/// const-generic parameters everywhere.
+/// Implemented for Ruint, and for crypto-bigint with the `crypto-bigint`
+/// feature. The code is also cleaner if we abstract this, otherwise we would
+/// have to pass along the const-generic parameters everywhere.
 pub trait UintMont:
-    Sized
-    + Copy
-    + PartialEq
-    + Eq
-    + PartialOrd
-    + Debug
-    + ConstantTimeEq
-    + ConditionallySelectable
-    + UintExp
+    Sized + Copy + PartialEq + Eq + Ord + Debug + ConstantTimeEq + ConditionallySelectable + UintExp
 {
     fn parameters_from_modulus(modulus: Self) -> ModRing<Self>;
     fn from_u64(value: u64) -> Self;
@@ -32,10 +25,53 @@ pub trait UintMont:
     fn inv_mod(self, modulus: Self) -> Option<Self>;
 
     /// Square root in Montgomery form.
-    fn sqrt_mont(self, modulus: Self, mont_r: Self, mod_inv: u64) -> Option<Self>;
+    ///
+    /// Only primes that are 3, 5 or 7 mod 8 are supported, for others this
+    /// returns `None` and [`ModRingElement::sqrt`] uses Tonelli-Shanks.
+    ///
+    /// [`ModRingElement::sqrt`]: super::ModRingElement::sqrt
+    fn sqrt_mont(self, modulus: Self, mont_r: Self, mod_inv: u64) -> Option<Self> {
+        // TODO: This requires modulus to be prime.
+        let shr = |shift| PredShr {
+            value: modulus,
+            shift,
+        };
+        let candidate = match (bool::from(modulus.bit_ct(1)), bool::from(modulus.bit_ct(2))) {
+            // 3 or 7 mod 8, the exponent (p + 1) / 4 is (p >> 2) + 1.
+            (true, _) => {
+                pow(self, shr(2), modulus, mont_r, mod_inv).mul_redc(self, modulus, mod_inv)
+            }
+            // 5 mod 8, the exponent (p + 3) / 8 is (p >> 3) + 1.
+            (false, true) => {
+                let candidate =
+                    pow(self, shr(3), modulus, mont_r, mod_inv).mul_redc(self, modulus, mod_inv);
+                if candidate.square_redc(modulus, mod_inv) == self {
+                    return Some(candidate);
+                }
+                // Multiply by 2^((modulus - 1) / 4)
+                let two = mont_r.add_mod(mont_r, modulus);
+                let factor = pow(two, shr(2), modulus, mont_r, mod_inv);
+                candidate.mul_redc(factor, modulus, mod_inv)
+            }
+            (false, false) => return None,
+        };
+        if candidate.square_redc(modulus, mod_inv) == self {
+            Some(candidate)
+        } else {
+            None
+        }
+    }
 
     fn from_be_bytes(bytes: &[u8]) -> Self;
+
+    /// Like [`UintMont::from_be_bytes`], but returns `None` if the value does
+    /// not fit.
+    fn try_from_be_bytes(bytes: &[u8]) -> Option<Self>;
     fn to_be_bytes(&self) -> Vec<u8>;
+
+    /// Number of significant bits, unlike [`UintExp::bit_len`] this depends
+    /// on the value and should only be used for public values.
+    fn actual_bit_len(&self) -> usize;
 }
 
 impl<const BITS: usize, const LIMBS: usize> UintMont for Uint<BITS, LIMBS> {
@@ -111,56 +147,32 @@ impl<const BITS: usize, const LIMBS: usize> UintMont for Uint<BITS, LIMBS> {
     }
 
     #[inline]
-    fn sqrt_mont(self, modulus: Self, mont_r: Self, mod_inv: u64) -> Option<Self> {
-        // TODO: This requires modulus to be prime.
-        let candidate = match modulus.to::<u64>() & 3 {
-            3 | 7 => {
-                let exponent = (modulus >> 2) + Self::from_u64(1);
-                pow(self, exponent, modulus, mont_r, mod_inv)
-            }
-            5 => {
-                let exponent = (modulus >> 3) + Self::from_u64(1);
-                let candidate = pow(self, exponent, modulus, mont_r, mod_inv);
-                if candidate.square_redc(modulus, mod_inv) == self {
-                    return Some(candidate);
-                }
-                // Multiply by 2^((modulus - 1) / 4)
-                let exponent = modulus >> 2;
-                let two = mont_r.add_mod(mont_r, modulus);
-                let factor = pow(two, exponent, modulus, mont_r, mod_inv);
-                candidate.mul_redc(factor, modulus, mod_inv)
-            }
-            _ => unimplemented!("Square root only implemented for primes that are 3, 5, 7 mod 8."),
-        };
-        if candidate.square_redc(modulus, mod_inv) == self {
-            Some(candidate)
-        } else {
-            None
-        }
+    fn from_be_bytes(bytes: &[u8]) -> Self {
+        Self::from_be_slice(bytes)
     }
 
     #[inline]
-    fn from_be_bytes(bytes: &[u8]) -> Self {
-        Self::from_be_slice(bytes)
+    fn try_from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from_be_slice(bytes)
     }
 
     #[inline]
     fn to_be_bytes(&self) -> Vec<u8> {
         self.to_be_bytes_vec()
     }
+
+    #[inline]
+    fn actual_bit_len(&self) -> usize {
+        BITS - self.leading_zeros()
+    }
 }
 
-fn pow<const BITS: usize, const LIMBS: usize>(
-    base: Uint<BITS, LIMBS>,
-    exponent: Uint<BITS, LIMBS>,
-    modulus: Uint<BITS, LIMBS>,
-    mont_r: Uint<BITS, LIMBS>,
-    mod_inv: u64,
-) -> Uint<BITS, LIMBS> {
+/// Exponentiation in Montgomery form, the run time depends on the exponent.
+fn pow<U: UintMont, E: UintExp>(base: U, exponent: E, modulus: U, mont_r: U, mod_inv: u64) -> U {
     let mut result = mont_r;
     let mut power = base;
     for i in 0..exponent.bit_len() {
-        if exponent.bit(i) {
+        if bool::from(exponent.bit_ct(i)) {
             result = result.mul_redc(power, modulus, mod_inv);
         }
         power = power.square_redc(modulus, mod_inv);
@@ -246,4 +258,14 @@ mod tests {
         );
         assert_eq!(ring.mod_inv(), 14042775128853446655_u64);
     }
+
+    #[test]
+    fn test_actual_bit_len() {
+        assert_eq!(UintExp::bit_len(&U256::ZERO), 256);
+        assert_eq!(U256::ZERO.actual_bit_len(), 0);
+        assert_eq!(U256::from(1).actual_bit_len(), 1);
+        assert_eq!(U256::from(60).actual_bit_len(), 6);
+        assert_eq!(U256::MAX.actual_bit_len(), 256);
+        assert_eq!(U160::from(1_u64 << 63).actual_bit_len(), 64);
+    }
 }
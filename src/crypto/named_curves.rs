@@ -17,3 +17,141 @@ pub const ID_BRAINPOOL_P256R1: Oid = Oid::new_unwrap("1.3.36.3.3.2.8.1.1.7");
 pub const ID_BRAINPOOL_P320R1: Oid = Oid::new_unwrap("1.3.36.3.3.2.8.1.1.9");
 pub const ID_BRAINPOOL_P384R1: Oid = Oid::new_unwrap("1.3.36.3.3.2.8.1.1.11");
 pub const ID_BRAINPOOL_P512R1: Oid = Oid::new_unwrap("1.3.36.3.3.2.8.1.1.13");
+
+/// OID of a curve by its name, see [`named::curve_name`].
+///
+/// [`named::curve_name`]: crate::crypto::groups::named::curve_name
+pub fn curve_oid(name: &str) -> Option<Oid> {
+    Some(match name {
+        "secp192r1" => ID_SEC_P192R1,
+        "secp224r1" => ID_SEC_P224R1,
+        "secp256r1" => ID_SEC_P256R1,
+        "secp384r1" => ID_SEC_P384R1,
+        "secp521r1" => ID_SEC_P521R1,
+        "brainpoolP160r1" => ID_BRAINPOOL_P160R1,
+        "brainpoolP192r1" => ID_BRAINPOOL_P192R1,
+        "brainpoolP224r1" => ID_BRAINPOOL_P224R1,
+        "brainpoolP256r1" => ID_BRAINPOOL_P256R1,
+        "brainpoolP320r1" => ID_BRAINPOOL_P320R1,
+        "brainpoolP384r1" => ID_BRAINPOOL_P384R1,
+        "brainpoolP512r1" => ID_BRAINPOOL_P512R1,
+        _ => return None,
+    })
+}
+
+/// Evaluate `$body` with `$curve` bound to the curve described by the
+/// [`ECAlgoParameters`], using the smallest fitting `Uint` size.
+///
+/// Errors on unknown named curves, unsupported fields and implicit CA
+/// parameters.
+///
+/// [`ECAlgoParameters`]: crate::asn1::public_key_info::ECAlgoParameters
+macro_rules! with_curve {
+    ($params:expr, | $curve:ident | $body:expr) => {{
+        use {
+            anyhow::bail,
+            ruint::Uint,
+            $crate::{
+                asn1::public_key_info::{ECAlgoParameters, FieldId},
+                crypto::{
+                    groups::{named, EllipticCurve},
+                    named_curves::*,
+                },
+            },
+        };
+        match $params {
+            ECAlgoParameters::NamedCurve(oid) => match *oid {
+                ID_SEC_P192R1 => {
+                    let $curve = named::secp192r1();
+                    $body
+                }
+                ID_SEC_P224R1 => {
+                    let $curve = named::secp224r1();
+                    $body
+                }
+                ID_SEC_P256R1 => {
+                    let $curve = named::secp256r1();
+                    $body
+                }
+                ID_SEC_P384R1 => {
+                    let $curve = named::secp384r1();
+                    $body
+                }
+                ID_SEC_P521R1 => {
+                    let $curve = named::secp521r1();
+                    $body
+                }
+                ID_BRAINPOOL_P160R1 => {
+                    let $curve = named::brainpool_p160r1();
+                    $body
+                }
+                ID_BRAINPOOL_P192R1 => {
+                    let $curve = named::brainpool_p192r1();
+                    $body
+                }
+                ID_BRAINPOOL_P224R1 => {
+                    let $curve = named::brainpool_p224r1();
+                    $body
+                }
+                ID_BRAINPOOL_P256R1 => {
+                    let $curve = named::brainpool_p256r1();
+                    $body
+                }
+                ID_BRAINPOOL_P320R1 => {
+                    let $curve = named::brainpool_p320r1();
+                    $body
+                }
+                ID_BRAINPOOL_P384R1 => {
+                    let $curve = named::brainpool_p384r1();
+                    $body
+                }
+                ID_BRAINPOOL_P512R1 => {
+                    let $curve = named::brainpool_p512r1();
+                    $body
+                }
+                _ => bail!("Unsupported named curve {oid}"),
+            },
+            ECAlgoParameters::EcParameters(params) => {
+                let FieldId::PrimeField { modulus } = &params.field_id else {
+                    bail!("Only prime field curves are supported");
+                };
+                let bits = modulus.as_bytes().iter().skip_while(|&&b| b == 0).count() * 8;
+                match bits {
+                    0..=192 => {
+                        let $curve = EllipticCurve::<Uint<192, 3>>::from_parameters(params)?;
+                        $body
+                    }
+                    193..=224 => {
+                        let $curve = EllipticCurve::<Uint<224, 4>>::from_parameters(params)?;
+                        $body
+                    }
+                    225..=256 => {
+                        let $curve = EllipticCurve::<Uint<256, 4>>::from_parameters(params)?;
+                        $body
+                    }
+                    257..=320 => {
+                        let $curve = EllipticCurve::<Uint<320, 5>>::from_parameters(params)?;
+                        $body
+                    }
+                    321..=384 => {
+                        let $curve = EllipticCurve::<Uint<384, 6>>::from_parameters(params)?;
+                        $body
+                    }
+                    385..=512 => {
+                        let $curve = EllipticCurve::<Uint<512, 8>>::from_parameters(params)?;
+                        $body
+                    }
+                    513..=528 => {
+                        let $curve = EllipticCurve::<Uint<528, 9>>::from_parameters(params)?;
+                        $body
+                    }
+                    _ => bail!("Curve field too large: {bits} bits"),
+                }
+            }
+            ECAlgoParameters::ImplicitlyCA(_) => {
+                bail!("Implicitly CA curve parameters not supported")
+            }
+        }
+    }};
+}
+pub(crate) use with_curve;
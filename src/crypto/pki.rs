@@ -0,0 +1,164 @@
+//! Verification of CSCA Master Lists and Document Signer certificate chains.
+
+use {
+    super::signature::{verify_signed_content, verify_with_spki},
+    crate::asn1::{
+        emrtd::EfSod,
+        master_list::{CscaMasterList, MasterList},
+        ContentType,
+    },
+    anyhow::{anyhow, ensure, Result},
+    cms::cert::x509::Certificate,
+    der::Encode,
+    std::time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Extension methods for X.509 certificates.
+pub trait CertificateExt {
+    /// Returns true if `time` is within the validity period of the
+    /// certificate.
+    fn is_valid_at(&self, time: SystemTime) -> bool;
+}
+
+impl CertificateExt for Certificate {
+    fn is_valid_at(&self, time: SystemTime) -> bool {
+        let Ok(time) = time.duration_since(UNIX_EPOCH) else {
+            return false;
+        };
+        let validity = &self.tbs_certificate.validity;
+        validity.not_before.to_unix_duration() <= time
+            && time <= validity.not_after.to_unix_duration()
+    }
+}
+
+impl CscaMasterList {
+    /// The CSCA certificates that are valid at `time`.
+    pub fn valid_certs_at(&self, time: SystemTime) -> Vec<&Certificate> {
+        self.cert_list
+            .iter()
+            .filter(|cert| cert.is_valid_at(time))
+            .collect()
+    }
+}
+
+impl MasterList {
+    /// Verify the Master List Signer signature over the master list.
+    ///
+    /// The signed attributes must bind the signature to the encapsulated
+    /// [`CscaMasterList`], otherwise the content could be swapped.
+    ///
+    /// See ICAO 9303-12 9.
+    pub fn verify_signature(&self) -> Result<()> {
+        verify_signed_content(&self.0, CscaMasterList::CONTENT_TYPE)
+    }
+}
+
+impl EfSod {
+    /// Verify that the Document Signer certificates in the SOD are issued by
+    /// a CSCA from the master list and that both are valid at `time`.
+    ///
+    /// Pass [`SystemTime::now`] to check the document today, or the signing
+    /// time to check it as it was issued.
+    ///
+    /// The master list itself should be verified separately, see
+    /// [`MasterList::verify_signature`].
+    pub fn verify_certificate_chain(
+        &self,
+        master_list: &CscaMasterList,
+        time: SystemTime,
+    ) -> Result<()> {
+        let cscas = master_list.valid_certs_at(time);
+        let mut count = 0;
+        for certificate in self.certificates() {
+            ensure!(
+                certificate.is_valid_at(time),
+                "Document Signer certificate {} is not valid at the given time",
+                certificate.tbs_certificate.subject
+            );
+            verify_issued_by_csca(certificate, &cscas)?;
+            count += 1;
+        }
+        ensure!(count > 0, "No Document Signer certificate in EF.SOD");
+        Ok(())
+    }
+}
+
+/// Verify a certificate against the CSCAs whose subject matches its issuer.
+///
+/// There may be multiple CSCAs with the same name after a key rollover, so
+/// any of them is accepted.
+fn verify_issued_by_csca(certificate: &Certificate, cscas: &[&Certificate]) -> Result<()> {
+    let tbs = certificate.tbs_certificate.to_der()?;
+    let signature = certificate
+        .signature
+        .as_bytes()
+        .ok_or_else(|| anyhow!("Invalid certificate signature"))?;
+    let issuer = &certificate.tbs_certificate.issuer;
+    let mut result = Err(anyhow!("No valid CSCA found for issuer {issuer}"));
+    for csca in cscas
+        .iter()
+        .filter(|csca| &csca.tbs_certificate.subject == issuer)
+    {
+        result = verify_with_spki(
+            &csca.tbs_certificate.subject_public_key_info,
+            &certificate.signature_algorithm,
+            &tbs,
+            signature,
+        );
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        cms::cert::x509::time::Time,
+        der::{asn1::GeneralizedTime, Decode},
+        std::time::Duration,
+    };
+
+    #[test]
+    fn test_expired_csca() -> Result<()> {
+        let master_list = MasterList::from_der(include_bytes!("../../tests/data/master_list.der"))?;
+        let sod = EfSod::from_der(include_bytes!("../../tests/data/ef_sod_csca.bin"))?;
+        let mut csca_master_list = master_list.csca_master_list()?;
+
+        // The CSCA and Document Signer are both valid from 2024 to 2049.
+        let year = Duration::from_secs(365 * 24 * 60 * 60);
+        sod.verify_certificate_chain(&csca_master_list, UNIX_EPOCH + 55 * year)?;
+        let error = sod
+            .verify_certificate_chain(&csca_master_list, UNIX_EPOCH + 80 * year)
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("is not valid at the given time"));
+
+        let csca = csca_master_list.cert_list.get(0).unwrap();
+        assert!(csca.is_valid_at(UNIX_EPOCH + 55 * year));
+        assert!(!csca.is_valid_at(UNIX_EPOCH + 50 * year));
+        assert!(!csca.is_valid_at(UNIX_EPOCH + 80 * year));
+        assert_eq!(
+            csca_master_list
+                .valid_certs_at(UNIX_EPOCH + 80 * year)
+                .len(),
+            0
+        );
+
+        // A CSCA that expired in 2020 is refused. Its own signature is not
+        // checked, so changing the validity is fine.
+        let mut csca = csca.clone();
+        csca.tbs_certificate.validity.not_after = Time::GeneralTime(
+            GeneralizedTime::from_unix_duration(Duration::from_secs(1_577_836_800))?,
+        );
+        csca_master_list.cert_list = [csca].try_into()?;
+        let error = sod
+            .verify_certificate_chain(&csca_master_list, UNIX_EPOCH + 55 * year)
+            .unwrap_err();
+        assert!(error.to_string().starts_with("No valid CSCA found"));
+        Ok(())
+    }
+}
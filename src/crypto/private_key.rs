@@ -0,0 +1,268 @@
+//! Private keys for key agreement and their PKCS #8 encoding.
+
+use {
+    crate::asn1::public_key_info::{
+        DhAlgoParameters, ECAlgoParameters, ID_DH, ID_EC, ID_EC_DH, ID_EC_MQV,
+    },
+    anyhow::{anyhow, bail, ensure, Result},
+    der::{
+        asn1::{AnyRef, OctetStringRef, UintRef},
+        Decode, Encode, Sequence,
+    },
+    pkcs8::{AlgorithmIdentifierRef, PrivateKeyInfo},
+    subtle::ConstantTimeEq,
+    zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing},
+};
+
+/// Private key for a [`super::KeyAgreementAlgorithm`].
+///
+/// Keys carry the domain parameters of the algorithm that generated them and
+/// are zeroized on drop. Equality is constant time in the secret.
+#[derive(PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub enum PrivateKey {
+    Ec(EcPrivateKey),
+    Dh(DhPrivateKey),
+}
+
+/// Elliptic curve private scalar in big-endian.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct EcPrivateKey {
+    #[zeroize(skip)]
+    pub(crate) parameters: ECAlgoParameters,
+    pub(crate) scalar:     Vec<u8>,
+}
+
+/// Diffie-Hellman private exponent in big-endian without leading zeros.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct DhPrivateKey {
+    #[zeroize(skip)]
+    pub(crate) parameters: DhAlgoParameters,
+    pub(crate) exponent:   Vec<u8>,
+}
+
+/// RFC 5915 `ECPrivateKey`.
+#[derive(Sequence)]
+struct EcPrivateKeyDer<'a> {
+    version:     u8,
+    private_key: OctetStringRef<'a>,
+    #[asn1(context_specific = "0", optional = "true")]
+    parameters:  Option<ECAlgoParameters>,
+    #[asn1(context_specific = "1", optional = "true")]
+    public_key:  Option<AnyRef<'a>>,
+}
+
+impl PrivateKey {
+    /// Encode as a PKCS #8 `PrivateKeyInfo`.
+    ///
+    /// EC keys use the RFC 5915 `ECPrivateKey` with RFC 5480 `ECParameters`
+    /// and DH keys the PKCS #3 integer encoding and `DHParameter`. The domain
+    /// parameters are in the `AlgorithmIdentifier`.
+    pub fn to_pkcs8_der(&self) -> Result<Vec<u8>> {
+        let (oid, parameters, private_key) = match self {
+            Self::Ec(key) => (
+                ID_EC,
+                key.parameters.to_der()?,
+                EcPrivateKeyDer {
+                    version:     1,
+                    private_key: OctetStringRef::new(&key.scalar)?,
+                    parameters:  None,
+                    public_key:  None,
+                }
+                .to_der()?,
+            ),
+            Self::Dh(key) => (
+                ID_DH,
+                key.parameters.to_der()?,
+                UintRef::new(&key.exponent)?.to_der()?,
+            ),
+        };
+        let private_key = Zeroizing::new(private_key);
+        let algorithm = AlgorithmIdentifierRef {
+            oid,
+            parameters: Some(AnyRef::from_der(&parameters)?),
+        };
+        Ok(PrivateKeyInfo::new(algorithm, &private_key).to_der()?)
+    }
+
+    /// Decode a PKCS #8 `PrivateKeyInfo` as produced by [`Self::to_pkcs8_der`].
+    ///
+    /// EC domain parameters may also be in the `ECPrivateKey`.
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self> {
+        let info = PrivateKeyInfo::from_der(der)?;
+        let parameters = info
+            .algorithm
+            .parameters
+            .map(|any| any.to_der())
+            .transpose()?;
+        match info.algorithm.oid {
+            ID_EC | ID_EC_DH | ID_EC_MQV => {
+                let key = EcPrivateKeyDer::from_der(info.private_key)?;
+                ensure!(key.version == 1, "Unsupported ECPrivateKey version");
+                let parameters = match parameters {
+                    Some(parameters) => ECAlgoParameters::from_der(&parameters)?,
+                    None => key
+                        .parameters
+                        .ok_or_else(|| anyhow!("Missing elliptic curve parameters"))?,
+                };
+                Ok(Self::Ec(EcPrivateKey {
+                    parameters,
+                    scalar: key.private_key.as_bytes().to_vec(),
+                }))
+            }
+            ID_DH => {
+                let parameters =
+                    parameters.ok_or_else(|| anyhow!("Missing Diffie-Hellman parameters"))?;
+                let key = UintRef::from_der(info.private_key)?;
+                Ok(Self::Dh(DhPrivateKey {
+                    parameters: DhAlgoParameters::from_der(&parameters)?,
+                    exponent:   key.as_bytes().to_vec(),
+                }))
+            }
+            oid => bail!("Unsupported private key algorithm {oid}"),
+        }
+    }
+}
+
+impl EcPrivateKey {
+    /// Curve the key belongs to.
+    pub const fn parameters(&self) -> &ECAlgoParameters {
+        &self.parameters
+    }
+}
+
+impl DhPrivateKey {
+    /// Group the key belongs to.
+    pub const fn parameters(&self) -> &DhAlgoParameters {
+        &self.parameters
+    }
+}
+
+impl PartialEq for EcPrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.parameters == other.parameters && bool::from(self.scalar.ct_eq(&other.scalar))
+    }
+}
+
+impl Eq for EcPrivateKey {}
+
+impl PartialEq for DhPrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.parameters == other.parameters && bool::from(self.exponent.ct_eq(&other.exponent))
+    }
+}
+
+impl Eq for DhPrivateKey {}
+
+impl AsRef<[u8]> for EcPrivateKey {
+    fn as_ref(&self) -> &[u8] {
+        self.scalar.as_ref()
+    }
+}
+
+impl AsRef<[u8]> for DhPrivateKey {
+    fn as_ref(&self) -> &[u8] {
+        self.exponent.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::crypto::{
+            groups::named::{brainpool_p256r1, modp_160, modp_224, secp256r1},
+            named_curves::ID_BRAINPOOL_P256R1,
+            KeyAgreementAlgorithm,
+        },
+        ruint::Uint,
+    };
+
+    #[test]
+    fn test_pkcs8_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let curve = brainpool_p256r1();
+        let group = modp_160();
+        let (ec_private, ec_public) = curve.generate_key_pair(&mut rng);
+        let (dh_private, dh_public) = group.generate_key_pair(&mut rng);
+        let PrivateKey::Ec(ec_key) = &ec_private else {
+            panic!("Expected an elliptic curve key");
+        };
+        let PrivateKey::Dh(dh_key) = &dh_private else {
+            panic!("Expected a Diffie-Hellman key");
+        };
+        assert_eq!(
+            ec_key.parameters(),
+            &ECAlgoParameters::NamedCurve(ID_BRAINPOOL_P256R1)
+        );
+        assert_eq!(
+            Uint::<1024, 16>::try_from(dh_key.parameters().prime.clone()).unwrap(),
+            group.base_field().modulus()
+        );
+
+        let ec_decoded = PrivateKey::from_pkcs8_der(&ec_private.to_pkcs8_der().unwrap()).unwrap();
+        let dh_decoded = PrivateKey::from_pkcs8_der(&dh_private.to_pkcs8_der().unwrap()).unwrap();
+        assert!(ec_decoded == ec_private);
+        assert!(dh_decoded == dh_private);
+
+        // Decoded keys remain usable and are bound to their algorithm.
+        let (_, other_ec_public) = curve.generate_key_pair(&mut rng);
+        assert_eq!(
+            curve.key_agreement(&ec_decoded, &other_ec_public).unwrap(),
+            curve.key_agreement(&ec_private, &other_ec_public).unwrap()
+        );
+        assert!(curve.key_agreement(&dh_decoded, &ec_public).is_err());
+        assert!(group.key_agreement(&ec_decoded, &dh_public).is_err());
+
+        // Keys of the same size for other parameters are rejected.
+        let (_, secp_public) = secp256r1().generate_key_pair(&mut rng);
+        assert!(secp256r1()
+            .key_agreement(&ec_decoded, &secp_public)
+            .is_err());
+        let (_, modp_public) = modp_224().generate_key_pair(&mut rng);
+        assert!(modp_224().key_agreement(&dh_decoded, &modp_public).is_err());
+    }
+
+    #[test]
+    fn test_pkcs8_parameters() {
+        let mut rng = rand::thread_rng();
+        let (private, _) = brainpool_p256r1().generate_key_pair(&mut rng);
+        let PrivateKey::Ec(key) = &private else {
+            panic!("Expected an elliptic curve key");
+        };
+        let der = private.to_pkcs8_der().unwrap();
+        let info = PrivateKeyInfo::from_der(&der).unwrap();
+        assert_eq!(info.algorithm.oid, ID_EC);
+        assert_eq!(
+            info.algorithm.parameters.unwrap().to_der().unwrap(),
+            ID_BRAINPOOL_P256R1.to_der().unwrap()
+        );
+
+        // RFC 5915 allows the parameters in the `ECPrivateKey` instead.
+        let inner = EcPrivateKeyDer {
+            version:     1,
+            private_key: OctetStringRef::new(&key.scalar).unwrap(),
+            parameters:  Some(ECAlgoParameters::NamedCurve(ID_BRAINPOOL_P256R1)),
+            public_key:  None,
+        }
+        .to_der()
+        .unwrap();
+        let algorithm = AlgorithmIdentifierRef {
+            oid:        ID_EC,
+            parameters: None,
+        };
+        let der = PrivateKeyInfo::new(algorithm, &inner).to_der().unwrap();
+        assert!(PrivateKey::from_pkcs8_der(&der).unwrap() == private);
+
+        // Without parameters the key is rejected.
+        let inner = EcPrivateKeyDer {
+            version:     1,
+            private_key: OctetStringRef::new(&key.scalar).unwrap(),
+            parameters:  None,
+            public_key:  None,
+        }
+        .to_der()
+        .unwrap();
+        let der = PrivateKeyInfo::new(algorithm, &inner).to_der().unwrap();
+        assert!(PrivateKey::from_pkcs8_der(&der).is_err());
+    }
+}
@@ -5,16 +5,28 @@
 //! using it. See e.g. https://blog.trailofbits.com/2019/07/08/fuck-rsa
 
 use {
-    super::mod_ring::{ModRing, ModRingElementRef, UintMont},
+    super::mod_ring::{ModRing, ModRingElementRef, RingRefExt, UintMont},
     crate::asn1::{
         public_key_info::SubjectPublicKeyInfo,
         signature_algorithm_identifier::{MaskGenAlgorithm, RsaPssParameters},
-        DigestAlgorithmIdentifier, SignatureAlgorithmIdentifier,
+        AnyAlgorithmIdentifier, DigestAlgorithmIdentifier, DigestAlgorithmParameters,
+        SignatureAlgorithmIdentifier,
     },
     anyhow::{anyhow, bail, ensure, Error, Result},
+    der::{
+        asn1::{Null, OctetString},
+        Encode, Sequence,
+    },
     ruint::Uint,
 };
 
+/// RFC 8017 9.2 `DigestInfo`.
+#[derive(Sequence)]
+struct DigestInfo {
+    digest_algorithm: AnyAlgorithmIdentifier,
+    digest:           OctetString,
+}
+
 #[derive(Clone, Debug)]
 pub struct RSAPublicKey<U: UintMont> {
     pub ring:        ModRing<U>,
@@ -22,6 +34,14 @@ pub struct RSAPublicKey<U: UintMont> {
 }
 
 impl<U: UintMont> RSAPublicKey<U> {
+    /// Length of the modulus in bits, which can be less than the width of `U`.
+    fn modulus_bits(&self) -> usize {
+        let modulus = self.ring.modulus().to_be_bytes();
+        modulus.iter().position(|&b| b != 0).map_or(0, |i| {
+            (modulus.len() - i) * 8 - modulus[i].leading_zeros() as usize
+        })
+    }
+
     /// Verify an RSA signature.
     pub fn verify<'s>(
         &'s self,
@@ -33,6 +53,9 @@ impl<U: UintMont> RSAPublicKey<U> {
             SignatureAlgorithmIdentifier::RsaPss(params) => {
                 self.verify_pss(message, signature, params)
             }
+            SignatureAlgorithmIdentifier::RsaPkcs1v15(digest) => {
+                self.verify_pkcs1v15(message, signature, digest)
+            }
             _ => bail!("Unrecognized RSA signature algorithm"),
         }
     }
@@ -54,7 +77,7 @@ impl<U: UintMont> RSAPublicKey<U> {
         ensure!(signature.ring() == &self.ring);
         ensure!(message.ring() == &self.ring);
 
-        let ring_bit_len = self.ring.modulus().bit_len();
+        let ring_bit_len = self.modulus_bits();
         let digest_algo = &params.hash_algorithm;
         let salt_len = params.salt_length.as_bytes()[0] as usize;
         let trailer_field = params.trailer_field.as_bytes()[0] as usize;
@@ -63,9 +86,11 @@ impl<U: UintMont> RSAPublicKey<U> {
             "Unrecognized trailer field {trailer_field}. Expected value 1 (= 0xbc)"
         );
 
+        let em_bits = ring_bit_len - 1;
+        let em_len = em_bits.div_ceil(8);
         let em_elem = signature.pow_ct(self.public_exponent);
         let em_bytes = em_elem.to_uint().to_be_bytes();
-        let em_len = (self.ring.modulus().bit_len() + 7) / 8;
+        let em_bytes = &em_bytes[em_bytes.len() - em_len..];
 
         // Check trailer (0xBC byte)
         ensure!(
@@ -93,7 +118,6 @@ impl<U: UintMont> RSAPublicKey<U> {
         for (i, &b) in db.iter().enumerate() {
             db_unmasked[i] = b ^ mgf_mask[i];
         }
-        let em_bits = ring_bit_len - 1;
         db_unmasked[0] &= 0xff >> (8 * em_len - em_bits);
 
         // Verify DB format
@@ -131,6 +155,113 @@ impl<U: UintMont> RSAPublicKey<U> {
 
         Ok(())
     }
+
+    /// Verify an RSASSA-PKCS1-v1_5 signature, per RFC 8017 8.2.2.
+    fn verify_pkcs1v15<'s>(
+        &'s self,
+        message: ModRingElementRef<'s, U>,
+        signature: ModRingElementRef<'s, U>,
+        digest_algo: &DigestAlgorithmIdentifier,
+    ) -> Result<()> {
+        // Verifies EM == 0x00 || 0x01 || PS || 0x00 || T, where,
+        // EM (encoded message) = signature^e mod n
+        // PS = 0xFF padding of at least 8 bytes
+        // T = DER encoded DigestInfo of hash(message)
+
+        ensure!(signature.ring() == &self.ring);
+        ensure!(message.ring() == &self.ring);
+
+        let em_len = self.modulus_bits().div_ceil(8);
+        let em_elem = signature.pow_ct(self.public_exponent);
+        let em_bytes = em_elem.to_uint().to_be_bytes();
+        let em_bytes = &em_bytes[em_bytes.len() - em_len..];
+
+        let hash_len = digest_algo.hash_bytes(&[]).len();
+        let message_bytes = message.to_uint().to_be_bytes();
+        let hash = OctetString::new(&message_bytes[message_bytes.len() - hash_len..])?;
+
+        // RFC 8017 9.2 note 1: the parameters should be NULL, but some
+        // implementations omit them.
+        for parameters in [Some(Null.into()), None] {
+            let t = DigestInfo {
+                digest_algorithm: AnyAlgorithmIdentifier {
+                    algorithm: digest_algo.oid(),
+                    parameters,
+                },
+                digest:           hash.clone(),
+            }
+            .to_der()?;
+            ensure!(
+                em_len >= t.len() + 11,
+                "Encoded message too short for PKCS#1 v1.5"
+            );
+            let ps_len = em_len - t.len() - 3;
+            if em_bytes[..2] == [0x00, 0x01]
+                && em_bytes[2..2 + ps_len].iter().all(|&b| b == 0xff)
+                && em_bytes[2 + ps_len] == 0x00
+                && em_bytes[3 + ps_len..] == t
+            {
+                return Ok(());
+            }
+        }
+        bail!("PKCS#1 v1.5 verification: encoded message mismatch")
+    }
+}
+
+impl<U: UintMont> RSAPublicKey<U> {
+    /// Verify an ISO/IEC 9796-2 Digital Signature Scheme 1 signature with
+    /// partial message recovery, as used by Active Authentication.
+    ///
+    /// The recovered message `M1` is prepended to `message` before hashing.
+    /// See ICAO 9303-11 6.1.2.
+    pub fn verify_iso9796_2(&self, signature: &[u8], message: &[u8]) -> Result<()> {
+        // Length of the modulus in bytes
+        let modulus = self.ring.modulus().to_be_bytes();
+        let k = modulus.iter().skip_while(|&&b| b == 0).count();
+        ensure!(
+            signature.len() == k,
+            "Signature length does not match modulus"
+        );
+        let signature = U::from_be_bytes(signature);
+        ensure!(signature < self.ring.modulus(), "Signature out of range");
+
+        // F = header || M1 || hash || trailer
+        let f = self.ring.from(signature).pow_ct(self.public_exponent);
+        let f = f.to_uint().to_be_bytes();
+        let f = &f[f.len() - k..];
+        ensure!(f[0] == 0x6a, "Invalid ISO 9796-2 header");
+
+        // Trailer is either 0xBC (implicit SHA-1) or a hash identifier
+        // followed by 0xCC. See ISO/IEC 9796-2 table 3.
+        let params = DigestAlgorithmParameters::Absent;
+        let (digest_algo, trailer_len) = match (f[k - 2], f[k - 1]) {
+            (_, 0xbc) => (DigestAlgorithmIdentifier::Sha1(params), 1),
+            (0x33, 0xcc) => (DigestAlgorithmIdentifier::Sha1(params), 2),
+            (0x34, 0xcc) => (DigestAlgorithmIdentifier::Sha256(params), 2),
+            (0x35, 0xcc) => (DigestAlgorithmIdentifier::Sha512(params), 2),
+            (0x36, 0xcc) => (DigestAlgorithmIdentifier::Sha384(params), 2),
+            (0x38, 0xcc) => (DigestAlgorithmIdentifier::Sha224(params), 2),
+            _ => bail!("Unrecognized ISO 9796-2 trailer"),
+        };
+        let hash_len = digest_algo.hash_bytes(&[]).len();
+        ensure!(
+            k >= 1 + hash_len + trailer_len,
+            "Encoded message too short for ISO 9796-2"
+        );
+        let hash_start = k - trailer_len - hash_len;
+        let m1 = &f[1..hash_start];
+        let hash = &f[hash_start..k - trailer_len];
+
+        // Compute hash' = hash(M1 || M2)
+        let mut m = m1.to_vec();
+        m.extend_from_slice(message);
+        ensure!(
+            digest_algo.hash_bytes(&m) == hash,
+            "ISO 9796-2 verification: hash check failed"
+        );
+
+        Ok(())
+    }
 }
 
 fn mgf1(digest_algo: &DigestAlgorithmIdentifier, seed: &[u8], out_len: usize) -> Vec<u8> {
@@ -219,4 +350,26 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_iso9796_2() -> Result<()> {
+        // Signature over a test challenge using the BSI TR-03105-5 DG15 key.
+        let dg15 = include_bytes!("../../tests/dataset/Datagroup15.bin");
+        let pubkey_info = SubjectPublicKeyInfo::from_der(&dg15[3..])?;
+        let pubkey = RSAPublicKey::<Uint<1024, 16>>::try_from(pubkey_info)?;
+
+        let challenge = hex!("0102030405060708");
+        let mut signature = hex!("5c1b1e4b1cdaf9099553088825edf286821efa0f53d78fb5b0c39f0763f103b7c074812a0b019842e035ae9efcb0eb6864af876ed4f68b9e69e6af6a0820c728f1a3ea1ff9e8308f7af0014df205df6b5c81fb55e23474680abd2b500f297fe87424df9e8eff11bfe7d5df249e27bf762567fe9299e0930e8da26507ce733432");
+        pubkey.verify_iso9796_2(&signature, &challenge)?;
+
+        // Wrong challenge
+        ensure!(pubkey
+            .verify_iso9796_2(&signature, &hex!("0102030405060709"))
+            .is_err());
+
+        // Corrupted signature
+        signature[10] ^= 1;
+        ensure!(pubkey.verify_iso9796_2(&signature, &challenge).is_err());
+        Ok(())
+    }
 }
@@ -1,30 +1,371 @@
-//! Signature verification for SOD
+//! Signature verification for SOD and other CMS signed data
 
 use {
-    crate::asn1::{emrtd::EfSod, DigestAlgorithmIdentifier},
-    anyhow::Result,
-    der::{Decode, Encode},
+    super::{ecdsa::verify_ecdsa, mod_ring::RingRefExt, RSAPublicKey},
+    crate::asn1::{
+        emrtd::{EfSod, LdsSecurityObject},
+        public_key_info::{ECAlgoParameters, SubjectPublicKeyInfo},
+        ContentType, DigestAlgorithmIdentifier, SignatureAlgorithmIdentifier,
+    },
+    anyhow::{anyhow, bail, ensure, Result},
+    cms::{
+        cert::{
+            x509::{
+                ext::pkix::{KeyUsage, SubjectKeyIdentifier},
+                spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned},
+                Certificate,
+            },
+            CertificateChoices,
+        },
+        signed_data::{SignedData, SignerIdentifier, SignerInfo},
+    },
+    const_oid::db::rfc5911::{
+        ID_CONTENT_TYPE, ID_COUNTERSIGNATURE, ID_MESSAGE_DIGEST, ID_SIGNING_TIME,
+    },
+    der::{
+        asn1::{ObjectIdentifier, OctetString},
+        Any, Decode, Encode,
+    },
+    ruint::Uint,
+    thiserror::Error,
 };
 
+/// A data group does not match its hash in the LDS security object.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error(
+    "Hash mismatch for DG{dg_number}: expected 0x{}, got 0x{}",
+    hex::encode(expected),
+    hex::encode(actual)
+)]
+pub struct DataGroupHashMismatch {
+    pub dg_number: usize,
+    pub expected:  Vec<u8>,
+    pub actual:    Vec<u8>,
+}
+
 impl EfSod {
+    /// The X.509 certificates included in the SOD, skipping other formats.
+    pub fn certificates(&self) -> impl Iterator<Item = &Certificate> {
+        certificates(self.signed_data())
+    }
+
+    /// The document signer certificate.
+    ///
+    /// This is the first included certificate that is not self-signed and
+    /// has the `digitalSignature` key usage. Intermediate certificates are
+    /// skipped.
+    pub fn document_signer_cert(&self) -> Option<&Certificate> {
+        self.certificates().find(|cert| {
+            let tbs = &cert.tbs_certificate;
+            tbs.issuer != tbs.subject
+                && tbs
+                    .get::<KeyUsage>()
+                    .ok()
+                    .flatten()
+                    .is_some_and(|(_, usage)| usage.digital_signature())
+        })
+    }
+
     /// Verify the signature of the SOD
     pub fn verify_signature(&self) -> Result<()> {
-        let signer = self.signer_info();
+        self.check_signed_attributes()?;
+        verify_signed_data(self.signed_data())
+    }
+
+    /// Verify that at least one of the signers has a valid signature.
+    ///
+    /// Errors with the failure of the last signer if none verify, or if there
+    /// are no signers.
+    pub fn verify_any_signature(&self) -> Result<()> {
+        let mut result = Err(anyhow!("Missing signer info"));
+        for signer in self.signer_infos() {
+            result = self
+                .check_signer_attributes(signer)
+                .and_then(|()| verify_signer(self.signed_data(), signer));
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Check the signed attributes bind the signature to the LDS security
+    /// object, see ICAO 9303-10 4.6.2.2.
+    ///
+    /// The `ContentType` attribute must be the LDS security object and the
+    /// `MessageDigest` attribute must match the encapsulated content. Each must
+    /// occur exactly once with a single value. See
+    /// [`crate::crypto::check_signed_attributes`] for the other attributes.
+    pub fn check_signed_attributes(&self) -> Result<()> {
+        self.check_signer_attributes(self.signer_info()?)
+    }
+
+    fn check_signer_attributes(&self, signer: &SignerInfo) -> Result<()> {
+        check_signed_attributes(self.signed_data(), signer, LdsSecurityObject::CONTENT_TYPE)
+    }
+
+    /// Verify raw data group contents against the hashes in the LDS security
+    /// object.
+    ///
+    /// Takes `(dg_number, raw_bytes)` pairs. On mismatch the error is a
+    /// [`DataGroupHashMismatch`]. This does not verify the signature, see
+    /// [`EfSod::verify_signature`].
+    pub fn verify_data_groups(&self, dgs: &[(usize, &[u8])]) -> Result<()> {
+        let lds_security_object = self.lds_security_object()?;
+        let hash_algorithm = &lds_security_object.hash_algorithm;
+        for &(dg_number, raw_bytes) in dgs {
+            let expected = lds_security_object
+                .hash_for_dg(dg_number)
+                .ok_or_else(|| anyhow!("No hash for DG{dg_number} in EF.SOD"))?;
+            let actual = hash_algorithm.hash_bytes(raw_bytes);
+            if actual != expected {
+                return Err(DataGroupHashMismatch {
+                    dg_number,
+                    expected: expected.to_vec(),
+                    actual,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Verify the signature of the first signer of a [`SignedData`] using the
+/// signer certificate included in it.
+///
+/// This does not validate the signer certificate itself, nor that the
+/// signature covers the encapsulated content, see [`verify_signed_content`].
+pub fn verify_signed_data(signed_data: &SignedData) -> Result<()> {
+    verify_signer(signed_data, first_signer(signed_data)?)
+}
 
-        // Message
-        let message = self.encapsulated_content();
+/// Verify the first signer of a [`SignedData`] and that its signed
+/// attributes bind the signature to encapsulated content of `content_type`.
+///
+/// This does not validate the signer certificate itself.
+pub fn verify_signed_content(
+    signed_data: &SignedData,
+    content_type: ObjectIdentifier,
+) -> Result<()> {
+    let signer = first_signer(signed_data)?;
+    check_signed_attributes(signed_data, signer, content_type)?;
+    verify_signer(signed_data, signer)
+}
+
+/// Check the signed attributes of `signer` bind the signature to the
+/// encapsulated content, see RFC 5652 5.3 and ICAO 9303-10 4.6.2.2.
+///
+/// The `ContentType` attribute and the encapsulated content type must both be
+/// `content_type` and the `MessageDigest` attribute must match the
+/// encapsulated content. Each attribute must occur exactly once with a single
+/// value.
+///
+/// ICAO 9303-12 4.6.2.2 lets the issuer add further signed attributes, which
+/// the receiver only has to cover by the signature, so they are accepted
+/// unless RFC 5652 11 forbids them: a `SigningTime` must have a single value
+/// and a `Countersignature` must not be signed.
+pub fn check_signed_attributes(
+    signed_data: &SignedData,
+    signer: &SignerInfo,
+    content_type: ObjectIdentifier,
+) -> Result<()> {
+    let attrs = signer
+        .signed_attrs
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing signed attributes"))?;
+    let single_value = |oid| -> Result<&Any> {
+        let mut matching = attrs.iter().filter(|attr| attr.oid == oid);
+        let attr = matching
+            .next()
+            .ok_or_else(|| anyhow!("Missing signed attribute {oid}"))?;
+        ensure!(
+            matching.next().is_none(),
+            "Duplicate signed attribute {oid}"
+        );
+        let mut values = attr.values.iter();
+        match (values.next(), values.next()) {
+            (Some(value), None) => Ok(value),
+            _ => bail!("Signed attribute {oid} must have a single value"),
+        }
+    };
+
+    if attrs.iter().any(|attr| attr.oid == ID_SIGNING_TIME) {
+        single_value(ID_SIGNING_TIME)?;
+    }
+    ensure!(
+        attrs.iter().all(|attr| attr.oid != ID_COUNTERSIGNATURE),
+        "Countersignature must not be a signed attribute"
+    );
+
+    let signed_type = single_value(ID_CONTENT_TYPE)?.decode_as::<ObjectIdentifier>()?;
+    ensure!(
+        signed_type == content_type,
+        "Signed content type {signed_type} is not {content_type}"
+    );
+    let encapsulated = &signed_data.encap_content_info;
+    ensure!(
+        encapsulated.econtent_type == content_type,
+        "Encapsulated content type {} is not {content_type}",
+        encapsulated.econtent_type
+    );
+
+    let digest = single_value(ID_MESSAGE_DIGEST)?.decode_as::<OctetString>()?;
+    let algorithm = DigestAlgorithmIdentifier::from_der(&signer.digest_alg.to_der()?)?;
+    if let DigestAlgorithmIdentifier::Unknown(algo) = &algorithm {
+        bail!("Unsupported digest algorithm {}", algo.algorithm);
+    }
+    let content = encapsulated
+        .econtent
+        .as_ref()
+        .ok_or_else(|| anyhow!("Missing encapsulated content"))?;
+    ensure!(
+        algorithm.hash_bytes(content.value()) == digest.as_bytes(),
+        "Message digest does not match the encapsulated content"
+    );
+    Ok(())
+}
+
+/// The first signer of a [`SignedData`].
+fn first_signer(signed_data: &SignedData) -> Result<&SignerInfo> {
+    signed_data
+        .signer_infos
+        .0
+        .as_slice()
+        .first()
+        .ok_or_else(|| anyhow!("Missing signer info"))
+}
 
-        // Message hash
-        let digest = DigestAlgorithmIdentifier::from_der(&signer.digest_alg.to_der()?)?;
-        let hash = digest.hash_der(message);
-        eprintln!("DIGEST: {} = 0x{}", &digest, hex::encode(&hash));
+/// Verify the signature of one signer of a [`SignedData`].
+fn verify_signer(signed_data: &SignedData, signer: &SignerInfo) -> Result<()> {
+    let certificate = signer_certificate(signed_data, &signer.sid)?;
 
-        // Signature
-        let signature = signer.signature.as_bytes();
-        eprintln!("SIGNATURE: 0x{}", hex::encode(signature));
+    // With signed attributes the signature is over their DER encoding as a
+    // SET OF, otherwise directly over the content. See RFC 5652 5.4.
+    let message = match &signer.signed_attrs {
+        Some(attrs) => attrs.to_der()?,
+        None => signed_data
+            .encap_content_info
+            .econtent
+            .as_ref()
+            .ok_or_else(|| anyhow!("Missing encapsulated content"))?
+            .value()
+            .to_vec(),
+    };
+    verify_with_spki(
+        &certificate.tbs_certificate.subject_public_key_info,
+        &signer.signature_algorithm,
+        &message,
+        signer.signature.as_bytes(),
+    )
+}
 
-        dbg!(signer);
+/// Verify a signature over `message` with the public key from a certificate.
+pub fn verify_with_spki(
+    spki: &SubjectPublicKeyInfoOwned,
+    algorithm: &AlgorithmIdentifierOwned,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    let algorithm = SignatureAlgorithmIdentifier::from_der(&algorithm.to_der()?)?;
+    match &algorithm {
+        SignatureAlgorithmIdentifier::RsaPss(params) => {
+            let hash = params.hash_algorithm.hash_bytes(message);
+            let public_key = SubjectPublicKeyInfo::from_der(&spki.to_der()?)?;
+            verify_rsa(public_key, &hash, signature, &algorithm)
+        }
+        SignatureAlgorithmIdentifier::RsaPkcs1v15(digest) => {
+            let hash = digest.hash_bytes(message);
+            let public_key = SubjectPublicKeyInfo::from_der(&spki.to_der()?)?;
+            verify_rsa(public_key, &hash, signature, &algorithm)
+        }
+        SignatureAlgorithmIdentifier::Ecdsa(digest) => {
+            let hash = digest.hash_bytes(message);
+            let params = spki
+                .algorithm
+                .parameters
+                .as_ref()
+                .ok_or_else(|| anyhow!("Missing EC parameters"))?;
+            let params = ECAlgoParameters::from_der(&params.to_der()?)?;
+            let point = spki
+                .subject_public_key
+                .as_bytes()
+                .ok_or_else(|| anyhow!("Invalid EC public key"))?;
+            verify_ecdsa(&params, point, &hash, signature)
+        }
+        SignatureAlgorithmIdentifier::Unknown(algo) => {
+            bail!("Unsupported signature algorithm {}", algo.algorithm)
+        }
+    }
+}
 
-        todo!()
+/// The X.509 certificates included in a [`SignedData`].
+fn certificates(signed_data: &SignedData) -> impl Iterator<Item = &Certificate> {
+    signed_data
+        .certificates
+        .iter()
+        .flat_map(|set| set.0.iter())
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(cert) => Some(cert),
+            _ => None,
+        })
+}
+
+/// The signer certificate identified by `sid`.
+fn signer_certificate<'a>(
+    signed_data: &'a SignedData,
+    sid: &SignerIdentifier,
+) -> Result<&'a Certificate> {
+    certificates(signed_data)
+        .find(|cert| match sid {
+            SignerIdentifier::IssuerAndSerialNumber(id) => {
+                cert.tbs_certificate.issuer == id.issuer
+                    && cert.tbs_certificate.serial_number == id.serial_number
+            }
+            SignerIdentifier::SubjectKeyIdentifier(ski) => cert
+                .tbs_certificate
+                .get::<SubjectKeyIdentifier>()
+                .ok()
+                .flatten()
+                .is_some_and(|(_, id)| id == *ski),
+        })
+        .ok_or_else(|| anyhow!("Signer certificate not found"))
+}
+
+fn verify_rsa(
+    public_key: SubjectPublicKeyInfo,
+    hash: &[u8],
+    signature: &[u8],
+    algorithm: &SignatureAlgorithmIdentifier,
+) -> Result<()> {
+    let SubjectPublicKeyInfo::Rsa(key) = &public_key else {
+        bail!("Document signer key is not an RSA key");
+    };
+    let bits = key.modulus_bits();
+    macro_rules! verify_with {
+        ($uint:ty) => {{
+            let key = RSAPublicKey::<$uint>::try_from(public_key)?;
+            let message = key
+                .ring
+                .from(<$uint>::try_from_be_slice(hash).ok_or_else(|| anyhow!("Hash too large"))?);
+            // RFC 8017 8.2.2 step 1 and RSAVP1: exactly k bytes and less than
+            // the modulus.
+            ensure!(
+                signature.len() == bits.div_ceil(8),
+                "Signature length does not match modulus"
+            );
+            let signature = <$uint>::try_from_be_slice(signature)
+                .ok_or_else(|| anyhow!("Signature too large"))?;
+            ensure!(signature < key.ring.modulus(), "Signature out of range");
+            let signature = key.ring.from(signature);
+            key.verify(message, signature, algorithm)
+        }};
+    }
+    match bits {
+        0..=1024 => verify_with!(Uint<1024, 16>),
+        1025..=2048 => verify_with!(Uint<2048, 32>),
+        2049..=3072 => verify_with!(Uint<3072, 48>),
+        3073..=4096 => verify_with!(Uint<4096, 64>),
+        _ => bail!("Unsupported RSA modulus size: {bits} bits"),
     }
 }
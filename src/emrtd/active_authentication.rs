@@ -0,0 +1,188 @@
+use {
+    super::{Emrtd, Error},
+    crate::{
+        asn1::{
+            emrtd::{security_info::ActiveAuthenticationInfo, EfDg14, EfDg15},
+            public_key_info::SubjectPublicKeyInfo,
+            signature_algorithm_identifier::EcdsaSigValue,
+        },
+        crypto::{self, RSAPublicKey},
+    },
+    anyhow::{anyhow, bail, ensure, Result},
+    der::Encode,
+    rand::Rng,
+    ruint::Uint,
+};
+
+impl Emrtd {
+    /// Perform Active Authentication.
+    ///
+    /// If EF.DG14 contains an [`ActiveAuthenticationInfo`] the signature is
+    /// verified as ECDSA using its signature algorithm, otherwise the key in
+    /// EF.DG15 is assumed to be RSA with ISO/IEC 9796-2 signatures.
+    ///
+    /// See ICAO 9303-11 section 6.1.
+    pub fn active_authenticate(&mut self, rng: &mut impl Rng) -> Result<()> {
+        let ef_dg15 = self.read_cached::<EfDg15>()?;
+
+        // EF.DG14 is optional when Active Authentication uses RSA.
+        let aa_info = match self.read_cached::<EfDg14>() {
+            Ok(ef_dg14) => ef_dg14.active_authentication().cloned(),
+            Err(Error::FileNotFound) => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        // INTERNAL AUTHENTICATE
+        let challenge: [u8; 8] = rng.gen();
+        let signature = self.internal_authenticate(&challenge)?;
+
+        match aa_info {
+            Some(info) => verify_ecdsa(&info, ef_dg15.0, &signature, &challenge),
+            None => verify_rsa(ef_dg15.0, &signature, &challenge),
+        }
+    }
+
+    /// Send a challenge to the chip to be signed by the Active Authentication
+    /// private key.
+    ///
+    /// See ICAO 9303-11 section 6.1.3.
+    pub fn internal_authenticate(&mut self, challenge: &[u8]) -> Result<Vec<u8>> {
+        ensure!(challenge.len() == 8);
+        let mut apdu = vec![0x00, 0x88, 0x00, 0x00, 0x08];
+        apdu.extend_from_slice(challenge);
+        apdu.push(0x00);
+        let (status, data) = self.send_apdu(&apdu)?;
+        ensure!(
+            status.is_success(),
+            "Failed to internal authenticate: {status}"
+        );
+        Ok(data)
+    }
+}
+
+/// Verify a plain format ECDSA signature over the challenge, see ICAO 9303-11
+/// section 6.1.2 and BSI TR-03111 section 5.2.1.
+fn verify_ecdsa(
+    info: &ActiveAuthenticationInfo,
+    public_key: SubjectPublicKeyInfo,
+    signature: &[u8],
+    challenge: &[u8],
+) -> Result<()> {
+    ensure!(
+        info.version == 1,
+        "Unsupported Active Authentication version"
+    );
+    let digest = info.digest_algorithm().ok_or_else(|| {
+        anyhow!(
+            "Unsupported signature algorithm {}",
+            info.signature_algorithm
+        )
+    })?;
+    let SubjectPublicKeyInfo::Ec(key) = &public_key else {
+        bail!("Active Authentication key is not an EC key");
+    };
+
+    // The plain format is `r || s` with both of equal length.
+    ensure!(
+        !signature.is_empty() && signature.len().is_multiple_of(2),
+        "Invalid plain ECDSA signature length"
+    );
+    let (r, s) = signature.split_at(signature.len() / 2);
+    let signature = EcdsaSigValue {
+        r: der::asn1::Uint::new(r)?,
+        s: der::asn1::Uint::new(s)?,
+    }
+    .to_der()?;
+    crypto::verify_ecdsa(
+        &key.parameters,
+        key.point.as_bytes(),
+        &digest.hash_bytes(challenge),
+        &signature,
+    )
+}
+
+fn verify_rsa(public_key: SubjectPublicKeyInfo, signature: &[u8], challenge: &[u8]) -> Result<()> {
+    let SubjectPublicKeyInfo::Rsa(key) = &public_key else {
+        bail!("Active Authentication key is not an RSA key");
+    };
+    let bits = key.modulus_bits();
+    match bits {
+        0..=1024 => RSAPublicKey::<Uint<1024, 16>>::try_from(public_key)?
+            .verify_iso9796_2(signature, challenge),
+        1025..=2048 => RSAPublicKey::<Uint<2048, 32>>::try_from(public_key)?
+            .verify_iso9796_2(signature, challenge),
+        2049..=3072 => RSAPublicKey::<Uint<3072, 48>>::try_from(public_key)?
+            .verify_iso9796_2(signature, challenge),
+        3073..=4096 => RSAPublicKey::<Uint<4096, 64>>::try_from(public_key)?
+            .verify_iso9796_2(signature, challenge),
+        _ => bail!("RSA modulus too large: {bits} bits"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::asn1::emrtd::security_info::ID_ACTIVE_AUTHENTICATION,
+        der::{asn1::ObjectIdentifier as Oid, Decode},
+        hex_literal::hex,
+    };
+
+    const CHALLENGE: [u8; 8] = hex!("0102030405060708");
+
+    fn ecdsa_sha256() -> ActiveAuthenticationInfo {
+        ActiveAuthenticationInfo {
+            protocol:            ID_ACTIVE_AUTHENTICATION,
+            version:             1,
+            signature_algorithm: Oid::new_unwrap("0.4.0.127.0.7.1.1.4.1.3"),
+        }
+    }
+
+    #[test]
+    fn test_verify_ecdsa() -> Result<()> {
+        // brainpoolP256r1 key and plain SHA-256 signature over the challenge.
+        let public_key = SubjectPublicKeyInfo::from_der(&hex!(
+            "305a301406072a8648ce3d020106092b240303020801010703420004407b31f59521f20165eb7c9c
+             641cd92ebdc43c7b0c1e83ff1cc75c3816ad8b3970e44d3b6ecc63e6f5e0a9d58402f13c8cf2621b
+             6edfc79673b3ed03b1209511"
+        ))?;
+        let mut signature = hex!(
+            "7bebc2fd1c0b1cf657c8264ddec4586ebf86504a77b62d83990a3c36ba6fa594
+             624240b617c8e22aaf580c650e21d684e2e4f052ad1ac22947ba93a141d665aa"
+        );
+        let info = ecdsa_sha256();
+        verify_ecdsa(&info, public_key.clone(), &signature, &CHALLENGE)?;
+
+        // Wrong challenge, hash or signature.
+        assert!(verify_ecdsa(
+            &info,
+            public_key.clone(),
+            &signature,
+            &hex!("0102030405060709")
+        )
+        .is_err());
+        let sha1 = ActiveAuthenticationInfo {
+            signature_algorithm: Oid::new_unwrap("0.4.0.127.0.7.1.1.4.1.1"),
+            ..info
+        };
+        assert!(verify_ecdsa(&sha1, public_key.clone(), &signature, &CHALLENGE).is_err());
+        assert!(verify_ecdsa(&info, public_key.clone(), &signature[1..], &CHALLENGE).is_err());
+        signature[40] ^= 1;
+        assert!(verify_ecdsa(&info, public_key, &signature, &CHALLENGE).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_rsa() -> Result<()> {
+        // Same key and signature as the ISO 9796-2 test in `crypto::rsa`.
+        let dg15 = include_bytes!("../../tests/dataset/Datagroup15.bin");
+        let public_key = SubjectPublicKeyInfo::from_der(&dg15[3..])?;
+        let signature = hex!("5c1b1e4b1cdaf9099553088825edf286821efa0f53d78fb5b0c39f0763f103b7c074812a0b019842e035ae9efcb0eb6864af876ed4f68b9e69e6af6a0820c728f1a3ea1ff9e8308f7af0014df205df6b5c81fb55e23474680abd2b500f297fe87424df9e8eff11bfe7d5df249e27bf762567fe9299e0930e8da26507ce733432");
+        verify_rsa(public_key.clone(), &signature, &CHALLENGE)?;
+        assert!(verify_rsa(public_key.clone(), &signature, &hex!("0102030405060709")).is_err());
+
+        // RSA keys are not accepted for ECDSA and vice versa.
+        assert!(verify_ecdsa(&ecdsa_sha256(), public_key, &signature, &CHALLENGE).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! Asynchronous eMRTD access.
+
+use {
+    super::{
+        exchange_apdu,
+        secure_messaging::{PlainText, SecureMessaging},
+        Result,
+    },
+    crate::{iso7816::StatusWord, nfc::AsyncNfcReader},
+};
+
+/// Asynchronous version of [`super::Emrtd`].
+///
+/// Unlike [`super::Emrtd`] this is `Send + Sync`, so it can be held across
+/// `.await` points on a multi-threaded runtime.
+pub struct AsyncEmrtd<R: AsyncNfcReader> {
+    /// NFC Reader connected to card.
+    nfc: R,
+
+    /// Current Secure Messaging cipher.
+    secure_messaging: Box<dyn SecureMessaging + Send + Sync>,
+}
+
+impl<R: AsyncNfcReader> AsyncEmrtd<R> {
+    pub fn new(nfc: R) -> Self {
+        Self {
+            nfc,
+            secure_messaging: Box::new(PlainText),
+        }
+    }
+
+    pub fn set_secure_messaging(
+        &mut self,
+        secure_messaging: Box<dyn SecureMessaging + Send + Sync>,
+    ) {
+        self.secure_messaging = secure_messaging;
+    }
+
+    /// Drop the secure messaging session, the chip reverts to plain APDUs.
+    fn reset_secure_messaging(&mut self) {
+        self.secure_messaging = Box::new(PlainText);
+    }
+
+    pub async fn send_apdu(&mut self, apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)> {
+        exchange_apdu!(self, apdu, |protected| self.nfc.send_apdu(&protected).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            emrtd::Error,
+            nfc::{mock::MockReader, BlockingNfcReader},
+        },
+        tokio::runtime::Builder,
+    };
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<AsyncEmrtd<BlockingNfcReader<MockReader>>>();
+    }
+
+    #[test]
+    fn test_send_apdu() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let reader = BlockingNfcReader::new(MockReader::new(vec![
+                (vec![0x00, 0x84, 0x00, 0x00], (StatusWord::SUCCESS, vec![])),
+                (
+                    vec![0x00, 0x84, 0x00, 0x00],
+                    (StatusWord::FILE_NOT_FOUND, vec![]),
+                ),
+            ]));
+            let mut emrtd = AsyncEmrtd::new(reader);
+            let (status, data) = emrtd.send_apdu(&[0x00, 0x84, 0x00, 0x00]).await.unwrap();
+            assert_eq!(status, StatusWord::SUCCESS);
+            assert!(data.is_empty());
+
+            let err = emrtd.send_apdu(&[0x00, 0x84, 0x00, 0x00]).await;
+            assert!(matches!(
+                err,
+                Err(Error::ErrorResponse(StatusWord::FILE_NOT_FOUND))
+            ));
+        });
+    }
+
+    #[test]
+    fn test_wrong_le() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let reader = BlockingNfcReader::new(MockReader::new(vec![
+                (
+                    vec![0x00, 0xb0, 0x00, 0x00, 0x00],
+                    (StatusWord::from(0x6c20), vec![]),
+                ),
+                (
+                    vec![0x00, 0xb0, 0x00, 0x00, 0x20],
+                    (StatusWord::SUCCESS, vec![0x42; 0x20]),
+                ),
+            ]));
+            let mut emrtd = AsyncEmrtd::new(reader);
+            let (status, data) = emrtd
+                .send_apdu(&[0x00, 0xb0, 0x00, 0x00, 0x00])
+                .await
+                .unwrap();
+            assert_eq!(status, StatusWord::SUCCESS);
+            assert_eq!(data, [0x42; 0x20]);
+        });
+    }
+}
@@ -1,12 +1,15 @@
 use {
     super::{
-        pad,
+        detect_key_information,
+        files::{DedicatedId, EMRTD_LDS1_AID},
         secure_messaging::{tdes::TDesCipher, Cipher, Encrypted},
-        seed_from_mrz, Emrtd,
+        seed_from_mrz, validate_mrz, Emrtd,
     },
+    crate::iso7816::pad_iso7816_2,
     anyhow::{anyhow, ensure, Result},
     rand::Rng,
     std::array,
+    zeroize::Zeroizing,
 };
 
 impl Emrtd {
@@ -35,15 +38,28 @@ impl Emrtd {
         Ok(data)
     }
 
+    /// Perform Basic Access Control.
+    ///
+    /// The `mrz` can be a full TD1, TD2 or TD3 MRZ or just the MRZ information
+    /// used for key derivation.
     pub fn basic_access_control(&mut self, rng: &mut impl Rng, mrz: &str) -> Result<()> {
+        validate_mrz(mrz)?;
+        let access_key = Zeroizing::new(mrz.to_owned());
+        let mrz = Zeroizing::new(detect_key_information(mrz)?);
+
         // Compute local randomness
         let rnd_ifd: [u8; 8] = rng.gen();
         let k_ifd: [u8; 16] = rng.gen();
 
         // Compute encryption / authentication keys from MRZ
-        let seed = seed_from_mrz(mrz);
+        let seed = seed_from_mrz(&mrz);
         let cipher = TDesCipher::from_seed(&seed);
 
+        // BAC keys belong to the eMRTD application, see ICAO 9303-10 section 3.6.
+        if self.parent != DedicatedId::EmrtdLds1 {
+            self.select_dedicated_file(EMRTD_LDS1_AID)?;
+        }
+
         // GET CHALLENGE
         let rnd_ic = self.get_challenge()?;
 
@@ -53,8 +69,7 @@ impl Emrtd {
         msg.extend_from_slice(&rnd_ic);
         msg.extend_from_slice(&k_ifd);
         cipher.enc(0, &mut msg);
-        let mut msg_mac = msg.clone();
-        pad(&mut msg_mac, cipher.block_size());
+        let msg_mac = pad_iso7816_2(&msg, cipher.block_size());
         msg.extend(cipher.mac(0, &msg_mac));
 
         // EXTERNAL AUTHENTICATE
@@ -62,8 +77,7 @@ impl Emrtd {
         ensure!(resp_data.len() == 40);
 
         // Check MAC and decrypt response
-        let mut msg_mac = resp_data[..32].to_vec();
-        pad(&mut msg_mac, cipher.block_size());
+        let msg_mac = pad_iso7816_2(&resp_data[..32], cipher.block_size());
         let mac = cipher.mac(0, &msg_mac);
         ensure!(&resp_data[32..] == &mac[..]);
         cipher.dec(0, &mut resp_data[..32]);
@@ -78,16 +92,76 @@ impl Emrtd {
         let seed: [u8; 16] = array::from_fn(|i| k_ifd[i] ^ k_ic[i]);
 
         // Construct initial send sequence counter
-        // See ICAO 9303-10 section 9.8.6.3
-        let mut ssc_bytes = vec![];
-        ssc_bytes.extend_from_slice(&rnd_ic[4..]);
-        ssc_bytes.extend_from_slice(&rnd_ifd[4..]);
-        let ssc: u64 = u64::from_be_bytes(ssc_bytes[..8].try_into().unwrap());
+        let ssc = send_sequence_counter(&rnd_ic, &rnd_ifd);
 
         // Add TDES session keys to secure messaging
         let tdes = Encrypted::new(TDesCipher::from_seed(&seed), ssc);
-        self.secure_messaging = Box::new(tdes);
+        self.set_secure_messaging(Box::new(tdes));
+        self.access_key = Some(access_key);
+        self.bac_session = true;
 
         Ok(())
     }
+
+    /// Re-establish secure messaging using the access key of the last
+    /// successful Basic Access Control.
+    pub fn reauthenticate(&mut self, rng: &mut impl Rng) -> Result<()> {
+        let mrz = self
+            .access_key
+            .clone()
+            .ok_or_else(|| anyhow!("No previous Basic Access Control"))?;
+        self.basic_access_control(rng, &mrz)
+    }
+}
+
+/// Initial send sequence counter from the least significant four bytes of
+/// `RND.IC` and `RND.IFD`.
+///
+/// See ICAO 9303-11 section 9.8.6.3.
+pub fn send_sequence_counter(rnd_ic: &[u8], rnd_ifd: &[u8]) -> u64 {
+    let mut ssc_bytes = [0; 8];
+    ssc_bytes[..4].copy_from_slice(&rnd_ic[4..8]);
+    ssc_bytes[4..].copy_from_slice(&rnd_ifd[4..8]);
+    u64::from_be_bytes(ssc_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{emrtd::Error, iso7816::StatusWord, nfc::mock::MockReader},
+        hex_literal::hex,
+    };
+
+    #[test]
+    fn test_selects_application() {
+        // The card refuses GET CHALLENGE, so BAC fails after it.
+        let reader = MockReader::new(vec![
+            (
+                hex!("00A4040C07A0000002471001").to_vec(),
+                (StatusWord::SUCCESS, vec![]),
+            ),
+            (
+                hex!("0084000008").to_vec(),
+                (StatusWord::ACCESS_DENIED, vec![]),
+            ),
+            // The application stays selected for the second attempt.
+            (
+                hex!("0084000008").to_vec(),
+                (StatusWord::ACCESS_DENIED, vec![]),
+            ),
+        ]);
+        let mut emrtd = Emrtd::new(Box::new(reader));
+        let mrz = "L898902C<369080619406236";
+
+        for _ in 0..2 {
+            let error = emrtd
+                .basic_access_control(&mut rand::thread_rng(), mrz)
+                .unwrap_err();
+            assert!(matches!(
+                error.downcast_ref(),
+                Some(Error::ErrorResponse(StatusWord::ACCESS_DENIED))
+            ));
+        }
+    }
 }
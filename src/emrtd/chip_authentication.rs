@@ -1,27 +1,39 @@
 use {
-    super::Emrtd,
+    super::{authentication_token, pace::push_length, Emrtd},
     crate::{
-        asn1::emrtd::{security_info::SymmetricCipher, EfDg14},
-        emrtd::secure_messaging::construct_secure_messaging,
+        asn1::emrtd::{
+            security_info::{ChipAuthenticationProtocol, SymmetricCipher},
+            tlv::read_tlv,
+            EfDg14,
+        },
+        emrtd::secure_messaging::{
+            aes::{kdf_128, kdf_192, kdf_256},
+            construct_secure_messaging, tdes, SecureMessaging, KDF_MAC,
+        },
+        iso7816::StatusWord,
     },
-    anyhow::{ensure, Result},
+    anyhow::{anyhow, bail, ensure, Result},
     der::asn1::ObjectIdentifier as Oid,
     rand::{CryptoRng, RngCore},
+    subtle::ConstantTimeEq,
+    tracing::debug,
 };
 
 impl Emrtd {
+    /// Perform Chip Authentication.
+    ///
+    /// Version 1 is described in ICAO-9303-11 section 6.2, version 2 in BSI
+    /// TR-03110-2 section 3.4.
     pub fn chip_authenticate(&mut self, mut rng: impl CryptoRng + RngCore) -> Result<()> {
-        // TODO: Some passports only have ChipAuthenticationPublicKeyInfo but no
-        // ChipAuthenticationInfo. In this case, CA_(EC)DH_3DES_CBC_CBC should be
-        // assumed.
-
         // Read EF.DG14
         let ef_dg14 = self.read_cached::<EfDg14>()?;
-        dbg!(&ef_dg14);
 
         // Find the Chip Authentication Info in DG14
-        let (ca, pk) = ef_dg14.chip_authentication().unwrap();
-        println!("Using algorithm: {}", ca.protocol);
+        let (ca, pk) = ef_dg14
+            .chip_authentication()
+            .ok_or_else(|| anyhow!("No supported Chip Authentication in EF.DG14"))?;
+        let key_id = pk.key_id;
+        debug!("Using algorithm: {}", ca.protocol.name());
 
         let (algo, card_public_key) = pk.public_key.to_algorithm_public_key()?;
 
@@ -31,66 +43,376 @@ impl Emrtd {
         // Compute shared secret
         let shared_secret = algo.key_agreement(&private_key, &card_public_key)?;
 
-        // Initiate Chip Authentication
-        // ICAO-9303-11 section 6.2
-        // 2. The terminal sends the public key to the eMRTD.
-        //
-        // For AES we need to use 6.2.4.2
-
-        // Send MSE Set AT to select the Chip Authentication protocol.
-        self.mset_at(ca.protocol.into(), pk.key_id)?;
-
-        // Send the public key using general authenticate
-        let data = self.general_authenticate(public_key.as_ref())?;
-        println!("==> General Authenticate: {}", hex::encode(data));
+        let cipher = ca.protocol.cipher.unwrap_or(SymmetricCipher::Tdes);
+        let secure_messaging = match ca.version {
+            1 => {
+                // ICAO-9303-11 section 6.2.4. 3DES uses MSE:Set KAT, AES uses
+                // MSE:Set AT followed by General Authenticate.
+                if cipher == SymmetricCipher::Tdes {
+                    self.mse_set_kat(public_key.as_ref(), key_id)?;
+                } else {
+                    self.mset_at(ca.protocol.into(), key_id)?;
+                    self.general_authenticate(public_key.as_ref())?;
+                }
+                construct_secure_messaging(cipher, &shared_secret, 0)
+            }
+            2 => {
+                // BSI TR-03110-2 section 3.4.1. The chip responds with a nonce
+                // and an authentication token over our ephemeral public key.
+                self.mset_at(ca.protocol.into(), key_id)?;
+                let data = self.general_authenticate(public_key.as_ref())?;
+                let (nonce, token) = parse_chip_authentication_response(&data)?;
+                chip_authentication_v2_session(
+                    ca.protocol,
+                    &shared_secret,
+                    nonce,
+                    token,
+                    public_key.as_ref(),
+                )?
+            }
+            version => bail!("Unsupported Chip Authentication version {version}"),
+        };
 
         // Keys should now have been changed.
-        let cipher = SymmetricCipher::Aes256;
-        self.set_secure_messaging(construct_secure_messaging(cipher, &shared_secret, 0));
+        self.set_secure_messaging(secure_messaging);
 
         Ok(())
     }
 
-    pub fn mset_at(&mut self, protocol: Oid, key_id: Option<u64>) -> Result<()> {
-        // Send MSE Set AT to select the Chip Authentication protocol.
-        let mut apdu = vec![0x00, 0x22, 0x41, 0xa4];
-        apdu.push(0x00); // Placeholder length
-
-        // Cryptographic mechanism: 0x80 <len> <OID>
-        let protocol = protocol.as_bytes();
-        apdu.push(0x80);
-        apdu.push(protocol.len().try_into()?);
-        apdu.extend_from_slice(protocol);
+    /// MSE:Set KAT, used for Chip Authentication with 3DES.
+    ///
+    /// See ICAO-9303-11 section 6.2.4.1.
+    pub fn mse_set_kat(&mut self, public_key: &[u8], key_id: Option<u64>) -> Result<()> {
+        // Ephemeral public key: 0x91 <len> <key>
+        let mut data = vec![0x91];
+        push_length(&mut data, public_key.len());
+        data.extend_from_slice(public_key);
 
-        // If the pivate key to be used has a reference, include it.
         if let Some(id) = key_id {
-            apdu.push(0x84);
-            apdu.push(0x01); // Assume id < 256
-            apdu.push(id.try_into()?);
+            push_key_reference(&mut data, id);
         }
 
-        // Update length
-        apdu[4] = (apdu.len() - 5).try_into()?;
+        let apdu = command_apdu([0x00, 0x22, 0x41, 0xa6], &data)?;
+        let (status, data) = self.send_apdu(&apdu)?;
+        check_status("MSE:Set KAT", status)?;
+        ensure!(data.is_empty());
+        Ok(())
+    }
+
+    pub fn mset_at(&mut self, protocol: Oid, key_id: Option<u64>) -> Result<()> {
+        // Send MSE Set AT to select the Chip Authentication protocol.
+        let apdu = mse_set_at_apdu(protocol, key_id)?;
 
         // Send MSE Set AT command to chip
         let (status, data) = self.send_apdu(&apdu)?;
-        ensure!(status.is_success());
+        check_status("MSE:Set AT", status)?;
         ensure!(data.is_empty());
         Ok(())
     }
 
     pub fn general_authenticate(&mut self, public_key: &[u8]) -> Result<Vec<u8>> {
-        // Send General Authenticate command to chip
-        let mut apdu = vec![0x00, 0x86, 0x00, 0x00];
-        apdu.push((public_key.len() + 4).try_into()?);
-        apdu.push(0x7c);
-        apdu.push((public_key.len() + 2).try_into()?);
-        apdu.push(0x80);
-        apdu.push(public_key.len().try_into()?);
-        apdu.extend_from_slice(public_key);
+        // Dynamic Authentication Data: 0x7C { 0x80 <key> }
+        let mut object = vec![0x80];
+        push_length(&mut object, public_key.len());
+        object.extend_from_slice(public_key);
+        let mut data = vec![0x7c];
+        push_length(&mut data, object.len());
+        data.extend_from_slice(&object);
 
+        // Send General Authenticate command to chip, the response data is
+        // only returned if Le is present.
+        let apdu = command_apdu_with_le([0x00, 0x86, 0x00, 0x00], &data)?;
         let (status, data) = self.send_apdu(&apdu)?;
-        ensure!(status.is_success());
+        check_status("General Authenticate", status)?;
         Ok(data)
     }
 }
+
+/// Errors if a command with response data did not succeed.
+///
+/// Error statuses without data are already returned as
+/// [`Error::ErrorResponse`](super::Error::ErrorResponse) by
+/// [`Emrtd::send_apdu`].
+fn check_status(command: &str, status: StatusWord) -> Result<()> {
+    ensure!(status.is_success(), "{command} failed: {status}");
+    Ok(())
+}
+
+/// MSE:Set AT command selecting `protocol` and optionally a private key.
+fn mse_set_at_apdu(protocol: Oid, key_id: Option<u64>) -> Result<Vec<u8>> {
+    // Cryptographic mechanism: 0x80 <len> <OID>
+    let protocol = protocol.as_bytes();
+    let mut data = vec![0x80];
+    push_length(&mut data, protocol.len());
+    data.extend_from_slice(protocol);
+
+    // If the private key to be used has a reference, include it.
+    if let Some(id) = key_id {
+        push_key_reference(&mut data, id);
+    }
+    command_apdu([0x00, 0x22, 0x41, 0xa4], &data)
+}
+
+/// Private key reference `0x84 <len> <id>`, with `id` as a big-endian
+/// integer of as few bytes as possible.
+fn push_key_reference(data: &mut Vec<u8>, id: u64) {
+    let bytes = id.to_be_bytes();
+    let start = bytes
+        .iter()
+        .position(|&b| b != 0)
+        .unwrap_or(bytes.len() - 1);
+    data.push(0x84);
+    push_length(data, bytes.len() - start);
+    data.extend_from_slice(&bytes[start..]);
+}
+
+/// Command APDU without Le, using an extended length Lc for DH public keys
+/// that do not fit in a short APDU.
+pub(super) fn command_apdu(header: [u8; 4], data: &[u8]) -> Result<Vec<u8>> {
+    let mut apdu = header.to_vec();
+    if let Ok(lc) = u8::try_from(data.len()) {
+        apdu.push(lc);
+    } else {
+        apdu.push(0x00);
+        apdu.extend_from_slice(&u16::try_from(data.len())?.to_be_bytes());
+    }
+    apdu.extend_from_slice(data);
+    Ok(apdu)
+}
+
+/// Command APDU with Le requesting all available response data, `00` for a
+/// short APDU and `0000` for an extended length APDU.
+pub(super) fn command_apdu_with_le(header: [u8; 4], data: &[u8]) -> Result<Vec<u8>> {
+    let mut apdu = command_apdu(header, data)?;
+    if data.len() > 0xff {
+        apdu.extend_from_slice(&[0x00, 0x00]);
+    } else {
+        apdu.push(0x00);
+    }
+    Ok(apdu)
+}
+
+/// Data objects in the dynamic authentication data of a General
+/// Authenticate response.
+///
+/// See BSI TR-03110-3 section B.11.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GeneralAuthenticateResponse<'a> {
+    /// `0x80`: Encrypted nonce (PACE).
+    pub encrypted_nonce: Option<&'a [u8]>,
+    /// `0x81`: Nonce (Chip Authentication version 2).
+    pub nonce:           Option<&'a [u8]>,
+    /// `0x82`: Authentication token (Chip Authentication version 2) or
+    /// mapping data (PACE).
+    pub token:           Option<&'a [u8]>,
+    /// `0x84`: Ephemeral public key (PACE).
+    pub public_key:      Option<&'a [u8]>,
+    /// `0x86`: Authentication token (PACE).
+    pub pace_token:      Option<&'a [u8]>,
+}
+
+/// Parse the `7C` dynamic authentication data of a General Authenticate
+/// response.
+pub fn parse_general_authenticate_response(
+    mut data: &[u8],
+) -> Result<GeneralAuthenticateResponse<'_>> {
+    let (tag, mut rest, _) = read_tlv(&mut data)?;
+    ensure!(
+        tag == 0x7c && data.is_empty(),
+        "Invalid General Authenticate response"
+    );
+    let mut response = GeneralAuthenticateResponse::default();
+    while !rest.is_empty() {
+        let (tag, value, _) = read_tlv(&mut rest)?;
+        let field = match tag {
+            0x80 => &mut response.encrypted_nonce,
+            0x81 => &mut response.nonce,
+            0x82 => &mut response.token,
+            0x84 => &mut response.public_key,
+            0x86 => &mut response.pace_token,
+            _ => bail!("Unexpected tag {tag:02X} in General Authenticate response"),
+        };
+        ensure!(
+            field.replace(value).is_none(),
+            "Duplicate tag {tag:02X} in General Authenticate response"
+        );
+    }
+    Ok(response)
+}
+
+/// Parse the Chip Authentication version 2 General Authenticate response
+/// `7C { 81 nonce, 82 token }` into the nonce and authentication token.
+///
+/// See BSI TR-03110-3 section B.11.5.
+pub fn parse_chip_authentication_response(data: &[u8]) -> Result<(&[u8], &[u8])> {
+    let response = parse_general_authenticate_response(data)?;
+    Ok((
+        response
+            .nonce
+            .ok_or_else(|| anyhow!("Missing Chip Authentication nonce"))?,
+        response
+            .token
+            .ok_or_else(|| anyhow!("Missing Chip Authentication token"))?,
+    ))
+}
+
+/// Derive the Chip Authentication version 2 session and verify the chip's
+/// authentication token.
+///
+/// Session keys are derived from `shared_secret || nonce` and the token is a
+/// MAC over the terminal's ephemeral public key.
+///
+/// See BSI TR-03110-2 section 3.4.1.
+pub fn chip_authentication_v2_session(
+    protocol: ChipAuthenticationProtocol,
+    shared_secret: &[u8],
+    nonce: &[u8],
+    token: &[u8],
+    terminal_public_key: &[u8],
+) -> Result<Box<dyn SecureMessaging>> {
+    let cipher = protocol
+        .cipher
+        .ok_or_else(|| anyhow!("Chip Authentication protocol without cipher"))?;
+    let seed = [shared_secret, nonce].concat();
+    let k_mac = match cipher {
+        SymmetricCipher::Tdes => tdes::kdf(&seed, KDF_MAC).to_vec(),
+        SymmetricCipher::Aes128 => kdf_128(&seed, KDF_MAC).to_vec(),
+        SymmetricCipher::Aes192 => kdf_192(&seed, KDF_MAC).to_vec(),
+        SymmetricCipher::Aes256 => kdf_256(&seed, KDF_MAC).to_vec(),
+    };
+    let expected = authentication_token(
+        cipher,
+        &k_mac,
+        protocol.into(),
+        protocol.key_agreement,
+        terminal_public_key,
+    )?;
+    ensure!(
+        bool::from(expected.as_slice().ct_eq(token)),
+        "Chip Authentication token mismatch"
+    );
+    Ok(construct_secure_messaging(cipher, &seed, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{iso7816::parse_apdu, nfc::mock::MockReader},
+        hex_literal::hex,
+    };
+
+    #[test]
+    fn test_error_status() {
+        let protocol = Oid::new_unwrap("0.4.0.127.0.7.2.2.3.2.2");
+        let apdu = mse_set_at_apdu(protocol, None).unwrap();
+        let reader = MockReader::new(
+            [0x6983, 0x6982, 0x6988, 0x6a80]
+                .map(|sw| (apdu.clone(), (StatusWord::from(sw), vec![])))
+                .to_vec(),
+        );
+        let mut card = Emrtd::new(Box::new(reader));
+        let mut message = || card.mset_at(protocol, None).unwrap_err().to_string();
+        assert!(message().ends_with("the authentication method is blocked"));
+        assert!(message().ends_with("the session must be re-established"));
+        assert!(message().ends_with("the session must be re-established."));
+        assert_eq!(
+            message(),
+            "Response Status: [6A80] Checking error: Incorrect parameters in the data field"
+        );
+    }
+
+    #[test]
+    fn test_mse_set_at_apdu() {
+        let protocol = Oid::new_unwrap("0.4.0.127.0.7.2.2.3.2.2");
+        assert_eq!(
+            mse_set_at_apdu(protocol, None).unwrap(),
+            hex!("00 22 41 A4 0C 80 0A 04007F00070202030202")
+        );
+        assert_eq!(
+            mse_set_at_apdu(protocol, Some(0)).unwrap(),
+            hex!("00 22 41 A4 0F 80 0A 04007F00070202030202 84 01 00")
+        );
+        assert_eq!(
+            mse_set_at_apdu(protocol, Some(256)).unwrap(),
+            hex!("00 22 41 A4 10 80 0A 04007F00070202030202 84 02 0100")
+        );
+    }
+
+    #[test]
+    fn test_parse_general_authenticate_response() {
+        let response =
+            parse_general_authenticate_response(&hex!("7C 06 81 01 AA 82 01 BB")).unwrap();
+        assert_eq!(response, GeneralAuthenticateResponse {
+            nonce: Some(&[0xaa]),
+            token: Some(&[0xbb]),
+            ..Default::default()
+        });
+
+        // Long form lengths, as used for ephemeral public keys.
+        let mut data = vec![0x7c, 0x81, 0x84, 0x84, 0x81, 0x81];
+        data.extend_from_slice(&[0x04; 0x81]);
+        let response = parse_general_authenticate_response(&data).unwrap();
+        assert_eq!(response.public_key, Some(&[0x04; 0x81][..]));
+
+        assert_eq!(
+            parse_general_authenticate_response(&hex!("7C 00")).unwrap(),
+            GeneralAuthenticateResponse::default()
+        );
+        assert!(parse_general_authenticate_response(&hex!("")).is_err());
+        assert!(parse_general_authenticate_response(&hex!("7C 03 80 01")).is_err());
+        assert!(parse_general_authenticate_response(&hex!("7C 03 80 02 AA")).is_err());
+        assert!(parse_general_authenticate_response(&hex!("7C 03 83 01 AA")).is_err());
+        assert!(parse_general_authenticate_response(&hex!("7C 06 80 01 AA 80 01 BB")).is_err());
+        assert!(parse_general_authenticate_response(&hex!("7D 00")).is_err());
+        assert!(parse_general_authenticate_response(&hex!("7C 00 00")).is_err());
+        assert!(parse_general_authenticate_response(&hex!("7C 84 00000000")).is_err());
+    }
+
+    #[test]
+    fn test_command_apdu() {
+        assert_eq!(
+            command_apdu([0x00, 0x86, 0x00, 0x00], &[0x42; 3]).unwrap(),
+            hex!("00 86 00 00 03 424242")
+        );
+        let apdu = command_apdu([0x00, 0x86, 0x00, 0x00], &[0x42; 256]).unwrap();
+        assert_eq!(apdu[..7], hex!("00 86 00 00 000100"));
+        assert_eq!(apdu.len(), 7 + 256);
+    }
+
+    #[test]
+    fn test_command_apdu_with_le() {
+        let apdu = command_apdu_with_le([0x00, 0x86, 0x00, 0x00], &[0x42; 3]).unwrap();
+        assert_eq!(apdu, hex!("00 86 00 00 03 424242 00"));
+        assert_eq!(parse_apdu(&apdu).unwrap().le, [0x00]);
+
+        let apdu = command_apdu_with_le([0x00, 0x86, 0x00, 0x00], &[0x42; 256]).unwrap();
+        assert_eq!(apdu[..7], hex!("00 86 00 00 000100"));
+        assert_eq!(apdu[7 + 256..], hex!("0000"));
+        assert_eq!(parse_apdu(&apdu).unwrap().le, [0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_general_authenticate() {
+        // The chip only returns the nonce and token if Le is sent.
+        let reader = MockReader::new(vec![(
+            hex!("00 86 00 00 07 7C 05 80 03 04AABB 00").to_vec(),
+            (
+                StatusWord::SUCCESS,
+                hex!("7C 06 81 01 AA 82 01 BB").to_vec(),
+            ),
+        )]);
+        let mut card = Emrtd::new(Box::new(reader));
+        let data = card.general_authenticate(&hex!("04AABB")).unwrap();
+        assert_eq!(
+            parse_chip_authentication_response(&data).unwrap(),
+            (&[0xaa][..], &[0xbb][..])
+        );
+
+        // Under secure messaging Le is protected in DO'97.
+        let apdu = command_apdu_with_le([0x00, 0x86, 0x00, 0x00], &hex!("7C 05 80 03 04AABB"));
+        let mut sm = construct_secure_messaging(SymmetricCipher::Aes128, &[0; 32], 0);
+        let protected = sm.enc_apdu(&apdu.unwrap()).unwrap();
+        assert!(protected.windows(3).any(|w| w == hex!("97 01 00")));
+    }
+}
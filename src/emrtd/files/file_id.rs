@@ -158,7 +158,7 @@ impl FileId {
         }
     }
 
-    pub fn short_id(&self) -> u8 {
+    pub const fn short_id(&self) -> u8 {
         match self {
             Self::AttrInfo => 0x01,
             Self::Dir => 0x1e,
@@ -185,12 +185,81 @@ impl FileId {
         }
     }
 
-    pub fn tag(&self) -> Tag {
+    /// The ICAO assigned Short File Identifier for use in READ BINARY.
+    ///
+    /// Every file has one, including `EF.DIR` (`0x1E`) and `EF.ATR/INFO`
+    /// (`0x01`) in the master file.
+    ///
+    /// See ICAO 9303-10 section 3.6.3.2 and ISO 7816-4 section 7.2.3.
+    pub const fn sfi(&self) -> u8 {
+        self.short_id()
+    }
+
+    /// The data group number for `EF.DG1` to `EF.DG16`.
+    pub fn data_group_number(&self) -> Option<usize> {
         match self {
-            Self::Sod => 0x77.try_into().unwrap(),
-            _ => unimplemented!(),
+            Self::Dg1
+            | Self::Dg2
+            | Self::Dg3
+            | Self::Dg4
+            | Self::Dg5
+            | Self::Dg6
+            | Self::Dg7
+            | Self::Dg8
+            | Self::Dg9
+            | Self::Dg10
+            | Self::Dg11
+            | Self::Dg12
+            | Self::Dg13
+            | Self::Dg14
+            | Self::Dg15
+            | Self::Dg16 => Some(self.short_id().into()),
+            _ => None,
         }
     }
+
+    /// The ICAO application tag byte the file content starts with.
+    ///
+    /// Returns `None` for master file entries without an ICAO tag.
+    ///
+    /// See ICAO 9303-10 Table 15.
+    pub const fn aid_byte(&self) -> Option<u8> {
+        Some(match self {
+            Self::Com => 0x60,
+            Self::Dg1 => 0x61,
+            Self::Dg2 => 0x75,
+            Self::Dg3 => 0x63,
+            Self::Dg4 => 0x76,
+            Self::Dg5 => 0x65,
+            Self::Dg6 => 0x66,
+            Self::Dg7 => 0x67,
+            Self::Dg8 => 0x68,
+            Self::Dg9 => 0x69,
+            Self::Dg10 => 0x6a,
+            Self::Dg11 => 0x6b,
+            Self::Dg12 => 0x6c,
+            Self::Dg13 => 0x6d,
+            Self::Dg14 => 0x6e,
+            Self::Dg15 => 0x6f,
+            Self::Dg16 => 0x70,
+            Self::Sod => 0x77,
+            Self::CardAccess | Self::CardSecurity | Self::Dir | Self::AttrInfo => return None,
+        })
+    }
+
+    /// The two byte elementary file name used in SELECT.
+    ///
+    /// See ICAO 9303-10 Table 15.
+    pub fn ef_name(&self) -> [u8; 2] {
+        self.file_id().to_be_bytes()
+    }
+
+    /// The DER tag of the file content.
+    ///
+    /// Returns `None` for master file entries without an ICAO tag.
+    pub fn tag(&self) -> Option<Tag> {
+        self.aid_byte().and_then(|byte| byte.try_into().ok())
+    }
 }
 
 impl Display for FileId {
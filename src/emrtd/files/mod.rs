@@ -1,14 +1,18 @@
 mod file_id;
 
-pub use self::file_id::{DedicatedId, FileId};
+pub use self::file_id::{DedicatedId, FileId, EMRTD_LDS1_AID};
 use {
     super::{Emrtd, Error, Result},
     crate::{
-        asn1::emrtd::{EfCardAccess, EfDg14, EfSod},
+        asn1::emrtd::{
+            security_info::SecurityInfo, EfCardAccess, EfCardSecurity, EfCom, EfDg11, EfDg12,
+            EfDg14, EfDg15, EfDg2, EfSod,
+        },
         ensure_err,
         iso7816::StatusWord,
     },
     der::{Decode, ErrorKind, Reader, SliceReader},
+    rand::Rng,
     std::collections::HashMap,
 };
 
@@ -18,6 +22,10 @@ pub trait HasFileId {
     const FILE_ID: FileId;
 }
 
+impl HasFileId for EfCom {
+    const FILE_ID: FileId = FileId::Com;
+}
+
 impl HasFileId for EfSod {
     const FILE_ID: FileId = FileId::Sod;
 }
@@ -26,29 +34,122 @@ impl HasFileId for EfCardAccess {
     const FILE_ID: FileId = FileId::CardAccess;
 }
 
+impl HasFileId for EfCardSecurity {
+    const FILE_ID: FileId = FileId::CardSecurity;
+}
+
+impl HasFileId for EfDg2 {
+    const FILE_ID: FileId = FileId::Dg2;
+}
+
+impl HasFileId for EfDg11 {
+    const FILE_ID: FileId = FileId::Dg11;
+}
+
+impl HasFileId for EfDg12 {
+    const FILE_ID: FileId = FileId::Dg12;
+}
+
 impl HasFileId for EfDg14 {
     const FILE_ID: FileId = FileId::Dg14;
 }
 
+impl HasFileId for EfDg15 {
+    const FILE_ID: FileId = FileId::Dg15;
+}
+
 impl Emrtd {
     pub fn read_cached<T: HasFileId + for<'a> Decode<'a>>(&mut self) -> Result<T> {
         let der = self
             .read_file_cached(T::FILE_ID)?
             .ok_or(Error::FileNotFound)?;
-        Ok(T::from_der(der.as_slice())?)
+        Ok(T::from_der(der)?)
+    }
+
+    /// Reads EF.CardAccess from the master file and re-selects the eMRTD
+    /// application.
+    ///
+    /// Must be called before Basic Access Control, the file is read without
+    /// secure messaging. Returns Ok(None) if the chip has no EF.CardAccess.
+    ///
+    /// See ICAO 9303-10 section 3.11.3.
+    pub fn read_card_access(&mut self) -> Result<Option<EfCardAccess>> {
+        let der = self
+            .read_file_cached(FileId::CardAccess)?
+            .map(<[u8]>::to_vec);
+        if self.parent != DedicatedId::EmrtdLds1 {
+            self.select_dedicated_file(EMRTD_LDS1_AID)?;
+        }
+        Ok(der.as_deref().map(EfCardAccess::from_der).transpose()?)
+    }
+
+    /// Returns true if EF.CardAccess lists a PACE protocol, otherwise Basic
+    /// Access Control has to be used.
+    ///
+    /// See ICAO 9303-11 section 4.2.
+    pub fn supports_pace(&mut self) -> Result<bool> {
+        Ok(self.read_card_access()?.is_some_and(|infos| {
+            infos
+                .0
+                .iter()
+                .any(|info| matches!(info, SecurityInfo::Pace(_)))
+        }))
     }
 
     /// Retrieves a file with caching.
     ///
     /// Assumes the file is a single TLV structure.
     ///
-    /// Returns Ok(None) if the file is not found.
-    pub fn read_file_cached(&mut self, file: FileId) -> Result<Option<Vec<u8>>> {
-        // Try cache first.
-        if let Some(entry) = self.file_cache.get(&file) {
-            return Ok(entry.clone());
+    /// Returns Ok(None) if the file is not found. The cache is cleared when a
+    /// new session is established, as access rights may have changed.
+    pub fn read_file_cached(&mut self, file: FileId) -> Result<Option<&[u8]>> {
+        if !self.file_cache.contains_key(&file) {
+            let result = self.read_file(file)?;
+            self.file_cache.insert(file, result);
         }
+        Ok(self.file_cache[&file].as_deref())
+    }
+
+    /// Reads all known files, skipping files that are not found or not
+    /// accessible.
+    ///
+    /// The `progress` callback is invoked with the result for every file. When
+    /// the chip reports incomplete secure messaging the session is
+    /// re-established with [`Emrtd::reauthenticate`] using `rng` and the file
+    /// is retried once.
+    pub fn read_all_files<F: Fn(FileId, &Result<Option<&[u8]>>)>(
+        &mut self,
+        rng: &mut impl Rng,
+        progress: F,
+    ) -> HashMap<FileId, Vec<u8>> {
+        let mut files = HashMap::new();
+        for file in FileId::iter() {
+            let mut result = self.read_file_cached(file).map(|_| ());
+            if matches!(
+                result,
+                Err(Error::SecureMessagingError(
+                    StatusWord::SECURE_MESSAGING_INCOMPLETE
+                ))
+            ) && self.reauthenticate(rng).is_ok()
+            {
+                result = self.read_file_cached(file).map(|_| ());
+            }
+            match result {
+                Ok(()) => {
+                    let data = self.file_cache[&file].as_deref();
+                    progress(file, &Ok(data));
+                    if let Some(data) = data {
+                        files.insert(file, data.to_vec());
+                    }
+                }
+                Err(error) => progress(file, &Err(error)),
+            }
+        }
+        files
+    }
 
+    /// Reads a file without caching, returns Ok(None) if the file is not found.
+    fn read_file(&mut self, file: FileId) -> Result<Option<Vec<u8>>> {
         // Select parent file if necessary.
         if self.parent != file.parent() {
             if let Some(application_id) = file.parent().aid() {
@@ -59,40 +160,28 @@ impl Emrtd {
         }
 
         // Read file by short EF.
-        let mut result: Option<Vec<u8>> = match self.read_binary_short_ef(file.short_id()) {
-            Ok(data) => Some(data),
-            Err(Error::ErrorResponse(StatusWord::FILE_NOT_FOUND)) => None,
-            Err(e) => return Err(e),
-        };
-        if let Some(result) = result.as_mut() {
-            loop {
-                // Check if we are done by parsing the header.
-                if sniff_len(result)? <= Some(result.len()) {
-                    break;
-                }
-                let chunk = self.read_binary_offset(result.len())?;
-                if chunk.is_empty() {
-                    break;
-                }
-                result.extend(&chunk);
-            }
-
-            // Some (e.g. Polish) passports will zero-extend the file on READ BINARY OFFSET
-            // commands. Trim the file to the actual length.
-            let expected_len = sniff_len(result)?.ok_or(Error::ResponseDataUnexpected)?;
-            ensure_err!(result.len() >= expected_len, Error::ResponseDataUnexpected);
-            result.truncate(expected_len);
+        match self.read_binary_sfi(file.sfi()) {
+            Ok(data) => Ok(Some(data)),
+            Err(Error::ErrorResponse(StatusWord::FILE_NOT_FOUND)) => Ok(None),
+            Err(e) => Err(e),
         }
-
-        // Insert in cache
-        self.file_cache.insert(file, result.clone());
-        Ok(result)
     }
 
+    /// Select the master file.
+    ///
+    /// Fails without sending a command while a Basic Access Control session
+    /// is active, as the session is bound to the eMRTD application.
     pub fn select_master_file(&mut self) -> Result<()> {
+        ensure_err!(!self.bac_session, Error::MasterFileUnavailable);
+
         // Select by file identifier
         // See ISO/IEC 7816-4 section 11.2.2
-        let (status, data) = self.send_apdu(&[0x00, 0xa4, 0x00, 0x0c, 0x02, 0x3f, 0x00])?;
+        let (status, data) = match self.send_apdu(&[0x00, 0xa4, 0x00, 0x0c, 0x02, 0x3f, 0x00]) {
+            Err(Error::ErrorResponse(StatusWord::SECURE_MESSAGING_NOT_SUPPORTED)) => {
+                return Err(Error::MasterFileUnavailable)
+            }
+            result => result?,
+        };
         ensure_err!(status.is_success(), status.into());
         self.parent = DedicatedId::MasterFile;
         ensure_err!(data.is_empty(), Error::ResponseDataUnexpected);
@@ -151,6 +240,35 @@ impl Emrtd {
         Ok(data)
     }
 
+    /// Read a complete file using its Short File Identifier.
+    ///
+    /// The first READ BINARY selects the file implicitly, so no SELECT is
+    /// needed. The remainder is read using offsets until the length from the
+    /// TLV header is reached.
+    ///
+    /// See ISO 7816-4 section 7.2.3.
+    pub fn read_binary_sfi(&mut self, sfi: u8) -> Result<Vec<u8>> {
+        let mut result = self.read_binary_short_ef(sfi)?;
+        loop {
+            // Check if we are done by parsing the header.
+            if sniff_len(&result)? <= Some(result.len()) {
+                break;
+            }
+            let chunk = self.read_binary_offset(result.len())?;
+            if chunk.is_empty() {
+                break;
+            }
+            result.extend(&chunk);
+        }
+
+        // Some (e.g. Polish) passports will zero-extend the file on READ BINARY OFFSET
+        // commands. Trim the file to the actual length.
+        let expected_len = sniff_len(&result)?.ok_or(Error::ResponseDataUnexpected)?;
+        ensure_err!(result.len() >= expected_len, Error::ResponseDataUnexpected);
+        result.truncate(expected_len);
+        Ok(result)
+    }
+
     /// Reads the current file at a given offset.
     pub fn read_binary_offset(&mut self, offset: usize) -> Result<Vec<u8>> {
         // TODO: use B1 for large offsets.
@@ -184,3 +302,212 @@ fn sniff_len(bytes: &[u8]) -> Result<Option<usize>> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            emrtd::secure_messaging::PlainText,
+            nfc::mock::{Exchange, MockReader},
+        },
+        der::{Tag, TagNumber},
+        hex_literal::hex,
+        std::cell::Cell,
+    };
+
+    const COM: [u8; 5] = [0x60, 0x03, 0x01, 0x02, 0x03];
+
+    fn success(command: &[u8], data: &[u8]) -> Exchange {
+        (command.to_vec(), (StatusWord::SUCCESS, data.to_vec()))
+    }
+
+    fn failure(command: &[u8], status: StatusWord) -> Exchange {
+        (command.to_vec(), (status, vec![]))
+    }
+
+    #[test]
+    fn test_read_all_files() {
+        // Card that only has EF.COM and denies access to EF.DG3.
+        let not_found = |command: &[u8]| failure(command, StatusWord::FILE_NOT_FOUND);
+        let mut script = vec![
+            not_found(&hex!("00B09C0000")),
+            not_found(&hex!("00B09E0000")),
+            not_found(&hex!("00B0810000")),
+            not_found(&hex!("00B09D0000")),
+            success(&hex!("00A4040C07A0000002471001"), &[]),
+            success(&hex!("00B09E0000"), &COM),
+            not_found(&hex!("00B0810000")),
+            not_found(&hex!("00B0820000")),
+            failure(&hex!("00B0830000"), StatusWord::ACCESS_DENIED),
+        ];
+        // EF.DG4 to EF.DG16 and EF.SOD.
+        script.extend(
+            (0x84..=0x90)
+                .chain([0x9d])
+                .map(|sfi| not_found(&[0x00, 0xb0, sfi, 0x00, 0x00])),
+        );
+        let mut card = Emrtd::new(Box::new(MockReader::new(script)));
+        let calls = Cell::new(0);
+        let denied = Cell::new(0);
+        let files = card.read_all_files(&mut rand::thread_rng(), |file, result| {
+            calls.set(calls.get() + 1);
+            match result {
+                Ok(_) => {}
+                Err(Error::ErrorResponse(StatusWord::ACCESS_DENIED)) => {
+                    assert_eq!(file, FileId::Dg3);
+                    denied.set(denied.get() + 1);
+                }
+                Err(error) => panic!("Unexpected error for {file:?}: {error}"),
+            }
+        });
+        assert_eq!(calls.get(), FileId::iter().count());
+        assert_eq!(denied.get(), 1);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[&FileId::Com], COM);
+    }
+
+    #[test]
+    fn test_read_card_access() {
+        // Select MF, read EF.CardAccess and select the eMRTD application.
+        let mut card = Emrtd::new(Box::new(MockReader::new(vec![
+            success(&hex!("00A4000C023F00"), &[]),
+            failure(&hex!("00B09C0000"), StatusWord::FILE_NOT_FOUND),
+            success(&hex!("00A4040C07A0000002471001"), &[]),
+            success(&hex!("00B09E0000"), &COM),
+        ])));
+        card.parent = DedicatedId::EmrtdLds1;
+        assert_eq!(card.read_card_access().unwrap(), None);
+        assert_eq!(card.parent, DedicatedId::EmrtdLds1);
+        assert!(card.read_file_cached(FileId::Com).unwrap().is_some());
+
+        // The result is cached.
+        assert!(!card.supports_pace().unwrap());
+    }
+
+    #[test]
+    fn test_master_file_after_bac() {
+        let mut card = Emrtd::new(Box::new(MockReader::new(vec![
+            success(&hex!("00B09E0000"), &COM),
+            success(&hex!("00A4000C023F00"), &[]),
+        ])));
+        card.parent = DedicatedId::EmrtdLds1;
+        card.bac_session = true;
+        assert!(matches!(
+            card.read_file_cached(FileId::CardAccess),
+            Err(Error::MasterFileUnavailable)
+        ));
+        assert!(card.read_file_cached(FileId::Com).unwrap().is_some());
+
+        // Chip Authentication replaces the session but it stays bound to the
+        // eMRTD application.
+        card.set_secure_messaging(Box::new(PlainText));
+        assert!(matches!(
+            card.select_master_file(),
+            Err(Error::MasterFileUnavailable)
+        ));
+
+        // After the session is torn down the master file can be selected.
+        card.reset_secure_messaging();
+        assert!(card.select_master_file().is_ok());
+    }
+
+    #[test]
+    fn test_master_file_secure_messaging_not_supported() {
+        let mut card = Emrtd::new(Box::new(MockReader::new(vec![failure(
+            &hex!("00A4000C023F00"),
+            StatusWord::SECURE_MESSAGING_NOT_SUPPORTED,
+        )])));
+        card.parent = DedicatedId::EmrtdLds1;
+        assert!(matches!(
+            card.select_master_file(),
+            Err(Error::MasterFileUnavailable)
+        ));
+        assert_eq!(card.parent, DedicatedId::EmrtdLds1);
+    }
+
+    #[test]
+    fn test_read_file_cached() {
+        let mut card = Emrtd::new(Box::new(MockReader::new(vec![
+            success(&hex!("00A4040C07A0000002471001"), &[]),
+            failure(&hex!("00B0810000"), StatusWord::FILE_NOT_FOUND),
+            success(&hex!("00B09E0000"), &COM),
+            success(&hex!("00B09E0000"), &COM),
+            failure(&hex!("00B0830000"), StatusWord::ACCESS_DENIED),
+            failure(&hex!("00B0830000"), StatusWord::ACCESS_DENIED),
+        ])));
+        assert_eq!(card.read_file_cached(FileId::Dg1).unwrap(), None);
+        assert_eq!(card.read_file_cached(FileId::Com).unwrap(), Some(&COM[..]));
+        assert_eq!(card.read_file_cached(FileId::Dg1).unwrap(), None);
+        assert!(card.read_file_cached(FileId::Com).unwrap().is_some());
+
+        // A new session invalidates the cache.
+        card.set_secure_messaging(Box::new(PlainText));
+        assert_eq!(card.read_file_cached(FileId::Com).unwrap(), Some(&COM[..]));
+
+        // Errors other than file not found are not cached.
+        for _ in 0..2 {
+            assert!(matches!(
+                card.read_file_cached(FileId::Dg3),
+                Err(Error::ErrorResponse(StatusWord::ACCESS_DENIED))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_sfi() {
+        assert_eq!(FileId::Dg1.sfi(), 0x01);
+        assert_eq!(FileId::Dg16.sfi(), 0x10);
+        assert_eq!(FileId::Com.sfi(), 0x1e);
+        assert_eq!(FileId::Sod.sfi(), 0x1d);
+        assert_eq!(FileId::Dir.sfi(), 0x1e);
+        assert_eq!(FileId::AttrInfo.sfi(), 0x01);
+
+        let mut card = Emrtd::new(Box::new(MockReader::new(vec![
+            success(&hex!("00A4040C07A0000002471001"), &[]),
+            success(&hex!("00B09E0000"), &COM),
+            failure(&hex!("00B0810000"), StatusWord::FILE_NOT_FOUND),
+        ])));
+        card.select_dedicated_file(DedicatedId::EmrtdLds1.aid().unwrap())
+            .unwrap();
+        assert_eq!(card.read_binary_sfi(0x1e).unwrap(), COM);
+        assert!(matches!(
+            card.read_binary_sfi(0x01),
+            Err(Error::ErrorResponse(StatusWord::FILE_NOT_FOUND))
+        ));
+    }
+
+    #[test]
+    fn test_read_master_file_sfi() {
+        let mut card = Emrtd::new(Box::new(MockReader::new(vec![
+            success(&hex!("00B09E0000"), &hex!("61034F01AA")),
+            failure(&hex!("00B0810000"), StatusWord::FILE_NOT_FOUND),
+        ])));
+        assert_eq!(
+            card.read_file_cached(FileId::Dir).unwrap(),
+            Some(&hex!("61034F01AA")[..])
+        );
+        assert_eq!(card.read_file_cached(FileId::AttrInfo).unwrap(), None);
+    }
+
+    #[test]
+    fn test_file_names() {
+        assert_eq!(FileId::iter().count(), 22);
+        assert!(FileId::iter()
+            .filter_map(|file| file.data_group_number())
+            .eq(1..=16));
+        assert_eq!(FileId::Dg1.aid_byte(), Some(0x61));
+        assert_eq!(FileId::Dg2.aid_byte(), Some(0x75));
+        assert_eq!(FileId::CardAccess.aid_byte(), None);
+        assert_eq!(FileId::Dg1.ef_name(), [0x01, 0x01]);
+        assert_eq!(FileId::Sod.ef_name(), [0x01, 0x1d]);
+        assert_eq!(
+            FileId::Sod.tag(),
+            Some(Tag::Application {
+                constructed: true,
+                number:      TagNumber::new(23),
+            })
+        );
+        assert_eq!(FileId::CardSecurity.tag(), None);
+    }
+}
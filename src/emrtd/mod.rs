@@ -1,12 +1,33 @@
 //! Library for interacting with an ICAO 9303 compliant eMRTD.
 
+mod active_authentication;
+#[cfg(feature = "async")]
+mod async_emrtd;
 mod bac;
 mod chip_authentication;
 mod files;
+mod mrz;
 mod pace;
 pub mod secure_messaging;
-
-pub use self::files::{DedicatedId, FileId, HasFileId};
+mod terminal_authentication;
+
+#[cfg(feature = "async")]
+pub use self::async_emrtd::AsyncEmrtd;
+pub use self::{
+    bac::send_sequence_counter,
+    chip_authentication::{
+        chip_authentication_v2_session, parse_chip_authentication_response,
+        parse_general_authenticate_response, GeneralAuthenticateResponse,
+    },
+    files::{DedicatedId, FileId, HasFileId, EMRTD_LDS1_AID},
+    mrz::{
+        bac_key_seed, detect_key_information, key_information, validate_mrz, MrzError, MrzFormat,
+    },
+    pace::{
+        authentication_token, decrypt_nonce, k_from_mrz, map_generic, AuthMethod, PacePassword,
+        KDF_PACE,
+    },
+};
 use {
     self::secure_messaging::{PlainText, SecureMessaging},
     crate::{
@@ -16,6 +37,7 @@ use {
     files::FileCache,
     sha1::{Digest, Sha1},
     thiserror::Error,
+    zeroize::Zeroizing,
 };
 
 pub struct Emrtd {
@@ -33,6 +55,18 @@ pub struct Emrtd {
 
     /// Cache of files read from the card.
     file_cache: FileCache,
+
+    /// MRZ used for the last successful Basic Access Control, zeroized when
+    /// replaced or dropped.
+    access_key: Option<Zeroizing<String>>,
+
+    /// If true, secure messaging was established by Basic Access Control.
+    ///
+    /// BAC sessions belong to the eMRTD application and some chips answer
+    /// `SELECT` of the master file with "secure messaging not supported".
+    /// Chip Authentication keeps the flag, it is only cleared when secure
+    /// messaging is torn down.
+    bac_session: bool,
 }
 
 #[derive(Debug, Error)]
@@ -40,10 +74,10 @@ pub enum Error {
     #[error("NFC error: {0}")]
     NfcError(anyhow::Error),
 
-    #[error("Response Status: {0}")]
+    #[error("Response Status: {0}{}", recovery_hint(*.0))]
     ErrorResponse(StatusWord),
 
-    #[error("Secure Messaging failed (status: {0}).")]
+    #[error("Secure Messaging failed (status: {0}){}.", recovery_hint(*.0))]
     SecureMessagingError(StatusWord),
 
     #[error("Invalid APDU: {0}")]
@@ -72,10 +106,27 @@ pub enum Error {
 
     #[error("File not found.")]
     FileNotFound,
+
+    #[error("Master file not accessible with Basic Access Control, perform PACE first.")]
+    MasterFileUnavailable,
+
+    #[error("PACE is not supported: {0}.")]
+    PaceUnsupported(&'static str),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Describes what the caller can do about an error status.
+const fn recovery_hint(status: StatusWord) -> &'static str {
+    if status.is_fatal() {
+        ", the authentication method is blocked"
+    } else if status.requires_reauth() {
+        ", the session must be re-established"
+    } else {
+        ""
+    }
+}
+
 impl From<StatusWord> for Error {
     fn from(status: StatusWord) -> Self {
         Error::ErrorResponse(status)
@@ -92,48 +143,72 @@ impl Emrtd {
             // On Reset chip is always in master file.
             parent: DedicatedId::MasterFile,
             file_cache: FileCache::new(),
+            access_key: None,
+            bac_session: false,
         }
     }
 
+    /// Replace the secure messaging session.
+    ///
+    /// Cached files are dropped, as they were read under the old session.
     pub fn set_secure_messaging(&mut self, secure_messaging: Box<dyn SecureMessaging>) {
         self.secure_messaging = secure_messaging;
+        self.file_cache.clear();
+    }
+
+    /// Drop the secure messaging session, the chip reverts to plain APDUs.
+    fn reset_secure_messaging(&mut self) {
+        self.secure_messaging = Box::new(PlainText);
+        self.bac_session = false;
     }
 
     pub fn send_apdu(&mut self, apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)> {
-        let protected_apdu = self.secure_messaging.enc_apdu(apdu)?;
+        exchange_apdu!(self, apdu, |protected| self.nfc.send_apdu(&protected))
+    }
+}
+
+/// Protect `$apdu`, exchange it using `$transmit` and unprotect the response.
+///
+/// Shared by [`Emrtd`] and [`AsyncEmrtd`], which only differ in how the
+/// reader is called. `$self` needs a `secure_messaging` field and a
+/// `reset_secure_messaging` method.
+macro_rules! exchange_apdu {
+    ($self:ident, $apdu:expr, | $protected:ident | $transmit:expr) => {{
+        use $crate::{
+            emrtd::Error,
+            iso7816::{self, StatusWord},
+        };
+        let apdu: &[u8] = $apdu;
+        let $protected = $self.secure_messaging.enc_apdu(apdu)?;
 
         // TODO: Apply command chaining and `GET RESPONSE` handling.
         // This goes after enctyption (`GET RESPONSE` is always plaintext).
 
-        let (status, data) = self
-            .nfc
-            .send_apdu(&protected_apdu)
-            .map_err(Error::NfcError)?;
-        // eprintln!("Status word: {}", status);
-        // eprintln!("Encrypted response APDU: {}", hex::encode(&data));
+        let (mut status, mut data) = $transmit.map_err(Error::NfcError)?;
+
+        // Wrong length, re-issue the command with the Le given by the chip.
+        // See ISO 7816-4 section 5.1.3. Under secure messaging Le is in the
+        // protected DO'97 and the SSC of the first attempt is consumed, so
+        // the corrected command is protected again.
+        if let Some(le) = status.wrong_le() {
+            let corrected_apdu = iso7816::parse_apdu(apdu)?.encode_with_le(le);
+            let $protected = $self.secure_messaging.enc_apdu(&corrected_apdu)?;
+            (status, data) = $transmit.map_err(Error::NfcError)?;
+        }
 
         match status {
             StatusWord::SECURE_MESSAGING_INCORRECT | StatusWord::SECURE_MESSAGING_INCOMPLETE => {
-                // Reset secure messaging.
-                self.set_secure_messaging(Box::new(PlainText));
-
+                $self.reset_secure_messaging();
                 return Err(Error::SecureMessagingError(status));
             }
             _ => {}
         }
 
-        // TODO: On SM error card will revert to plain APDU. Check for SM error.
-        let data = self.secure_messaging.dec_response(status, &data)?;
-        // eprintln!("Decrypted response APDU: {}", hex::encode(&data));
-
+        let data = $self.secure_messaging.dec_response(status, &data)?;
         Ok((status, data))
-    }
-}
-
-pub fn pad(bytes: &mut Vec<u8>, block_size: usize) {
-    bytes.push(0x80);
-    bytes.resize(bytes.len().next_multiple_of(block_size), 0x00);
+    }};
 }
+pub(crate) use exchange_apdu;
 
 pub fn seed_from_mrz(mrz: &str) -> [u8; 16] {
     let mut hasher = Sha1::new();
@@ -141,3 +216,56 @@ pub fn seed_from_mrz(mrz: &str) -> [u8; 16] {
     let hash = hasher.finalize();
     hash[0..16].try_into().unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{iso7816::pad_iso7816_2, nfc::mock::MockReader},
+        hex_literal::hex,
+        secure_messaging::{tdes::TDesCipher, Cipher, Encrypted},
+    };
+
+    #[test]
+    fn test_wrong_le() {
+        let reader = MockReader::new(vec![
+            (
+                hex!("00B0000000").to_vec(),
+                (StatusWord::from(0x6c20), vec![]),
+            ),
+            (
+                hex!("00B0000020").to_vec(),
+                (StatusWord::SUCCESS, vec![0x42; 0x20]),
+            ),
+        ]);
+        let mut emrtd = Emrtd::new(Box::new(reader));
+        let (status, data) = emrtd.send_apdu(&[0x00, 0xb0, 0x00, 0x00, 0x00]).unwrap();
+        assert_eq!(status, StatusWord::SUCCESS);
+        assert_eq!(data, [0x42; 0x20]);
+    }
+
+    #[test]
+    fn test_wrong_le_secure_messaging() {
+        let seed = hex!("0036D272F5C350ACAC50C3F572D23600");
+        let mut expected = Encrypted::new(TDesCipher::from_seed(&seed), 0);
+        let first = expected.enc_apdu(&hex!("00B0000000")).unwrap();
+        let retry = expected.enc_apdu(&hex!("00B0000020")).unwrap();
+        assert_ne!(first, retry);
+
+        // Protected response without data for SSC 3.
+        let cipher = TDesCipher::from_seed(&seed);
+        let do99 = hex!("99 02 9000");
+        let message = pad_iso7816_2(&[&3_u64.to_be_bytes()[..], &do99].concat(), 8);
+        let response = [&do99[..], &[0x8e, 0x08], &cipher.mac(3, &message)].concat();
+
+        let reader = MockReader::new(vec![
+            (first, (StatusWord::from(0x6c20), vec![])),
+            (retry, (StatusWord::SUCCESS, response)),
+        ]);
+        let mut emrtd = Emrtd::new(Box::new(reader));
+        emrtd.set_secure_messaging(Box::new(Encrypted::new(TDesCipher::from_seed(&seed), 0)));
+        let (status, data) = emrtd.send_apdu(&hex!("00B0000000")).unwrap();
+        assert_eq!(status, StatusWord::SUCCESS);
+        assert!(data.is_empty());
+    }
+}
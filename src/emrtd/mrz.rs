@@ -0,0 +1,307 @@
+//! Machine Readable Zone validation.
+//!
+//! See ICAO 9303-3 section 4.9 for the check digit algorithm and ICAO 9303-4
+//! to 9303-6 for the TD3, TD2 and TD1 layouts.
+
+use {
+    sha1::{Digest, Sha1},
+    thiserror::Error,
+};
+
+/// Length of the MRZ information used to derive BAC and PACE keys: document
+/// number, date of birth and date of expiry, each followed by its check digit.
+///
+/// See ICAO 9303-11 section 9.7.2.
+pub const KEY_INFORMATION_LENGTH: usize = 24;
+
+/// Machine Readable Travel Document size, determining the MRZ layout.
+///
+/// See ICAO 9303-5, 9303-6 and 9303-4 respectively.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MrzFormat {
+    /// Three lines of 30 characters, e.g. identity cards.
+    Td1,
+
+    /// Two lines of 36 characters.
+    Td2,
+
+    /// Two lines of 44 characters, e.g. passports.
+    Td3,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum MrzError {
+    #[error("Invalid MRZ length {0}")]
+    InvalidLength(usize),
+
+    #[error("Invalid MRZ character {0:?}")]
+    InvalidCharacter(char),
+
+    #[error("Bad check digit for {field}: expected {expected}, got {got}")]
+    BadCheckDigit {
+        field:    &'static str,
+        expected: char,
+        got:      char,
+    },
+}
+
+impl MrzFormat {
+    /// Detect the format from the length of an MRZ without line breaks.
+    pub const fn from_len(len: usize) -> Option<Self> {
+        match len {
+            90 => Some(Self::Td1),
+            72 => Some(Self::Td2),
+            88 => Some(Self::Td3),
+            _ => None,
+        }
+    }
+
+    /// Length of the MRZ without line breaks.
+    pub const fn mrz_len(self) -> usize {
+        match self {
+            Self::Td1 => 90,
+            Self::Td2 => 72,
+            Self::Td3 => 88,
+        }
+    }
+
+    /// Offsets of the document number, date of birth and date of expiry. Each
+    /// field is directly followed by its check digit.
+    const fn key_fields(self) -> [(&'static str, usize, usize); 3] {
+        match self {
+            Self::Td1 => [
+                ("document number", 5, 14),
+                ("date of birth", 30, 36),
+                ("date of expiry", 38, 44),
+            ],
+            Self::Td2 => [
+                ("document number", 36, 45),
+                ("date of birth", 49, 55),
+                ("date of expiry", 57, 63),
+            ],
+            Self::Td3 => [
+                ("document number", 44, 53),
+                ("date of birth", 57, 63),
+                ("date of expiry", 65, 71),
+            ],
+        }
+    }
+
+    /// Ranges covered by the composite check digit, and its offset.
+    const fn composite(self) -> (&'static [(usize, usize)], usize) {
+        match self {
+            Self::Td1 => (&[(5, 30), (30, 37), (38, 45), (48, 59)], 59),
+            Self::Td2 => (&[(36, 46), (49, 56), (57, 71)], 71),
+            Self::Td3 => (&[(44, 54), (57, 64), (65, 87)], 87),
+        }
+    }
+}
+
+/// Validate the check digits of an MRZ.
+///
+/// Accepts either a full TD1, TD2 or TD3 MRZ (with or without line breaks)
+/// or the MRZ information used for key derivation.
+pub fn validate_mrz(mrz: &str) -> Result<(), MrzError> {
+    let mrz = strip_mrz(mrz)?;
+    if mrz.len() == KEY_INFORMATION_LENGTH {
+        check_field("document number", &mrz[0..9], mrz[9])?;
+        check_field("date of birth", &mrz[10..16], mrz[16])?;
+        return check_field("date of expiry", &mrz[17..23], mrz[23]);
+    }
+    let format = MrzFormat::from_len(mrz.len()).ok_or(MrzError::InvalidLength(mrz.len()))?;
+    for (field, start, end) in format.key_fields() {
+        check_field(field, &mrz[start..end], mrz[end])?;
+    }
+
+    // The personal number check digit may be a filler if the field is unused.
+    if format == MrzFormat::Td3 && !mrz[72..87].iter().all(|&c| c == b'<') {
+        check_field("personal number", &mrz[72..86], mrz[86])?;
+    }
+
+    let (ranges, offset) = format.composite();
+    let composite: Vec<u8> = ranges
+        .iter()
+        .flat_map(|&(start, end)| &mrz[start..end])
+        .copied()
+        .collect();
+    check_field("composite", &composite, mrz[offset])
+}
+
+/// Extract the MRZ information used for key derivation.
+///
+/// See ICAO 9303-11 section 9.7.2.
+pub fn key_information(mrz: &str, format: MrzFormat) -> Result<String, MrzError> {
+    let mrz = strip_mrz(mrz)?;
+    if mrz.len() != format.mrz_len() {
+        return Err(MrzError::InvalidLength(mrz.len()));
+    }
+    let info: Vec<u8> = format
+        .key_fields()
+        .iter()
+        .flat_map(|&(_, start, end)| &mrz[start..=end])
+        .copied()
+        .collect();
+    Ok(String::from_utf8(info).expect("MRZ is ASCII"))
+}
+
+/// Extract the MRZ information, detecting the format from the length.
+///
+/// Input that already is MRZ information is returned as is.
+pub fn detect_key_information(mrz: &str) -> Result<String, MrzError> {
+    let stripped = strip_mrz(mrz)?;
+    if stripped.len() == KEY_INFORMATION_LENGTH {
+        return Ok(String::from_utf8(stripped).expect("MRZ is ASCII"));
+    }
+    let format =
+        MrzFormat::from_len(stripped.len()).ok_or(MrzError::InvalidLength(stripped.len()))?;
+    key_information(mrz, format)
+}
+
+/// Compute the SHA-1 hash of the MRZ information.
+///
+/// The first 16 bytes are the BAC key seed, see ICAO 9303-11 section 4.3.2.
+pub fn bac_key_seed(mrz: &str, format: MrzFormat) -> Result<[u8; 20], MrzError> {
+    let info = key_information(mrz, format)?;
+    Ok(Sha1::digest(info.as_bytes()).into())
+}
+
+/// Compute the check digit of an MRZ field.
+///
+/// Returns `None` if the field contains characters not allowed in an MRZ.
+pub fn check_digit(field: &[u8]) -> Option<u8> {
+    let mut sum = 0_u32;
+    for (&c, weight) in field.iter().zip([7, 3, 1].into_iter().cycle()) {
+        sum += check_value(c)? * weight;
+    }
+    Some(b'0' + (sum % 10) as u8)
+}
+
+/// Remove line breaks and check for invalid characters.
+fn strip_mrz(mrz: &str) -> Result<Vec<u8>, MrzError> {
+    let mrz: Vec<u8> = mrz.bytes().filter(|&c| c != b'\n' && c != b'\r').collect();
+    if let Some(&c) = mrz.iter().find(|&&c| check_value(c).is_none()) {
+        return Err(MrzError::InvalidCharacter(c as char));
+    }
+    Ok(mrz)
+}
+
+fn check_value(c: u8) -> Option<u32> {
+    match c {
+        b'0'..=b'9' => Some((c - b'0').into()),
+        b'A'..=b'Z' => Some((c - b'A' + 10).into()),
+        b'<' => Some(0),
+        _ => None,
+    }
+}
+
+fn check_field(field: &'static str, value: &[u8], got: u8) -> Result<(), MrzError> {
+    let expected = check_digit(value).ok_or(MrzError::InvalidCharacter(got as char))?;
+    if expected == got {
+        Ok(())
+    } else {
+        Err(MrzError::BadCheckDigit {
+            field,
+            expected: expected as char,
+            got: got as char,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    // ICAO 9303-4 Appendix A specimen.
+    const SPECIMEN: &str = concat!(
+        "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<\n",
+        "L898902C36UTO7408122F1204159ZE184226B<<<<<10"
+    );
+
+    // ICAO 9303-5 and 9303-6 specimens.
+    const TD1: &str = concat!(
+        "I<UTOD231458907<<<<<<<<<<<<<<<\n",
+        "7408122F1204159UTO<<<<<<<<<<<6\n",
+        "ERIKSSON<<ANNA<MARIA<<<<<<<<<<"
+    );
+    const TD2: &str = concat!(
+        "I<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<\n",
+        "D231458907UTO7408122F1204159<<<<<<<6"
+    );
+
+    #[test]
+    fn test_check_digit() {
+        assert_eq!(check_digit(b"L898902C3"), Some(b'6'));
+        assert_eq!(check_digit(b"740812"), Some(b'2'));
+        assert_eq!(check_digit(b"120415"), Some(b'9'));
+        assert_eq!(check_digit(b"ZE184226B<<<<<"), Some(b'1'));
+        assert_eq!(check_digit(b"l"), None);
+    }
+
+    #[test]
+    fn test_validate_mrz() {
+        assert_eq!(validate_mrz(SPECIMEN), Ok(()));
+        assert_eq!(validate_mrz("L898902C3674081221204159"), Ok(()));
+        assert_eq!(validate_mrz("T22000129364081251010318"), Ok(()));
+        assert_eq!(
+            validate_mrz("L898902C3674081231204159"),
+            Err(MrzError::BadCheckDigit {
+                field:    "date of birth",
+                expected: '2',
+                got:      '3',
+            })
+        );
+        assert_eq!(
+            validate_mrz(&SPECIMEN.replace("<10", "<11")),
+            Err(MrzError::BadCheckDigit {
+                field:    "composite",
+                expected: '0',
+                got:      '1',
+            })
+        );
+        assert_eq!(validate_mrz("L898902C36"), Err(MrzError::InvalidLength(10)));
+    }
+
+    #[test]
+    fn test_validate_td1_td2() {
+        assert_eq!(validate_mrz(TD1), Ok(()));
+        assert_eq!(validate_mrz(TD2), Ok(()));
+        assert_eq!(
+            validate_mrz(&TD2.replace("<6", "<7")),
+            Err(MrzError::BadCheckDigit {
+                field:    "composite",
+                expected: '6',
+                got:      '7',
+            })
+        );
+    }
+
+    // ICAO 9303-11 Appendix D.2
+    #[test]
+    fn test_bac_key_seed() {
+        let mrz = concat!(
+            "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<",
+            "L898902C<3UTO6908061F9406236ZE184226B<<<<<14"
+        );
+        assert_eq!(validate_mrz(mrz), Ok(()));
+        assert_eq!(
+            key_information(mrz, MrzFormat::Td3).unwrap(),
+            "L898902C<369080619406236"
+        );
+        assert_eq!(
+            bac_key_seed(mrz, MrzFormat::Td3).unwrap(),
+            hex!("239AB9CB282DAF66231DC5A4DF6BFBAEDF477565")
+        );
+        assert_eq!(
+            bac_key_seed(TD1, MrzFormat::Td1).unwrap(),
+            bac_key_seed(TD2, MrzFormat::Td2).unwrap()
+        );
+        assert_eq!(
+            key_information(TD1, MrzFormat::Td1).unwrap(),
+            "D23145890774081221204159"
+        );
+        assert_eq!(
+            bac_key_seed(TD1, MrzFormat::Td3),
+            Err(MrzError::InvalidLength(90))
+        );
+    }
+}
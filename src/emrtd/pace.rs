@@ -1,24 +1,112 @@
 use {
-    super::Emrtd,
-    crate::{asn1::public_key_info::EcParameters, emrtd::secure_messaging::aes::kdf_128},
-    anyhow::Result,
+    super::{detect_key_information, validate_mrz, Emrtd, Error},
+    crate::{
+        asn1::emrtd::security_info::{KeyAgreement, SymmetricCipher},
+        crypto::{
+            cmac::{aes_cmac_128, aes_cmac_192, aes_cmac_256},
+            groups::{EllipticCurve, EllipticCurvePoint},
+            mac::retail_mac,
+            mod_ring::{RingRefExt, UintMont},
+            Codec, Icao9303Codec, PublicKeyEC,
+        },
+        emrtd::secure_messaging::aes::kdf_128,
+    },
+    aes::Aes128,
+    anyhow::{anyhow, ensure, Result},
+    bytes::Bytes,
+    cbc::Decryptor as CbcDec,
+    cipher::{block_padding::NoPadding, BlockDecryptMut, KeyIvInit},
+    der::{asn1::ObjectIdentifier as Oid, Encode},
     rand::{CryptoRng, RngCore},
+    ruint::aliases::U0,
     sha1::{Digest, Sha1},
+    tracing::warn,
 };
 
 pub const KDF_PACE: u32 = 3;
 
+/// Password for PACE, see ICAO 9303-11 section 4.4.1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PacePassword {
+    /// MRZ information, also usable for Basic Access Control.
+    Mrz(String),
+
+    /// Card Access Number printed on the document.
+    Can(String),
+}
+
+/// Access control protocol that established secure messaging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthMethod {
+    Pace,
+    Bac,
+}
+
 impl Emrtd {
+    /// Establish secure messaging with PACE if the chip supports it, falling
+    /// back to Basic Access Control otherwise.
+    ///
+    /// Support is determined from EF.CardAccess, which is read first. If PACE
+    /// fails for a reason other than communication with the reader, BAC is
+    /// tried with the MRZ. A [`PacePassword::Can`] can not be used for BAC.
+    ///
+    /// PACE itself is not implemented yet and fails with
+    /// [`Error::PaceUnsupported`], so chips supporting PACE are downgraded to
+    /// BAC with a warning. Check the returned [`AuthMethod`] if BAC is not
+    /// acceptable.
+    ///
+    /// See ICAO 9303-11 section 4.2.
+    pub fn authenticate(
+        &mut self,
+        rng: &mut (impl CryptoRng + RngCore),
+        password: PacePassword,
+    ) -> Result<AuthMethod> {
+        let pace_error = if self.supports_pace()? {
+            let result = match &password {
+                PacePassword::Mrz(mrz) => self.pace(&mut *rng, mrz),
+                PacePassword::Can(_) => {
+                    Err(Error::PaceUnsupported("CAN passwords are not implemented").into())
+                }
+            };
+            match result {
+                Ok(()) => return Ok(AuthMethod::Pace),
+                Err(error) if matches!(error.downcast_ref(), Some(Error::NfcError(_))) => {
+                    return Err(error)
+                }
+                Err(error) => {
+                    warn!("PACE failed, falling back to Basic Access Control: {error}");
+                    Some(error)
+                }
+            }
+        } else {
+            None
+        };
+        match password {
+            PacePassword::Mrz(mrz) => {
+                self.basic_access_control(rng, &mrz)?;
+                Ok(AuthMethod::Bac)
+            }
+            PacePassword::Can(_) => {
+                Err(pace_error.unwrap_or_else(|| anyhow!("Chip does not support PACE")))
+            }
+        }
+    }
+
+    /// Establish secure messaging with PACE, see ICAO 9303-11 section 4.4.
+    ///
+    /// Not implemented yet, always fails with [`Error::PaceUnsupported`].
     pub fn pace(&mut self, _rng: impl CryptoRng + RngCore, mrz: &str) -> Result<()> {
+        validate_mrz(mrz)?;
+
         // Derive symmetric key K_pi
-        let k = k_from_mrz(mrz);
+        let k = k_from_mrz(&detect_key_information(mrz)?);
         let _k_pi = kdf_128(&k[..], KDF_PACE);
 
         // Send MSE:Set AT.
 
         // Send GENERAL AUTHENTICATE
 
-        todo!()
+        Err(Error::PaceUnsupported("the protocol is not implemented").into())
     }
 }
 
@@ -28,20 +116,147 @@ pub fn k_from_mrz(mrz: &str) -> [u8; 20] {
     hasher.finalize().into()
 }
 
-/// ICAO 9303-11 9.5.1
-pub fn standardized_parameters(id: u64) -> Option<EcParameters> {
-    match id {
-        0 => todo!(),
-        1 => todo!(),
-        2 => todo!(),
-        3..=7 => todo!(),
-        _ => None,
+/// Decrypt the nonce `s` from the chip using `K_π`.
+///
+/// AES-128 in CBC mode with a zero IV, see ICAO 9303-11 4.4.3.1.
+pub fn decrypt_nonce(k_pi: &[u8; 16], encrypted_nonce: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = encrypted_nonce.to_vec();
+    CbcDec::<Aes128>::new(k_pi.into(), &[0; 16].into())
+        .decrypt_padded_mut::<NoPadding>(&mut nonce)
+        .map_err(|_| anyhow!("Encrypted nonce is not a multiple of the block size"))?;
+    Ok(nonce)
+}
+
+/// Generic Mapping for ECDH, see ICAO 9303-11 4.4.3.3.1.
+///
+/// Returns the curve with the mapped generator `G~ = s * G + H`, where `H` is
+/// the shared point from the mapping key agreement.
+pub fn map_generic<'a, U: UintMont>(
+    curve: &'a EllipticCurve<U>,
+    nonce: &[u8],
+    shared: EllipticCurvePoint<'a, U>,
+) -> Result<EllipticCurve<U>> {
+    let s = U::from_be_bytes(nonce);
+    ensure!(
+        s < curve.scalar_field().modulus(),
+        "Nonce larger than group order"
+    );
+    let s = curve.scalar_field().from(s);
+    Ok(curve.with_generator(curve.generator() * s + shared)?)
+}
+
+/// Authentication token, see ICAO 9303-11 4.4.3.4.
+///
+/// The MAC over the public key data object of the other party's ephemeral
+/// public key, see BSI TR-03110-3 D.3.1. Elliptic curve points are encoded
+/// in tag `0x86` and Diffie-Hellman public values in tag `0x84`, domain
+/// parameters are not included.
+pub fn authentication_token(
+    cipher: SymmetricCipher,
+    k_mac: &[u8],
+    protocol: Oid,
+    key_agreement: KeyAgreement,
+    public_key: &[u8],
+) -> Result<[u8; 8]> {
+    let mut data = Vec::new();
+    match key_agreement {
+        KeyAgreement::Ecdh => {
+            let key = PublicKeyEC::<U0>::new(protocol, Bytes::copy_from_slice(public_key));
+            Icao9303Codec::default().encode(&mut data, key);
+        }
+        KeyAgreement::Dh => {
+            // Unsigned integers are encoded without leading zeros.
+            let zeros = public_key.iter().take_while(|&&b| b == 0).count();
+            let public_key = &public_key[zeros..];
+            data = protocol.to_der()?;
+            data.push(0x84);
+            push_length(&mut data, public_key.len());
+            data.extend_from_slice(public_key);
+        }
+    }
+    let mut object = vec![0x7f, 0x49];
+    push_length(&mut object, data.len());
+    object.extend_from_slice(&data);
+
+    let mac = match cipher {
+        SymmetricCipher::Tdes => return Ok(retail_mac(k_mac.try_into()?, &object)),
+        SymmetricCipher::Aes128 => aes_cmac_128(k_mac.try_into()?, &object),
+        SymmetricCipher::Aes192 => aes_cmac_192(k_mac.try_into()?, &object),
+        SymmetricCipher::Aes256 => aes_cmac_256(k_mac.try_into()?, &object),
+    };
+    Ok(mac[0..8].try_into().unwrap())
+}
+
+/// BER length encoding for lengths up to 65535.
+pub(super) fn push_length(buffer: &mut Vec<u8>, length: usize) {
+    match length {
+        0..=0x7f => buffer.push(length as u8),
+        0x80..=0xff => buffer.extend_from_slice(&[0x81, length as u8]),
+        _ => {
+            buffer.push(0x82);
+            buffer.extend_from_slice(&(length as u16).to_be_bytes());
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use {super::*, crate::emrtd::secure_messaging::aes::kdf_128, hex_literal::hex};
+    use {
+        super::*,
+        crate::{
+            emrtd::secure_messaging::aes::kdf_128, iso7816::StatusWord, nfc::mock::MockReader,
+        },
+        hex_literal::hex,
+    };
+
+    #[test]
+    fn test_authenticate_fallback() {
+        let card_access = hex!("3114 3012 060a04007f00070202040202 020102 02010d");
+        let reader = MockReader::new(vec![
+            (
+                hex!("00B09C0000").to_vec(),
+                (StatusWord::SUCCESS, card_access.to_vec()),
+            ),
+            (
+                hex!("00A4040C07A0000002471001").to_vec(),
+                (StatusWord::SUCCESS, vec![]),
+            ),
+            (
+                hex!("0084000008").to_vec(),
+                (StatusWord::ACCESS_DENIED, vec![]),
+            ),
+        ]);
+        let mut card = Emrtd::new(Box::new(reader));
+
+        // PACE is advertised but fails, so after reading EF.CardAccess and
+        // selecting the eMRTD application BAC sends GET CHALLENGE.
+        let mrz = PacePassword::Mrz("L898902C3674081221204159".into());
+        let error = card.authenticate(&mut rand::thread_rng(), mrz).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(Error::ErrorResponse(StatusWord::ACCESS_DENIED))
+        ));
+
+        // A CAN can not be used for BAC, no further commands are sent.
+        let can = PacePassword::Can("123456".into());
+        let error = card.authenticate(&mut rand::thread_rng(), can).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(Error::PaceUnsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_pace_unsupported() {
+        let mut card = Emrtd::new(Box::new(MockReader::new(vec![])));
+        let error = card
+            .pace(rand::thread_rng(), "L898902C3674081221204159")
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(Error::PaceUnsupported(_))
+        ));
+    }
 
     // ICAO 9303-11, Appendix G
     #[test]
@@ -2,13 +2,13 @@
 
 use {
     super::{Cipher, KDF_ENC, KDF_MAC},
+    crate::crypto::cmac::{aes_cmac_128, aes_cmac_192, aes_cmac_256},
     aes::{Aes128, Aes192, Aes256},
     cbc::{Decryptor as CbcDec, Encryptor as CbcEnc},
     cipher::{
         block_padding::NoPadding, BlockDecryptMut, BlockEncrypt, BlockEncryptMut, KeyInit,
         KeyIvInit,
     },
-    cmac::{Cmac, Mac},
     sha1::{Digest, Sha1},
     sha2::Sha256,
 };
@@ -85,9 +85,7 @@ impl Cipher for Aes128Cipher {
 
     fn mac(&self, _ssc: u64, data: &[u8]) -> [u8; 8] {
         assert!(data.len() % BLOCK_SIZE == 0);
-        let mut cmac = <Cmac<Aes128> as KeyInit>::new(&self.kmac.into());
-        cmac.update(data);
-        cmac.finalize().into_bytes()[0..8].try_into().unwrap()
+        aes_cmac_128(&self.kmac, data)[0..8].try_into().unwrap()
     }
 }
 
@@ -118,9 +116,7 @@ impl Cipher for Aes192Cipher {
 
     fn mac(&self, _ssc: u64, data: &[u8]) -> [u8; 8] {
         assert!(data.len() % BLOCK_SIZE == 0);
-        let mut cmac = <Cmac<Aes192> as KeyInit>::new(&self.kmac.into());
-        cmac.update(data);
-        cmac.finalize().into_bytes()[0..8].try_into().unwrap()
+        aes_cmac_192(&self.kmac, data)[0..8].try_into().unwrap()
     }
 }
 
@@ -151,9 +147,7 @@ impl Cipher for Aes256Cipher {
 
     fn mac(&self, _ssc: u64, data: &[u8]) -> [u8; 8] {
         assert!(data.len() % BLOCK_SIZE == 0);
-        let mut cmac = <Cmac<Aes256> as KeyInit>::new(&self.kmac.into());
-        cmac.update(data);
-        cmac.finalize().into_bytes()[0..8].try_into().unwrap()
+        aes_cmac_256(&self.kmac, data)[0..8].try_into().unwrap()
     }
 }
 
@@ -230,37 +224,22 @@ mod tests {
         assert_eq!(kdf_128(&shared_secret, KDF_MAC), k_mac);
     }
 
-    // NIST SP 800-38B section D.1
+    // Same shared secret, keys computed independently with SHA-256
     #[test]
-    fn test_cmac_aes128() {
-        let k = hex!("2b7e1516 28aed2a6 abf71588 09cf4f3c");
-        let msg = hex!(
-            "6bc1bee2 2e409f96 e93d7e11 7393172a
-            ae2d8a57 1e03ac9c 9eb76fac 45af8e51
-            30c81c46 a35ce411 e5fbc119 1a0a52ef
-            f69f2445 df4f9b17 ad2b417b e66c3710"
+    fn test_derive_keys_256() {
+        let shared_secret = hex!(
+            "
+            79 1D A0 42 73 CC FE 86 2E 52 DF 60 34 7E 25 57
+            19 2E 1F 8D 75 17 82 2C E3 D3 06 05 6C 1C DE B4
+            42 87 B3 07 2A 3E DC 60"
         );
+        let k_enc = hex!("3ABF5B4E 4C8108F0 A7FDDC55 0AC80555 0B1EA5DC C957D1F5 A5B25CDD 7922EE54");
+        let k_mac = hex!("236F6D93 5D7C63C4 BE508C2A 597606B2 2DD6C6AF 4C8E312F 967746B2 E15B12BD");
 
-        let cmac = |msg: &[u8]| {
-            let mut cmac = <Cmac<Aes128> as KeyInit>::new(&k.into());
-            cmac.update(msg);
-            let result: [u8; 16] = cmac.finalize().into_bytes().into();
-            result
-        };
-
-        assert_eq!(cmac(&msg[..0]), hex!("bb1d6929 e9593728 7fa37d12 9b756746"));
-        assert_eq!(
-            cmac(&msg[..16]),
-            hex!("070a16b4 6b4d4144 f79bdd9d d04a287c")
-        );
-        assert_eq!(
-            cmac(&msg[..40]),
-            hex!("dfa66747 de9ae630 30ca3261 1497c827")
-        );
-        assert_eq!(
-            cmac(&msg[..64]),
-            hex!("51f0bebf 7e3b9d92 fc497417 79363cfe")
-        );
+        assert_eq!(kdf_256(&shared_secret, KDF_ENC), k_enc);
+        assert_eq!(kdf_256(&shared_secret, KDF_MAC), k_mac);
+        assert_eq!(kdf_192(&shared_secret, KDF_ENC), k_enc[..24]);
+        assert_eq!(kdf_192(&shared_secret, KDF_MAC), k_mac[..24]);
     }
 
     // Example TR 03110 Worked Example 8
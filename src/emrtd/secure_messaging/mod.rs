@@ -8,11 +8,11 @@ use {
         aes::{Aes128Cipher, Aes192Cipher, Aes256Cipher},
         tdes::TDesCipher,
     },
-    super::{pad, Error, Result},
+    super::{Error, Result},
     crate::{
         asn1::emrtd::security_info::SymmetricCipher,
         ensure_err,
-        iso7816::{parse_apdu, StatusWord},
+        iso7816::{pad_iso7816_2, parse_apdu, unpad_iso7816_2, StatusWord},
     },
 };
 
@@ -21,6 +21,12 @@ pub const KDF_MAC: u32 = 2;
 
 pub trait SecureMessaging {
     fn enc_apdu(&mut self, apdu: &[u8]) -> Result<Vec<u8>>;
+
+    /// Unwrap a response APDU.
+    ///
+    /// Implementations must check `status` first: a non-success status
+    /// without data is returned as [`Error::ErrorResponse`], chips do not
+    /// protect these.
     fn dec_response(&mut self, status: StatusWord, resp: &[u8]) -> Result<Vec<u8>>;
 }
 
@@ -59,7 +65,8 @@ impl SecureMessaging for PlainText {
         Ok(apdu.to_vec())
     }
 
-    fn dec_response(&mut self, _status: StatusWord, resp: &[u8]) -> Result<Vec<u8>> {
+    fn dec_response(&mut self, status: StatusWord, resp: &[u8]) -> Result<Vec<u8>> {
+        ensure_err!(status.is_success() || !resp.is_empty(), status.into());
         Ok(resp.to_vec())
     }
 }
@@ -93,8 +100,7 @@ impl<C: Cipher> SecureMessaging for Encrypted<C> {
 
         // Write encrypted data
         if !apdu.data.is_empty() {
-            let mut payload = apdu.data.to_vec();
-            pad(&mut payload, self.cipher.block_size());
+            let mut payload = pad_iso7816_2(apdu.data, self.cipher.block_size());
             self.cipher.enc(ssc, &mut payload);
             papdu.push(if ins_even { 0x87 } else { 0x85 });
             papdu.push((payload.len() + 1) as u8);
@@ -115,13 +121,13 @@ impl<C: Cipher> SecureMessaging for Encrypted<C> {
             let mut message = vec![0; self.cipher.block_size() - 8];
             message.extend_from_slice(&ssc.to_be_bytes());
             message.extend_from_slice(&papdu[..4]);
-            pad(&mut message, self.cipher.block_size());
+            let mut message = pad_iso7816_2(&message, self.cipher.block_size());
             if extended_length {
                 message.extend_from_slice(&papdu[7..]);
             } else {
                 message.extend_from_slice(&papdu[5..]);
             }
-            pad(&mut message, self.cipher.block_size());
+            let message = pad_iso7816_2(&message, self.cipher.block_size());
 
             // Compute MAC and append to papdu
             let mac = self.cipher.mac(ssc, &message);
@@ -152,6 +158,7 @@ impl<C: Cipher> SecureMessaging for Encrypted<C> {
     }
 
     fn dec_response(&mut self, status: StatusWord, resp: &[u8]) -> Result<Vec<u8>> {
+        ensure_err!(status.is_success() || !resp.is_empty(), status.into());
         ensure_err!(resp.len() >= 14, Error::SMResponseInvalid);
 
         // Split off DO'8E object containing MAC
@@ -165,8 +172,9 @@ impl<C: Cipher> SecureMessaging for Encrypted<C> {
         let mut n = vec![0; self.cipher.block_size() - 8];
         n.extend_from_slice(&self.ssc.to_be_bytes());
         n.extend_from_slice(resp);
-        pad(&mut n, self.cipher.block_size());
-        let mac2 = self.cipher.mac(self.ssc, &n);
+        let mac2 = self
+            .cipher
+            .mac(self.ssc, &pad_iso7816_2(&n, self.cipher.block_size()));
         ensure_err!(mac == mac2, Error::SMResponseMacFailed);
 
         // Split off DO'99 object and check (redundant) status word.
@@ -213,13 +221,10 @@ impl<C: Cipher> SecureMessaging for Encrypted<C> {
             Error::SMResponseInvalid
         );
         self.cipher.dec(self.ssc, &mut resp);
-        let length = resp
-            .iter()
-            .rposition(|&x| x == 0x80)
-            .ok_or(Error::SMResponseInvalid)?; // Unpadding failed
-        resp.truncate(length);
+        let resp = unpad_iso7816_2(&resp, self.cipher.block_size())
+            .map_err(|_| Error::SMResponseInvalid)?;
 
-        Ok(resp)
+        Ok(resp.to_vec())
     }
 }
 
@@ -228,3 +233,32 @@ impl<C: Cipher + 'static> From<C> for Box<dyn SecureMessaging> {
         Box::new(Encrypted::new(cipher, 0))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    #[test]
+    fn test_error_status() {
+        let mut plain = PlainText;
+        assert!(matches!(
+            plain.dec_response(StatusWord::FILE_NOT_FOUND, &[]),
+            Err(Error::ErrorResponse(StatusWord::FILE_NOT_FOUND))
+        ));
+        assert_eq!(
+            plain.dec_response(StatusWord::SUCCESS, &[]).unwrap(),
+            Vec::<u8>::new()
+        );
+
+        let seed = hex!("0036D272F5C350ACAC50C3F572D23600");
+        let mut tdes = Encrypted::new(TDesCipher::from_seed(&seed), 0);
+        assert!(matches!(
+            tdes.dec_response(StatusWord::ACCESS_DENIED, &[]),
+            Err(Error::ErrorResponse(StatusWord::ACCESS_DENIED))
+        ));
+        assert!(matches!(
+            tdes.dec_response(StatusWord::SUCCESS, &[]),
+            Err(Error::SMResponseInvalid)
+        ));
+    }
+}
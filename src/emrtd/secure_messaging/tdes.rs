@@ -2,12 +2,13 @@
 
 use {
     super::{Cipher, KDF_ENC, KDF_MAC},
+    crate::crypto::mac::retail_mac_padded,
     cbc::{Decryptor as CbcDec, Encryptor as CbcEnc},
     cipher::{
-        block_padding::NoPadding, BlockDecrypt as _, BlockDecryptMut as _, BlockEncrypt as _,
-        BlockEncryptMut as _, InnerIvInit as _, KeyInit as _,
+        block_padding::NoPadding, BlockDecryptMut as _, BlockEncryptMut as _, InnerIvInit as _,
+        KeyInit as _,
     },
-    des::{Des, TdesEde2},
+    des::TdesEde2,
     sha1::{Digest, Sha1},
 };
 
@@ -52,23 +53,13 @@ impl Cipher for TDesCipher {
     /// Retail MAC (ISO 9797-1 mode 3) using DES.
     // See <https://crypto.stackexchange.com/questions/18951/what-are-options-to-compute-des-retail-mac-aka-iso-9797-1-mode-3-under-pkcs11>
     fn mac(&self, _ssc: u64, data: &[u8]) -> [u8; 8] {
-        assert_eq!(data.len() % BLOCK_SIZE, 0);
-        let des1 = Des::new_from_slice(&self.kmac[..8]).unwrap();
-        let des2 = Des::new_from_slice(&self.kmac[8..]).unwrap();
-        let mut state = [0_u8; 8];
-        for block in data.chunks_exact(8) {
-            for i in 0..8 {
-                state[i] ^= block[i];
-            }
-            des1.encrypt_block((&mut state).into());
-        }
-        des2.decrypt_block((&mut state).into());
-        des1.encrypt_block((&mut state).into());
-        state
+        retail_mac_padded(&self.kmac, data)
     }
 }
 
-fn kdf(seed: &[u8], counter: u32) -> [u8; 16] {
+/// Key Derivation Function (KDF) for 3DES keys with parity bits set.
+/// ICAO 9303-11 section 9.7.1.1
+pub fn kdf(seed: &[u8], counter: u32) -> [u8; 16] {
     let mut hasher = Sha1::new();
     hasher.update(seed);
     hasher.update(counter.to_be_bytes());
@@ -91,7 +82,10 @@ fn set_parity_bits(key: &mut [u8]) {
 mod tests {
     use {
         super::{super::SecureMessaging, *},
-        crate::emrtd::{pad, secure_messaging::Encrypted, seed_from_mrz},
+        crate::{
+            emrtd::{secure_messaging::Encrypted, seed_from_mrz},
+            iso7816::pad_iso7816_2,
+        },
         hex_literal::hex,
     };
 
@@ -123,9 +117,7 @@ mod tests {
                 kenc: *key,
                 kmac: *key,
             };
-            let mut msg = msg.to_vec();
-            pad(&mut msg, 8);
-            cipher.mac(0, &msg)
+            cipher.mac(0, &pad_iso7816_2(msg, 8))
         }
 
         let key = hex!("7962D9ECE03D1ACD4C76089DCE131543");
@@ -0,0 +1,171 @@
+use {
+    super::{chip_authentication::command_apdu, pace::push_length, Emrtd},
+    crate::asn1::cvc::CardVerifiableCertificate,
+    anyhow::{anyhow, ensure, Result},
+};
+
+impl Emrtd {
+    /// Perform Terminal Authentication version 1.
+    ///
+    /// The chain starts with a certificate issued by a CVCA trusted by the chip
+    /// and ends with the terminal certificate. The terminal then signs
+    /// `id_picc || r_picc || ephemeral_public_key` using `sign`, where
+    /// `ephemeral_public_key` is the compressed key from Chip Authentication.
+    /// `id_picc` is the document number for BAC, or the compressed chip
+    /// ephemeral key for PACE.
+    ///
+    /// See ICAO 9303-11 section 7.1 and BSI TR-03110-3 section B.11.
+    pub fn terminal_authenticate(
+        &mut self,
+        cvc_chain: &[CardVerifiableCertificate],
+        id_picc: &[u8],
+        ephemeral_public_key: &[u8],
+        sign: impl FnOnce(&[u8]) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        let terminal = cvc_chain
+            .last()
+            .ok_or_else(|| anyhow!("Empty certificate chain"))?;
+
+        // Let the chip verify the chain, each certificate with the public key
+        // imported from the previous one.
+        for certificate in cvc_chain {
+            self.mse_set_dst(certificate.authority_reference())?;
+            self.pso_verify_certificate(certificate)?;
+        }
+
+        self.mse_set_at_terminal(terminal.holder_reference())?;
+        let r_picc = self.get_challenge()?;
+        let signature = sign(&[id_picc, &r_picc, ephemeral_public_key].concat())?;
+        self.external_authenticate_signature(&signature)
+    }
+
+    /// MSE:Set DST, select the public key to verify the next certificate.
+    ///
+    /// See BSI TR-03110-3 section B.11.4.
+    pub fn mse_set_dst(&mut self, authority_reference: &str) -> Result<()> {
+        let data = reference_data(authority_reference);
+        let (status, _) = self.send_apdu(&command_apdu([0x00, 0x22, 0x81, 0xb6], &data)?)?;
+        ensure!(status.is_success(), "MSE:Set DST failed: {status}");
+        Ok(())
+    }
+
+    /// PSO:Verify Certificate, import the public key of a certificate.
+    ///
+    /// See BSI TR-03110-3 section B.11.5.
+    pub fn pso_verify_certificate(
+        &mut self,
+        certificate: &CardVerifiableCertificate,
+    ) -> Result<()> {
+        let mut data = certificate.body().to_vec();
+        data.extend_from_slice(&[0x5f, 0x37]);
+        push_length(&mut data, certificate.signature().len());
+        data.extend_from_slice(certificate.signature());
+        let (status, _) = self.send_apdu(&command_apdu([0x00, 0x2a, 0x00, 0xbe], &data)?)?;
+        ensure!(
+            status.is_success(),
+            "PSO:Verify Certificate failed: {status}"
+        );
+        Ok(())
+    }
+
+    /// MSE:Set AT, select the terminal key for Terminal Authentication.
+    ///
+    /// See BSI TR-03110-3 section B.11.1.
+    pub fn mse_set_at_terminal(&mut self, holder_reference: &str) -> Result<()> {
+        let data = reference_data(holder_reference);
+        let (status, _) = self.send_apdu(&command_apdu([0x00, 0x22, 0x81, 0xa4], &data)?)?;
+        ensure!(status.is_success(), "MSE:Set AT failed: {status}");
+        Ok(())
+    }
+
+    /// EXTERNAL AUTHENTICATE with the terminal signature.
+    ///
+    /// See BSI TR-03110-3 section B.11.6.
+    pub fn external_authenticate_signature(&mut self, signature: &[u8]) -> Result<()> {
+        let (status, _) = self.send_apdu(&command_apdu([0x00, 0x82, 0x00, 0x00], signature)?)?;
+        ensure!(
+            status.is_success(),
+            "Terminal Authentication failed: {status}"
+        );
+        Ok(())
+    }
+}
+
+/// Public key reference: 0x83 <len> <reference>
+fn reference_data(reference: &str) -> Vec<u8> {
+    let mut data = vec![0x83];
+    push_length(&mut data, reference.len());
+    data.extend_from_slice(reference.as_bytes());
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{iso7816::StatusWord, nfc::mock::MockReader},
+        hex_literal::hex,
+    };
+
+    fn tlv(tag: &[u8], value: &[u8]) -> Vec<u8> {
+        [tag, &[value.len() as u8], value].concat()
+    }
+
+    /// Certificate body and signature, the data of PSO:Verify Certificate.
+    fn certificate_contents(car: &str, chr: &str) -> Vec<u8> {
+        let public_key = [
+            tlv(&[0x06], &hex!("04007F00070202020101")),
+            tlv(&[0x81], &hex!("C1C3")),
+            tlv(&[0x82], &hex!("010001")),
+        ]
+        .concat();
+        let authorization = [
+            tlv(&[0x06], &hex!("04007F000703010201")),
+            tlv(&[0x53], &hex!("E3")),
+        ]
+        .concat();
+        let body = [
+            tlv(&[0x5f, 0x29], &[0]),
+            tlv(&[0x42], car.as_bytes()),
+            tlv(&[0x7f, 0x49], &public_key),
+            tlv(&[0x5f, 0x20], chr.as_bytes()),
+            tlv(&[0x7f, 0x4c], &authorization),
+            tlv(&[0x5f, 0x25], &hex!("020400010105")),
+            tlv(&[0x5f, 0x24], &hex!("020700010105")),
+        ]
+        .concat();
+        [tlv(&[0x7f, 0x4e], &body), tlv(&[0x5f, 0x37], &hex!("aabb"))].concat()
+    }
+
+    fn certificate(car: &str, chr: &str) -> CardVerifiableCertificate {
+        let encoded = tlv(&[0x7f, 0x21], &certificate_contents(car, chr));
+        CardVerifiableCertificate::from_bytes(&encoded).unwrap()
+    }
+
+    #[test]
+    fn test_terminal_authenticate() {
+        let success =
+            |command: Vec<u8>, data: &[u8]| (command, (StatusWord::SUCCESS, data.to_vec()));
+        let verify_certificate = |car, chr| {
+            let data = certificate_contents(car, chr);
+            [&hex!("002A00BE")[..], &[data.len() as u8], &data].concat()
+        };
+        let reader = MockReader::new(vec![
+            success(hex!("002281B6 06 8304 43564341").to_vec(), &[]),
+            success(verify_certificate("CVCA", "DV"), &[]),
+            success(hex!("002281B6 04 8302 4456").to_vec(), &[]),
+            success(verify_certificate("DV", "IS"), &[]),
+            success(hex!("002281A4 04 8302 4953").to_vec(), &[]),
+            success(hex!("0084000008").to_vec(), &hex!("0102030405060708")),
+            success(hex!("00820000 02 5151").to_vec(), &[]),
+        ]);
+        let mut emrtd = Emrtd::new(Box::new(reader));
+        let chain = [certificate("CVCA", "DV"), certificate("DV", "IS")];
+        emrtd
+            .terminal_authenticate(&chain, b"ID", &hex!("aa"), |data| {
+                assert_eq!(data, hex!("4944 0102030405060708 aa"));
+                Ok(hex!("5151").to_vec())
+            })
+            .unwrap();
+    }
+}
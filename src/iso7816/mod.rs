@@ -19,6 +19,9 @@ pub enum Error {
 
     #[error("Invalid Extended APDU: Trailing bytes.")]
     ExtendedApduTooLong,
+
+    #[error("Invalid padding: No 0x80 marker in the last block.")]
+    InvalidPadding,
 }
 
 #[derive(Debug)]
@@ -46,9 +49,70 @@ impl ApduRef<'_> {
         self.header[3]
     }
 
+    /// Class byte uses the first interindustry encoding (`000x xxxx`).
+    /// See ISO 7816-4 section 5.4.1
+    pub fn is_interindustry(&self) -> bool {
+        self.cla() & 0xe0 == 0x00
+    }
+
+    /// Further interindustry class byte (`01xx xxxx`).
+    pub fn is_further_interindustry(&self) -> bool {
+        self.cla() & 0xc0 == 0x40
+    }
+
+    pub fn is_proprietary(&self) -> bool {
+        self.cla() & 0xe0 == 0x80
+    }
+
+    /// Logical channel number, 0 to 3 for the first and 4 to 19 for the
+    /// further interindustry class.
+    pub fn channel(&self) -> u8 {
+        if self.is_further_interindustry() {
+            4 + (self.cla() & 0x0f)
+        } else {
+            self.cla() & 0x03
+        }
+    }
+
+    pub fn has_secure_messaging(&self) -> bool {
+        if self.is_further_interindustry() {
+            self.cla() & 0x20 != 0x00
+        } else {
+            self.cla() & 0x0c != 0x00
+        }
+    }
+
+    /// Command is not followed by further chained commands.
+    pub fn is_command_chaining_last_block(&self) -> bool {
+        self.cla() & 0x10 == 0x00
+    }
+
     pub fn is_extended_length(&self) -> bool {
         self.lc.len() > 1 || self.le.len() > 1
     }
+
+    /// Re-assemble the APDU bytes.
+    pub fn encode(&self) -> Vec<u8> {
+        [self.header, self.lc, self.data, self.le].concat()
+    }
+
+    /// Re-assemble the APDU bytes with a different `Le`.
+    ///
+    /// Keeps short or extended length, an `Le` of 256 (65536 extended) is
+    /// encoded as zero.
+    pub fn encode_with_le(&self, le: usize) -> Vec<u8> {
+        let le = if self.is_extended_length() {
+            let le = (le as u16).to_be_bytes();
+            if self.lc.is_empty() {
+                vec![0x00, le[0], le[1]]
+            } else {
+                le.to_vec()
+            }
+        } else {
+            vec![le as u8]
+        };
+        [self.header, self.lc, self.data, &le].concat()
+    }
 }
 
 /// Parse APDU into header, Lc, data, and Le.
@@ -81,11 +145,11 @@ pub fn parse_apdu(apdu: &[u8]) -> Result<ApduRef, Error> {
         },
         // Extended length with data and maybe Le
         (_, Some(&0x00)) => {
-            let lc = u16::from_be_bytes([apdu[4], apdu[5]]) as usize;
+            let lc = u16::from_be_bytes([apdu[5], apdu[6]]) as usize;
             if lc == 0 {
                 return Err(Error::ExtendedLcZero);
             }
-            if apdu.len() - 7 == lc {
+            if apdu.len() == 7 + lc {
                 // Extended length with data and no Le
                 ApduRef {
                     header: &apdu[..4],
@@ -93,7 +157,7 @@ pub fn parse_apdu(apdu: &[u8]) -> Result<ApduRef, Error> {
                     data:   &apdu[7..],
                     le:     empty,
                 }
-            } else if apdu.len() - 9 == lc {
+            } else if apdu.len() == 9 + lc {
                 // Extended length with data and Le
                 ApduRef {
                     header: &apdu[..4],
@@ -122,3 +186,104 @@ pub fn parse_apdu(apdu: &[u8]) -> Result<ApduRef, Error> {
         _ => return Err(Error::ApduTooLong),
     })
 }
+
+/// Pad `data` with 0x80 followed by zeros to a multiple of `block_size`.
+/// See ISO 7816-4 section 5.6.3 and ISO 9797-1 padding method 2.
+pub fn pad_iso7816_2(data: &[u8], block_size: usize) -> Vec<u8> {
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    padded.resize(padded.len().next_multiple_of(block_size), 0x00);
+    padded
+}
+
+/// Remove padding added by [`pad_iso7816_2`].
+///
+/// Fails if the last non-zero byte is not 0x80 or not in the last block.
+pub fn unpad_iso7816_2(data: &[u8], block_size: usize) -> Result<&[u8], Error> {
+    let marker = data
+        .iter()
+        .rposition(|&byte| byte != 0x00)
+        .ok_or(Error::InvalidPadding)?;
+    if data[marker] != 0x80 || data.len() - marker > block_size {
+        return Err(Error::InvalidPadding);
+    }
+    Ok(&data[..marker])
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    #[test]
+    fn test_parse_extended() {
+        let apdu = hex!("00 B0 0000 000002 0102 0100");
+        let parsed = parse_apdu(&apdu).unwrap();
+        assert!(parsed.is_extended_length());
+        assert_eq!(parsed.lc, hex!("000002"));
+        assert_eq!(parsed.data, hex!("0102"));
+        assert_eq!(parsed.le, hex!("0100"));
+        assert_eq!(parsed.encode(), apdu);
+
+        assert_eq!(
+            parsed.encode_with_le(0x20),
+            hex!("00 B0 0000 000002 0102 0020")
+        );
+        let parsed = parse_apdu(&hex!("00 B0 0000 000100")).unwrap();
+        assert_eq!(parsed.encode_with_le(0x20), hex!("00 B0 0000 000020"));
+        let parsed = parse_apdu(&hex!("00 B0 0000 00")).unwrap();
+        assert_eq!(parsed.encode_with_le(0x20), hex!("00 B0 0000 20"));
+        assert_eq!(parsed.encode_with_le(256), hex!("00 B0 0000 00"));
+
+        assert!(matches!(
+            parse_apdu(&hex!("00 B0 0000 000003 01")),
+            Err(Error::ExtendedApduTooLong)
+        ));
+    }
+
+    #[test]
+    fn test_class() {
+        let parsed = parse_apdu(&hex!("0C B0 0000 00")).unwrap();
+        assert!(parsed.is_interindustry());
+        assert!(!parsed.is_proprietary());
+        assert!(parsed.has_secure_messaging());
+        assert!(parsed.is_command_chaining_last_block());
+        assert_eq!(parsed.channel(), 0);
+
+        let parsed = parse_apdu(&hex!("13 B0 0000 00")).unwrap();
+        assert!(!parsed.has_secure_messaging());
+        assert!(!parsed.is_command_chaining_last_block());
+        assert_eq!(parsed.channel(), 3);
+
+        let parsed = parse_apdu(&hex!("65 B0 0000 00")).unwrap();
+        assert!(!parsed.is_interindustry());
+        assert!(parsed.has_secure_messaging());
+        assert_eq!(parsed.channel(), 9);
+
+        let parsed = parse_apdu(&hex!("80 CA 0000 00")).unwrap();
+        assert!(parsed.is_proprietary());
+        assert!(!parsed.has_secure_messaging());
+    }
+
+    #[test]
+    fn test_padding() {
+        assert_eq!(pad_iso7816_2(&[], 8), hex!("80 00000000000000"));
+        assert_eq!(
+            pad_iso7816_2(&hex!("01020304050607"), 8),
+            hex!("0102030405060780")
+        );
+        assert_eq!(
+            pad_iso7816_2(&hex!("0102030405060708"), 8),
+            hex!("0102030405060708 80 00000000000000")
+        );
+        for len in 0..40 {
+            let data = vec![0x42; len];
+            let padded = pad_iso7816_2(&data, 16);
+            assert_eq!(padded.len() % 16, 0);
+            assert_eq!(unpad_iso7816_2(&padded, 16).unwrap(), data);
+        }
+
+        assert!(unpad_iso7816_2(&[], 8).is_err());
+        assert!(unpad_iso7816_2(&hex!("0102030405060708"), 8).is_err());
+        assert!(unpad_iso7816_2(&hex!("80 0000000000000000"), 8).is_err());
+    }
+}
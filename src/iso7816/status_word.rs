@@ -6,54 +6,81 @@ use std::fmt::{self, Display, Formatter};
 pub struct StatusWord(u16);
 
 impl StatusWord {
-    pub const SUCCESS: StatusWord = StatusWord(0x9000);
-    pub const FILE_NOT_FOUND: StatusWord = StatusWord(0x6a82);
-    pub const ACCESS_DENIED: StatusWord = StatusWord(0x6982);
+    pub const SUCCESS: Self = Self(0x9000);
+    pub const FILE_NOT_FOUND: Self = Self(0x6a82);
+    pub const ACCESS_DENIED: Self = Self(0x6982);
+    pub const SECURE_MESSAGING_NOT_SUPPORTED: Self = Self(0x6882);
 
-    pub const SECURE_MESSAGING_INCOMPLETE: StatusWord = StatusWord(0x6987);
-    pub const SECURE_MESSAGING_INCORRECT: StatusWord = StatusWord(0x6988);
+    pub const SECURE_MESSAGING_INCOMPLETE: Self = Self(0x6987);
+    pub const SECURE_MESSAGING_INCORRECT: Self = Self(0x6988);
 
-    pub fn sw1(self) -> u8 {
+    pub const fn sw1(self) -> u8 {
         (self.0 >> 8) as u8
     }
 
-    pub fn sw2(self) -> u8 {
+    pub const fn sw2(self) -> u8 {
         (self.0 & 0xff) as u8
     }
 
-    pub fn is_success(self) -> bool {
+    pub const fn is_success(self) -> bool {
         matches!(self.0, 0x9000 | 0x6100..=0x61ff)
     }
 
-    pub fn data_remaining(self) -> Option<usize> {
+    pub const fn data_remaining(self) -> Option<usize> {
         match self.0 {
             0x6100..=0x61ff => Some(self.0 as usize & 0xff),
             _ => None,
         }
     }
 
-    pub fn is_valid(self) -> bool {
+    /// The correct `Le` from a `6Cxx` wrong length response.
+    pub const fn wrong_le(self) -> Option<usize> {
+        match self.0 {
+            0x6c00 => Some(256),
+            0x6c01..=0x6cff => Some(self.0 as usize & 0xff),
+            _ => None,
+        }
+    }
+
+    /// Whether the command can be repeated as is or with a corrected `Le`,
+    /// or the response fetched with GET RESPONSE.
+    pub const fn is_retry_possible(self) -> bool {
+        matches!(self.0, 0x6100..=0x61ff | 0x6700 | 0x6c00..=0x6cff)
+    }
+
+    /// Whether the secure messaging session is lost and the chip needs to be
+    /// authenticated again.
+    pub const fn requires_reauth(self) -> bool {
+        matches!(self.0, 0x6982 | 0x6987 | 0x6988)
+    }
+
+    /// Whether the authentication method is blocked, retrying will not help.
+    pub const fn is_fatal(self) -> bool {
+        self.0 == 0x6983
+    }
+
+    pub const fn is_valid(self) -> bool {
         matches!(self.0, 0x6100..=0x6FFF | 0x9000..=0x9FFF)
     }
 
-    pub fn is_warning(self) -> bool {
+    pub const fn is_warning(self) -> bool {
         matches!(self.0, 0x6200..=0x63ff)
     }
 
     /// Note: If the this is the status, the data must be absent.
-    pub fn is_error(self) -> bool {
+    pub const fn is_error(self) -> bool {
         matches!(self.0, 0x6400..=0x6fff)
     }
 
-    pub fn is_execution_error(self) -> bool {
+    pub const fn is_execution_error(self) -> bool {
         matches!(self.0, 0x6400..=0x65ff)
     }
 
-    pub fn is_checking_error(self) -> bool {
+    pub const fn is_checking_error(self) -> bool {
         matches!(self.0, 0x6700..=0x6fff)
     }
 
-    pub fn class_as_str(self) -> &'static str {
+    pub const fn class_as_str(self) -> &'static str {
         match self.0 {
             0x9000 | 0x6100..=0x61ff => "Success",
             0x6200..=0x63ff => "Warning",
@@ -64,7 +91,7 @@ impl StatusWord {
         }
     }
 
-    pub fn as_str(self) -> &'static str {
+    pub const fn as_str(self) -> &'static str {
         // See ISO/IEC 7816-4 section 5.6
         #[allow(clippy::match_overlapping_arm)] // Used for catch-alls
         match self.0 {
@@ -181,7 +208,27 @@ impl From<u16> for StatusWord {
 }
 
 impl From<StatusWord> for u16 {
-    fn from(value: StatusWord) -> u16 {
+    fn from(value: StatusWord) -> Self {
         value.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery() {
+        for sw in [0x6110, 0x6700, 0x6c20] {
+            assert!(StatusWord(sw).is_retry_possible(), "{sw:04X}");
+        }
+        assert!(!StatusWord(0x6701).is_retry_possible());
+        assert!(StatusWord::ACCESS_DENIED.requires_reauth());
+        assert!(StatusWord::SECURE_MESSAGING_INCOMPLETE.requires_reauth());
+        assert!(StatusWord::SECURE_MESSAGING_INCORRECT.requires_reauth());
+        assert!(!StatusWord::FILE_NOT_FOUND.requires_reauth());
+        assert!(StatusWord(0x6983).is_fatal());
+        assert!(!StatusWord(0x6983).requires_reauth());
+        assert!(!StatusWord::SUCCESS.is_fatal());
+    }
+}
@@ -0,0 +1,205 @@
+//! Minimal LDIF parser for the ICAO PKD downloads.
+//!
+//! Supports the entry format of RFC 2849 with folded lines and base64
+//! values. Change records and URL values are not supported.
+
+use {
+    crate::asn1::master_list::MasterList,
+    anyhow::{anyhow, bail, ensure, Result},
+    base64::{engine::general_purpose::STANDARD, Engine},
+    cms::cert::x509::Certificate,
+    der::{Decode, Sequence},
+    std::{
+        collections::HashMap,
+        io::{BufRead, BufReader, Read},
+    },
+};
+
+/// A single LDIF entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LdifEntry {
+    pub dn:         String,
+    /// Attribute values by attribute name as written in the file.
+    pub attributes: HashMap<String, Vec<Vec<u8>>>,
+}
+
+/// Master lists and CSCA certificates from ICAO PKD LDIF files.
+pub struct MasterListBundle;
+
+/// RFC 4523 `CertificatePair`.
+#[derive(Sequence)]
+struct CertificatePair {
+    #[asn1(context_specific = "0", optional = "true")]
+    forward: Option<Certificate>,
+    #[asn1(context_specific = "1", optional = "true")]
+    reverse: Option<Certificate>,
+}
+
+impl LdifEntry {
+    /// Values of an attribute.
+    ///
+    /// Names match case-insensitively and options such as `;binary` are
+    /// ignored.
+    pub fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a [u8]> + 'a {
+        self.attributes
+            .iter()
+            .filter(move |(key, _)| {
+                let base = key.split(';').next().unwrap_or_default();
+                base.eq_ignore_ascii_case(name)
+            })
+            .flat_map(|(_, values)| values.iter().map(Vec::as_slice))
+    }
+}
+
+impl MasterListBundle {
+    /// Decode the master lists in a PKD master list LDIF file.
+    ///
+    /// These are the `pkdMasterListContent` attributes.
+    pub fn from_ldif(reader: impl Read) -> Result<Vec<MasterList>> {
+        let mut master_lists = Vec::new();
+        for entry in parse(reader)? {
+            for value in entry.values("pkdMasterListContent") {
+                master_lists.push(MasterList::from_der(value)?);
+            }
+        }
+        Ok(master_lists)
+    }
+
+    /// Decode the certificates in the `cACertificate` and
+    /// `crossCertificatePair` attributes of an LDIF file.
+    pub fn certificates_from_ldif(reader: impl Read) -> Result<Vec<Certificate>> {
+        let mut certificates = Vec::new();
+        for entry in parse(reader)? {
+            for value in entry.values("cACertificate") {
+                certificates.push(Certificate::from_der(value)?);
+            }
+            for value in entry.values("crossCertificatePair") {
+                let pair = CertificatePair::from_der(value)?;
+                certificates.extend(pair.forward);
+                certificates.extend(pair.reverse);
+            }
+        }
+        Ok(certificates)
+    }
+}
+
+/// Parse all entries of an LDIF file.
+pub fn parse(reader: impl Read) -> Result<Vec<LdifEntry>> {
+    let mut entries = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        if let Some(continuation) = line.strip_prefix(' ') {
+            lines
+                .last_mut()
+                .ok_or_else(|| anyhow!("Continuation line without preceding line"))?
+                .push_str(continuation);
+        } else if line.is_empty() {
+            entries.extend(parse_entry(&lines)?);
+            lines.clear();
+        } else {
+            lines.push(line);
+        }
+    }
+    entries.extend(parse_entry(&lines)?);
+    Ok(entries)
+}
+
+/// Parse the unfolded lines of a record, `None` if it has no entry.
+fn parse_entry(lines: &[String]) -> Result<Option<LdifEntry>> {
+    let mut lines = lines
+        .iter()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| parse_line(line))
+        .peekable();
+    if let Some(Ok(("version", _))) = lines.peek() {
+        lines.next();
+    }
+    let Some((name, dn)) = lines.next().transpose()? else {
+        return Ok(None);
+    };
+    ensure!(name.eq_ignore_ascii_case("dn"), "Entry must start with dn");
+    let mut entry = LdifEntry {
+        dn: String::from_utf8(dn)?,
+        ..LdifEntry::default()
+    };
+    for line in lines {
+        let (name, value) = line?;
+        entry
+            .attributes
+            .entry(name.to_string())
+            .or_default()
+            .push(value);
+    }
+    Ok(Some(entry))
+}
+
+/// Parse an unfolded `name: value` or `name:: base64` line.
+fn parse_line(line: &str) -> Result<(&str, Vec<u8>)> {
+    let (name, value) = line
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Missing ':' in LDIF line"))?;
+    let value = if let Some(value) = value.strip_prefix(':') {
+        STANDARD.decode(value.trim())?
+    } else if value.starts_with('<') {
+        bail!("URL values are not supported")
+    } else {
+        value.trim_start_matches(' ').as_bytes().to_vec()
+    };
+    Ok((name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let ldif = concat!(
+            "version: 1\n",
+            "\n",
+            "# A comment\n",
+            "  that is folded\n",
+            "dn: cn=CSCA,c=NL,dc=data\n",
+            "objectClass: top\n",
+            "objectClass: person\n",
+            "description: folded\n",
+            " value\n",
+            "userCertificate;binary:: AAEC\n",
+            " Aw==\n",
+            "\n",
+            "\n",
+            "dn:: Y249RGVtbw==\r\n",
+            "cn: Demo\r\n",
+        );
+        let entries = parse(ldif.as_bytes()).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let entry = &entries[0];
+        assert_eq!(entry.dn, "cn=CSCA,c=NL,dc=data");
+        assert_eq!(entry.attributes["objectClass"], [
+            b"top".to_vec(),
+            b"person".to_vec()
+        ]);
+        assert_eq!(entry.attributes["description"], [b"foldedvalue".to_vec()]);
+        assert_eq!(entry.attributes["userCertificate;binary"], [vec![
+            0, 1, 2, 3
+        ]]);
+        assert_eq!(entry.values("USERCERTIFICATE").collect::<Vec<_>>(), [[
+            0, 1, 2, 3
+        ]]);
+
+        assert_eq!(entries[1].dn, "cn=Demo");
+        assert_eq!(entries[1].attributes["cn"], [b"Demo".to_vec()]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&b" leading continuation\n"[..]).is_err());
+        assert!(parse(&b"cn: no dn\n"[..]).is_err());
+        assert!(parse(&b"dn: cn=a\nno separator\n"[..]).is_err());
+        assert!(parse(&b"dn: cn=a\ncn:< file:///etc/passwd\n"[..]).is_err());
+        assert!(parse(&b"dn: cn=a\ncn:: !!!\n"[..]).is_err());
+        assert!(parse(&b""[..]).unwrap().is_empty());
+    }
+}
@@ -1,8 +1,17 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(dead_code)] // While still under rapid development
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod asn1;
 pub mod crypto;
+#[cfg(feature = "std")]
 pub mod emrtd;
+#[cfg(feature = "std")]
 pub mod iso7816;
+#[cfg(feature = "std")]
+pub mod ldif;
+#[cfg(feature = "std")]
 pub mod nfc;
 pub mod utils;
@@ -0,0 +1,82 @@
+//! Asynchronous NFC readers.
+
+use {
+    super::{CardType, NfcReader},
+    crate::iso7816::StatusWord,
+    anyhow::{anyhow, Result},
+    async_trait::async_trait,
+    std::sync::{Arc, Mutex},
+};
+
+/// Asynchronous version of [`NfcReader`].
+#[async_trait]
+pub trait AsyncNfcReader: Send + Sync {
+    async fn connect(&mut self) -> Result<Option<CardType>>;
+    async fn disconnect(&mut self) -> Result<()>;
+    async fn send_apdu(&mut self, apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)>;
+}
+
+/// Adapts a blocking [`NfcReader`] by running it on the Tokio blocking thread
+/// pool, so it does not stall the async runtime.
+pub struct BlockingNfcReader<R: NfcReader> {
+    inner: Arc<Mutex<R>>,
+}
+
+impl<R: NfcReader + Send + 'static> BlockingNfcReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(inner)),
+        }
+    }
+
+    async fn run<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut R) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || {
+            let mut reader = inner
+                .lock()
+                .map_err(|_| anyhow!("NFC reader poisoned by an earlier panic"))?;
+            f(&mut reader)
+        })
+        .await?
+    }
+}
+
+#[async_trait]
+impl<R: NfcReader + Send + 'static> AsyncNfcReader for BlockingNfcReader<R> {
+    async fn connect(&mut self) -> Result<Option<CardType>> {
+        self.run(|reader| reader.connect()).await
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.run(|reader| reader.disconnect()).await
+    }
+
+    async fn send_apdu(&mut self, apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)> {
+        let apdu = apdu.to_vec();
+        self.run(move |reader| reader.send_apdu(&apdu)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::nfc::mock::MockReader, tokio::runtime::Builder};
+
+    #[test]
+    fn test_blocking_reader() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let mut reader = BlockingNfcReader::new(MockReader::new(vec![(
+            vec![0x00, 0x84, 0x00, 0x00],
+            (StatusWord::SUCCESS, vec![0x01, 0x02]),
+        )]));
+        runtime.block_on(async {
+            assert_eq!(reader.connect().await.unwrap(), None);
+            let (status, data) = reader.send_apdu(&[0x00, 0x84, 0x00, 0x00]).await.unwrap();
+            assert_eq!(status, StatusWord::SUCCESS);
+            assert_eq!(data, [0x01, 0x02]);
+            reader.disconnect().await.unwrap();
+        });
+    }
+}
@@ -0,0 +1,84 @@
+//! Scripted reader for testing card interactions without hardware.
+
+use {
+    super::{CardType, NfcReader},
+    crate::iso7816::StatusWord,
+    anyhow::{anyhow, ensure, Result},
+    std::collections::VecDeque,
+};
+
+/// An APDU command and the response the card gives to it.
+pub type Exchange = (Vec<u8>, (StatusWord, Vec<u8>));
+
+/// Reader that replays a script of APDU exchanges.
+///
+/// Every command sent must match the next command in the script, otherwise
+/// [`NfcReader::send_apdu`] fails. Commands after the end of the script fail
+/// too.
+pub struct MockReader {
+    script: VecDeque<Exchange>,
+}
+
+impl MockReader {
+    pub fn new(script: Vec<Exchange>) -> Self {
+        Self {
+            script: script.into(),
+        }
+    }
+
+    /// Number of exchanges not yet sent.
+    pub fn remaining(&self) -> usize {
+        self.script.len()
+    }
+}
+
+impl NfcReader for MockReader {
+    fn connect(&mut self) -> Result<Option<CardType>> {
+        Ok(None)
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn send_apdu(&mut self, apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)> {
+        let (command, response) = self
+            .script
+            .pop_front()
+            .ok_or_else(|| anyhow!("Unexpected APDU {}, script exhausted", hex::encode(apdu)))?;
+        ensure!(
+            command == apdu,
+            "Unexpected APDU {}, expected {}",
+            hex::encode(apdu),
+            hex::encode(command)
+        );
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, hex_literal::hex};
+
+    #[test]
+    fn test_mock_reader() {
+        let mut reader = MockReader::new(vec![
+            (
+                hex!("0084000008").to_vec(),
+                (StatusWord::SUCCESS, vec![1; 8]),
+            ),
+            (
+                hex!("00A4020C02011E").to_vec(),
+                (StatusWord::FILE_NOT_FOUND, vec![]),
+            ),
+        ]);
+        assert_eq!(
+            reader.send_apdu(&hex!("0084000008")).unwrap(),
+            (StatusWord::SUCCESS, vec![1; 8])
+        );
+        assert_eq!(reader.remaining(), 1);
+        assert!(reader.send_apdu(&hex!("00A4020C02011D")).is_err());
+        assert_eq!(reader.remaining(), 0);
+        assert!(reader.send_apdu(&hex!("0084000008")).is_err());
+    }
+}
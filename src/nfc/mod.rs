@@ -1,6 +1,23 @@
-mod proxmark3;
+#[cfg(feature = "async")]
+mod async_reader;
+pub mod mock;
+pub mod proxmark3;
 
-use {crate::iso7816::StatusWord, anyhow::Result};
+#[cfg(feature = "async")]
+pub use self::async_reader::{AsyncNfcReader, BlockingNfcReader};
+#[cfg(feature = "proxmark3")]
+use std::{env, fs::File};
+use {
+    crate::iso7816::{parse_apdu, StatusWord},
+    anyhow::{bail, Result},
+    std::{
+        io::Write,
+        thread,
+        time::{Duration, Instant},
+    },
+    thiserror::Error,
+    tracing::{debug, warn},
+};
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum CardType {
@@ -37,6 +54,102 @@ pub struct CardTypeB {
     cid: u8,
 }
 
+/// Answer to Request, Type B.
+///
+/// See ISO/IEC 14443-3 section 7.9.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AtqB {
+    /// Pseudo-Unique PICC Identifier.
+    pub pupi:             [u8; 4],
+    pub application_data: [u8; 4],
+    pub protocol_info:    ProtocolInfo,
+}
+
+/// Protocol Info field of the ATQB.
+///
+/// See ISO/IEC 14443-3 section 7.9.4.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ProtocolInfo {
+    /// Supported bit rates in each direction.
+    pub bit_rate_capability: u8,
+    /// Maximum frame size code, see [`ProtocolInfo::max_frame_size`].
+    pub max_frame_size_code: u8,
+    pub protocol_type:       u8,
+    /// Frame Waiting time Integer.
+    pub fwi:                 u8,
+    /// Application Data Coding.
+    pub adc:                 u8,
+    /// Frame Options, NAD and CID support.
+    pub fo:                  u8,
+    /// Start-up Frame Guard time Integer, only in the extended ATQB.
+    pub sfgi:                Option<u8>,
+}
+
+impl CardTypeB {
+    /// Parse the ATQB, the PUPI is the UID of the card.
+    pub fn parsed_atqb(&self) -> Result<AtqB> {
+        AtqB::from_bytes(&[&[0x50], self.uid.as_slice(), &self.atqb].concat())
+    }
+}
+
+impl AtqB {
+    /// Parse an ATQB response, starting with the `0x50` byte.
+    pub fn from_bytes(atqb: &[u8]) -> Result<Self> {
+        let (&[0x50, p0, p1, p2, p3, a0, a1, a2, a3, i0, i1, i2], extended) =
+            (atqb.get(..12).unwrap_or_default(), atqb.get(12..))
+        else {
+            bail!("Invalid ATQB: {}", hex::encode(atqb));
+        };
+        let sfgi = match extended {
+            Some(&[byte]) => Some(byte >> 4),
+            Some([]) => None,
+            _ => bail!("Invalid ATQB length: {}", atqb.len()),
+        };
+        Ok(Self {
+            pupi:             [p0, p1, p2, p3],
+            application_data: [a0, a1, a2, a3],
+            protocol_info:    ProtocolInfo {
+                bit_rate_capability: i0,
+                max_frame_size_code: i1 >> 4,
+                protocol_type: i1 & 0x0f,
+                fwi: i2 >> 4,
+                adc: (i2 >> 2) & 0x03,
+                fo: i2 & 0x03,
+                sfgi,
+            },
+        })
+    }
+}
+
+impl ProtocolInfo {
+    /// Whether the card supports the ISO/IEC 14443-4 transmission protocol,
+    /// required for ISO/IEC 7816-4 APDUs.
+    pub const fn is_iso14443_4_compliant(&self) -> bool {
+        self.protocol_type & 0x09 == 0x01
+    }
+
+    /// Maximum frame size the card accepts in bytes.
+    pub const fn max_frame_size(&self) -> usize {
+        match self.max_frame_size_code {
+            0 => 16,
+            1 => 24,
+            2 => 32,
+            3 => 40,
+            4 => 48,
+            5 => 64,
+            6 => 96,
+            7 => 128,
+            _ => 256,
+        }
+    }
+}
+
+/// Transient failure exchanging data with the card, for example due to RF
+/// noise or a timeout. Readers return this so it can be retried.
+#[derive(Clone, Copy, Debug, Error)]
+#[error("Card exchange failed.")]
+pub struct CardExchangeFailed;
+
 pub trait NfcReader {
     // TODO: Should return card info, and reader/card capabilities like extended
     // length.
@@ -45,6 +158,281 @@ pub trait NfcReader {
     fn send_apdu(&mut self, apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)>;
 }
 
+/// Wraps an [`NfcReader`] and traces every APDU exchange.
+///
+/// Commands and responses are emitted as `tracing` debug events. Optionally
+/// every exchange is also written as a hex dump to a [`Write`] sink, one line
+/// per APDU prefixed with `>` for commands and `<` for responses.
+pub struct TracingReader<R: NfcReader> {
+    inner: R,
+    dump:  Option<Box<dyn Write>>,
+}
+
+impl<R: NfcReader> TracingReader<R> {
+    pub const fn new(inner: R) -> Self {
+        Self { inner, dump: None }
+    }
+
+    /// Also write a hex dump of every APDU to `dump`.
+    pub fn with_dump(mut self, dump: impl Write + 'static) -> Self {
+        self.dump = Some(Box::new(dump));
+        self
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn dump(&mut self, prefix: char, bytes: &[u8]) {
+        if let Some(dump) = self.dump.as_mut() {
+            // Tracing must not interfere with the exchange, so errors are ignored.
+            let _ = writeln!(dump, "{prefix} {}", hex::encode(bytes));
+        }
+    }
+}
+
+impl<R: NfcReader> NfcReader for TracingReader<R> {
+    fn connect(&mut self) -> Result<Option<CardType>> {
+        let card = self.inner.connect()?;
+        debug!(?card, "NFC connect");
+        Ok(card)
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        debug!("NFC disconnect");
+        self.inner.disconnect()
+    }
+
+    fn send_apdu(&mut self, apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)> {
+        match parse_apdu(apdu) {
+            Ok(command) => debug!(
+                cla = format_args!("{:02X}", command.cla()),
+                ins = format_args!("{:02X}", command.ins()),
+                p1 = format_args!("{:02X}", command.p1()),
+                p2 = format_args!("{:02X}", command.p2()),
+                sm = command.has_secure_messaging(),
+                lc = command.data.len(),
+                data = hex::encode(command.data),
+                le = hex::encode(command.le),
+                "APDU command"
+            ),
+            Err(error) => debug!(apdu = hex::encode(apdu), %error, "Malformed APDU command"),
+        }
+        self.dump('>', apdu);
+
+        let start = Instant::now();
+        let result = self.inner.send_apdu(apdu);
+        let elapsed = start.elapsed();
+
+        match &result {
+            Ok((status, data)) => {
+                debug!(%status, data = hex::encode(data), ?elapsed, "APDU response");
+                let response = [data.as_slice(), &[status.sw1(), status.sw2()]].concat();
+                self.dump('<', &response);
+            }
+            Err(error) => debug!(%error, ?elapsed, "APDU exchange failed"),
+        }
+        result
+    }
+}
+
+/// Wraps an [`NfcReader`] and retries APDUs on transient reader errors.
+///
+/// A [`CardExchangeFailed`] error makes the reader reconnect to the card and
+/// re-send the APDU, up to `retries` times with exponential back-off. Note
+/// that reconnecting resets the card, including any secure messaging session.
+///
+/// All other errors are propagated immediately. Status words are never
+/// retried: in particular on `SECURE_MESSAGING_INCOMPLETE` and
+/// `SECURE_MESSAGING_INCORRECT` the chip has already aborted the session, so
+/// re-sending would only corrupt the session state further.
+pub struct RetryReader<R: NfcReader> {
+    inner:   R,
+    retries: usize,
+    backoff: Duration,
+}
+
+impl<R: NfcReader> RetryReader<R> {
+    pub const fn new(inner: R) -> Self {
+        Self {
+            inner,
+            retries: 3,
+            backoff: Duration::from_millis(100),
+        }
+    }
+
+    /// Maximum number of retries per APDU.
+    pub const fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled on every subsequent retry.
+    pub const fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: NfcReader> NfcReader for RetryReader<R> {
+    fn connect(&mut self) -> Result<Option<CardType>> {
+        self.inner.connect()
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        self.inner.disconnect()
+    }
+
+    fn send_apdu(&mut self, apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)> {
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match self.inner.send_apdu(apdu) {
+                Err(error) if error.is::<CardExchangeFailed>() && attempt < self.retries => {
+                    attempt += 1;
+                    warn!(%error, attempt, "Card exchange failed, reconnecting");
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                    self.inner.connect()?;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Connect to the first available reader.
+///
+/// If the `APDU_TRACE` environment variable is set, the reader is wrapped in a
+/// [`TracingReader`]. A value other than `1` is used as the path of a file to
+/// write a hex dump of all APDUs to.
+#[cfg(feature = "proxmark3")]
 pub fn connect_reader() -> Result<Box<dyn NfcReader>> {
-    Ok(Box::new(proxmark3::Proxmark3::new()?))
+    let reader = proxmark3::Proxmark3::new()?;
+    Ok(match env::var("APDU_TRACE") {
+        Ok(path) if !path.is_empty() && path != "1" => {
+            Box::new(TracingReader::new(reader).with_dump(File::create(path)?))
+        }
+        Ok(_) => Box::new(TracingReader::new(reader)),
+        Err(_) => Box::new(reader),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        hex_literal::hex,
+        mock::MockReader,
+        std::{cell::RefCell, io, rc::Rc},
+    };
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_tracing_reader() {
+        let buffer = SharedBuffer::default();
+        let mock = MockReader::new(vec![(
+            hex!("00A4020C02011E").to_vec(),
+            (StatusWord::SUCCESS, vec![0x01, 0x1e]),
+        )]);
+        let mut reader = TracingReader::new(mock).with_dump(buffer.clone());
+        let (status, data) = reader
+            .send_apdu(&[0x00, 0xa4, 0x02, 0x0c, 0x02, 0x01, 0x1e])
+            .unwrap();
+        assert_eq!(status, StatusWord::SUCCESS);
+        assert_eq!(data, [0x01, 0x1e]);
+        let dump = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+        assert_eq!(dump, "> 00a4020c02011e\n< 011e9000\n");
+    }
+
+    /// Reader that fails the first `failures` exchanges.
+    #[derive(Default)]
+    struct FlakyReader {
+        failures: usize,
+        connects: usize,
+    }
+
+    impl NfcReader for FlakyReader {
+        fn connect(&mut self) -> Result<Option<CardType>> {
+            self.connects += 1;
+            Ok(None)
+        }
+
+        fn disconnect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn send_apdu(&mut self, _apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(CardExchangeFailed.into());
+            }
+            Ok((StatusWord::SECURE_MESSAGING_INCOMPLETE, vec![]))
+        }
+    }
+
+    #[test]
+    fn test_retry_reader() {
+        let flaky = FlakyReader {
+            failures: 2,
+            ..Default::default()
+        };
+        let mut reader = RetryReader::new(flaky).with_backoff(Duration::ZERO);
+        let (status, _) = reader.send_apdu(&[0x00, 0xb0, 0x00, 0x00, 0x00]).unwrap();
+        assert_eq!(status, StatusWord::SECURE_MESSAGING_INCOMPLETE);
+        assert_eq!(reader.into_inner().connects, 2);
+
+        let flaky = FlakyReader {
+            failures: 2,
+            ..Default::default()
+        };
+        let mut reader = RetryReader::new(flaky)
+            .with_retries(1)
+            .with_backoff(Duration::ZERO);
+        let error = reader
+            .send_apdu(&[0x00, 0xb0, 0x00, 0x00, 0x00])
+            .unwrap_err();
+        assert!(error.is::<CardExchangeFailed>());
+    }
+
+    #[test]
+    fn test_atqb() {
+        let atqb = AtqB::from_bytes(&hex!("50 01020304 00000000 008171")).unwrap();
+        assert_eq!(atqb.pupi, [1, 2, 3, 4]);
+        let info = atqb.protocol_info;
+        assert!(info.is_iso14443_4_compliant());
+        assert_eq!(info.max_frame_size(), 256);
+        assert_eq!((info.fwi, info.adc, info.fo, info.sfgi), (7, 0, 1, None));
+
+        let card = CardTypeB {
+            uid:     vec![1, 2, 3, 4],
+            atqb:    hex!("00000000 000071").to_vec(),
+            chip_id: 0,
+            cid:     0,
+        };
+        let atqb = card.parsed_atqb().unwrap();
+        assert!(!atqb.protocol_info.is_iso14443_4_compliant());
+        assert_eq!(atqb.protocol_info.max_frame_size(), 16);
+
+        let extended = AtqB::from_bytes(&hex!("50 01020304 00000000 008171 80"));
+        assert_eq!(extended.unwrap().protocol_info.sfgi, Some(8));
+        assert!(AtqB::from_bytes(&hex!("50 01020304 00000000 0081")).is_err());
+        assert!(AtqB::from_bytes(&hex!("51 01020304 00000000 008171")).is_err());
+    }
 }
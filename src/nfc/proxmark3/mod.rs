@@ -7,15 +7,18 @@ mod usb; // TODO: BLE
 
 use {
     self::usb::UsbConnection,
-    super::{CardType, CardTypeA, CardTypeB, NfcReader},
+    super::{CardExchangeFailed, CardType, CardTypeA, CardTypeB, NfcReader},
     crate::iso7816::StatusWord,
     anyhow::{bail, ensure, Result},
     bytes::{Buf, BufMut, BytesMut},
     crc::{Crc, CRC_16_ISO_IEC_14443_3_A},
-    std::array,
+    std::{array, io, time::Duration},
+    thiserror::Error,
+    tracing::{debug, trace},
 };
 
 #[repr(u16)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Command {
     DebugPrintString = 0x0100, // Used for error responses.
 
@@ -39,18 +42,64 @@ pub enum Status {
     CardExchangeFailed = -18,
 }
 
+/// Bits of the `capabilities_t` flags that gate commands.
+/// See https://github.com/RfidResearchGroup/proxmark3/blob/55ef252a5d0d590026a4959a4c1b7a6028d1ad13/include/pm3_cmd.h#L174
+const CAPABILITY_ISO14443A: u32 = 1 << 14;
+const CAPABILITY_ISO14443B: u32 = 1 << 15;
+
 pub struct Proxmark3 {
     connection:   Box<dyn Connection>,
     crc:          bool,
-    trace:        bool,
     current_card: Option<CardType>,
+    capabilities: u32,
+}
+
+/// Firmware version of the Proxmark3 OS image.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Proxmark3Version {
+    /// Commit hash the firmware was built from.
+    pub git_hash:      String,
+    /// Firmware flavour, branch and version, e.g. `Iceman/master/v4.17768`.
+    pub os_type:       String,
+    /// Build date and time.
+    pub firmware_date: String,
+}
+
+/// A connected Proxmark3 USB device, see [`Proxmark3::list_devices`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DeviceInfo {
+    pub bus:        u8,
+    pub address:    u8,
+    pub vendor_id:  u16,
+    pub product_id: u16,
+    /// USB serial number, `None` if the device could not be opened.
+    pub serial:     Option<String>,
+}
+
+/// Failure of the physical connection to the Proxmark3.
+///
+/// Errors returned by [`Proxmark3`], including those from
+/// [`NfcReader::send_apdu`], can be downcast to this type.
+#[derive(Debug, Error)]
+pub enum ConnectionError {
+    #[error("Proxmark3 disconnected.")]
+    Disconnected,
+
+    #[error("Proxmark3 did not respond within {0:?}.")]
+    Timeout(Duration),
+
+    #[error("Proxmark3 response CRC mismatch (expected {expected:04X}, got {actual:04X}).")]
+    Crc { expected: u16, actual: u16 },
+
+    #[error("Proxmark3 I/O error: {0}")]
+    Io(#[from] io::Error),
 }
 
 /// Connection to a Proxmark3 UART interface.
 trait Connection {
-    fn read(&mut self, buffer: &mut [u8]) -> Result<()>;
-    fn write(&mut self, data: &[u8]) -> Result<()>;
-    fn close(self) -> Result<()>;
+    fn read(&mut self, buffer: &mut [u8]) -> Result<(), ConnectionError>;
+    fn write(&mut self, data: &[u8]) -> Result<(), ConnectionError>;
+    fn close(self) -> Result<(), ConnectionError>;
 }
 
 impl Proxmark3 {
@@ -62,6 +111,20 @@ impl Proxmark3 {
         Ok(proxmark3)
     }
 
+    /// Connect to the Proxmark3 with the given USB serial number, for use
+    /// when more than one is connected.
+    pub fn new_with_serial(serial: &str) -> Result<Self> {
+        let connection = UsbConnection::new_with_serial(serial)?;
+        let mut proxmark3 = Self::from_connection(Box::new(connection));
+        proxmark3.test_connection()?;
+        Ok(proxmark3)
+    }
+
+    /// List the connected Proxmark3 devices.
+    pub fn list_devices() -> Result<Vec<DeviceInfo>> {
+        usb::list_devices()
+    }
+
     pub fn close(mut self) -> Result<()> {
         self.send_command_ng(Command::QuitSession, &[])?;
         // self.connection.close()?;
@@ -72,8 +135,43 @@ impl Proxmark3 {
         Proxmark3 {
             connection,
             crc: true,
-            trace: false,
             current_card: None,
+            capabilities: 0,
+        }
+    }
+
+    /// Query the firmware version.
+    pub fn version(&mut self) -> Result<Proxmark3Version> {
+        Proxmark3Version::parse(&self.version_string()?)
+    }
+
+    /// Query the raw firmware version string.
+    fn version_string(&mut self) -> Result<String> {
+        self.send_command_ng(Command::Version, &[])?;
+        let (status, cmd, response) = self.receive_response()?;
+        ensure!(status == Status::Success as i16);
+        ensure!(cmd == Command::Version as u16);
+        // https://github.com/RfidResearchGroup/proxmark3/blame/55ef252a5d0d590026a4959a4c1b7a6028d1ad13/client/src/cmdhw.c#L1560
+        let mut response = &response[..];
+        ensure!(response.len() >= 12);
+        let _chip_id = response.get_u32_le();
+        let _section_size = response.get_u32_le();
+        let version_str_len = response.get_u32_le() as usize;
+        ensure!(version_str_len <= response.len());
+        let version_str = String::from_utf8_lossy(&response[..version_str_len]);
+        debug!("Proxmark3 version: {version_str}");
+        Ok(version_str.into_owned())
+    }
+
+    /// Whether the firmware reported support for the command.
+    ///
+    /// Only meaningful after connecting, before that only the commands
+    /// supported by all firmware are reported.
+    pub const fn supports_command(&self, cmd: Command) -> bool {
+        match cmd {
+            Command::Hf14aReader => self.capabilities & CAPABILITY_ISO14443A != 0,
+            Command::Hf14bReader => self.capabilities & CAPABILITY_ISO14443B != 0,
+            _ => true,
         }
     }
 
@@ -96,24 +194,14 @@ impl Proxmark3 {
         ensure!(cmd == Command::Capabilities as u16);
         // See https://github.com/RfidResearchGroup/proxmark3/blob/55ef252a5d0d590026a4959a4c1b7a6028d1ad13/include/pm3_cmd.h#L174
         ensure!(response.len() == 13);
-
-        // Check version
-        self.send_command_ng(Command::Version, &[])?;
-        let (status, cmd, response) = self.receive_response()?;
-        ensure!(status == Status::Success as i16);
-        ensure!(cmd == Command::Version as u16);
-        // https://github.com/RfidResearchGroup/proxmark3/blame/55ef252a5d0d590026a4959a4c1b7a6028d1ad13/client/src/cmdhw.c#L1560
-        let mut response = &response[..];
-        let _chip_id = response.get_u32_le();
-        let _secttion_size = response.get_u32_le();
-        let version_str_len = response.get_u32_le();
-        let version_str = &response[..version_str_len as usize];
-
-        if self.trace {
-            eprintln!(
-                "Proxmark3 version: {}",
-                String::from_utf8(version_str.to_vec()).unwrap()
-            );
+        let mut response = &response[9..];
+        self.capabilities = response.get_u32_le();
+
+        // Check version, custom builds may not report a parseable version.
+        let version_str = self.version_string()?;
+        match Proxmark3Version::parse(&version_str) {
+            Ok(version) => debug!("Proxmark3 firmware: {version:?}"),
+            Err(error) => debug!("Unrecognized Proxmark3 firmware version: {error}"),
         }
         Ok(())
     }
@@ -124,7 +212,11 @@ impl Proxmark3 {
         // https://github.com/RfidResearchGroup/proxmark3/blob/55ef252a5d0d590026a4959a4c1b7a6028d1ad13/include/mifare.h#L88
         self.send_command_mix(Command::Hf14aReader, 3, 0, 0, &[])?; // 3 = CONNECT | NO_DISCONNECT
         let (status, cmd, response) = self.receive_response()?;
-        ensure!(status == Status::Success as i16);
+        // A missing card is reported in `arg0`, a failing status is an error.
+        ensure!(
+            status == Status::Success as i16,
+            "ISO 14443-A scan failed with status {status}"
+        );
         ensure!(cmd == Command::Ack as u16);
         let mut response = &response[..];
         ensure!(response.len() >= 24);
@@ -166,10 +258,14 @@ impl Proxmark3 {
         let (status, cmd, response) = self.receive_response()?;
         ensure!(cmd == Command::Hf14bReader as u16);
         if status == Status::CardExchangeFailed as i16 {
+            // No card answered.
             // TODO: Retry with SELECT_SR and then with SELECT_CTS
             return Ok(None);
         }
-        ensure!(status == Status::Success as i16);
+        ensure!(
+            status == Status::Success as i16,
+            "ISO 14443-B scan failed with status {status}"
+        );
 
         // Parse response as iso14b_card_select_t
         ensure!(response.len() == 20);
@@ -195,6 +291,9 @@ impl Proxmark3 {
         // 6 = SEND_APDU | NO_DISCONNECT
         self.send_command_mix(Command::Hf14aReader, 6, apdu.len() as u64, 0, apdu)?;
         let (status, cmd, response) = self.receive_response()?;
+        if status == Status::CardExchangeFailed as i16 {
+            bail!(CardExchangeFailed);
+        }
         ensure!(status == Status::Success as i16);
         ensure!(cmd == Command::Ack as u16);
         ensure!(response.len() == 512);
@@ -224,6 +323,9 @@ impl Proxmark3 {
         // TODO: Support send chaining.
         self.hf14b(0x0004, data_in)?;
         let (status, cmd, response) = self.receive_response()?;
+        if status == Status::CardExchangeFailed as i16 {
+            bail!(CardExchangeFailed);
+        }
         ensure!(status == Status::Success as i16);
         ensure!(cmd == Command::Hf14bReader as u16);
         ensure!(response.len() >= 5);
@@ -303,8 +405,8 @@ impl Proxmark3 {
 
         let mut header = &header[..];
         ensure!(header.get_u32_le() == 0x62334d50); // magic
-        let len = header.get_u16_le();
-        let (len, _ng) = (len & 0x7fff, len & 0x8000 != 0);
+        let len_field = header.get_u16_le();
+        let (len, _ng) = (len_field & 0x7fff, len_field & 0x8000 != 0);
         ensure!(len <= 512);
         let status = header.get_i16_le();
         let cmd = header.get_u16_le();
@@ -317,19 +419,60 @@ impl Proxmark3 {
         // }
         // print!(" | ");
 
-        // Read CRC
+        // Read CRC, the firmware sends the magic 'b3' instead if CRCs are
+        // disabled for the interface.
         let mut crc = [0_u8; 2];
         self.connection.read(&mut crc)?;
-        // TODO: Check CRC
         // for byte in crc.iter() {
         //     print!(" {:02X} ", byte);
         // }
         // println!("");
+        if crc != *b"b3" {
+            let mut packet = BytesMut::with_capacity(10 + data.len());
+            packet.put_u32_le(0x62334d50);
+            packet.put_u16_le(len_field);
+            packet.put_i16_le(status);
+            packet.put_u16_le(cmd);
+            packet.put_slice(&data);
+            let expected = Crc::<u16>::new(&CRC_16_ISO_IEC_14443_3_A).checksum(&packet);
+            let actual = u16::from_be_bytes(crc);
+            if actual != expected {
+                return Err(ConnectionError::Crc { expected, actual }.into());
+            }
+        }
 
         Ok((status, cmd, data))
     }
 }
 
+impl Proxmark3Version {
+    /// Parse the OS line of the version string.
+    ///
+    /// The line is formatted by `FormatVersionInformation` as
+    /// `OS.... <version> <date> <time> <hash>`.
+    /// See https://github.com/RfidResearchGroup/proxmark3/blob/55ef252a5d0d590026a4959a4c1b7a6028d1ad13/common/commonutil.c#L69
+    fn parse(version_str: &str) -> Result<Self> {
+        let Some(line) = version_str
+            .lines()
+            .find_map(|line| line.trim_start().strip_prefix("OS."))
+        else {
+            bail!("No OS version in Proxmark3 version string");
+        };
+        let fields = line
+            .trim_start_matches('.')
+            .split_whitespace()
+            .collect::<Vec<_>>();
+        let [os_type, date, time, git_hash] = fields[..] else {
+            bail!("Unexpected Proxmark3 OS version: {line}");
+        };
+        Ok(Self {
+            git_hash:      git_hash.to_string(),
+            os_type:       os_type.to_string(),
+            firmware_date: format!("{date} {time}"),
+        })
+    }
+}
+
 impl NfcReader for Proxmark3 {
     fn connect(&mut self) -> Result<Option<CardType>> {
         if let Some(card) = self.connect_type_a()? {
@@ -343,9 +486,7 @@ impl NfcReader for Proxmark3 {
 
     fn disconnect(&mut self) -> Result<()> {
         // Switch field off
-        if self.trace {
-            eprintln!("Switching field off:");
-        }
+        trace!("Switching field off");
         self.send_command_mix(Command::Hf14aReader, 1, 0, 0, &[])?;
         let _response = self.receive_response()?;
         Ok(())
@@ -354,7 +495,13 @@ impl NfcReader for Proxmark3 {
     fn send_apdu(&mut self, apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)> {
         let data = match self.current_card {
             Some(CardType::A(_)) => self.hf14a_send(apdu)?,
-            Some(CardType::B(_)) => self.hf14b_send(apdu)?,
+            Some(CardType::B(ref card)) => {
+                ensure!(
+                    card.parsed_atqb()?.protocol_info.is_iso14443_4_compliant(),
+                    "Type B card does not support ISO 14443-4"
+                );
+                self.hf14b_send(apdu)?
+            }
             None => bail!("No card connected"),
         };
         ensure!(data.len() >= 2);
@@ -363,3 +510,150 @@ impl NfcReader for Proxmark3 {
         Ok((status, data.to_vec()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Connection that replays a fixed byte stream.
+    struct ReplayConnection(Vec<u8>);
+
+    impl ReplayConnection {
+        /// Replay the given response frames, without CRC.
+        fn frames(frames: &[(i16, Command, &[u8])]) -> Self {
+            let mut stream = Vec::new();
+            for &(status, cmd, data) in frames {
+                stream.put_u32_le(0x62334d50);
+                stream.put_u16_le(data.len() as u16 | 0x8000);
+                stream.put_i16_le(status);
+                stream.put_u16_le(cmd as u16);
+                stream.put_slice(data);
+                stream.put_slice(b"b3");
+            }
+            Self(stream)
+        }
+    }
+
+    impl Connection for ReplayConnection {
+        fn read(&mut self, buffer: &mut [u8]) -> Result<(), ConnectionError> {
+            if self.0.len() < buffer.len() {
+                return Err(ConnectionError::Disconnected);
+            }
+            buffer.copy_from_slice(&self.0[..buffer.len()]);
+            self.0.drain(..buffer.len());
+            Ok(())
+        }
+
+        fn write(&mut self, _data: &[u8]) -> Result<(), ConnectionError> {
+            Ok(())
+        }
+
+        fn close(self) -> Result<(), ConnectionError> {
+            Ok(())
+        }
+    }
+
+    fn response(crc: [u8; 2]) -> Vec<u8> {
+        // Ping response with data `01 02`, status success.
+        let mut packet = vec![
+            0x50, 0x4d, 0x33, 0x62, 0x02, 0x80, 0x00, 0x00, 0x09, 0x01, 0x01, 0x02,
+        ];
+        packet.extend_from_slice(&crc);
+        packet
+    }
+
+    #[test]
+    fn test_connect() {
+        const SUCCESS: i16 = Status::Success as i16;
+        const FAILED: i16 = Status::UndefinedError as i16;
+        const NO_CARD: i16 = Status::CardExchangeFailed as i16;
+        let no_type_a = [0; 24];
+        let type_b = [
+            &[1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 4][..],
+            &[0, 0, 0, 0, 0x00, 0x81, 0x71],
+            &[0, 0],
+        ]
+        .concat();
+        let connect = |frames: &[(i16, Command, &[u8])]| {
+            let connection = ReplayConnection::frames(frames);
+            Proxmark3::from_connection(Box::new(connection)).connect()
+        };
+
+        // No card of either type.
+        assert_eq!(
+            connect(&[
+                (SUCCESS, Command::Ack, &no_type_a),
+                (NO_CARD, Command::Hf14bReader, &[]),
+            ])
+            .unwrap(),
+            None
+        );
+
+        // Type B card after no Type A card.
+        let card = connect(&[
+            (SUCCESS, Command::Ack, &no_type_a),
+            (SUCCESS, Command::Hf14bReader, &type_b),
+        ])
+        .unwrap();
+        assert!(matches!(card, Some(CardType::B(card)) if card.uid == [1, 2, 3, 4]));
+
+        // A failing Type A scan does not fall through to Type B.
+        let error = connect(&[
+            (FAILED, Command::Ack, &[]),
+            (SUCCESS, Command::Hf14bReader, &type_b),
+        ])
+        .unwrap_err();
+        assert!(error.to_string().contains("14443-A"));
+
+        // Neither does a failing Type B scan report no card.
+        let error = connect(&[
+            (SUCCESS, Command::Ack, &no_type_a),
+            (FAILED, Command::Hf14bReader, &[]),
+        ])
+        .unwrap_err();
+        assert!(error.to_string().contains("14443-B"));
+    }
+
+    #[test]
+    fn test_receive_response_crc() {
+        let checksum = Crc::<u16>::new(&CRC_16_ISO_IEC_14443_3_A).checksum(&response(*b"b3")[..12]);
+        for crc in [*b"b3", checksum.to_be_bytes()] {
+            let mut pm3 = Proxmark3::from_connection(Box::new(ReplayConnection(response(crc))));
+            let (status, cmd, data) = pm3.receive_response().unwrap();
+            assert_eq!((status, cmd, data), (0, Command::Ping as u16, vec![1, 2]));
+        }
+
+        let bad = (checksum ^ 1).to_be_bytes();
+        let mut pm3 = Proxmark3::from_connection(Box::new(ReplayConnection(response(bad))));
+        let error = pm3.receive_response().unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(ConnectionError::Crc { actual, .. }) if *actual == checksum ^ 1
+        ));
+
+        let mut pm3 = Proxmark3::from_connection(Box::new(ReplayConnection(vec![])));
+        let error = pm3.receive_response().unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(ConnectionError::Disconnected)
+        ));
+    }
+
+    #[test]
+    fn test_parse_version() {
+        let version_str = concat!(
+            " [ ARM ]\n",
+            "  Bootrom.... Iceman/master/v4.17768-suspect 2023-12-01 10:12:01 3c3d6ec6b\n",
+            "       OS.... Iceman/master/v4.17768 2023-12-01 10:12:15 3c3d6ec6b \n",
+        );
+        let version = Proxmark3Version::parse(version_str).unwrap();
+        assert_eq!(version, Proxmark3Version {
+            git_hash:      "3c3d6ec6b".to_string(),
+            os_type:       "Iceman/master/v4.17768".to_string(),
+            firmware_date: "2023-12-01 10:12:15".to_string(),
+        });
+
+        assert!(Proxmark3Version::parse(" [ ARM ]\n").is_err());
+        assert!(Proxmark3Version::parse("  OS.... Missing/Invalid version information").is_err());
+    }
+}
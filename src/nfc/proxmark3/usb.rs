@@ -1,12 +1,15 @@
 use {
-    super::Connection,
-    anyhow::{anyhow, Result},
-    rusb::{DeviceHandle, GlobalContext, UsbContext},
-    std::time::Duration,
+    super::{Connection, ConnectionError, DeviceInfo},
+    anyhow::{anyhow, bail, Result},
+    rusb::{Device, DeviceDescriptor, DeviceHandle, GlobalContext, UsbContext},
+    std::{io, time::Duration},
 };
 
-const PROXMARK3_VENDOR_ID: u16 = 0x9ac4;
-const PROXMARK3_PRODUCT_ID: u16 = 0x4b8f;
+/// Known USB vendor and product IDs.
+///
+/// The Iceman firmware (RDV4, Easy and other clones) uses `9AC4:4B8F`, the
+/// original firmware `2D2D:504D`.
+const PROXMARK3_DEVICES: &[(u16, u16)] = &[(0x9ac4, 0x4b8f), (0x2d2d, 0x504d)];
 const TIMEOUT: Duration = Duration::from_secs(3);
 
 pub struct UsbConnection {
@@ -17,18 +20,22 @@ pub struct UsbConnection {
 }
 
 impl UsbConnection {
+    /// Connect to the first Proxmark3 found.
     pub fn new() -> Result<Self> {
-        // Get device handle
-        let devices = rusb::devices()?;
-        for device in devices.iter() {
-            let device_desc = device.device_descriptor()?;
-            if device_desc.vendor_id() == PROXMARK3_VENDOR_ID
-                && device_desc.product_id() == PROXMARK3_PRODUCT_ID
-            {
+        let Some((device, _)) = devices()?.into_iter().next() else {
+            bail!("Proxmark3 device not found");
+        };
+        Self::from_device(device)
+    }
+
+    /// Connect to the Proxmark3 with the given USB serial number.
+    pub fn new_with_serial(serial: &str) -> Result<Self> {
+        for (device, descriptor) in devices()? {
+            if serial_number(&device, &descriptor).as_deref() == Some(serial) {
                 return Self::from_device(device);
             }
         }
-        panic!("Proxmark3 device not found");
+        bail!("Proxmark3 device with serial {serial} not found");
     }
 
     pub fn from_device(device: rusb::Device<GlobalContext>) -> Result<Self> {
@@ -74,8 +81,18 @@ impl UsbConnection {
     }
 }
 
+impl From<rusb::Error> for ConnectionError {
+    fn from(error: rusb::Error) -> Self {
+        match error {
+            rusb::Error::NoDevice => Self::Disconnected,
+            rusb::Error::Timeout => Self::Timeout(TIMEOUT),
+            error => Self::Io(io::Error::other(error)),
+        }
+    }
+}
+
 impl Connection for UsbConnection {
-    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<(), ConnectionError> {
         while self.buffer.len() < buf.len() {
             let mut chunk = [0_u8; 64];
             let read = self
@@ -89,7 +106,7 @@ impl Connection for UsbConnection {
         Ok(())
     }
 
-    fn write(&mut self, data: &[u8]) -> Result<()> {
+    fn write(&mut self, data: &[u8]) -> Result<(), ConnectionError> {
         let bytes_written = self
             .handle
             .write_bulk(self.bulk_out_endpoint, data, TIMEOUT)?;
@@ -102,12 +119,44 @@ impl Connection for UsbConnection {
         Ok(())
     }
 
-    fn close(self) -> Result<()> {
+    fn close(self) -> Result<(), ConnectionError> {
         self.handle.release_interface(1)?;
         Ok(())
     }
 }
 
+/// Describe all connected Proxmark3 devices.
+pub fn list_devices() -> Result<Vec<DeviceInfo>> {
+    Ok(devices()?
+        .into_iter()
+        .map(|(device, descriptor)| DeviceInfo {
+            bus:        device.bus_number(),
+            address:    device.address(),
+            vendor_id:  descriptor.vendor_id(),
+            product_id: descriptor.product_id(),
+            serial:     serial_number(&device, &descriptor),
+        })
+        .collect())
+}
+
+/// Connected devices with a known Proxmark3 vendor and product ID.
+fn devices() -> Result<Vec<(Device<GlobalContext>, DeviceDescriptor)>> {
+    let mut result = Vec::new();
+    for device in rusb::devices()?.iter() {
+        let descriptor = device.device_descriptor()?;
+        if PROXMARK3_DEVICES.contains(&(descriptor.vendor_id(), descriptor.product_id())) {
+            result.push((device, descriptor));
+        }
+    }
+    Ok(result)
+}
+
+/// Read the serial number string, requires opening the device.
+fn serial_number(device: &Device<GlobalContext>, descriptor: &DeviceDescriptor) -> Option<String> {
+    let handle = device.open().ok()?;
+    handle.read_serial_number_string_ascii(descriptor).ok()
+}
+
 fn get_endpoints<T: UsbContext>(device: &rusb::Device<T>) -> Result<(u8, u8)> {
     let config_desc = device.active_config_descriptor()?;
     let mut bulk_in_endpoint = None;
@@ -0,0 +1,111 @@
+//! Property based tests for [`ModRing`] arithmetic.
+
+use {
+    icao_9303::crypto::mod_ring::{ModRing, RingRefExt, UintMont},
+    num_traits::Inv,
+    proptest::prelude::*,
+    ruint::{uint, Uint},
+};
+
+/// P-256 base field prime, 3 mod 4.
+const P256: Uint<256, 4> =
+    uint!(0xffffffff00000001000000000000000000000000ffffffffffffffffffffffff_U256);
+
+/// brainpoolP512r1 base field prime, 3 mod 4.
+const P512: Uint<512, 8> = uint!(0xaadd9db8dbe9c48b3fd4e6ae33c9fc07cb308db3b3c9d20ed6639cca703308717d4d9b009bc66842aecda12ae6a380e62881ff2f2d82c68528aa6056583a48f3_U512);
+
+fn arb_uint<const B: usize, const L: usize>() -> impl Strategy<Value = Uint<B, L>> {
+    prop::array::uniform(any::<u64>()).prop_map(Uint::from_limbs)
+}
+
+/// Arbitrary odd modulus larger than one.
+fn arb_modulus<const B: usize, const L: usize>() -> impl Strategy<Value = Uint<B, L>> {
+    arb_uint()
+        .prop_map(|n: Uint<B, L>| n | Uint::from(1))
+        .prop_filter("modulus must be > 1", |n| *n > Uint::from(1))
+}
+
+/// Converts between backends, the inputs are generated as Ruint values.
+fn convert<T: UintMont, S: UintMont>(value: S) -> T {
+    T::from_be_bytes(&value.to_be_bytes())
+}
+
+macro_rules! mod_ring_tests {
+    ($name:ident, $bits:literal, $limbs:literal, $prime:expr) => {
+        mod_ring_tests!($name, $bits, $limbs, $prime, Uint<$bits, $limbs>);
+    };
+    ($name:ident, $bits:literal, $limbs:literal, $prime:expr, $backend:ty) => {
+        mod $name {
+            use super::*;
+
+            type U = Uint<$bits, $limbs>;
+            type B = $backend;
+
+            fn ring(modulus: U) -> ModRing<B> {
+                ModRing::from_modulus(convert(modulus))
+            }
+
+            proptest! {
+                #[test]
+                fn add_sub(m in arb_modulus::<$bits, $limbs>(), a in arb_uint::<$bits, $limbs>(), b in arb_uint::<$bits, $limbs>()) {
+                    let ring = ring(m);
+                    let a = ring.from(convert::<B, _>(a % m));
+                    let b = ring.from(convert::<B, _>(b % m));
+                    prop_assert_eq!((a + b) - b, a);
+                }
+
+                #[test]
+                fn mul_inv(m in arb_modulus::<$bits, $limbs>(), a in arb_uint::<$bits, $limbs>(), b in arb_uint::<$bits, $limbs>()) {
+                    let ring = ring(m);
+                    let a = ring.from(convert::<B, _>(a % m));
+                    let b = ring.from(convert::<B, _>(b % m));
+                    if let Some(b_inv) = b.inv() {
+                        prop_assert_eq!(b * b_inv, ring.one());
+                        prop_assert_eq!(a * b * b_inv, a);
+                    } else {
+                        prop_assert!(convert::<U, _>(b.to_uint()).gcd(m) != U::from(1));
+                    }
+                }
+
+                #[test]
+                fn fermat(a in arb_uint::<$bits, $limbs>()) {
+                    let p: U = $prime;
+                    prop_assume!(a % p != U::ZERO);
+                    let ring = ring(p);
+                    let a = ring.from(convert::<B, _>(a % p));
+                    prop_assert_eq!(a.pow_ct(p - U::from(1)), ring.one());
+                }
+
+                #[test]
+                fn sqrt(a in arb_uint::<$bits, $limbs>()) {
+                    let p: U = $prime;
+                    let ring = ring(p);
+                    let a = ring.from(convert::<B, _>(a % p));
+                    if let Some(root) = a.sqrt() {
+                        prop_assert_eq!(root.square(), a);
+                    }
+                    let square = a.square();
+                    let root = square.sqrt();
+                    prop_assert!(root.is_some());
+                    prop_assert_eq!(root.unwrap().square(), square);
+                }
+
+                #[test]
+                fn pow_ct(m in arb_modulus::<$bits, $limbs>(), a in arb_uint::<$bits, $limbs>(), e in 0_usize..300) {
+                    let ring = ring(m);
+                    let a = ring.from(convert::<B, _>(a % m));
+                    let expected = (0..e).fold(ring.one(), |acc, _| acc * a);
+                    prop_assert_eq!(a.pow_ct(U::from(e)), expected);
+                    prop_assert_eq!(a.pow(e), expected);
+                }
+            }
+        }
+    };
+}
+
+mod_ring_tests!(uint_256, 256, 4, P256);
+mod_ring_tests!(uint_512, 512, 8, P512);
+#[cfg(feature = "crypto-bigint")]
+mod_ring_tests!(bigint_256, 256, 4, P256, crypto_bigint::U256);
+#[cfg(feature = "crypto-bigint")]
+mod_ring_tests!(bigint_512, 512, 8, P512, crypto_bigint::U512);
@@ -0,0 +1,187 @@
+//! ICAO 9303-11 Appendix D.3 worked example for Basic Access Control.
+
+use {
+    hex_literal::hex,
+    icao_9303::{
+        emrtd::{
+            secure_messaging::{
+                aes::kdf_128,
+                tdes::{kdf, TDesCipher},
+                Cipher, Encrypted, SecureMessaging, KDF_ENC, KDF_MAC,
+            },
+            seed_from_mrz, send_sequence_counter, Emrtd, Error, FileId,
+        },
+        iso7816::{pad_iso7816_2, StatusWord},
+        nfc::mock::MockReader,
+    },
+    rand::RngCore,
+    std::cell::Cell,
+};
+
+const MRZ: &str = "L898902C<369080619406236";
+const K_SEED: [u8; 16] = hex!("239AB9CB282DAF66231DC5A4DF6BFBAE");
+const K_ENC: [u8; 16] = hex!("AB94FDECF2674FDFB9B391F85D7F76F2");
+const K_MAC: [u8; 16] = hex!("7962D9ECE03D1ACD4C76089DCE131543");
+
+const RND_IC: [u8; 8] = hex!("4608F91988702212");
+const RND_IFD: [u8; 8] = hex!("781723860C06C226");
+const K_IFD: [u8; 16] = hex!("0B795240CB7049B01C19B33E32804F0B");
+const K_IC: [u8; 16] = hex!("0B4F80323EB3191CB04970CB4052790B");
+
+const E_IFD: [u8; 32] = hex!("72C29C2371CC9BDB65B779B8E8D37B29ECC154AA56A8799FAE2F498F76ED92F2");
+const M_IFD: [u8; 8] = hex!("5F1448EEA8AD90A7");
+const E_IC: [u8; 32] = hex!("46B9342A41396CD7386BF5803104D7CEDC122B9132139BAF2EEDC94EE178534F");
+const M_IC: [u8; 8] = hex!("2F2D235D074D7449");
+
+const KS_SEED: [u8; 16] = hex!("0036D272F5C350ACAC50C3F572D23600");
+const KS_ENC: [u8; 16] = hex!("979EC13B1CBFE9DCD01AB0FED307EAE5");
+const KS_MAC: [u8; 16] = hex!("F1CB1F1FB5ADF208806B89DC579DC1F8");
+const SSC: u64 = 0x887022120c06c226;
+
+fn mac(cipher: &TDesCipher, data: &[u8]) -> [u8; 8] {
+    cipher.mac(0, &pad_iso7816_2(data, cipher.block_size()))
+}
+
+#[test]
+fn test_bac_key_derivation() {
+    let seed = seed_from_mrz(MRZ);
+    assert_eq!(seed, K_SEED);
+    assert_eq!(kdf(&seed, KDF_ENC), K_ENC);
+    assert_eq!(kdf(&seed, KDF_MAC), K_MAC);
+
+    // The 3DES keys are the AES-128 keys with DES parity bits adjusted.
+    let strip = |key: [u8; 16]| key.map(|byte| byte & 0xfe);
+    assert_eq!(strip(kdf_128(&seed, KDF_ENC)), strip(K_ENC));
+    assert_eq!(strip(kdf_128(&seed, KDF_MAC)), strip(K_MAC));
+}
+
+#[test]
+fn test_bac_external_authenticate_command() {
+    let cipher = TDesCipher::from_seed(&K_SEED);
+
+    let mut s = [RND_IFD.as_slice(), &RND_IC, &K_IFD].concat();
+    cipher.enc(0, &mut s);
+    assert_eq!(s, E_IFD);
+
+    assert_eq!(mac(&cipher, &E_IFD), M_IFD);
+}
+
+#[test]
+fn test_bac_external_authenticate_response() {
+    let cipher = TDesCipher::from_seed(&K_SEED);
+
+    assert_eq!(mac(&cipher, &E_IC), M_IC);
+
+    let mut r = E_IC;
+    cipher.dec(0, &mut r);
+    assert_eq!(r[0..8], RND_IC);
+    assert_eq!(r[8..16], RND_IFD);
+    assert_eq!(r[16..], K_IC);
+}
+
+#[test]
+fn test_bac_session_keys() {
+    let seed: Vec<u8> = K_IFD.iter().zip(K_IC).map(|(a, b)| a ^ b).collect();
+    assert_eq!(seed, KS_SEED);
+    assert_eq!(kdf(&seed, KDF_ENC), KS_ENC);
+    assert_eq!(kdf(&seed, KDF_MAC), KS_MAC);
+    assert_eq!(send_sequence_counter(&RND_IC, &RND_IFD), SSC);
+}
+
+/// Yields the given bytes, one per generated integer.
+struct ReplayRng(std::vec::IntoIter<u8>);
+
+impl RngCore for ReplayRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next().expect("random bytes exhausted").into()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.next_u32().into()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill_with(|| self.next_u32() as u8);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_bac_with_mock_reader() {
+    let success = |data: &[u8]| (StatusWord::SUCCESS, data.to_vec());
+    let external_authenticate = [&hex!("0082000028")[..], &E_IFD, &M_IFD, &[0x00]].concat();
+    let reader = MockReader::new(vec![
+        // SELECT eMRTD application
+        (hex!("00A4040C07A0000002471001").to_vec(), success(&[])),
+        (hex!("0084000008").to_vec(), success(&RND_IC)),
+        (
+            external_authenticate,
+            success(&[E_IC.as_slice(), &M_IC].concat()),
+        ),
+        // ICAO 9303-11 Appendix D.4, SELECT EF.COM with secure messaging.
+        (
+            hex!("0CA4020C158709016375432908C044F68E08BF8B92D635FF24F800").to_vec(),
+            success(&hex!("990290008E08FA855A5D4C50A8ED")),
+        ),
+    ]);
+    let mut card = Emrtd::new(Box::new(reader));
+    let mut rng = ReplayRng([RND_IFD.as_slice(), &K_IFD].concat().into_iter());
+    card.basic_access_control(&mut rng, MRZ).unwrap();
+    card.select_elementary_file(0x011e).unwrap();
+}
+
+#[test]
+fn test_read_all_files_reauthenticates() {
+    let success = |data: &[u8]| (StatusWord::SUCCESS, data.to_vec());
+    let not_found = (StatusWord::FILE_NOT_FOUND, vec![]);
+    let external_authenticate = [&hex!("0082000028")[..], &E_IFD, &M_IFD, &[0x00]].concat();
+    let bac = [
+        (hex!("0084000008").to_vec(), success(&RND_IC)),
+        (
+            external_authenticate,
+            success(&[E_IC.as_slice(), &M_IC].concat()),
+        ),
+    ];
+
+    // The chip drops the session on the first READ BINARY of EF.COM. Both
+    // sessions use the same keys, as the chip and the rng repeat themselves.
+    let mut session = Encrypted::new(TDesCipher::from_seed(&KS_SEED), SSC);
+    let read_com = hex!("00B09E0000");
+    let mut script = vec![(hex!("00A4040C07A0000002471001").to_vec(), success(&[]))];
+    script.extend(bac.clone());
+    script.push((
+        session.enc_apdu(&read_com).unwrap(),
+        (StatusWord::SECURE_MESSAGING_INCOMPLETE, vec![]),
+    ));
+    script.extend(bac);
+    let mut session = Encrypted::new(TDesCipher::from_seed(&KS_SEED), SSC);
+    // EF.COM is retried, then EF.DG1 to EF.DG16 and EF.SOD.
+    for sfi in std::iter::once(0x9e).chain(0x81..=0x90).chain([0x9d]) {
+        let command = session.enc_apdu(&[0x00, 0xb0, sfi, 0x00, 0x00]).unwrap();
+        script.push((command, not_found.clone()));
+    }
+
+    let mut card = Emrtd::new(Box::new(MockReader::new(script)));
+    let random = [RND_IFD.as_slice(), &K_IFD].concat().repeat(2);
+    let mut rng = ReplayRng(random.into_iter());
+    card.basic_access_control(&mut rng, MRZ).unwrap();
+
+    let calls = Cell::new(0);
+    let files = card.read_all_files(&mut rng, |file, result| {
+        calls.set(calls.get() + 1);
+        match file {
+            // The master file is not accessible with BAC.
+            FileId::CardAccess | FileId::Dir | FileId::AttrInfo | FileId::CardSecurity => {
+                assert!(matches!(result, Err(Error::MasterFileUnavailable)));
+            }
+            _ => assert!(matches!(result, Ok(None)), "{file}: {result:?}"),
+        }
+    });
+    assert!(files.is_empty());
+    assert_eq!(calls.get(), FileId::iter().count());
+    assert_eq!(rng.0.len(), 0);
+}
@@ -0,0 +1,232 @@
+mod dataset;
+
+use {
+    anyhow::Result,
+    dataset::Dataset,
+    der::{asn1::Int, Decode, Encode},
+    hex_literal::hex,
+    icao_9303::{
+        asn1::{
+            emrtd::{
+                security_info::{ChipAuthenticationProtocol, KeyAgreement, SymmetricCipher},
+                EfDg14,
+            },
+            public_key_info::{DhAlgoParameters, DhPublicKeyInfo, SubjectPublicKeyInfo},
+        },
+        crypto::{
+            groups::named::{brainpool_p224r1, modp_224},
+            mod_ring::RingRefExt,
+        },
+        emrtd::{
+            authentication_token, chip_authentication_v2_session,
+            parse_chip_authentication_response,
+            secure_messaging::{aes::kdf_128, KDF_MAC},
+            Emrtd,
+        },
+        iso7816::StatusWord,
+        nfc::mock::MockReader,
+    },
+    rand::{rngs::StdRng, SeedableRng},
+    ruint::Uint,
+};
+
+/// Private key of the DG14 Chip Authentication key from `DG14_sk.pkcs8`.
+const CHIP_PRIVATE_KEY: [u8; 28] = hex!("ad1b6f5bf65b63f53e6ee2e45c1f09243d9aa2ab0c9a78c8c4d25647");
+
+#[test]
+fn test_chip_authentication_key_agreement() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let dg14 = EfDg14::from_der(&dataset.dg14)?;
+    let (_, pk) = dg14.chip_authentication().unwrap();
+    assert_eq!(pk.public_key.bit_len(), 224);
+
+    // Terminal side
+    let (algo, card_public_key) = pk.public_key.to_algorithm_public_key()?;
+    let (private_key, public_key) = algo.generate_key_pair(&mut rand::thread_rng());
+    let shared_secret = algo.key_agreement(&private_key, &card_public_key)?;
+
+    // Chip side, using the static private key.
+    let curve = brainpool_p224r1();
+    let scalar = curve
+        .scalar_field()
+        .from(Uint::<224, 4>::from_be_bytes(CHIP_PRIVATE_KEY));
+    let shared = curve.decode_point(public_key.as_ref())? * scalar;
+    let x = shared.x().unwrap().to_uint().to_be_bytes::<28>();
+    assert_eq!(shared_secret, x);
+    Ok(())
+}
+
+#[test]
+fn test_chip_authentication_v2_token() -> Result<()> {
+    // Tokens computed independently with Python's `cryptography` AES-CMAC over
+    // the hand-assembled public key data object of BSI TR-03110-3 D.3.1,
+    // `7F49 { 06 OID, 86 point }`, with `K_MAC = SHA-256(K || r || 00000002)`.
+    let protocol = ChipAuthenticationProtocol {
+        key_agreement: KeyAgreement::Ecdh,
+        cipher:        Some(SymmetricCipher::Aes256),
+    };
+    let shared_secret =
+        hex!("28768D20 701247DA E81804C9 E780EDE5 82A9996D B4A31502 0B273319 7DB84925");
+    let nonce = hex!("01020304 05060708");
+    let terminal_public_key = hex!(
+        "04
+         2DB7A64C 0355044E C9DF1905 14C625CB A2CEA487 54887122 F3A5EF0D 5EDD301C
+         3556F3B3 B186DF10 B857B58F 6A7EB80F 20BA5DC7 BE1D43D9 BF850149 FBB36462"
+    );
+    let token = hex!("714B2B88 5B202F33");
+
+    let mut response = vec![0x7c, 0x14, 0x81, 0x08];
+    response.extend_from_slice(&nonce);
+    response.extend_from_slice(&[0x82, 0x08]);
+    response.extend_from_slice(&token);
+
+    let (parsed_nonce, parsed_token) = parse_chip_authentication_response(&response)?;
+    assert_eq!(parsed_nonce, nonce);
+    assert_eq!(parsed_token, token);
+    chip_authentication_v2_session(
+        protocol,
+        &shared_secret,
+        parsed_nonce,
+        parsed_token,
+        &terminal_public_key,
+    )?;
+
+    // Tampered token or wrong terminal key must be rejected.
+    let mut tampered = token;
+    tampered[0] ^= 1;
+    assert!(chip_authentication_v2_session(
+        protocol,
+        &shared_secret,
+        &nonce,
+        &tampered,
+        &terminal_public_key
+    )
+    .is_err());
+    let mut other_key = terminal_public_key;
+    other_key[64] ^= 1;
+    assert!(
+        chip_authentication_v2_session(protocol, &shared_secret, &nonce, &token, &other_key)
+            .is_err()
+    );
+
+    // Malformed responses.
+    assert!(parse_chip_authentication_response(&response[..10]).is_err());
+    assert!(parse_chip_authentication_response(&hex!("7c 02 81 00")).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_chip_authentication_v2_token_dh() -> Result<()> {
+    // As above, with `7F49 { 06 OID, 84 y }` and
+    // `K_MAC = SHA-1(K || r || 00000002)[..16]`.
+    let protocol = ChipAuthenticationProtocol {
+        key_agreement: KeyAgreement::Dh,
+        cipher:        Some(SymmetricCipher::Aes128),
+    };
+    let shared_secret: Vec<u8> = (0..=255).rev().collect();
+    let nonce = hex!("01020304 05060708");
+    let terminal_public_key: Vec<u8> = (0..=255_u8)
+        .map(|i| i.wrapping_mul(7).wrapping_add(3))
+        .collect();
+    let token = hex!("344D545D FB2B818B");
+    chip_authentication_v2_session(
+        protocol,
+        &shared_secret,
+        &nonce,
+        &token,
+        &terminal_public_key,
+    )?;
+
+    // The elliptic curve encoding of the same key gives a different token.
+    let ecdh = ChipAuthenticationProtocol {
+        key_agreement: KeyAgreement::Ecdh,
+        ..protocol
+    };
+    let k_mac = kdf_128(&[shared_secret.as_slice(), &nonce].concat(), KDF_MAC);
+    assert_ne!(
+        authentication_token(
+            SymmetricCipher::Aes128,
+            &k_mac,
+            protocol.into(),
+            KeyAgreement::Dh,
+            &terminal_public_key
+        )?,
+        authentication_token(
+            SymmetricCipher::Aes128,
+            &k_mac,
+            ecdh.into(),
+            KeyAgreement::Ecdh,
+            &terminal_public_key
+        )?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_chip_authentication_dh_key_agreement() -> Result<()> {
+    // Chip Authentication public key on the RFC 5114 2048-bit MODP group.
+    let group = modp_224();
+    let int = |bytes: Vec<u8>| {
+        // Minimal two's-complement encoding of an unsigned value.
+        let bytes: Vec<u8> = bytes.into_iter().skip_while(|&b| b == 0).collect();
+        Int::new(&[[0].as_slice(), &bytes].concat()[usize::from(bytes[0] < 0x80)..])
+    };
+    let chip_private = Uint::<224, 4>::from(0x1234_5678_9abc_def0_u64);
+    let chip_public = group.generator().pow_ct(chip_private).to_uint();
+    let spki = SubjectPublicKeyInfo::Dh(DhPublicKeyInfo {
+        parameters: DhAlgoParameters {
+            prime:                int(group.base_field().modulus().to_be_bytes_vec())?,
+            base:                 int(group.generator().to_uint().to_be_bytes_vec())?,
+            private_value_length: None,
+        },
+        public_key: int(chip_public.to_be_bytes_vec())?,
+    });
+    let spki = SubjectPublicKeyInfo::from_der(&spki.to_der()?)?;
+    assert!(matches!(spki, SubjectPublicKeyInfo::Dh(_)));
+
+    // Terminal side
+    let (algo, card_public_key) = spki.to_algorithm_public_key()?;
+    assert_eq!(algo.to_string(), "DH-2048");
+    let (private_key, public_key) = algo.generate_key_pair(&mut rand::thread_rng());
+    let shared_secret = algo.key_agreement(&private_key, &card_public_key)?;
+    assert_eq!(shared_secret.len(), 256);
+
+    // Chip side
+    let terminal_public = Uint::<2048, 32>::from_be_slice(public_key.as_ref());
+    let shared = group
+        .base_field()
+        .from(terminal_public)
+        .pow_ct(chip_private)
+        .to_uint();
+    assert_eq!(shared_secret, shared.to_be_bytes_vec());
+    Ok(())
+}
+
+#[test]
+fn test_chip_authenticate_with_mock_reader() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let dg14 = EfDg14::from_der(&dataset.dg14)?;
+    let (_, pk) = dg14.chip_authentication().unwrap();
+    assert_eq!(pk.key_id, None);
+
+    // The ephemeral key the terminal will generate from the same seed.
+    let rng = StdRng::seed_from_u64(9303);
+    let (algo, _) = pk.public_key.to_algorithm_public_key()?;
+    let (_, public_key) = algo.generate_key_pair(&mut rng.clone());
+    let public_key = public_key.as_ref();
+    assert_eq!(public_key.len(), 57);
+
+    // DG14 is read in two chunks, then 3DES Chip Authentication uses
+    // MSE:Set KAT with the ephemeral public key.
+    let success = |data: &[u8]| (StatusWord::SUCCESS, data.to_vec());
+    let mse_set_kat = [&hex!("002241A63B9139")[..], public_key].concat();
+    let reader = MockReader::new(vec![
+        (hex!("00A4040C07A0000002471001").to_vec(), success(&[])),
+        (hex!("00B08E0000").to_vec(), success(&dataset.dg14[..256])),
+        (hex!("00B0010000").to_vec(), success(&dataset.dg14[256..])),
+        (mse_set_kat, success(&[])),
+    ]);
+    let mut card = Emrtd::new(Box::new(reader));
+    card.chip_authenticate(rng)?;
+    Ok(())
+}
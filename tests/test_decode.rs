@@ -4,19 +4,60 @@ use {
     anyhow::{anyhow as err, bail, ensure, Result},
     cms::content_info::CmsVersion,
     dataset::Dataset,
-    der::Decode,
-    icao_9303::asn1::{
-        emrtd::{security_info::SecurityInfo, EfDg14, EfSod},
-        DigestAlgorithmIdentifier,
+    der::{Decode, Encode},
+    icao_9303::{
+        asn1::{
+            emrtd::{
+                security_info::{decode_security_infos, SecurityInfo},
+                EfCom, EfDg14, EfDg15, EfSod,
+            },
+            master_list::CscaMasterList,
+            DigestAlgorithmIdentifier,
+        },
+        crypto::Leniency,
+        emrtd::Emrtd,
+        iso7816::StatusWord,
+        nfc::{CardType, NfcReader},
     },
+    std::collections::HashMap,
 };
 
+/// Reader that serves files by short file identifier.
+struct FileReader(HashMap<u8, Vec<u8>>);
+
+impl NfcReader for FileReader {
+    fn connect(&mut self) -> Result<Option<CardType>> {
+        Ok(None)
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn send_apdu(&mut self, apdu: &[u8]) -> Result<(StatusWord, Vec<u8>)> {
+        Ok(match (apdu[1], apdu[2]) {
+            (0xa4, _) => (StatusWord::SUCCESS, vec![]),
+            (0xb0, p1) if p1 & 0x80 != 0 => self
+                .0
+                .get(&(p1 & 0x1f))
+                .map_or((StatusWord::FILE_NOT_FOUND, vec![]), |file| {
+                    (StatusWord::SUCCESS, file.clone())
+                }),
+            _ => (StatusWord::FILE_NOT_FOUND, vec![]),
+        })
+    }
+}
+
 #[test]
 fn test_decode_dg14() -> Result<()> {
     let dataset = Dataset::load()?;
     let dg14 = EfDg14::from_der(&dataset.dg14)?;
 
     assert_eq!(dg14.0 .0.len(), 3);
+    // The SET is not in DER order, re-encoding must preserve the input order
+    // for the data group hash to match.
+    assert!(!dg14.0.is_canonical()?);
+    assert_eq!(dg14.to_der()?, dataset.dg14);
 
     let _chip_auth = dg14
         .0
@@ -45,6 +86,39 @@ fn test_decode_dg14() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_decode_com() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let com = EfCom::from_der(&dataset.com)?;
+    assert_eq!(com.lds_version, "0107");
+    assert_eq!(com.tag_list, [0x61, 0x75, 0x63, 0x76, 0x6e]);
+    assert_eq!(com.to_der()?, dataset.com);
+    Ok(())
+}
+
+#[test]
+fn test_read_cached() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let files = HashMap::from([
+        (0x1e, dataset.com.clone()),
+        (0x1d, dataset.sod.clone()),
+        (0x0e, dataset.dg14.clone()),
+        (0x0f, dataset.dg15.clone()),
+    ]);
+    let mut card = Emrtd::new(Box::new(FileReader(files)));
+    assert_eq!(card.read_cached::<EfCom>()?, EfCom::from_der(&dataset.com)?);
+    assert_eq!(card.read_cached::<EfSod>()?, EfSod::from_der(&dataset.sod)?);
+    assert_eq!(
+        card.read_cached::<EfDg14>()?,
+        EfDg14::from_der(&dataset.dg14)?
+    );
+    assert_eq!(
+        card.read_cached::<EfDg15>()?,
+        EfDg15::from_der(&dataset.dg15)?
+    );
+    Ok(())
+}
+
 #[test]
 fn test_decode_sod() -> Result<()> {
     let dataset = Dataset::load()?;
@@ -52,6 +126,7 @@ fn test_decode_sod() -> Result<()> {
 
     // SecurityObject
     let security_object = sod.lds_security_object()?;
+    assert!(sod.0.decode_econtent::<CscaMasterList>().is_err());
 
     let dgs = [1, 2, 3, 4, 14];
     for dg in dgs {
@@ -70,8 +145,83 @@ fn test_decode_sod() -> Result<()> {
         "SecurityObject hash algorithm should be SHA256"
     );
 
+    let dg1_hash = &security_object.data_group_hash_values[0];
+    assert_eq!(dg1_hash.data_group_number, 1);
+    assert!(dg1_hash.verify(&dataset.dg1, &security_object.hash_algorithm));
+    assert!(!dg1_hash.verify(&dataset.dg2, &security_object.hash_algorithm));
+
     // Signer
-    assert_eq!(sod.signer_info().version, CmsVersion::V1);
+    assert_eq!(sod.signer_info()?.version, CmsVersion::V1);
+
+    Ok(())
+}
 
+#[test]
+fn test_canonical_hash() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let sod = EfSod::from_der(&dataset.sod)?;
+
+    // sha256sum tests/dataset/EF_SOD.bin
+    assert_eq!(
+        hex::encode(sod.canonical_hash()?),
+        "68f32f11f8541003f80a2fcf95eb288ce01d4e10834fbf9c2c6bc2a27c399b36"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_decode_malformed() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let sod = &dataset.sod;
+    let dg14 = &dataset.dg14;
+
+    // Empty input
+    assert!(EfSod::from_der(&[]).is_err());
+    assert!(EfDg14::from_der(&[]).is_err());
+
+    // Truncated TLV
+    assert!(EfSod::from_der(&sod[..sod.len() - 1]).is_err());
+    assert!(EfDg14::from_der(&dg14[..dg14.len() / 2]).is_err());
+
+    // Length claiming more bytes than available
+    assert!(EfSod::from_der(&[0x77, 0x82, 0xff, 0xff, 0x30, 0x00]).is_err());
+    assert!(EfDg14::from_der(&[0x6e, 0x84, 0x7f, 0xff, 0xff, 0xff, 0x31, 0x00]).is_err());
+
+    // Application tags with wrong inner tag
+    assert!(EfSod::from_der(&[0x77, 0x02, 0x31, 0x00]).is_err());
+    assert!(EfDg14::from_der(&[0x6e, 0x02, 0x30, 0x00]).is_err());
+
+    // Indefinite length BER encoding
+    assert!(EfSod::from_der(&[0x77, 0x80, 0x30, 0x80, 0x00, 0x00, 0x00, 0x00]).is_err());
+    assert!(EfDg14::from_der(&[0x6e, 0x80, 0x31, 0x00, 0x00, 0x00]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_decode_unknown_security_info() -> Result<()> {
+    // A proprietary protocol 1.2.3.4 next to a PaceInfo.
+    let der = hex_literal::hex!(
+        "311E
+         3008 06032A0304 020105
+         3012 060A04007F00070202040202 020102 02010D"
+    );
+    for leniency in [Leniency::Allow, Leniency::Warn] {
+        let infos = decode_security_infos(&der, leniency)?;
+        let [SecurityInfo::Unknown(unknown), SecurityInfo::Pace(_)] = infos.as_ref() else {
+            bail!("Unexpected SecurityInfos {infos:?}");
+        };
+        assert_eq!(unknown.protocol.to_string(), "1.2.3.4");
+        assert_eq!(unknown.required_data.value(), [0x05]);
+        assert_eq!(infos.to_der()?, der);
+    }
+    assert!(decode_security_infos(&der, Leniency::Strict).is_err());
+
+    // Strict mode accepts known protocols.
+    let pace = hex_literal::hex!("3114 3012 060A04007F00070202040202 020102 02010D");
+    assert_eq!(
+        decode_security_infos(&pace, Leniency::Strict)?.to_der()?,
+        pace
+    );
     Ok(())
 }
@@ -0,0 +1,183 @@
+//! ICAO 9303-11 Appendix G.1 worked example for PACE-ECDH-GM-AES-CBC-CMAC-128
+//! on brainpoolP256r1.
+
+use {
+    der::asn1::ObjectIdentifier as Oid,
+    hex_literal::hex,
+    icao_9303::{
+        asn1::emrtd::security_info::{KeyAgreement, SymmetricCipher},
+        crypto::{
+            groups::{named::brainpool_p256r1, EllipticCurve, EllipticCurvePoint},
+            mod_ring::{ModRingElementRef, RingRefExt},
+        },
+        emrtd::{
+            authentication_token, decrypt_nonce, k_from_mrz, map_generic,
+            secure_messaging::{aes::kdf_128, KDF_ENC, KDF_MAC},
+            KDF_PACE,
+        },
+    },
+    ruint::aliases::U256,
+};
+
+/// id-PACE-ECDH-GM-AES-CBC-CMAC-128
+const PROTOCOL: Oid = Oid::new_unwrap("0.4.0.127.0.7.2.2.4.2.2");
+
+const MRZ: &str = "T22000129364081251010318";
+const ENCRYPTED_NONCE: [u8; 16] = hex!("95A3A016 522EE98D 01E76CB6 B98B42C3");
+const NONCE: [u8; 16] = hex!("3F00C4D3 9D153F2B 2A214A07 8D899B22");
+
+const SK_MAP_PCD: [u8; 32] =
+    hex!("7F4EF07B 9EA82FD7 8AD689B3 8D0BC78C F21F249D 953BC46F 4C6E1925 9C010F99");
+const PK_MAP_PCD: ([u8; 32], [u8; 32]) = (
+    hex!("7ACF3EFC 982EC455 65A4B155 129EFBC7 4650DCBF A6362D89 6FC70262 E0C2CC5E"),
+    hex!("544552DC B6725218 799115B5 5C9BAA6D 9F6BC3A9 618E70C2 5AF71777 A9C4922D"),
+);
+const SK_MAP_IC: [u8; 32] =
+    hex!("498FF497 56F2DC15 87840041 839A8598 2BE7761D 14715FB0 91EFA7BC E9058560");
+const PK_MAP_IC: ([u8; 32], [u8; 32]) = (
+    hex!("824FBA91 C9CBE26B EF53A0EB E7342A3B F178CEA9 F45DE0B7 0AA60165 1FBA3F57"),
+    hex!("30D8C879 AAA9C9F7 3991E61B 58F4D52E B87A0A0C 709A49DC 63719363 CCD13C54"),
+);
+const SHARED_MAP: ([u8; 32], [u8; 32]) = (
+    hex!("60332EF2 450B5D24 7EF6D386 8397D398 852ED6E8 CAF6FFEE F6BF85CA 57057FD5"),
+    hex!("0840CA74 15BAF3E4 3BD414D3 5AA4608B 93A2CAF3 A4E3EA4E 82C9C13D 03EB7181"),
+);
+const MAPPED_GENERATOR: ([u8; 32], [u8; 32]) = (
+    hex!("8CED63C9 1426D4F0 EB1435E7 CB1D74A4 6723A0AF 21C89634 F65A9AE8 7A9265E2"),
+    hex!("8C879506 743F8611 AC33645C 5B985C80 B5F09A0B 83407C1B 6A4D857A E76FE522"),
+);
+
+const SK_PCD: [u8; 32] =
+    hex!("A73FB703 AC1436A1 8E0CFA5A BB3F7BEC 7A070E7A 6788486B EE230C4A 22762595");
+const PK_PCD: ([u8; 32], [u8; 32]) = (
+    hex!("2DB7A64C 0355044E C9DF1905 14C625CB A2CEA487 54887122 F3A5EF0D 5EDD301C"),
+    hex!("3556F3B3 B186DF10 B857B58F 6A7EB80F 20BA5DC7 BE1D43D9 BF850149 FBB36462"),
+);
+const SK_IC: [u8; 32] =
+    hex!("107CF586 96EF6155 053340FD 633392BA 81909DF7 B9706F22 6F32086C 7AFF974A");
+const PK_IC: ([u8; 32], [u8; 32]) = (
+    hex!("9E880F84 2905B8B3 181F7AF7 CAA9F0EF B743847F 44A306D2 D28C1D9E C65DF6DB"),
+    hex!("7764B222 77A2EDDC 3C265A9F 018F9CB8 52E111B7 68B32690 4B59A019 3776F094"),
+);
+const SHARED_SECRET: [u8; 32] =
+    hex!("28768D20 701247DA E81804C9 E780EDE5 82A9996D B4A31502 0B273319 7DB84925");
+
+const K_ENC: [u8; 16] = hex!("F5F0E35C 0D7161EE 6724EE51 3A0D9A7F");
+const K_MAC: [u8; 16] = hex!("FE251C78 58B356B2 4514B3BD 5F4297D1");
+const T_PCD: [u8; 8] = hex!("C2B0BD78 D94BA866");
+const T_IC: [u8; 8] = hex!("3ABB9674 BCE93C08");
+
+fn scalar<'a>(curve: &'a EllipticCurve<U256>, bytes: &[u8]) -> ModRingElementRef<'a, U256> {
+    curve.scalar_field().from(U256::from_be_slice(bytes))
+}
+
+fn point<'a>(
+    curve: &'a EllipticCurve<U256>,
+    (x, y): ([u8; 32], [u8; 32]),
+) -> EllipticCurvePoint<'a, U256> {
+    let field = curve.base_field();
+    curve
+        .from_affine(
+            field.from(U256::from_be_bytes(x)),
+            field.from(U256::from_be_bytes(y)),
+        )
+        .unwrap()
+}
+
+/// Uncompressed encoding, BSI TR-03111 3.2.1.
+fn encode(point: EllipticCurvePoint<'_, U256>) -> Vec<u8> {
+    let (x, y) = point.coordinates().unwrap();
+    let mut bytes = vec![0x04];
+    bytes.extend_from_slice(&x.to_uint().to_be_bytes::<32>());
+    bytes.extend_from_slice(&y.to_uint().to_be_bytes::<32>());
+    bytes
+}
+
+#[test]
+fn test_pace_k_pi() {
+    let k = k_from_mrz(MRZ);
+    assert_eq!(k, hex!("7E2D2A41 C74EA0B3 8CD36F86 3939BFA8 E9032AAD"));
+    let k_pi = kdf_128(&k, KDF_PACE);
+    assert_eq!(k_pi, hex!("89DED1B2 6624EC1E 634C1989 302849DD"));
+}
+
+#[test]
+fn test_pace_decrypt_nonce() {
+    let k_pi = kdf_128(&k_from_mrz(MRZ), KDF_PACE);
+    assert_eq!(decrypt_nonce(&k_pi, &ENCRYPTED_NONCE).unwrap(), NONCE);
+}
+
+#[test]
+fn test_pace_mapping() {
+    let curve = brainpool_p256r1();
+    let g = curve.generator();
+
+    let pk_map_pcd = g * scalar(&curve, &SK_MAP_PCD);
+    assert_eq!(pk_map_pcd, point(&curve, PK_MAP_PCD));
+    let pk_map_ic = g * scalar(&curve, &SK_MAP_IC);
+    assert_eq!(pk_map_ic, point(&curve, PK_MAP_IC));
+
+    // Both sides arrive at the same shared point H.
+    let shared = pk_map_ic * scalar(&curve, &SK_MAP_PCD);
+    assert_eq!(shared, point(&curve, SHARED_MAP));
+    assert_eq!(pk_map_pcd * scalar(&curve, &SK_MAP_IC), shared);
+
+    let mapped = map_generic(&curve, &NONCE, shared).unwrap();
+    assert_eq!(mapped.generator(), point(&mapped, MAPPED_GENERATOR));
+}
+
+#[test]
+fn test_pace_key_agreement() {
+    let curve = brainpool_p256r1();
+    let mapped = curve
+        .with_generator(point(&curve, MAPPED_GENERATOR))
+        .unwrap();
+    let g = mapped.generator();
+
+    let pk_pcd = g * scalar(&mapped, &SK_PCD);
+    assert_eq!(pk_pcd, point(&mapped, PK_PCD));
+    let pk_ic = g * scalar(&mapped, &SK_IC);
+    assert_eq!(pk_ic, point(&mapped, PK_IC));
+
+    let shared = pk_ic * scalar(&mapped, &SK_PCD);
+    assert_eq!(pk_pcd * scalar(&mapped, &SK_IC), shared);
+    let shared_secret = shared.x().unwrap().to_uint().to_be_bytes::<32>();
+    assert_eq!(shared_secret, SHARED_SECRET);
+
+    assert_eq!(kdf_128(&shared_secret, KDF_ENC), K_ENC);
+    assert_eq!(kdf_128(&shared_secret, KDF_MAC), K_MAC);
+}
+
+#[test]
+fn test_pace_authentication_tokens() {
+    let curve = brainpool_p256r1();
+    let mapped = curve
+        .with_generator(point(&curve, MAPPED_GENERATOR))
+        .unwrap();
+    let pk_pcd = encode(point(&mapped, PK_PCD));
+    let pk_ic = encode(point(&mapped, PK_IC));
+
+    // Each party MACs the ephemeral public key of the other.
+    assert_eq!(
+        authentication_token(
+            SymmetricCipher::Aes128,
+            &K_MAC,
+            PROTOCOL,
+            KeyAgreement::Ecdh,
+            &pk_ic
+        )
+        .unwrap(),
+        T_PCD
+    );
+    assert_eq!(
+        authentication_token(
+            SymmetricCipher::Aes128,
+            &K_MAC,
+            PROTOCOL,
+            KeyAgreement::Ecdh,
+            &pk_pcd
+        )
+        .unwrap(),
+        T_IC
+    );
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "serde")]
+
+mod dataset;
+
+use {
+    anyhow::Result,
+    dataset::Dataset,
+    der::Decode,
+    icao_9303::asn1::emrtd::{EfDg14, EfSod, LdsSecurityObject},
+};
+
+#[test]
+fn test_lds_security_object_json() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let ef_sod = EfSod::from_der(&dataset.sod)?;
+    let lso = ef_sod.lds_security_object()?;
+
+    let json = serde_json::to_value(&lso)?;
+    assert_eq!(json["hash_algorithm"]["Sha256"], "Null");
+    assert_eq!(json["data_group_hash_values"][0]["data_group_number"], 1);
+    assert_eq!(
+        json["data_group_hash_values"][0]["hash_value"],
+        hex::encode(lso.data_group_hash_values[0].hash_value.as_bytes())
+    );
+
+    let decoded: LdsSecurityObject = serde_json::from_value(json)?;
+    assert_eq!(decoded, lso);
+    Ok(())
+}
+
+#[test]
+fn test_ef_sod_json() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let ef_sod = EfSod::from_der(&dataset.sod)?;
+
+    let json = serde_json::to_string(&ef_sod)?;
+    let decoded: EfSod = serde_json::from_str(&json)?;
+    assert_eq!(decoded, ef_sod);
+    Ok(())
+}
+
+#[test]
+fn test_security_infos_json() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let dg14 = EfDg14::from_der(&dataset.dg14)?;
+
+    let json = serde_json::to_string(&dg14)?;
+    let decoded: EfDg14 = serde_json::from_str(&json)?;
+    assert_eq!(decoded, dg14);
+    Ok(())
+}
@@ -1,6 +1,33 @@
 mod dataset;
 
-use {anyhow::Result, dataset::Dataset, der::Decode, icao_9303::asn1::emrtd::EfSod};
+use {
+    anyhow::Result,
+    base64::{engine::general_purpose::STANDARD, Engine},
+    cms::{
+        cert::x509::{
+            attr::Attribute,
+            spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned},
+        },
+        signed_data::{SignerInfo, SignerInfos},
+    },
+    dataset::Dataset,
+    der::{
+        asn1::{ObjectIdentifier, OctetString, SetOfVec},
+        Any, Decode, Encode, Length, Tag,
+    },
+    hex_literal::hex,
+    icao_9303::{
+        asn1::{
+            emrtd::{EfSod, LdsSecurityObject},
+            master_list::{CscaMasterList, MasterList},
+            ApplicationTagged, ContentInfo, ContentType,
+        },
+        crypto::{verify_with_spki, DataGroupHashMismatch},
+        ldif::MasterListBundle,
+    },
+    ruint::aliases::U1024,
+    std::time::{Duration, UNIX_EPOCH},
+};
 
 #[test]
 fn test_verify() -> Result<()> {
@@ -11,3 +38,355 @@ fn test_verify() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_document_signer_cert() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let sod = EfSod::from_der(&dataset.sod)?;
+
+    assert_eq!(sod.certificates().count(), 1);
+    let cert = sod.document_signer_cert().unwrap();
+    assert_ne!(cert.tbs_certificate.issuer, cert.tbs_certificate.subject);
+
+    Ok(())
+}
+
+#[test]
+fn test_check_signed_attributes() -> Result<()> {
+    let dataset = Dataset::load()?;
+    EfSod::from_der(&dataset.sod)?.check_signed_attributes()?;
+
+    // Flip a bit in the MessageDigest attribute value.
+    let message_digest = hex!("06092a864886f70d010904 3122 0420");
+    let offset = dataset
+        .sod
+        .windows(message_digest.len())
+        .position(|window| window == message_digest)
+        .unwrap()
+        + message_digest.len();
+    let mut tampered = dataset.sod;
+    tampered[offset] ^= 1;
+    let sod = EfSod::from_der(&tampered)?;
+    let error = sod.check_signed_attributes().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Message digest does not match the encapsulated content"
+    );
+    assert!(sod.verify_signature().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_check_other_signed_attributes() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let sod = EfSod::from_der(&dataset.sod)?;
+    let with_attribute = |oid: &str, values: Vec<Any>| -> Result<EfSod> {
+        let mut signer = sod.signer_info()?.clone();
+        let attrs = signer.signed_attrs.as_mut().unwrap();
+        attrs.insert(Attribute {
+            oid:    ObjectIdentifier::new_unwrap(oid),
+            values: SetOfVec::try_from(values)?,
+        })?;
+        let mut signed_data = sod.signed_data().clone();
+        signed_data.signer_infos = SignerInfos(SetOfVec::try_from(vec![signer])?);
+        Ok(ApplicationTagged(ContentInfo(signed_data)))
+    };
+    let time = |time: &str| Any::new(Tag::UtcTime, time.as_bytes());
+
+    // Other attributes are allowed, a signing time must have a single value.
+    with_attribute("1.2.840.113549.1.9.5", vec![time("240131120000Z")?])?
+        .check_signed_attributes()?;
+    with_attribute("1.2.3.4", vec![Any::new(Tag::Null, [])?])?.check_signed_attributes()?;
+    let error = with_attribute("1.2.840.113549.1.9.5", vec![
+        time("240131120000Z")?,
+        time("240201120000Z")?,
+    ])?
+    .check_signed_attributes()
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Signed attribute 1.2.840.113549.1.9.5 must have a single value"
+    );
+
+    // A countersignature is an unsigned attribute.
+    let error = with_attribute("1.2.840.113549.1.9.6", vec![Any::new(Tag::Sequence, [])?])?
+        .check_signed_attributes()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Countersignature must not be a signed attribute"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_any_signature() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let sod = EfSod::from_der(&dataset.sod)?;
+    sod.verify_any_signature()?;
+
+    let with_signers = |signers: Vec<SignerInfo>| -> Result<EfSod> {
+        let mut signed_data = sod.signed_data().clone();
+        signed_data.signer_infos = SignerInfos(SetOfVec::try_from(signers)?);
+        Ok(ApplicationTagged(ContentInfo(signed_data)))
+    };
+
+    // No signers.
+    let unsigned = with_signers(vec![])?;
+    assert!(unsigned.signer_infos().is_empty());
+    assert!(unsigned.signer_info().is_err());
+    assert!(unsigned.verify_signature().is_err());
+    assert!(unsigned.verify_any_signature().is_err());
+
+    // One of two signers is valid.
+    let good = sod.signer_info()?.clone();
+    let mut bad = good.clone();
+    let mut signature = bad.signature.as_bytes().to_vec();
+    signature[0] ^= 1;
+    bad.signature = OctetString::new(signature)?;
+    let sod = with_signers(vec![bad.clone(), good])?;
+    assert_eq!(sod.signer_infos().len(), 2);
+    sod.verify_any_signature()?;
+    assert!(with_signers(vec![bad])?.verify_any_signature().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_ecdsa() -> Result<()> {
+    // EF.SOD signed with ecdsa-with-SHA256 by a brainpoolP256r1 document signer.
+    let sod = EfSod::from_der(&std::fs::read("tests/data/ef_sod_ecdsa.bin")?)?;
+
+    sod.verify_signature()?;
+
+    // Corrupted signature.
+    let mut signer = sod.signer_info()?.clone();
+    let mut signature = signer.signature.as_bytes().to_vec();
+    let last = signature.len() - 1;
+    signature[last] ^= 1;
+    signer.signature = OctetString::new(signature)?;
+    let mut signed_data = sod.signed_data().clone();
+    signed_data.signer_infos = SignerInfos(SetOfVec::try_from(vec![signer])?);
+    let tampered = ApplicationTagged(ContentInfo(signed_data));
+    assert!(tampered.verify_signature().is_err());
+
+    // Corrupted message digest attribute.
+    let mut der = std::fs::read("tests/data/ef_sod_ecdsa.bin")?;
+    let message_digest = hex!("06092a864886f70d010904 3122 0420");
+    let offset = der
+        .windows(message_digest.len())
+        .position(|window| window == message_digest)
+        .unwrap()
+        + message_digest.len();
+    der[offset] ^= 1;
+    assert!(EfSod::from_der(&der)?.verify_signature().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_rsa_pkcs1v15() -> Result<()> {
+    // sha256WithRSAEncryption signature by a 1000 bit key, generated with
+    // `openssl dgst -sha256 -sign`.
+    let spki = SubjectPublicKeyInfoOwned::from_der(&hex!("30819b300d06092a864886f70d010101050003818900308185027e00e1fdd8a70de465bc3a121f230be9729aa2b4f523b1f9093f8f4cf1dbe8e6a7d6fa0d01e0e0c7f9125ad9a6210c9c957e390b516070129f35dc52585c6b8902de05539d574cda2b28a73e9e43a91b5e10a8f2f7578bb77f8b99345c10f0f5ce1f0fa2e9dc41071a2bd7b9b958fcb25c68f90113cdaa51ec4fadd96ddb330203010001"))?;
+    let algorithm = AlgorithmIdentifierOwned::from_der(&hex!("300d06092a864886f70d01010b0500"))?;
+    let message = b"ICAO 9303";
+    let mut signature = hex!("2e5b25ce5a21ad0bbd94ce45bbce94bdc7274b6783edb1a34e810452f56586d17a01b0499310ddaf81d656abb80569763a1e7b68e988e17936e99d5d4c72342cd36521a4f5fb885b061842de676d80ff50ae31389c0d425ab700843c93dcf0cdb81a140a2802065eeb2ed72e01dfb4e2c0130aa626ef4eda8d377bd4fb");
+
+    verify_with_spki(&spki, &algorithm, message, &signature)?;
+
+    // Wrong message.
+    assert!(verify_with_spki(&spki, &algorithm, b"ICAO 9304", &signature).is_err());
+
+    // Wrong digest algorithm (sha512WithRSAEncryption).
+    let sha512 = AlgorithmIdentifierOwned::from_der(&hex!("300d06092a864886f70d01010d0500"))?;
+    assert!(verify_with_spki(&spki, &sha512, message, &signature).is_err());
+
+    // Signatures that are congruent modulo n, or equal as integers, but not
+    // the exact encoding.
+    let modulus = U1024::from_be_slice(&hex!("e1fdd8a70de465bc3a121f230be9729aa2b4f523b1f9093f8f4cf1dbe8e6a7d6fa0d01e0e0c7f9125ad9a6210c9c957e390b516070129f35dc52585c6b8902de05539d574cda2b28a73e9e43a91b5e10a8f2f7578bb77f8b99345c10f0f5ce1f0fa2e9dc41071a2bd7b9b958fcb25c68f90113cdaa51ec4fadd96ddb33"));
+    let s_plus_n = (U1024::from_be_slice(&signature) + modulus).to_be_bytes_trimmed_vec();
+    assert!(verify_with_spki(&spki, &algorithm, message, &s_plus_n).is_err());
+    let padded = [&[0][..], &signature].concat();
+    assert!(verify_with_spki(&spki, &algorithm, message, &padded).is_err());
+    let modulus = modulus.to_be_bytes_trimmed_vec();
+    assert_eq!(modulus.len(), signature.len());
+    assert!(verify_with_spki(&spki, &algorithm, message, &modulus).is_err());
+
+    // Corrupted signature.
+    signature[10] ^= 1;
+    assert!(verify_with_spki(&spki, &algorithm, message, &signature).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_data_groups() -> Result<()> {
+    let dataset = Dataset::load()?;
+    let sod = EfSod::from_der(&dataset.sod)?;
+
+    sod.verify_data_groups(&[
+        (1, &dataset.dg1),
+        (2, &dataset.dg2),
+        (3, &dataset.dg3),
+        (4, &dataset.dg4),
+        (14, &dataset.dg14),
+    ])?;
+
+    let mut dg1 = dataset.dg1;
+    dg1[5] ^= 1;
+    let error = sod.verify_data_groups(&[(1, &dg1)]).unwrap_err();
+    let mismatch = error.downcast_ref::<DataGroupHashMismatch>().unwrap();
+    assert_eq!(mismatch.dg_number, 1);
+    assert_eq!(
+        mismatch.expected,
+        sod.lds_security_object()?.hash_for_dg(1).unwrap()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_master_list() -> Result<()> {
+    // Master list with a single brainpoolP256r1 CSCA, signed by a master list
+    // signer issued by that CSCA.
+    let master_list = MasterList::from_der(&std::fs::read("tests/data/master_list.der")?)?;
+
+    master_list.verify_signature()?;
+
+    let csca_master_list = master_list.csca_master_list()?;
+    assert_eq!(csca_master_list.version, 0);
+    assert_eq!(csca_master_list.cert_list.len(), 1);
+    assert_eq!(csca_master_list.countries(), ["UT"]);
+    assert_eq!(csca_master_list.certs_for_country("ut").count(), 1);
+    assert_eq!(csca_master_list.certs_for_country("NL").count(), 0);
+
+    // Swapping the encapsulated master list breaks the message digest.
+    let mut tampered = master_list.clone();
+    let swapped = CscaMasterList {
+        version:   0,
+        cert_list: SetOfVec::new(),
+    };
+    tampered.0.encap_content_info.econtent = Some(Any::new(Tag::OctetString, swapped.to_der()?)?);
+    let error = tampered.verify_signature().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Message digest does not match the encapsulated content"
+    );
+
+    // The content must be a CSCA master list.
+    let mut tampered = master_list;
+    tampered.0.encap_content_info.econtent_type = LdsSecurityObject::CONTENT_TYPE;
+    assert!(tampered.verify_signature().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_master_list_from_ldif() -> Result<()> {
+    let der = std::fs::read("tests/data/master_list.der")?;
+    let encoded = STANDARD.encode(&der);
+    let mut ldif = String::from(
+        "version: 1\n\ndn: cn=ml,o=ml,c=NL,dc=data\npkdVersion: 1\npkdMasterListContent:: ",
+    );
+    for (i, chunk) in encoded.as_bytes().chunks(76).enumerate() {
+        if i > 0 {
+            ldif.push_str("\n ");
+        }
+        ldif.push_str(std::str::from_utf8(chunk)?);
+    }
+    ldif.push('\n');
+
+    let master_lists = MasterListBundle::from_ldif(ldif.as_bytes())?;
+    assert_eq!(master_lists.len(), 1);
+    master_lists[0].verify_signature()?;
+
+    let certificate = master_lists[0]
+        .csca_master_list()?
+        .cert_list
+        .get(0)
+        .unwrap()
+        .clone();
+    let ldif = format!(
+        "dn: cn=csca,c=NL\ncACertificate;binary:: {}\n",
+        STANDARD.encode(certificate.to_der()?)
+    );
+    assert_eq!(
+        MasterListBundle::certificates_from_ldif(ldif.as_bytes())?,
+        [certificate]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_certificates_from_ldif() -> Result<()> {
+    let master_list = MasterList::from_der(&std::fs::read("tests/data/master_list.der")?)?;
+    let csca = master_list
+        .csca_master_list()?
+        .cert_list
+        .get(0)
+        .unwrap()
+        .clone();
+    let sod = EfSod::from_der(&std::fs::read("tests/data/ef_sod_csca.bin")?)?;
+    let document_signer = sod.document_signer_cert().unwrap().clone();
+
+    // RFC 4523 CertificatePair with explicitly tagged optional certificates.
+    let tlv = |tag: u8, value: &[u8]| -> Result<Vec<u8>> {
+        let mut out = vec![tag];
+        out.extend(Length::try_from(value.len())?.to_der()?);
+        out.extend(value);
+        Ok(out)
+    };
+    let forward = tlv(0xa0, &csca.to_der()?)?;
+    let reverse = tlv(0xa1, &document_signer.to_der()?)?;
+    let ldif = format!(
+        concat!(
+            "dn: cn=csca,c=NL\n",
+            "cACertificate;binary:: {}\n",
+            "\n",
+            "dn: cn=pairs,c=NL\n",
+            "crossCertificatePair;binary:: {}\n",
+            "crossCertificatePair;binary:: {}\n",
+        ),
+        STANDARD.encode(csca.to_der()?),
+        STANDARD.encode(tlv(0x30, &[forward.as_slice(), &reverse].concat())?),
+        STANDARD.encode(tlv(0x30, &reverse)?),
+    );
+    assert_eq!(
+        MasterListBundle::certificates_from_ldif(ldif.as_bytes())?,
+        [csca.clone(), csca, document_signer.clone(), document_signer]
+    );
+
+    let ldif = "dn: cn=pair,c=NL\ncrossCertificatePair;binary:: MAOgAQA=\n";
+    assert!(MasterListBundle::certificates_from_ldif(ldif.as_bytes()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_certificate_chain() -> Result<()> {
+    // Same CSCA as the master list, with a Document Signer issued by it.
+    let master_list = MasterList::from_der(&std::fs::read("tests/data/master_list.der")?)?;
+    let sod = EfSod::from_der(&std::fs::read("tests/data/ef_sod_csca.bin")?)?;
+
+    master_list.verify_signature()?;
+    let csca_master_list = master_list.csca_master_list()?;
+    sod.verify_signature()?;
+    // Both certificates are valid from 2024 to 2049.
+    let year = Duration::from_secs(365 * 24 * 60 * 60);
+    sod.verify_certificate_chain(&csca_master_list, UNIX_EPOCH + 55 * year)?;
+    assert!(sod
+        .verify_certificate_chain(&csca_master_list, UNIX_EPOCH + 80 * year)
+        .is_err());
+
+    // The self-signed Document Signer is not in the master list.
+    let sod = EfSod::from_der(&std::fs::read("tests/data/ef_sod_ecdsa.bin")?)?;
+    assert!(sod
+        .verify_certificate_chain(&csca_master_list, UNIX_EPOCH + 55 * year)
+        .is_err());
+
+    Ok(())
+}